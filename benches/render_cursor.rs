@@ -0,0 +1,42 @@
+//! Cursor hot path benchmark.
+//!
+//! Run with `cargo bench --bench render_cursor`. Measures `CursorManager::get_render_cursor`,
+//! which runs for every rendered frame, with the XCursor theme from the environment.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use niri::animation::Clock;
+use niri::cursor::CursorManager;
+use smithay::input::pointer::{CursorIcon, CursorImageStatus};
+use smithay::output::Scale;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn main() {
+    let theme = std::env::var("XCURSOR_THEME").unwrap_or_else(|_| String::from("default"));
+    let mut manager = CursorManager::new(Clock::default(), &theme, 24);
+
+    let cases = [
+        ("named", CursorImageStatus::Named(CursorIcon::Default)),
+        ("hidden", CursorImageStatus::Hidden),
+    ];
+    for (name, status) in cases {
+        manager.set_cursor_image(status);
+
+        for scale in [1, 2] {
+            let scale = Scale::Integer(scale);
+
+            // Loads the cursor into the cache.
+            black_box(manager.get_render_cursor(scale));
+
+            let start = Instant::now();
+            for _ in 0..ITERATIONS {
+                black_box(manager.get_render_cursor(scale));
+            }
+            let per_call = start.elapsed() / ITERATIONS;
+
+            println!("{name}@{}: {per_call:?} per call", scale.integer_scale());
+        }
+    }
+}
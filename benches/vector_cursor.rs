@@ -0,0 +1,72 @@
+//! Vector cursor rendering benchmark.
+//!
+//! Run with `cargo bench --bench vector_cursor [THEME_DIR]`. Defaults to the bundled example
+//! theme in `resources/cursors`.
+//!
+//! Every cursor is measured with the software rasterizer alone, then, if a surfaceless EGL
+//! display is available, with each frame also uploaded and drawn on the GPU.
+
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use niri::cursor::vector::bench::{load_store, RenderBench};
+use smithay::backend::egl::native::EGLSurfacelessDisplay;
+use smithay::backend::egl::{EGLContext, EGLDisplay};
+use smithay::backend::renderer::gles::GlesRenderer;
+
+const FRAMES: u32 = 500;
+const SCALES: [f64; 4] = [1., 1.5, 2., 3.];
+
+fn main() -> anyhow::Result<()> {
+    let theme_dir = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/resources/cursors"))
+        });
+
+    let store = load_store(&theme_dir, 24)?;
+
+    let mut gles = match create_renderer() {
+        Ok(renderer) => Some(renderer),
+        Err(err) => {
+            eprintln!("GPU benchmarks skipped: {err:?}");
+            None
+        }
+    };
+
+    let mut cursor_ids: Vec<_> = store.get_config().cursors.keys().cloned().collect();
+    cursor_ids.sort();
+
+    for cursor_id in &cursor_ids {
+        for scale in SCALES {
+            let mut bench = match RenderBench::new(&store, cursor_id, scale) {
+                Ok(bench) => bench,
+                Err(err) => {
+                    eprintln!("{cursor_id}@{scale}: skipped: {err:?}");
+                    continue;
+                }
+            };
+
+            let report = bench.run(FRAMES)?;
+            println!("{cursor_id}@{scale}: {}", report.histogram.summary());
+
+            if let Some(renderer) = &mut gles {
+                let report = bench.run_gpu(renderer, FRAMES)?;
+                println!("{cursor_id}@{scale} (gpu): {}", report.histogram.summary());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn create_renderer() -> anyhow::Result<GlesRenderer> {
+    unsafe {
+        let display =
+            EGLDisplay::new(EGLSurfacelessDisplay).context("error creating EGL display")?;
+        let context = EGLContext::new(&display).context("error creating EGL context")?;
+        GlesRenderer::new(context).context("error creating renderer")
+    }
+}
@@ -6,7 +6,8 @@ use bitflags::bitflags;
 use knuffel::errors::DecodeError;
 use miette::miette;
 use niri_ipc::{
-    ColumnDisplay, LayoutSwitchTarget, PositionChange, SizeChange, WorkspaceReferenceArg,
+    ColumnDisplay, CursorColorScheme, LayoutSwitchTarget, PositionChange, SizeChange,
+    WorkspaceReferenceArg,
 };
 use smithay::input::keyboard::keysyms::KEY_NoSymbol;
 use smithay::input::keyboard::xkb::{keysym_from_name, KEYSYM_CASE_INSENSITIVE, KEYSYM_NO_FLAGS};
@@ -369,6 +370,13 @@ pub enum Action {
     UnsetWindowUrgent(u64),
     #[knuffel(skip)]
     LoadConfigFile,
+    SetCursorColor(#[knuffel(argument)] Option<String>),
+    SetCursorColorScheme(#[knuffel(argument, str)] CursorColorScheme),
+    ToggleCursorMirror,
+    PauseCursorAnimations,
+    ResumeCursorAnimations,
+    FreezeCursorFrame(#[knuffel(argument)] u32),
+    StepCursorFrame(#[knuffel(argument)] i32),
     #[knuffel(skip)]
     MruAdvance {
         direction: MruDirection,
@@ -700,6 +708,13 @@ impl From<niri_ipc::Action> for Action {
             niri_ipc::Action::SetWindowUrgent { id } => Self::SetWindowUrgent(id),
             niri_ipc::Action::UnsetWindowUrgent { id } => Self::UnsetWindowUrgent(id),
             niri_ipc::Action::LoadConfigFile {} => Self::LoadConfigFile,
+            niri_ipc::Action::SetCursorColor { color } => Self::SetCursorColor(color),
+            niri_ipc::Action::SetCursorColorScheme { scheme } => Self::SetCursorColorScheme(scheme),
+            niri_ipc::Action::ToggleCursorMirror {} => Self::ToggleCursorMirror,
+            niri_ipc::Action::PauseCursorAnimations {} => Self::PauseCursorAnimations,
+            niri_ipc::Action::ResumeCursorAnimations {} => Self::ResumeCursorAnimations,
+            niri_ipc::Action::FreezeCursorFrame { frame } => Self::FreezeCursorFrame(frame),
+            niri_ipc::Action::StepCursorFrame { count } => Self::StepCursorFrame(count),
         }
     }
 }
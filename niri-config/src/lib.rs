@@ -734,6 +734,10 @@ mod tests {
                     bottom-left
                     bottom-right
                 }
+                cursor-color {
+                    primaries "display-p3"
+                    gamma 2.2
+                }
             }
 
             output "eDP-2" {
@@ -810,6 +814,9 @@ mod tests {
                 xcursor-size 16
                 hide-when-typing
                 hide-after-inactive-ms 3000
+                fallback "arrow"
+                reduce-motion
+                max-animation-fps 15
             }
 
             screenshot-path "~/Screenshots/screenshot.png"
@@ -1176,6 +1183,19 @@ mod tests {
                             },
                         ),
                         layout: None,
+                        cursor_color: Some(
+                            CursorColor {
+                                icc: None,
+                                primaries: Some(
+                                    DisplayP3,
+                                ),
+                                gamma: Some(
+                                    FloatOrInt(
+                                        2.2,
+                                    ),
+                                ),
+                            },
+                        ),
                     },
                     Output {
                         off: false,
@@ -1202,6 +1222,7 @@ mod tests {
                         backdrop_color: None,
                         hot_corners: None,
                         layout: None,
+                        cursor_color: None,
                     },
                     Output {
                         off: false,
@@ -1231,6 +1252,7 @@ mod tests {
                         backdrop_color: None,
                         hot_corners: None,
                         layout: None,
+                        cursor_color: None,
                     },
                 ],
             ),
@@ -1460,6 +1482,11 @@ mod tests {
                 hide_after_inactive_ms: Some(
                     3000,
                 ),
+                fallback: Arrow,
+                reduce_motion: true,
+                max_animation_fps: Some(
+                    15,
+                ),
             },
             screenshot_path: ScreenshotPath(
                 Some(
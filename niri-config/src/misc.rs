@@ -1,3 +1,7 @@
+use std::str::FromStr;
+
+use miette::miette;
+
 use crate::appearance::{Color, WorkspaceShadow, WorkspaceShadowPart, DEFAULT_BACKDROP_COLOR};
 use crate::utils::{Flag, MergeWith};
 use crate::FloatOrInt;
@@ -20,6 +24,9 @@ pub struct Cursor {
     pub xcursor_size: u8,
     pub hide_when_typing: bool,
     pub hide_after_inactive_ms: Option<u32>,
+    pub fallback: CursorFallback,
+    pub reduce_motion: bool,
+    pub max_animation_fps: Option<u32>,
 }
 
 impl Default for Cursor {
@@ -29,10 +36,25 @@ impl Default for Cursor {
             xcursor_size: 24,
             hide_when_typing: false,
             hide_after_inactive_ms: None,
+            fallback: CursorFallback::default(),
+            reduce_motion: false,
+            max_animation_fps: None,
         }
     }
 }
 
+/// Cursor shown when the theme has no default cursor.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum CursorFallback {
+    /// The arrow image built into niri.
+    #[default]
+    Embedded,
+    /// An arrow drawn at the cursor size.
+    Arrow,
+    /// An SVG file rendered at the cursor size, with the hotspot in the top-left corner.
+    Svg(String),
+}
+
 #[derive(knuffel::Decode, Debug, PartialEq)]
 pub struct CursorPart {
     #[knuffel(child, unwrap(argument))]
@@ -43,13 +65,34 @@ pub struct CursorPart {
     pub hide_when_typing: Option<Flag>,
     #[knuffel(child, unwrap(argument))]
     pub hide_after_inactive_ms: Option<u32>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub fallback: Option<CursorFallback>,
+    #[knuffel(child)]
+    pub reduce_motion: Option<Flag>,
+    #[knuffel(child, unwrap(argument))]
+    pub max_animation_fps: Option<u32>,
 }
 
 impl MergeWith<CursorPart> for Cursor {
     fn merge_with(&mut self, part: &CursorPart) {
-        merge_clone!((self, part), xcursor_theme, xcursor_size);
-        merge!((self, part), hide_when_typing);
-        merge_clone_opt!((self, part), hide_after_inactive_ms);
+        merge_clone!((self, part), xcursor_theme, xcursor_size, fallback);
+        merge!((self, part), hide_when_typing, reduce_motion);
+        merge_clone_opt!((self, part), hide_after_inactive_ms, max_animation_fps);
+    }
+}
+
+impl FromStr for CursorFallback {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "embedded" => Ok(Self::Embedded),
+            "arrow" => Ok(Self::Arrow),
+            path if path.ends_with(".svg") => Ok(Self::Svg(path.to_owned())),
+            _ => Err(miette!(
+                r#"invalid cursor fallback, can be "embedded", "arrow" or a path to an .svg file"#
+            )),
+        }
     }
 }
 
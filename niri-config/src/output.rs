@@ -5,6 +5,7 @@ use knuffel::decode::Context;
 use knuffel::errors::DecodeError;
 use knuffel::traits::ErrorSpan;
 use knuffel::Decode;
+use miette::miette;
 use niri_ipc::{ConfiguredMode, HSyncPolarity, Transform, VSyncPolarity};
 
 use crate::gestures::HotCorners;
@@ -76,6 +77,8 @@ pub struct Output {
     pub hot_corners: Option<HotCorners>,
     #[knuffel(child)]
     pub layout: Option<LayoutPart>,
+    #[knuffel(child)]
+    pub cursor_color: Option<CursorColor>,
 }
 
 impl Output {
@@ -108,6 +111,7 @@ impl Default for Output {
             backdrop_color: None,
             hot_corners: None,
             layout: None,
+            cursor_color: None,
         }
     }
 }
@@ -128,6 +132,27 @@ pub struct Position {
     pub y: i32,
 }
 
+/// Color space of the display, which vector cursors are converted to.
+#[derive(knuffel::Decode, Debug, Clone, PartialEq)]
+pub struct CursorColor {
+    /// Path of an RGB matrix/TRC ICC profile of the display.
+    #[knuffel(child, unwrap(argument))]
+    pub icc: Option<String>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub primaries: Option<Primaries>,
+    /// Power curve of a display set up with `primaries`, the sRGB curve if unset.
+    #[knuffel(child, unwrap(argument))]
+    pub gamma: Option<FloatOrInt<0, 10>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primaries {
+    Srgb,
+    DisplayP3,
+    AdobeRgb,
+    Bt2020,
+}
+
 #[derive(knuffel::Decode, Debug, Clone, PartialEq, Default)]
 pub struct Vrr {
     #[knuffel(property, default = false)]
@@ -493,6 +518,22 @@ impl<S: ErrorSpan> Decode<S> for Modeline {
     }
 }
 
+impl FromStr for Primaries {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "srgb" => Ok(Self::Srgb),
+            "display-p3" => Ok(Self::DisplayP3),
+            "adobe-rgb" => Ok(Self::AdobeRgb),
+            "bt2020" => Ok(Self::Bt2020),
+            _ => Err(miette!(
+                r#"invalid primaries, can be "srgb", "display-p3", "adobe-rgb" or "bt2020""#
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use insta::assert_debug_snapshot;
@@ -119,6 +119,8 @@ pub enum Request {
     OverviewState,
     /// Request information about screencasts.
     Casts,
+    /// Request cursor rendering statistics.
+    CursorStats,
 }
 
 /// Reply from niri to client.
@@ -165,6 +167,8 @@ pub enum Response {
     OverviewState(Overview),
     /// Information about screencasts.
     Casts(Vec<Cast>),
+    /// Cursor rendering statistics.
+    CursorStats(CursorStats),
 }
 
 /// Overview information.
@@ -937,6 +941,45 @@ pub enum Action {
     /// Can be useful for scripts changing the config file, to avoid waiting the small duration for
     /// niri's config file watcher to notice the changes.
     LoadConfigFile {},
+    /// Set the color symbolic vector cursors are drawn in.
+    ///
+    /// This is the color of `currentColor` in SVG cursors.
+    SetCursorColor {
+        /// CSS color, like `#ffffff` or `rgb(255, 255, 255)`.
+        ///
+        /// If `None`, uses the `foreground` of the cursor theme.
+        #[cfg_attr(feature = "clap", arg())]
+        color: Option<String>,
+    },
+    /// Set the color scheme vector cursors with light and dark artwork follow.
+    SetCursorColorScheme {
+        /// Color scheme to set.
+        #[cfg_attr(feature = "clap", arg())]
+        scheme: CursorColorScheme,
+    },
+    /// Toggle horizontal mirroring of vector cursors, for left-handed use.
+    ToggleCursorMirror {},
+    /// Freeze animated vector cursors at their current frame.
+    ///
+    /// Useful for screenshots, screen recording and saving power.
+    PauseCursorAnimations {},
+    /// Continue vector cursor animations frozen by `pause-cursor-animations` or
+    /// `freeze-cursor-frame`.
+    ResumeCursorAnimations {},
+    /// Freeze the vector cursor at a frame of its animation.
+    ///
+    /// Useful for debugging frames that render wrong. Continue with `resume-cursor-animations`.
+    FreezeCursorFrame {
+        /// Index of the frame, counting all frames of the animation from 0.
+        #[cfg_attr(feature = "clap", arg())]
+        frame: u32,
+    },
+    /// Step the vector cursor animation by a number of frames, freezing it first.
+    StepCursorFrame {
+        /// Number of frames to step, negative to step back.
+        #[cfg_attr(feature = "clap", arg(allow_hyphen_values = true, default_value = "1"))]
+        count: i32,
+    },
 }
 
 /// Change in window or column size.
@@ -1001,6 +1044,16 @@ pub enum ColumnDisplay {
     Tabbed,
 }
 
+/// Desktop color scheme, for cursors with light and dark artwork.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub enum CursorColorScheme {
+    /// Artwork for light desktops.
+    Light,
+    /// Artwork for dark desktops.
+    Dark,
+}
+
 /// Output actions that niri can perform.
 // Variants in this enum should match the spelling of the ones in niri-config. Most thigs from
 // niri-config should be present here.
@@ -1559,6 +1612,40 @@ pub enum CastTarget {
     },
 }
 
+/// Cursor rendering statistics, collected since the vector cursor theme was loaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CursorStats {
+    /// Raster times of vector cursor frames, across all cursors.
+    ///
+    /// `None` if no vector cursor theme is in use.
+    pub render_times: Option<RenderTimes>,
+    /// Raster times of vector cursor frames, by cursor ID.
+    pub cursor_render_times: HashMap<String, RenderTimes>,
+    /// Bytes per second of named XCursor buffers uploaded, over the last few seconds.
+    pub xcursor_upload_bandwidth: f64,
+    /// Bytes per second of vector cursor buffers uploaded, over the last few seconds.
+    pub vector_upload_bandwidth: f64,
+}
+
+/// Percentiles of frame raster times, in microseconds.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct RenderTimes {
+    /// Number of frames rasterized.
+    pub count: u64,
+    /// Mean raster time.
+    pub mean_us: u64,
+    /// Median raster time.
+    pub p50_us: u64,
+    /// 95th percentile raster time.
+    pub p95_us: u64,
+    /// 99th percentile raster time.
+    pub p99_us: u64,
+    /// Longest raster time.
+    pub max_us: u64,
+}
+
 /// A compositor event.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
@@ -1841,6 +1928,18 @@ impl FromStr for ColumnDisplay {
     }
 }
 
+impl FromStr for CursorColorScheme {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            _ => Err(r#"invalid cursor color scheme, can be "light" or "dark""#),
+        }
+    }
+}
+
 impl FromStr for Transform {
     type Err = &'static str;
 
@@ -1736,6 +1736,9 @@ impl Tty {
         output_state.last_drm_sequence = Some(meta.sequence);
 
         output_state.frame_clock.presented(presentation_time);
+        if !presentation_time.is_zero() {
+            niri.cursor_manager.presented(presentation_time);
+        }
 
         if redraw_needed || output_state.unfinished_animations_remain {
             let vblank_frame = tracy_client::Client::running()
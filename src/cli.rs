@@ -57,6 +57,21 @@ pub enum Sub {
     Panic,
     /// Generate shell completions.
     Completions { shell: CompletionShell },
+    /// Render vector cursors for another niri process.
+    ///
+    /// Start niri with `NIRI_CURSOR_SERVER` set to the socket path to keep the cursor asset
+    /// parsers out of the compositor.
+    CursorServer {
+        /// Path to the vector cursor theme directory.
+        #[arg(long)]
+        theme: PathBuf,
+        /// Path of the socket to listen on.
+        #[arg(long)]
+        socket: PathBuf,
+        /// Base cursor size.
+        #[arg(long, default_value_t = 24)]
+        size: u8,
+    },
 }
 
 #[derive(Subcommand)]
@@ -109,6 +124,8 @@ pub enum Msg {
     OverviewState,
     /// List screencasts.
     Casts,
+    /// Print cursor rendering statistics.
+    CursorStats,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
@@ -1,51 +1,169 @@
-use std::cell::RefCell;
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
 use std::env;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context};
+use niri_config::output::CursorColor;
+use niri_config::CursorFallback;
 use smithay::backend::allocator::Fourcc;
 use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
 use smithay::input::pointer::{CursorIcon, CursorImageStatus, CursorImageSurfaceData};
+use smithay::output;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::{IsAlive, Logical, Physical, Point, Transform};
+use smithay::utils::{IsAlive, Logical, Physical, Point, Size, Transform};
 use smithay::wayland::compositor::with_states;
 use xcursor::parser::{parse_xcursor, Image};
 use xcursor::CursorTheme;
 
+use crate::animation::Clock;
 use crate::cur_buf::{get_cursor_hotspot, get_cursor_surface};
-use crate::cursor::vector::{CursorAnimator, VectorCursorStore};
-
+use crate::cursor::bandwidth::{BandwidthEstimator, UploadPath};
+use crate::cursor::custom::{CustomCursor, CustomCursors};
+use crate::cursor::scheduler::FrameScheduler;
+use crate::cursor::vector::animator::AnimationObserver;
+use crate::cursor::vector::color::ColorTransform;
+use crate::cursor::vector::config::{
+    is_valid_foreground, ColorScheme, TransitionConfig, TransitionType,
+};
+use crate::cursor::vector::golden;
+use crate::cursor::vector::history::{self, CursorHistory};
+use crate::cursor::vector::remote::RemoteConnection;
+#[cfg(feature = "scripting")]
+use crate::cursor::vector::script::CursorScript;
+use crate::cursor::vector::script::{ScriptEvent, ScriptOutput};
+use crate::cursor::vector::transition::{TransitionEffect, TransitionEffects};
+use crate::cursor::vector::types::{FrameRange, LoopMode, RenderedFrame, TransitionState};
+use crate::cursor::vector::{frame_cache, morph, motion_blur};
+use crate::cursor::vector::{
+    CursorAnimator, CursorId, CursorThemeConfig, RenderScale, RenderStats, VectorCursorStore,
+    VectorRenderer,
+};
+use crate::cursor::velocity::VelocityTracker;
+use crate::utils::{expand_home, get_monotonic_time};
+
+pub mod bandwidth;
+pub mod custom;
+pub mod fallback;
+pub mod hotspot;
+pub mod scheduler;
 pub mod vector;
-
-/// Some default looking `left_ptr` icon.
-static FALLBACK_CURSOR_DATA: &[u8] = include_bytes!("../resources/cursor.rgba");
+pub mod velocity;
 
 type XCursorCache = HashMap<(CursorIcon, i32), Option<Rc<XCursor>>>;
 
+/// Pointer inactivity after which cached vector cursor frames are freed.
+pub const IDLE_TRIM_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// Vector cursor frames pre-rendered per call of [`CursorManager::prerender_some`].
+const PRERENDER_BATCH: usize = 4;
+
+/// Frames of the vector cursor still to be pre-rendered at one scale.
+struct Prerender {
+    cursor_id: CursorId,
+    scale: f64,
+    frames: std::ops::Range<u32>,
+    /// Size of the frames pre-rendered so far, which stop at what a frame cache holds.
+    bytes: usize,
+}
+
 pub struct CursorManager {
+    /// Time of animations and timeouts, from the compositor.
+    clock: Clock,
     theme: CursorTheme,
     size: u8,
+    fallback: CursorFallback,
     current_cursor: CursorImageStatus,
     named_cursor_cache: RefCell<XCursorCache>,
     vector_system: Option<VectorCursorSystem>,
-    icon_to_vector_id: HashMap<CursorIcon, String>,
+    vector_theme_path: Option<PathBuf>,
+    icon_to_vector_id: HashMap<CursorIcon, CursorId>,
+    custom: CustomCursors,
+    bandwidth: RefCell<BandwidthEstimator>,
+    velocity: VelocityTracker,
+    /// Observers of vector cursor animation events, kept across vector theme changes.
+    animation_observers: Vec<AnimationObserver>,
+    /// Effects compositing vector cursor transitions, kept across vector theme changes.
+    transition_effects: TransitionEffects,
+    /// Time of the last pointer motion.
+    last_activity: Duration,
+    /// Whether the frame caches were trimmed since the last pointer motion.
+    caches_trimmed: bool,
+    /// Vector cursor frames to render into the caches while niri is idle.
+    prerender: Vec<Prerender>,
+    /// Recently shown vector cursor frames, see [`CursorHistory`].
+    history: RefCell<CursorHistory>,
+    /// Fractional scales of the connected outputs, sorted.
+    output_scales: Vec<f64>,
+    /// Render scales outside of `output_scales` that were already warned about.
+    unregistered_scales: RefCell<Vec<f64>>,
+    /// Color spaces of the outputs with `cursor-color` set, by output name, with their
+    /// transforms, `None` if they failed to load.
+    output_colors: HashMap<String, (CursorColor, Option<Arc<ColorTransform>>)>,
+    /// Vector cursor frame last converted for each output, and what it was converted to.
+    converted_frames: RefCell<HashMap<String, (RenderedFrame, RenderedFrame)>>,
+    /// Color of `currentColor` in SVG cursors set at runtime, kept across vector theme changes.
+    foreground: Option<String>,
+    /// Desktop color scheme, picking light or dark artwork of vector cursors.
+    color_scheme: ColorScheme,
+    /// Mirroring of vector cursors set at runtime, kept across vector theme changes.
+    mirror: Option<bool>,
+    /// Whether vector cursor animations are paused, kept across vector theme changes.
+    animations_paused: bool,
+    /// Whether vector cursors are shown still, kept across vector theme changes.
+    reduce_motion: bool,
+    /// Whether the monitors are on, the cursor can't be seen otherwise.
+    monitors_active: bool,
+    /// Frame rate vector cursor animations are capped at, kept across vector theme changes.
+    max_animation_fps: Option<u32>,
 }
 
 struct VectorCursorSystem {
     store: VectorCursorStore,
     animator: CursorAnimator,
+    stats: RefCell<RenderStats>,
+    /// Scale the cursor was last rendered at, for rewarming the caches after idle.
+    last_scale: Cell<Option<f64>>,
+    /// Cursor that was replaced by its idle variant, shown again once the pointer moves.
+    idle_from: RefCell<Option<CursorId>>,
+    /// Whether the frame last rendered was smeared by motion blur.
+    smeared: Cell<bool>,
+    #[cfg(feature = "scripting")]
+    script: Option<RefCell<CursorScript>>,
+    /// What the theme script returned on the last animation step, applied on every output.
+    scripted: RefCell<ScriptOutput>,
+}
+
+impl VectorCursorSystem {
+    /// Passes `event` to the theme script, if there is one.
+    fn run_script(&self, event: ScriptEvent) -> ScriptOutput {
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            return script.borrow_mut().handle(event);
+        }
+
+        #[cfg(not(feature = "scripting"))]
+        let _ = event;
+
+        ScriptOutput::default()
+    }
 }
 
 impl CursorManager {
-    pub fn new(theme: &str, size: u8) -> Self {
-        Self::new_with_vector_theme(theme, size, None)
+    pub fn new(clock: Clock, theme: &str, size: u8) -> Self {
+        Self::new_with_vector_theme(clock, theme, size, None)
     }
 
+    /// Creates a cursor manager whose animations follow `clock`, like the compositor's clock
+    /// frozen at the presentation time of the frame being drawn.
     pub fn new_with_vector_theme(
+        clock: Clock,
         theme: &str,
         size: u8,
         vector_theme_path: Option<PathBuf>,
@@ -54,9 +172,9 @@ impl CursorManager {
 
         let theme = CursorTheme::load(theme);
 
-        let vector_system = if let Some(path) = vector_theme_path {
+        let vector_system = if let Some(path) = &vector_theme_path {
             debug!("Loading vector cursor system from path: {}", path.display());
-            let result = Self::load_vector_system(&path, size);
+            let result = Self::load_vector_system(path, size);
             match &result {
                 Ok(_) => info!("Vector cursor system loaded successfully"),
                 Err(e) => warn!(
@@ -70,92 +188,408 @@ impl CursorManager {
             None
         };
 
-        let icon_to_vector_id = if vector_system.is_some() {
-            info!("Vector system available, mapping CursorIcon to vector cursor IDs");
-            let vs = vector_system.as_ref().unwrap();
-            let config = vs.store.get_config();
-
-            debug!("Available cursors in config: {:?}", config.cursors.keys());
-
-            let mut mapping = HashMap::new();
-
-            // Map CursorIcon enum variants to vector cursor IDs
-            // Use CursorIcon::name() to get the xcursor name
-            for (cursor_id, _) in &config.cursors {
-                debug!("Processing cursor ID: '{}'", cursor_id);
-
-                // Try to find matching CursorIcon by name
-                // Common cursor names in XCursor themes
-                let icon_name = cursor_id.to_lowercase();
-
-                let icon = match icon_name.as_str() {
-                    "default" | "left_ptr" => CursorIcon::Default,
-                    "move" | "fleur" | "move" => CursorIcon::AllScroll,
-                    "text" | "xterm" | "ibeam" => CursorIcon::Text,
-                    "wait" | "watch" => CursorIcon::Wait,
-                    "progress" | "left_ptr_watch" => CursorIcon::Progress,
-                    "crosshair" | "cross_reverse" => CursorIcon::Crosshair,
-                    "nwse-resize" | "top_left_corner" => CursorIcon::NwResize,
-                    "pointer" | "hand" | "hand1" | "hand2" => CursorIcon::Pointer,
-                    "grab" | "openhand" => CursorIcon::Grab,
-                    "grabbing" | "grabbing" | "closedhand" => CursorIcon::Grabbing,
-                    "not-allowed" | "circle" | "dnd-none" => CursorIcon::NotAllowed,
-                    "help" | "question_arrow" => CursorIcon::Help,
-                    "copy" => CursorIcon::Copy,
-                    "alias" => CursorIcon::Alias,
-                    "cell" => CursorIcon::Cell,
-                    "vertical-text" => CursorIcon::VerticalText,
-                    "context-menu" => CursorIcon::ContextMenu,
-                    "no-drop" => CursorIcon::NoDrop,
-                    "col-resize" | "sb_h_double_arrow" => CursorIcon::WResize,
-                    "row-resize" | "sb_v_double_arrow" => CursorIcon::NResize,
-                    "ew-resize" => CursorIcon::WResize,
-                    "ns-resize" => CursorIcon::NResize,
-                    "nesw-resize" | "top_right_corner" => CursorIcon::NeResize,
-                    "swne-resize" | "bottom_left_corner" => CursorIcon::SwResize,
-                    "sene-resize" | "bottom_right_corner" => CursorIcon::SeResize,
-                    "zoom-in" => CursorIcon::ZoomIn,
-                    "zoom-out" => CursorIcon::ZoomOut,
-                    _ => {
-                        debug!("No CursorIcon match for cursor ID: '{}'", cursor_id);
-                        continue;
-                    }
-                };
-
-                mapping.insert(icon, cursor_id.clone());
-                info!(
-                    "Mapped cursor icon {:?} (name: '{}') to vector cursor '{}'",
-                    icon, cursor_id, cursor_id
-                );
+        let icon_to_vector_id = match &vector_system {
+            Some(vector) => {
+                info!("Vector system available, mapping CursorIcon to vector cursor IDs");
+                Self::map_vector_icons(vector.store.get_config())
+            }
+            None => {
+                info!("No vector system available, no cursor icon mapping");
+                HashMap::new()
             }
-
-            info!("Mapped {} cursor icons to vector cursors", mapping.len());
-            mapping
-        } else {
-            info!("No vector system available, no cursor icon mapping");
-            HashMap::new()
         };
 
         Self {
             theme,
             size,
+            fallback: CursorFallback::default(),
             current_cursor: CursorImageStatus::default_named(),
             named_cursor_cache: Default::default(),
             vector_system,
+            vector_theme_path,
             icon_to_vector_id,
+            custom: Default::default(),
+            bandwidth: Default::default(),
+            velocity: Default::default(),
+            animation_observers: Vec::new(),
+            transition_effects: TransitionEffects::default(),
+            last_activity: clock.now_unadjusted(),
+            caches_trimmed: false,
+            prerender: Vec::new(),
+            history: RefCell::new(CursorHistory::new(history::capacity_from_env())),
+            output_scales: Vec::new(),
+            unregistered_scales: RefCell::new(Vec::new()),
+            output_colors: HashMap::new(),
+            converted_frames: RefCell::new(HashMap::new()),
+            foreground: None,
+            color_scheme: ColorScheme::default(),
+            mirror: None,
+            animations_paused: false,
+            reduce_motion: false,
+            monitors_active: true,
+            max_animation_fps: None,
+            clock,
         }
     }
 
+    /// Maps cursor icons to the vector cursors of a theme by their XCursor names.
+    fn map_vector_icons(config: &CursorThemeConfig) -> HashMap<CursorIcon, CursorId> {
+        debug!("Available cursors in config: {:?}", config.cursors.keys());
+
+        let mut mapping = HashMap::new();
+
+        // Map CursorIcon enum variants to vector cursor IDs
+        // Use CursorIcon::name() to get the xcursor name
+        for (cursor_id, _) in &config.cursors {
+            debug!("Processing cursor ID: '{}'", cursor_id);
+
+            // Try to find matching CursorIcon by name
+            // Common cursor names in XCursor themes
+            let icon_name = cursor_id.to_lowercase();
+
+            let icon = match icon_name.as_str() {
+                "default" | "left_ptr" => CursorIcon::Default,
+                "move" | "fleur" | "move" => CursorIcon::AllScroll,
+                "text" | "xterm" | "ibeam" => CursorIcon::Text,
+                "wait" | "watch" => CursorIcon::Wait,
+                "progress" | "left_ptr_watch" => CursorIcon::Progress,
+                "crosshair" | "cross_reverse" => CursorIcon::Crosshair,
+                "nwse-resize" | "top_left_corner" => CursorIcon::NwResize,
+                "pointer" | "hand" | "hand1" | "hand2" => CursorIcon::Pointer,
+                "grab" | "openhand" => CursorIcon::Grab,
+                "grabbing" | "grabbing" | "closedhand" => CursorIcon::Grabbing,
+                "not-allowed" | "circle" | "dnd-none" => CursorIcon::NotAllowed,
+                "help" | "question_arrow" => CursorIcon::Help,
+                "copy" => CursorIcon::Copy,
+                "alias" => CursorIcon::Alias,
+                "cell" => CursorIcon::Cell,
+                "vertical-text" => CursorIcon::VerticalText,
+                "context-menu" => CursorIcon::ContextMenu,
+                "no-drop" => CursorIcon::NoDrop,
+                "col-resize" | "sb_h_double_arrow" => CursorIcon::WResize,
+                "row-resize" | "sb_v_double_arrow" => CursorIcon::NResize,
+                "ew-resize" => CursorIcon::WResize,
+                "ns-resize" => CursorIcon::NResize,
+                "nesw-resize" | "top_right_corner" => CursorIcon::NeResize,
+                "swne-resize" | "bottom_left_corner" => CursorIcon::SwResize,
+                "sene-resize" | "bottom_right_corner" => CursorIcon::SeResize,
+                "zoom-in" => CursorIcon::ZoomIn,
+                "zoom-out" => CursorIcon::ZoomOut,
+                _ => {
+                    debug!("No CursorIcon match for cursor ID: '{}'", cursor_id);
+                    continue;
+                }
+            };
+
+            mapping.insert(icon, cursor_id.clone());
+            info!(
+                "Mapped cursor icon {:?} (name: '{}') to vector cursor '{}'",
+                icon, cursor_id, cursor_id
+            );
+        }
+
+        info!("Mapped {} cursor icons to vector cursors", mapping.len());
+        mapping
+    }
+
     /// Reload the cursor theme.
     pub fn reload(&mut self, theme: &str, size: u8) {
         Self::ensure_env(theme, size);
         self.theme = CursorTheme::load(theme);
         self.size = size;
         self.named_cursor_cache.get_mut().clear();
+
+        if let Some(path) = self.vector_theme_path.clone() {
+            if let Err(err) = self.set_vector_theme(path) {
+                warn!("error reloading vector cursor theme: {err:?}");
+            }
+        }
+    }
+
+    /// Registers a cursor under `name`, replacing any custom cursor with the same name.
+    ///
+    /// The cursor shows once activated with [`Self::push_custom_cursor`].
+    pub fn register_custom(&mut self, name: impl Into<String>, cursor: CustomCursor) {
+        self.custom.register(name.into(), cursor);
+    }
+
+    /// Unregisters a custom cursor, deactivating it.
+    pub fn unregister_custom(&mut self, name: &str) -> Option<CustomCursor> {
+        self.custom.unregister(name)
+    }
+
+    /// Shows a registered custom cursor over every other cursor until it's removed.
+    ///
+    /// Custom cursors form a stack, the most recently pushed one is shown.
+    pub fn push_custom_cursor(&mut self, name: &str) -> anyhow::Result<()> {
+        self.custom.push(name, self.clock.now_unadjusted())
+    }
+
+    /// Stops showing a custom cursor, revealing the one below it on the stack.
+    pub fn remove_custom_cursor(&mut self, name: &str) {
+        self.custom.remove(name);
+    }
+
+    /// Sets the cursor shown when the theme has no default cursor.
+    pub fn set_fallback(&mut self, fallback: CursorFallback) {
+        if self.fallback != fallback {
+            self.fallback = fallback;
+            self.named_cursor_cache.get_mut().clear();
+        }
+    }
+
+    /// Registers a callback for vector cursor animation events, e.g. to hide a cursor once its
+    /// exit animation completes.
+    pub fn add_animation_observer(&mut self, observer: AnimationObserver) {
+        if let Some(vector) = &self.vector_system {
+            vector.animator.add_observer(observer.clone());
+        }
+        self.animation_observers.push(observer);
+    }
+
+    /// Registers a transition effect that themes can use with `effect = "<name>"`, replacing an
+    /// effect of the same name.
+    pub fn register_transition_effect(
+        &mut self,
+        name: impl Into<String>,
+        effect: Arc<dyn TransitionEffect>,
+    ) {
+        self.transition_effects.register(name, effect);
+    }
+
+    /// Sets the color of `currentColor` in SVG cursors, `None` for the theme's `foreground`.
+    pub fn set_foreground_color(&mut self, color: Option<String>) -> anyhow::Result<()> {
+        if let Some(color) = &color {
+            if !is_valid_foreground(color) {
+                anyhow::bail!("invalid cursor foreground color {color:?}");
+            }
+        }
+
+        debug!("setting cursor foreground color to {color:?}");
+        if let Some(vector) = &mut self.vector_system {
+            vector.store.set_foreground(color.clone());
+        }
+        self.foreground = color;
+        Ok(())
+    }
+
+    /// Switches vector cursors with light and dark artwork to the one for `scheme`.
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        debug!("setting cursor color scheme to {scheme:?}");
+        if let Some(vector) = &mut self.vector_system {
+            vector.store.set_color_scheme(scheme);
+        }
+        self.color_scheme = scheme;
+    }
+
+    /// Mirrors vector cursors horizontally if they aren't, and back otherwise.
+    pub fn toggle_mirror(&mut self) {
+        let Some(vector) = &mut self.vector_system else {
+            return;
+        };
+        let mirror = !vector.store.is_mirrored();
+        debug!("setting cursor mirroring to {mirror}");
+        vector.store.set_mirror(Some(mirror));
+        self.mirror = Some(mirror);
+    }
+
+    /// Freezes vector cursor animations at their current frame, or continues them.
+    pub fn set_animations_paused(&mut self, paused: bool) {
+        self.animations_paused = paused;
+        self.sync_animations_paused();
+    }
+
+    /// Freezes the vector cursor at `frame` of its animation, or lets it play again with `None`,
+    /// for debugging frames that render wrong.
+    pub fn freeze_animation_at_frame(&mut self, frame: Option<u32>) {
+        let Some(vector) = &self.vector_system else {
+            return;
+        };
+        Self::advance_animation(vector, self.clock.now_unadjusted());
+        vector.animator.freeze_at_frame(frame);
+    }
+
+    /// Steps the vector cursor `delta` frames forward, or back if negative, freezing it at its
+    /// current frame first.
+    pub fn step_animation_frame(&mut self, delta: i32) {
+        let Some(vector) = &self.vector_system else {
+            return;
+        };
+        Self::advance_animation(vector, self.clock.now_unadjusted());
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Static => return,
+            TransitionState::Animated { cursor_id, .. } => cursor_id.clone(),
+            // Freezing skips the transition.
+            TransitionState::Transitioning { to_id, .. }
+            | TransitionState::Finishing { to_id, .. } => to_id.clone(),
+        };
+        let scale = vector.last_scale.get().unwrap_or(1.);
+        match vector.store.get_renderer_at(&cursor_id, scale) {
+            Ok(renderer) => vector.animator.step_frame(delta, &*renderer),
+            Err(err) => warn!("error stepping cursor '{cursor_id}': {err:?}"),
+        }
+    }
+
+    /// Caps the frame rate of vector cursor animations, e.g. to save power on battery.
+    pub fn set_max_animation_fps(&mut self, max_fps: Option<u32>) {
+        if let Some(vector) = &self.vector_system {
+            vector.animator.set_max_fps(max_fps);
+        }
+        self.max_animation_fps = max_fps;
+    }
+
+    /// Tells the manager whether the monitors are on. Animations stop while they are off.
+    pub fn set_monitors_active(&mut self, active: bool) {
+        self.monitors_active = active;
+        self.sync_animations_paused();
+    }
+
+    /// Whether the cursor can't be seen, hidden by the client or with the monitors off.
+    fn is_invisible(&self) -> bool {
+        matches!(self.current_cursor, CursorImageStatus::Hidden) || !self.monitors_active
+    }
+
+    /// Pauses the vector cursor animation while it's paused through IPC or can't be seen, so
+    /// that an invisible cursor doesn't keep advancing and asking for redraws.
+    fn sync_animations_paused(&self) {
+        let Some(vector) = &self.vector_system else {
+            return;
+        };
+        let paused = self.animations_paused || self.is_invisible();
+        if paused == vector.animator.is_paused() {
+            return;
+        }
+        // Time up to now counts as played before pausing, and as skipped before resuming.
+        Self::advance_animation(vector, self.clock.now_unadjusted());
+        if paused {
+            vector.animator.pause();
+        } else {
+            vector.animator.resume();
+        }
+    }
+
+    /// Shows vector cursors still on their first frame and switches them without transitions.
+    pub fn set_reduce_motion(&mut self, reduce: bool) {
+        if let Some(vector) = &self.vector_system {
+            vector.animator.set_reduce_motion(reduce);
+        }
+        self.reduce_motion = reduce;
+    }
+
+    /// Switches to another vector cursor theme.
+    ///
+    /// The switch is transactional: if the new theme fails to load, the current one stays
+    /// active. Otherwise an ongoing animation or transition continues in the new theme for the
+    /// same icon, at the same phase.
+    pub fn set_vector_theme(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let mut new = Self::load_vector_system(&path, self.size)?;
+        if self.foreground.is_some() {
+            new.store.set_foreground(self.foreground.clone());
+        }
+        new.store.set_color_scheme(self.color_scheme);
+        if self.mirror.is_some() {
+            new.store.set_mirror(self.mirror);
+        }
+        for observer in &self.animation_observers {
+            new.animator.add_observer(observer.clone());
+        }
+        if self.animations_paused || self.is_invisible() {
+            new.animator.pause();
+        }
+        let icon_to_vector_id = Self::map_vector_icons(new.store.get_config());
+
+        // Translates a cursor ID of the current theme to the new theme.
+        let translate = |cursor_id: &CursorId| -> Option<CursorId> {
+            self.icon_to_vector_id
+                .iter()
+                .find(|(_, id)| *id == cursor_id)
+                .and_then(|(icon, _)| icon_to_vector_id.get(icon).cloned())
+                .or_else(|| new.store.get_config().cursor_id(cursor_id))
+        };
+
+        let snapshot = self.vector_system.as_ref().and_then(|old| {
+            let renderer = match &*old.animator.current_state() {
+                TransitionState::Animated { cursor_id, .. } => {
+                    old.store.get_renderer(cursor_id).ok()
+                }
+                _ => None,
+            };
+            old.animator.snapshot(renderer.as_deref())
+        });
+
+        let snapshot = snapshot.and_then(|mut snapshot| {
+            snapshot.cursor_id = translate(&snapshot.cursor_id)?;
+            if let Some((from_id, progress)) = snapshot.transition.take() {
+                snapshot.transition = translate(&from_id).map(|from_id| (from_id, progress));
+            }
+            Some(snapshot)
+        });
+
+        if let Some(snapshot) = snapshot {
+            debug!("migrating vector cursor animation: {snapshot:?}");
+            let renderer = new.store.get_renderer(&snapshot.cursor_id).ok();
+            new.animator.restore(&snapshot, renderer.as_deref());
+
+            // An idle cursor still wakes up once the pointer moves.
+            let idle_from = self
+                .vector_system
+                .as_ref()
+                .and_then(|old| old.idle_from.borrow().clone());
+            new.idle_from
+                .replace(idle_from.as_ref().and_then(&translate));
+        } else if let CursorImageStatus::Named(icon) = &self.current_cursor {
+            if let Some(cursor_id) = icon_to_vector_id.get(icon) {
+                new.animator.set_cursor_now(cursor_id)?;
+            }
+        }
+
+        if self.reduce_motion {
+            new.animator.set_reduce_motion(true);
+        }
+        if self.max_animation_fps.is_some() {
+            new.animator.set_max_fps(self.max_animation_fps);
+        }
+        let frozen_frame = self
+            .vector_system
+            .as_ref()
+            .and_then(|old| old.animator.frozen_frame());
+        if frozen_frame.is_some() {
+            new.animator.freeze_at_frame(frozen_frame);
+        }
+
+        self.vector_system = Some(new);
+        self.icon_to_vector_id = icon_to_vector_id;
+        self.vector_theme_path = Some(path);
+        Ok(())
+    }
+
+    /// Directory of the vector cursor theme, if one is loaded.
+    pub fn vector_theme_path(&self) -> Option<&Path> {
+        self.vector_theme_path.as_deref()
+    }
+
+    /// Loads the `changed` files of the vector cursor theme again, as paths relative to its
+    /// directory.
+    ///
+    /// Changed artwork only drops the cursors and transitions drawn from it. Any other file, like
+    /// `theme.toml`, reloads the whole theme like [`Self::set_vector_theme`], which keeps the
+    /// animation and the current theme if the new one fails to load.
+    pub fn reload_vector_files(&mut self, changed: &[PathBuf]) -> anyhow::Result<()> {
+        let Some(vector) = &self.vector_system else {
+            return Ok(());
+        };
+        if changed.iter().all(|file| vector.store.draws_from(file)) {
+            vector.store.invalidate_files(changed);
+            return Ok(());
+        }
+
+        let Some(path) = self.vector_theme_path.clone() else {
+            return Ok(());
+        };
+        self.set_vector_theme(path)
     }
 
     fn load_vector_system(path: &PathBuf, size: u8) -> anyhow::Result<VectorCursorSystem> {
-        use crate::cursor::vector::CursorThemeConfig;
         use std::fs;
 
         debug!(
@@ -177,10 +611,49 @@ impl CursorManager {
             config.cursors.len()
         );
 
-        let store = VectorCursorStore::new(path.clone(), config, size)?;
+        let remote = RemoteConnection::from_env();
+
+        for (cursor_id, cursor) in &config.cursors {
+            if remote.is_none() && !cursor.format.is_supported() {
+                warn!(
+                    "vector cursor '{cursor_id}' can't be shown: \
+                     niri was built without {:?} cursor support",
+                    cursor.format
+                );
+            }
+        }
+
+        #[cfg(feature = "scripting")]
+        let script = match &config.script {
+            Some(file) => Some(RefCell::new(CursorScript::load(&path.join(file))?)),
+            None => None,
+        };
+        #[cfg(not(feature = "scripting"))]
+        if config.script.is_some() {
+            warn!("ignoring vector cursor theme script: niri was built without scripting support");
+        }
+
+        let mut store = VectorCursorStore::new(path.clone(), config, size)?;
+        if let Some(remote) = remote {
+            info!(
+                "rendering vector cursors with the cursor server at {}",
+                remote.socket_path().display()
+            );
+            store = store.with_remote(remote);
+        }
         let animator = CursorAnimator::new(store.get_config().clone(), size);
 
-        Ok(VectorCursorSystem { store, animator })
+        Ok(VectorCursorSystem {
+            store,
+            animator,
+            stats: RefCell::new(RenderStats::default()),
+            last_scale: Cell::new(None),
+            idle_from: RefCell::new(None),
+            smeared: Cell::new(false),
+            #[cfg(feature = "scripting")]
+            script,
+            scripted: RefCell::new(ScriptOutput::default()),
+        })
     }
 
     /// Checks if the cursor WlSurface is alive, and if not, cleans it up.
@@ -193,25 +666,61 @@ impl CursorManager {
     }
 
     /// Get the current rendering cursor.
-    pub fn get_render_cursor(&self, scale: i32) -> RenderCursor {
-        // Try vector system first
-        if let Some(vector) = &self.vector_system {
-            if let Ok(render_cursor) = self.get_vector_cursor(vector, scale) {
+    ///
+    /// This runs for every rendered frame, so the result borrows from the manager instead of
+    /// cloning the current cursor. Vector cursors are converted to the color space of `output`,
+    /// see [`CursorManager::update_output_colors`].
+    pub fn get_render_cursor(
+        &self,
+        scale: output::Scale,
+        output: Option<&str>,
+    ) -> RenderCursor<'_> {
+        // Custom cursors registered by the compositor take priority over everything.
+        if let Some((custom, activated)) = self.custom.top() {
+            let elapsed = self.clock.now_unadjusted().saturating_sub(activated);
+            match custom.render(elapsed, scale.integer_scale()) {
+                Ok((buffer, hotspot)) => {
+                    return RenderCursor::Vector {
+                        hotspot,
+                        buffer,
+                        size: None,
+                        opacity: 1.,
+                    }
+                }
+                Err(err) => warn!("error rendering custom cursor: {err:?}"),
+            }
+        }
+
+        // Try vector system first, unless the client hid the cursor.
+        let hidden = matches!(self.current_cursor, CursorImageStatus::Hidden);
+        if let Some(vector) = self.vector_system.as_ref().filter(|_| !hidden) {
+            let render_scale = vector
+                .store
+                .get_config()
+                .scale_snapping
+                .snap(scale.fractional_scale());
+            self.check_registered_scale(vector, render_scale.snapped);
+            if let Ok(render_cursor) = self.get_vector_cursor(vector, render_scale, output) {
                 return render_cursor;
             }
         }
 
+        let scale = scale.integer_scale();
+
         // Try to get the custom cursor surface from curBuf
         if let Some(surface) = get_cursor_surface() {
             let hotspot = get_cursor_hotspot();
-            return RenderCursor::Surface { hotspot, surface };
+            return RenderCursor::Surface {
+                hotspot,
+                surface: Cow::Owned(surface),
+            };
         }
 
         // Fallback to original logic if no custom surface is available
-        match self.current_cursor.clone() {
+        match &self.current_cursor {
             CursorImageStatus::Hidden => RenderCursor::Hidden,
             CursorImageStatus::Surface(surface) => {
-                let hotspot = with_states(&surface, |states| {
+                let hotspot = with_states(surface, |states| {
                     states
                         .data_map
                         .get::<CursorImageSurfaceData>()
@@ -221,20 +730,30 @@ impl CursorManager {
                         .hotspot
                 });
 
-                RenderCursor::Surface { hotspot, surface }
+                RenderCursor::Surface {
+                    hotspot,
+                    surface: Cow::Borrowed(surface),
+                }
             }
-            CursorImageStatus::Named(icon) => self.get_render_cursor_named(icon, scale),
+            CursorImageStatus::Named(icon) => self.get_render_cursor_named(*icon, scale),
         }
     }
 
     fn get_vector_cursor(
         &self,
         vector: &VectorCursorSystem,
-        scale: i32,
-    ) -> Result<RenderCursor, anyhow::Error> {
-        use crate::cursor::vector::types::TransitionState;
+        render_scale: RenderScale,
+        output: Option<&str>,
+    ) -> Result<RenderCursor<'static>, anyhow::Error> {
+        debug!("get_vector_cursor called with scale: {:?}", render_scale);
+
+        let now = self.clock.now_unadjusted();
+        let velocity = self.velocity.velocity(now);
+        vector.animator.set_pointer_velocity(velocity);
+        Self::advance_animation(vector, now);
+        self.update_idle(vector, now);
+        let scripted = vector.scripted.borrow().clone();
 
-        debug!("get_vector_cursor called with scale: {}", scale);
         let state = vector.animator.current_state();
         debug!("Current animator state: {:?}", state);
 
@@ -251,21 +770,724 @@ impl CursorManager {
                 debug!("State is Transitioning to cursor: '{}'", to_id);
                 to_id.clone()
             }
+            TransitionState::Finishing { cursor_id, .. } => cursor_id.clone(),
         };
 
         debug!("Getting renderer for cursor: '{}'", cursor_id);
-        let renderer = vector.store.get_renderer(&cursor_id)?;
-        debug!("Renderer obtained, rendering frame 0");
-        let frame_data = renderer.render_frame(0, scale)?;
+        let renderer = vector
+            .store
+            .get_renderer_at(&cursor_id, render_scale.snapped)?;
+        let animated = matches!(
+            &*state,
+            TransitionState::Animated { .. } | TransitionState::Finishing { .. }
+        );
+        let frame = if animated {
+            vector.animator.current_frame(&*renderer)
+        } else {
+            vector.animator.entry_frame(&cursor_id, &*renderer)
+        };
+        debug!("Renderer obtained, rendering frame {frame}");
+        let start = Instant::now();
+        // Interpolated cursors render between frames, the history records the frame shown.
+        let mut frame_data = if animated && vector.animator.interpolates(&cursor_id) {
+            let position = vector.animator.current_position(&*renderer);
+            renderer.render_frame_at(position, render_scale.snapped)?
+        } else {
+            renderer.render_frame(frame, render_scale.snapped)?
+        };
+        if let TransitionState::Transitioning {
+            from_id,
+            progress: elapsed,
+            ..
+        } = &*state
+        {
+            let progress = vector.animator.transition_progress().unwrap_or(1.);
+            frame_data = self
+                .composite_transition(vector, from_id, &cursor_id, progress, *elapsed, frame_data);
+        }
+        frame_data = self.apply_motion_blur(vector, frame_data, now);
+        self.history
+            .borrow_mut()
+            .record(now, &cursor_id, frame, &frame_data);
+        frame_data = self.convert_to_output(output, frame_data);
+        vector.last_scale.set(Some(render_scale.snapped));
+        vector
+            .stats
+            .borrow_mut()
+            .record(&cursor_id, start.elapsed());
         debug!("Frame rendered successfully");
 
+        let bytes = frame_data.size.w as usize * frame_data.size.h as usize * 4;
+        self.bandwidth
+            .borrow_mut()
+            .record(UploadPath::Vector, get_monotonic_time(), bytes);
+
+        let mut hotspot = render_scale.hotspot_to_logical(frame_data.hotspot);
+        let mut size = None;
+        let scale_bias = vector
+            .store
+            .get_config()
+            .get_cursor(&cursor_id)
+            .and_then(|def| def.scale_bias)
+            .unwrap_or(1.)
+            * scripted.scale.unwrap_or(1.);
+        // Buffers rendered at fractional scales don't know their logical size.
+        if scale_bias != 1. || render_scale.snapped.fract() != 0. {
+            hotspot = hotspot.upscale(scale_bias);
+            size = Some(frame_data.logical_size().upscale(scale_bias).to_i32_round());
+        }
+
         Ok(RenderCursor::Vector {
-            hotspot: frame_data.hotspot,
+            hotspot,
             buffer: frame_data.buffer,
+            size,
+            opacity: frame_data.opacity * scripted.opacity.unwrap_or(1.),
         })
     }
 
-    fn get_render_cursor_named(&self, icon: CursorIcon, scale: i32) -> RenderCursor {
+    /// Whether the vector cursor changes on its own, `None` if no vector cursor is shown.
+    ///
+    /// Finished `once` animations don't, so they stop causing redraws.
+    fn is_vector_cursor_animating(vector: &VectorCursorSystem) -> Option<bool> {
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Static => return None,
+            TransitionState::Animated { cursor_id, .. } => Some(cursor_id.clone()),
+            _ => None,
+        };
+        let scale = vector.last_scale.get().unwrap_or(1.);
+        let renderer =
+            cursor_id.and_then(|cursor_id| vector.store.get_renderer_at(&cursor_id, scale).ok());
+        Some(vector.animator.is_animating(renderer.as_deref()))
+    }
+
+    /// Advances the animation of `vector` to `now`.
+    ///
+    /// Runs before every cursor switch too, so the time until then counts towards the cursor
+    /// being left rather than the new one. The theme script's `on_tick` runs once for every step
+    /// the animation advances, however many outputs draw the cursor.
+    fn advance_animation(vector: &VectorCursorSystem, now: Duration) {
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Animated { cursor_id, .. }
+            | TransitionState::Finishing { cursor_id, .. } => Some(cursor_id.clone()),
+            _ => None,
+        };
+        // Loops and completions are found through the renderer of the animated cursor.
+        let scale = vector.last_scale.get().unwrap_or(1.);
+        let renderer =
+            cursor_id.and_then(|cursor_id| vector.store.get_renderer_at(&cursor_id, scale).ok());
+        if !vector.animator.tick(now, renderer.as_deref()) {
+            return;
+        }
+
+        let scripted = vector.run_script(ScriptEvent::Tick {
+            time_ms: now.as_millis() as u64,
+            velocity: vector.animator.pointer_velocity(),
+        });
+        if let Some(cursor_id) = &scripted.cursor {
+            if let Err(err) = vector.animator.set_cursor_now(cursor_id) {
+                warn!("error switching to scripted cursor '{cursor_id}': {err:?}");
+            }
+        }
+        *vector.scripted.borrow_mut() = scripted;
+    }
+
+    /// Composites the frame of the cursor a transition leaves with `to`, if the transition uses
+    /// an effect or morphs, or replaces `to` with the Lottie animation the transition plays.
+    ///
+    /// Morphs need the outline of both cursors; between cursors that have none, like Lottie
+    /// ones, they cross-fade instead. `progress` is eased, while `elapsed` is the fraction of
+    /// the duration that passed.
+    fn composite_transition(
+        &self,
+        vector: &VectorCursorSystem,
+        from_id: &str,
+        to_id: &str,
+        progress: f32,
+        elapsed: f32,
+        to: RenderedFrame,
+    ) -> RenderedFrame {
+        let config = vector.store.get_config();
+        let Some((transition, reversed)) = config.get_transition_or_reverse(from_id, to_id) else {
+            return to;
+        };
+        // A reversed transition plays backwards.
+        let reversed_transition;
+        let transition = if reversed {
+            reversed_transition = TransitionConfig {
+                rotate_deg: -transition.rotate_deg,
+                ..transition.clone()
+            };
+            &reversed_transition
+        } else {
+            transition
+        };
+        if transition.effect.is_none()
+            && matches!(transition.transition_type, TransitionType::Lottie)
+        {
+            // Without a hotspot of its own, the animation carries the one of the source cursor
+            // over to the target's.
+            let hotspot = if transition.hotspot.is_none() {
+                Self::exit_hotspot(vector, from_id, to_id, to.scale).map(|from| {
+                    let lerp = |a: i32, b: i32| (a as f32 + (b - a) as f32 * elapsed).round();
+                    Point::from((
+                        lerp(from.x, to.hotspot.x) as i32,
+                        lerp(from.y, to.hotspot.y) as i32,
+                    ))
+                })
+            } else {
+                None
+            };
+            let elapsed = if reversed { 1. - elapsed } else { elapsed };
+            return Self::lottie_transition(vector, transition, elapsed, hotspot, to);
+        }
+        let morph = transition.effect.is_none()
+            && matches!(transition.transition_type, TransitionType::Morph);
+        let effect = if morph {
+            self.transition_effects.get("crossfade").cloned()
+        } else {
+            self.transition_effects.for_transition(transition)
+        };
+        let Some(effect) = effect else {
+            return to;
+        };
+
+        let composited = vector
+            .store
+            .get_renderer_at(from_id, to.scale)
+            .and_then(|renderer| {
+                let frame = vector.animator.exit_frame(from_id, to_id, &*renderer);
+                if morph {
+                    if let Some(morphed) =
+                        Self::morph_transition(vector, &*renderer, frame, to_id, progress, &to)
+                    {
+                        return Ok(morphed);
+                    }
+                }
+                let from = renderer.render_frame(frame, to.scale)?;
+                effect.composite(&from, &to, progress)
+            });
+        match composited {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!("error compositing transition from '{from_id}' to '{to_id}': {err:?}");
+                to
+            }
+        }
+    }
+
+    /// Hotspot of the frame the cursor `from_id` leaves a transition to `to_id` on, in pixels of
+    /// a buffer at `scale`.
+    fn exit_hotspot(
+        vector: &VectorCursorSystem,
+        from_id: &str,
+        to_id: &str,
+        scale: f64,
+    ) -> Option<Point<i32, Physical>> {
+        let renderer = vector.store.get_renderer_at(from_id, scale).ok()?;
+        let frame = vector.animator.exit_frame(from_id, to_id, &*renderer);
+        Some(renderer.frame_hotspot(frame, scale))
+    }
+
+    /// The frame of the Lottie animation `transition` plays, stretched over its duration, or
+    /// `to` if the animation can't be played.
+    ///
+    /// `hotspot` replaces the one of the animation, clamped to its frame.
+    fn lottie_transition(
+        vector: &VectorCursorSystem,
+        transition: &TransitionConfig,
+        elapsed: f32,
+        hotspot: Option<Point<i32, Physical>>,
+        to: RenderedFrame,
+    ) -> RenderedFrame {
+        let played = vector
+            .store
+            .get_transition_renderer(transition)
+            .and_then(|renderer| {
+                // Played once, its frames spread evenly over the transition.
+                let range = FrameRange::new(0, renderer.total_frames());
+                let duration = Duration::from_millis(u64::from(transition.duration_ms));
+                let scheduler = range.scheduler(duration / range.len(), LoopMode::Once);
+                let elapsed = duration.mul_f32(elapsed.clamp(0., 1.));
+                renderer.render_frame(range.frame_at(&scheduler, elapsed), to.scale)
+            });
+        match played {
+            Ok(mut frame) => {
+                frame.opacity = to.opacity;
+                if let Some(hotspot) = hotspot {
+                    frame.hotspot = Point::from((
+                        hotspot.x.clamp(0, (frame.size.w - 1).max(0)),
+                        hotspot.y.clamp(0, (frame.size.h - 1).max(0)),
+                    ));
+                }
+                frame
+            }
+            Err(err) => {
+                warn!("error playing transition {:?}: {err:?}", transition.file);
+                to
+            }
+        }
+    }
+
+    /// Morphs the outline of `from` at `frame` into the one of `to`, `None` if either cursor has
+    /// no outline.
+    fn morph_transition(
+        vector: &VectorCursorSystem,
+        from: &dyn VectorRenderer,
+        frame: u32,
+        to_id: &str,
+        progress: f32,
+        to: &RenderedFrame,
+    ) -> Option<RenderedFrame> {
+        let renderer = vector.store.get_renderer_at(to_id, to.scale).ok()?;
+        let to_frame = vector.animator.entry_frame(to_id, &*renderer);
+        let from_outline = from.outline(frame, to.scale)?;
+        let to_outline = renderer.outline(to_frame, to.scale)?;
+
+        let mut morphed = morph::morph(&from_outline, &to_outline, progress, to.scale);
+        morphed.opacity = to.opacity;
+        Some(morphed)
+    }
+
+    /// Smears the frame along the pointer motion, if the theme uses motion blur and motion
+    /// isn't reduced.
+    fn apply_motion_blur(
+        &self,
+        vector: &VectorCursorSystem,
+        frame: RenderedFrame,
+        now: Duration,
+    ) -> RenderedFrame {
+        vector.smeared.set(false);
+        if self.reduce_motion {
+            return frame;
+        }
+        let Some(blur) = &vector.store.get_config().motion_blur else {
+            return frame;
+        };
+        let length = blur.length(self.velocity.velocity(now));
+        match self.velocity.direction(now) {
+            Some(direction) if length > 0. => {
+                vector.smeared.set(true);
+                motion_blur::smear(&frame, direction, length * frame.scale)
+            }
+            _ => frame,
+        }
+    }
+
+    /// Converts the frame to the color space of `output`, reusing the last conversion for the
+    /// output while the frame stays the same.
+    fn convert_to_output(&self, output: Option<&str>, frame: RenderedFrame) -> RenderedFrame {
+        #[cfg(feature = "effects")]
+        if let Some((name, (_, Some(transform)))) =
+            output.and_then(|output| self.output_colors.get_key_value(output))
+        {
+            let mut converted_frames = self.converted_frames.borrow_mut();
+            if let Some((source, converted)) = converted_frames.get(name) {
+                if source.is_same(&frame) {
+                    return converted.clone();
+                }
+            }
+
+            let mut pixels = frame.read_pixels();
+            transform.apply(&mut pixels);
+            let buffer = MemoryRenderBuffer::from_slice(
+                &pixels,
+                Fourcc::Argb8888,
+                (frame.size.w, frame.size.h),
+                crate::cursor::vector::scale::memory_buffer_scale(frame.scale),
+                Transform::Normal,
+                None,
+            );
+            let mut converted = RenderedFrame::new(
+                buffer,
+                frame.size,
+                frame.scale,
+                frame.hotspot,
+                frame.duration,
+            );
+            converted.opacity = frame.opacity;
+            converted_frames.insert(name.clone(), (frame, converted.clone()));
+            return converted;
+        }
+
+        #[cfg(not(feature = "effects"))]
+        let _ = output;
+
+        frame
+    }
+
+    /// Whether the vector cursor is smeared or moves fast enough to be, and needs redraws until
+    /// the smear is gone.
+    ///
+    /// Pointer motion below the blur threshold doesn't smear, so it doesn't ask for redraws.
+    fn is_motion_blurred(&self) -> bool {
+        if self.reduce_motion {
+            return false;
+        }
+        let Some(vector) = &self.vector_system else {
+            return false;
+        };
+        let Some(blur) = &vector.store.get_config().motion_blur else {
+            return false;
+        };
+        let speed = self.velocity.velocity(self.clock.now_unadjusted());
+        vector.smeared.get() || blur.length(speed) > 0.
+    }
+
+    /// Time the pointer started resting at, after which the vector cursor switches to its idle
+    /// variant, `None` if it doesn't have one or already switched.
+    fn idle_deadline(&self, vector: &VectorCursorSystem) -> Option<Duration> {
+        if vector.idle_from.borrow().is_some() {
+            return None;
+        }
+        let config = vector.store.get_config();
+        match &*vector.animator.current_state() {
+            TransitionState::Animated { cursor_id, .. } => config.idle_variant(cursor_id)?,
+            _ => return None,
+        };
+        let after = Duration::from_millis(u64::from(config.idle.after_ms));
+        Some(self.last_activity + after)
+    }
+
+    /// Switches the vector cursor to its idle variant once the pointer rested long enough.
+    fn update_idle(&self, vector: &VectorCursorSystem, now: Duration) {
+        match self.idle_deadline(vector) {
+            Some(deadline) if now >= deadline => (),
+            _ => return,
+        }
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Animated { cursor_id, .. } => cursor_id.clone(),
+            _ => return,
+        };
+        let Some(variant) = vector.store.get_config().idle_variant(&cursor_id) else {
+            return;
+        };
+
+        debug!("pointer idle, switching cursor '{cursor_id}' to '{variant}'");
+        if let Err(err) = vector.animator.set_cursor_now(&variant) {
+            warn!("error switching to idle cursor '{variant}': {err:?}");
+        }
+        vector.idle_from.replace(Some(cursor_id));
+    }
+
+    /// Records a pointer motion by `delta` logical pixels for velocity tracking.
+    ///
+    /// Wakes an idle vector cursor up.
+    pub fn pointer_motion(&mut self, delta: Point<f64, Logical>) {
+        let now = self.clock.now_unadjusted();
+        self.velocity.motion_delta(delta, now);
+        self.last_activity = now;
+
+        if let Some(vector) = &self.vector_system {
+            if let Some(cursor_id) = vector.idle_from.take() {
+                debug!("pointer moved, switching back to cursor '{cursor_id}'");
+                Self::advance_animation(vector, now);
+                if let Err(err) = vector.animator.set_cursor(&cursor_id) {
+                    warn!("error switching back from idle cursor: {err:?}");
+                }
+            }
+        }
+
+        if self.caches_trimmed {
+            self.caches_trimmed = false;
+            self.rewarm_vector_caches();
+        }
+    }
+
+    /// Frees cached vector cursor frames once the pointer was idle for [`IDLE_TRIM_AFTER`].
+    ///
+    /// Meant to be called periodically. The caches are refilled on the next pointer motion.
+    pub fn trim_caches_if_idle(&mut self) {
+        let idle = self
+            .clock
+            .now_unadjusted()
+            .saturating_sub(self.last_activity);
+        if self.caches_trimmed || idle < IDLE_TRIM_AFTER {
+            return;
+        }
+
+        if let Some(vector) = &self.vector_system {
+            debug!("pointer idle for {idle:?}, trimming vector cursor caches");
+            vector.store.trim_caches();
+        }
+        self.caches_trimmed = true;
+    }
+
+    /// Sets the fractional scales of the connected outputs.
+    ///
+    /// The vector cursor is cached and pre-rendered at the render scales these snap to, see
+    /// [`CursorManager::prerender_some`], and rendering at any other scale is warned about.
+    pub fn update_scales(&mut self, scales: &[f64]) {
+        let mut scales = scales.to_vec();
+        scales.sort_by(f64::total_cmp);
+        scales.dedup();
+        if scales == self.output_scales {
+            return;
+        }
+
+        let old = self.render_scales();
+        self.output_scales = scales;
+        self.unregistered_scales.get_mut().clear();
+        debug!("output scales changed to {:?}", self.output_scales);
+
+        for scale in self.render_scales() {
+            if !old.contains(&scale) {
+                self.queue_prerender(scale, false);
+            }
+        }
+    }
+
+    /// Sets the color spaces of the connected outputs by name, `None` for outputs showing
+    /// cursors as they are.
+    ///
+    /// Only the color spaces that changed are loaded again.
+    pub fn update_output_colors(&mut self, colors: &[(String, Option<CursorColor>)]) {
+        let mut output_colors = HashMap::new();
+        for (name, color) in colors {
+            let Some(color) = color else {
+                continue;
+            };
+
+            if let Some(old) = self.output_colors.remove(name) {
+                if old.0 == *color {
+                    output_colors.insert(name.clone(), old);
+                    continue;
+                }
+            }
+
+            let transform = if !cfg!(feature = "effects") {
+                warn!(
+                    "ignoring cursor-color of output {name}: \
+                     niri was built without cursor effects support"
+                );
+                None
+            } else {
+                match ColorTransform::load(color) {
+                    Ok(transform) => Some(Arc::new(transform)),
+                    Err(err) => {
+                        warn!("ignoring cursor-color of output {name}: {err:#}");
+                        None
+                    }
+                }
+            };
+            output_colors.insert(name.clone(), (color.clone(), transform));
+        }
+
+        self.output_colors = output_colors;
+        self.converted_frames.get_mut().clear();
+    }
+
+    /// Scales the vector cursor is rendered at for the connected outputs, after snapping.
+    fn render_scales(&self) -> Vec<f64> {
+        let Some(vector) = &self.vector_system else {
+            return Vec::new();
+        };
+        let snapping = &vector.store.get_config().scale_snapping;
+        let mut scales: Vec<f64> = self
+            .output_scales
+            .iter()
+            .map(|scale| snapping.snap(*scale).snapped)
+            .collect();
+        scales.sort_by(f64::total_cmp);
+        scales.dedup();
+        scales
+    }
+
+    /// Warns once about rendering at a scale no connected output uses.
+    fn check_registered_scale(&self, vector: &VectorCursorSystem, scale: f64) {
+        if self.output_scales.is_empty() {
+            return;
+        }
+        let snapping = &vector.store.get_config().scale_snapping;
+        if self
+            .output_scales
+            .iter()
+            .any(|output_scale| snapping.snap(*output_scale).snapped == scale)
+        {
+            return;
+        }
+
+        let mut warned = self.unregistered_scales.borrow_mut();
+        if !warned.contains(&scale) {
+            warn!(
+                "rendering vector cursor at scale {scale}, which no output uses ({:?})",
+                self.output_scales
+            );
+            warned.push(scale);
+        }
+    }
+
+    /// Renders the shown frame of the current vector cursor again, and queues its other frames
+    /// for [`CursorManager::prerender_some`], so that its animation doesn't stutter while the
+    /// caches refill.
+    fn rewarm_vector_caches(&mut self) {
+        let Some(vector) = &self.vector_system else {
+            return;
+        };
+        let mut scales = self.render_scales();
+        if scales.is_empty() {
+            scales.extend(vector.last_scale.get());
+        }
+        for scale in scales {
+            self.queue_prerender(scale, true);
+        }
+    }
+
+    /// Queues the frames of the current vector cursor at `scale` for
+    /// [`CursorManager::prerender_some`], rendering the shown one right away if `now`.
+    fn queue_prerender(&mut self, scale: f64, now: bool) {
+        let Some(vector) = &self.vector_system else {
+            return;
+        };
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Animated { cursor_id, .. } => cursor_id.clone(),
+            _ => return,
+        };
+        let Ok(renderer) = vector.store.get_renderer_at(&cursor_id, scale) else {
+            return;
+        };
+
+        if now {
+            let frame = vector.animator.current_frame(&*renderer);
+            if let Err(err) = renderer.render_frame(frame, scale) {
+                debug!("error pre-rendering cursor '{cursor_id}': {err:?}");
+                return;
+            }
+        }
+
+        let range = vector.animator.frame_range(&cursor_id, &*renderer);
+        debug!(
+            "queueing {} frames of cursor '{cursor_id}' at scale {scale} for pre-rendering",
+            range.len()
+        );
+        self.prerender.retain(|prerender| prerender.scale != scale);
+        self.prerender.push(Prerender {
+            cursor_id,
+            scale,
+            frames: range.start..range.end,
+            bytes: 0,
+        });
+    }
+
+    /// Whether vector cursor frames are queued for [`CursorManager::prerender_some`].
+    pub fn has_prerender_work(&self) -> bool {
+        !self.prerender.is_empty()
+    }
+
+    /// Renders a few queued vector cursor frames into the caches.
+    ///
+    /// Meant to be called from idle callbacks while [`CursorManager::has_prerender_work`], so
+    /// that filling the caches doesn't hold up input and rendering. Frames of a cursor that is
+    /// no longer shown are dropped, and so are the frames past [`frame_cache::DEFAULT_BUDGET`].
+    pub fn prerender_some(&mut self) {
+        let Some(vector) = &self.vector_system else {
+            self.prerender.clear();
+            return;
+        };
+        let Some(prerender) = self.prerender.last_mut() else {
+            return;
+        };
+
+        let shown = matches!(
+            &*vector.animator.current_state(),
+            TransitionState::Animated { cursor_id, .. } if *cursor_id == prerender.cursor_id
+        );
+        let renderer = match vector
+            .store
+            .get_renderer_at(&prerender.cursor_id, prerender.scale)
+        {
+            Ok(renderer) if shown => renderer,
+            _ => {
+                self.prerender.pop();
+                return;
+            }
+        };
+
+        for _ in 0..PRERENDER_BATCH {
+            let Some(frame) = prerender.frames.next() else {
+                break;
+            };
+            match renderer.render_frame(frame, prerender.scale) {
+                Ok(rendered) => {
+                    let size = rendered.size;
+                    prerender.bytes += size.w.max(0) as usize * size.h.max(0) as usize * 4;
+                }
+                Err(err) => {
+                    debug!(
+                        "error pre-rendering cursor '{}': {err:?}",
+                        prerender.cursor_id
+                    );
+                    prerender.frames = 0..0;
+                }
+            }
+            // Frames past the budget would only evict the ones before them.
+            if prerender.bytes >= frame_cache::DEFAULT_BUDGET {
+                debug!(
+                    "cursor '{}' doesn't fit the frame cache at scale {}, pre-rendered {} KiB",
+                    prerender.cursor_id,
+                    prerender.scale,
+                    prerender.bytes / 1024
+                );
+                prerender.frames = 0..0;
+            }
+        }
+        if prerender.frames.is_empty() {
+            self.prerender.pop();
+        }
+    }
+
+    /// Raster duration statistics of the vector cursor system, if it is active.
+    pub fn vector_render_stats(&self) -> Option<Ref<'_, RenderStats>> {
+        self.vector_system
+            .as_ref()
+            .map(|vector| vector.stats.borrow())
+    }
+
+    /// Cursor rendering statistics, as reported over IPC.
+    pub fn ipc_stats(&self) -> niri_ipc::CursorStats {
+        let stats = self.vector_render_stats();
+        let bandwidth = self.bandwidth.borrow();
+        let now = get_monotonic_time();
+        niri_ipc::CursorStats {
+            render_times: stats.as_ref().map(|stats| stats.all().summary().into()),
+            cursor_render_times: stats
+                .iter()
+                .flat_map(|stats| stats.cursors())
+                .map(|(cursor_id, histogram)| (cursor_id.to_owned(), histogram.summary().into()))
+                .collect(),
+            xcursor_upload_bandwidth: bandwidth.bytes_per_second(UploadPath::XCursor, now),
+            vector_upload_bandwidth: bandwidth.bytes_per_second(UploadPath::Vector, now),
+        }
+    }
+
+    /// Recently shown vector cursor frames, empty unless enabled with `NIRI_CURSOR_HISTORY`.
+    pub fn cursor_history(&self) -> Ref<'_, CursorHistory> {
+        self.history.borrow()
+    }
+
+    /// The recently shown vector cursor frames side by side, see [`CursorHistory::strip`].
+    pub fn cursor_history_strip(&self) -> Option<golden::Image> {
+        self.history.borrow().strip()
+    }
+
+    /// Starts keeping the last `capacity` shown vector cursor frames, regardless of
+    /// `NIRI_CURSOR_HISTORY`, and forgets the ones kept so far.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        *self.history.get_mut() = CursorHistory::new(capacity);
+    }
+
+    /// Store of the vector cursor theme in use, if any.
+    pub fn vector_store(&self) -> Option<&VectorCursorStore> {
+        self.vector_system.as_ref().map(|vector| &vector.store)
+    }
+
+    /// Clears the collected vector raster duration statistics.
+    pub fn reset_vector_render_stats(&self) {
+        if let Some(vector) = &self.vector_system {
+            vector.stats.borrow_mut().reset();
+        }
+    }
+
+    fn get_render_cursor_named(&self, icon: CursorIcon, scale: i32) -> RenderCursor<'static> {
         self.get_cursor_with_name(icon, scale)
             .map(|cursor| RenderCursor::Named {
                 icon,
@@ -280,9 +1502,23 @@ impl CursorManager {
     }
 
     pub fn is_current_cursor_animated(&self, scale: i32) -> bool {
+        if let Some((custom, _)) = self.custom.top() {
+            return custom.is_animated();
+        }
+        if self.is_invisible() {
+            return false;
+        }
+
+        if let Some(vector) = &self.vector_system {
+            if let Some(animating) = Self::is_vector_cursor_animating(vector) {
+                return animating || self.is_motion_blurred();
+            }
+        }
+
         match &self.current_cursor {
             CursorImageStatus::Hidden => false,
             CursorImageStatus::Surface(_) => false,
+            CursorImageStatus::Named(_) if self.is_motion_blurred() => true,
             CursorImageStatus::Named(icon) => self
                 .get_cursor_with_name(*icon, scale)
                 .unwrap_or_else(|| self.get_default_cursor(scale))
@@ -290,6 +1526,39 @@ impl CursorManager {
         }
     }
 
+    /// Monotonic time the vector cursor shows its next frame or switches to its idle variant,
+    /// `None` if it doesn't change on its own or another cursor is shown.
+    ///
+    /// Lets the compositor redraw when a frame is due instead of on every vblank.
+    pub fn next_frame_deadline(&self) -> Option<Duration> {
+        if self.custom.top().is_some() || self.is_motion_blurred() || self.is_invisible() {
+            return None;
+        }
+        let vector = self.vector_system.as_ref()?;
+        let cursor_id = match &*vector.animator.current_state() {
+            TransitionState::Static => return None,
+            TransitionState::Animated { cursor_id, .. } => Some(cursor_id.clone()),
+            _ => None,
+        };
+        let scale = vector.last_scale.get().unwrap_or(1.);
+        let renderer =
+            cursor_id.and_then(|cursor_id| vector.store.get_renderer_at(&cursor_id, scale).ok());
+        let frame = vector.animator.next_frame_deadline(renderer.as_deref());
+        frame.into_iter().chain(self.idle_deadline(vector)).min()
+    }
+
+    /// Advances the cursor animation to `presentation_time`, the time a frame hit the screen
+    /// according to presentation feedback.
+    ///
+    /// Frames are rendered for their predicted presentation time. Advancing to the actual one
+    /// keeps the animation on what was shown when frames are late or dropped, and fires its
+    /// events once their frame is on screen.
+    pub fn presented(&self, presentation_time: Duration) {
+        if let Some(vector) = &self.vector_system {
+            Self::advance_animation(vector, presentation_time);
+        }
+    }
+
     /// Get named cursor for the given `icon` and `scale`.
     pub fn get_cursor_with_name(&self, icon: CursorIcon, scale: i32) -> Option<Rc<XCursor>> {
         self.named_cursor_cache
@@ -315,7 +1584,21 @@ impl CursorManager {
 
                 // The default cursor must always have a fallback.
                 if *icon == CursorIcon::Default && cursor.is_err() {
-                    cursor = Ok(Self::fallback_cursor());
+                    cursor = Ok(self.fallback_cursor(size));
+                }
+
+                if let Ok(cursor) = &cursor {
+                    // The texture cache uploads all frames of a cursor at once.
+                    let bytes = cursor
+                        .images
+                        .iter()
+                        .map(|image| image.pixels_rgba.len())
+                        .sum();
+                    self.bandwidth.borrow_mut().record(
+                        UploadPath::XCursor,
+                        get_monotonic_time(),
+                        bytes,
+                    );
                 }
 
                 cursor.ok().map(Rc::new)
@@ -342,8 +1625,21 @@ impl CursorManager {
         // Update vector animator if we have a vector system
         if let Some(vector) = &mut self.vector_system {
             if let CursorImageStatus::Named(icon) = &cursor {
-                if let Some(vector_id) = self.icon_to_vector_id.get(icon) {
+                let scripted = vector
+                    .run_script(ScriptEvent::IconChanged { icon: icon.name() })
+                    .cursor;
+                let mapped = self.icon_to_vector_id.get(icon).map(CursorId::as_str);
+                let idle_from = vector.idle_from.get_mut();
+                if let Some(vector_id) = scripted.as_deref().or(mapped) {
+                    // The idle variant stays up until the pointer moves.
+                    if idle_from.as_deref() == Some(vector_id) {
+                        self.current_cursor = cursor;
+                        self.sync_animations_paused();
+                        return;
+                    }
+                    *idle_from = None;
                     debug!("Updating vector animator to cursor: {}", vector_id);
+                    Self::advance_animation(vector, self.clock.now_unadjusted());
                     match vector.animator.set_cursor(vector_id) {
                         Ok(()) => debug!("Vector animator updated successfully"),
                         Err(err) => warn!("Failed to update vector animator: {:?}", err),
@@ -355,6 +1651,7 @@ impl CursorManager {
         }
 
         self.current_cursor = cursor;
+        self.sync_animations_paused();
     }
 
     /// Load the cursor with the given `name` from the file system picking the closest
@@ -381,12 +1678,7 @@ impl CursorManager {
 
         images.retain(move |image| image.width == width && image.height == height);
 
-        let animation_duration = images.iter().fold(0, |acc, image| acc + image.delay);
-
-        Ok(XCursor {
-            images,
-            animation_duration,
-        })
+        Ok(XCursor::new(images))
     }
 
     /// Set the common XCURSOR env variables.
@@ -395,31 +1687,39 @@ impl CursorManager {
         env::set_var("XCURSOR_SIZE", size.to_string());
     }
 
-    fn fallback_cursor() -> XCursor {
-        let images = vec![Image {
-            size: 32,
-            width: 64,
-            height: 64,
-            xhot: 1,
-            yhot: 1,
-            delay: 0,
-            pixels_rgba: Vec::from(FALLBACK_CURSOR_DATA),
-            pixels_argb: vec![],
-        }];
+    /// The configured fallback cursor, `size` pixels large.
+    fn fallback_cursor(&self, size: i32) -> XCursor {
+        let image = match &self.fallback {
+            CursorFallback::Embedded => None,
+            CursorFallback::Arrow => Some(fallback::arrow(size)),
+            CursorFallback::Svg(path) => {
+                let path = Path::new(path);
+                let expanded = expand_home(path).ok().flatten();
+                match fallback::svg(expanded.as_deref().unwrap_or(path), size) {
+                    Ok(image) => Some(image),
+                    Err(err) => {
+                        warn!("error rendering fallback cursor: {err:?}");
+                        None
+                    }
+                }
+            }
+        };
+
+        let image = image.unwrap_or_else(|| fallback::embedded(size));
 
-        XCursor {
-            images,
-            animation_duration: 0,
-        }
+        XCursor::new(vec![image])
     }
 }
 
 /// The cursor prepared for renderer.
-pub enum RenderCursor {
+///
+/// Hotspots are always logical. For named cursors it depends on the animation frame, use
+/// [`RenderCursor::hotspot`] or [`XCursor::hotspot_logical`].
+pub enum RenderCursor<'a> {
     Hidden,
     Surface {
         hotspot: Point<i32, Logical>,
-        surface: WlSurface,
+        surface: Cow<'a, WlSurface>,
     },
     Named {
         icon: CursorIcon,
@@ -427,11 +1727,29 @@ pub enum RenderCursor {
         cursor: Rc<XCursor>,
     },
     Vector {
-        hotspot: Point<i32, Physical>,
+        hotspot: Point<f64, Logical>,
         buffer: MemoryRenderBuffer,
+        /// Logical size to draw the buffer at, if it differs from its natural size.
+        size: Option<Size<i32, Logical>>,
+        opacity: f32,
     },
 }
 
+impl RenderCursor<'_> {
+    /// Logical hotspot of the cursor image shown at `millis`.
+    pub fn hotspot(&self, millis: u32) -> Point<f64, Logical> {
+        match self {
+            RenderCursor::Hidden => Point::from((0., 0.)),
+            RenderCursor::Surface { hotspot, .. } => hotspot.to_f64(),
+            RenderCursor::Named { scale, cursor, .. } => {
+                let (_, image) = cursor.frame(millis);
+                XCursor::hotspot_logical(image, *scale)
+            }
+            RenderCursor::Vector { hotspot, .. } => *hotspot,
+        }
+    }
+}
+
 type TextureCache = HashMap<(CursorIcon, i32), Vec<MemoryRenderBuffer>>;
 
 #[derive(Default)]
@@ -480,32 +1798,31 @@ impl CursorTextureCache {
 pub struct XCursor {
     /// The image for the underlying named cursor.
     images: Vec<Image>,
-    /// The total duration of the animation.
-    animation_duration: u32,
+    /// Timing of the frames.
+    scheduler: FrameScheduler,
 }
 
 impl XCursor {
+    fn new(images: Vec<Image>) -> Self {
+        let delays: Vec<_> = images
+            .iter()
+            .map(|image| Duration::from_millis(image.delay.into()))
+            .collect();
+        let scheduler = FrameScheduler::new(&delays, LoopMode::Loop);
+
+        Self { images, scheduler }
+    }
+
     /// Given a time, calculate which frame to show, and how much time remains until the next frame.
     ///
     /// Time will wrap, so if for instance the cursor has an animation lasting 100ms,
     /// then calling this function with 5ms and 105ms as input gives the same output.
-    pub fn frame(&self, mut millis: u32) -> (usize, &Image) {
-        if self.animation_duration == 0 {
-            return (0, &self.images[0]);
-        }
-
-        millis %= self.animation_duration;
-
-        let mut res = 0;
-        for (i, img) in self.images.iter().enumerate() {
-            if millis < img.delay {
-                res = i;
-                break;
-            }
-            millis -= img.delay;
-        }
-
-        (res, &self.images[res])
+    pub fn frame(&self, millis: u32) -> (usize, &Image) {
+        let idx = self
+            .scheduler
+            .frame_at(Duration::from_millis(millis.into()))
+            .index;
+        (idx, &self.images[idx])
     }
 
     /// Get the frames for the given `XCursor`.
@@ -522,4 +1839,9 @@ impl XCursor {
     pub fn hotspot(image: &Image) -> Point<i32, Physical> {
         (image.xhot as i32, image.yhot as i32).into()
     }
+
+    /// Get logical hotspot for the given `image` loaded at `scale`.
+    pub fn hotspot_logical(image: &Image, scale: i32) -> Point<f64, Logical> {
+        hotspot::to_logical(Self::hotspot(image), f64::from(scale))
+    }
 }
@@ -0,0 +1,116 @@
+//! Estimation of the cursor buffer upload bandwidth.
+//!
+//! Every new cursor buffer has to be uploaded to the GPU (or copied into the cursor plane) before
+//! it can be shown. Static cursors pay this once, but animated cursors pay it on every frame
+//! change, which adds up on bandwidth-constrained setups like DisplayLink.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Averaging window for the bytes-per-second estimate.
+const WINDOW: Duration = Duration::from_secs(5);
+
+/// Where the uploaded cursor buffer came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadPath {
+    /// Named XCursor frames, uploaded once per icon and scale.
+    XCursor,
+    /// Vector cursor frames, uploaded every time a frame is rendered.
+    Vector,
+}
+
+#[derive(Debug, Default)]
+pub struct BandwidthEstimator {
+    xcursor: UploadWindow,
+    vector: UploadWindow,
+}
+
+#[derive(Debug, Default)]
+struct UploadWindow {
+    samples: VecDeque<(Duration, usize)>,
+    total_bytes: u64,
+}
+
+impl BandwidthEstimator {
+    pub fn record(&mut self, path: UploadPath, now: Duration, bytes: usize) {
+        self.window_mut(path).record(now, bytes);
+    }
+
+    /// Average upload rate over the last few seconds.
+    pub fn bytes_per_second(&self, path: UploadPath, now: Duration) -> f64 {
+        self.window(path).bytes_per_second(now)
+    }
+
+    /// Total bytes uploaded since the estimator was created.
+    pub fn total_bytes(&self, path: UploadPath) -> u64 {
+        self.window(path).total_bytes
+    }
+
+    fn window(&self, path: UploadPath) -> &UploadWindow {
+        match path {
+            UploadPath::XCursor => &self.xcursor,
+            UploadPath::Vector => &self.vector,
+        }
+    }
+
+    fn window_mut(&mut self, path: UploadPath) -> &mut UploadWindow {
+        match path {
+            UploadPath::XCursor => &mut self.xcursor,
+            UploadPath::Vector => &mut self.vector,
+        }
+    }
+}
+
+impl UploadWindow {
+    fn record(&mut self, now: Duration, bytes: usize) {
+        while let Some(&(time, _)) = self.samples.front() {
+            if now.saturating_sub(time) <= WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back((now, bytes));
+        self.total_bytes += bytes as u64;
+    }
+
+    fn bytes_per_second(&self, now: Duration) -> f64 {
+        let bytes: usize = self
+            .samples
+            .iter()
+            .filter(|(time, _)| now.saturating_sub(*time) <= WINDOW)
+            .map(|(_, bytes)| bytes)
+            .sum();
+        bytes as f64 / WINDOW.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_uses_sliding_window() {
+        let mut estimator = BandwidthEstimator::default();
+
+        // 60 frames per second of 24×24 ARGB for 10 seconds.
+        for i in 0..600 {
+            let now = Duration::from_millis(i * 1000 / 60);
+            estimator.record(UploadPath::Vector, now, 24 * 24 * 4);
+        }
+
+        let now = Duration::from_secs(10);
+        let rate = estimator.bytes_per_second(UploadPath::Vector, now);
+        assert!(
+            (rate - 60. * 24. * 24. * 4.).abs() < 24. * 24. * 4.,
+            "{rate}"
+        );
+        assert_eq!(estimator.total_bytes(UploadPath::Vector), 600 * 24 * 24 * 4);
+
+        assert_eq!(estimator.bytes_per_second(UploadPath::XCursor, now), 0.);
+        assert_eq!(
+            estimator.bytes_per_second(UploadPath::Vector, now + WINDOW * 2),
+            0.
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Cursors registered by the compositor at runtime.
+//!
+//! Compositor features like a window picker can register their own cursors by name, independent
+//! of any theme, and activate them on a stack: the most recently activated custom cursor takes
+//! priority over every other cursor source until it's removed again.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use anyhow::{ensure, Result};
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Logical, Point};
+
+use crate::cursor::hotspot;
+use crate::cursor::vector::VectorRenderer;
+
+/// A prerendered frame of a custom cursor.
+#[derive(Clone)]
+pub struct CustomFrame {
+    pub buffer: MemoryRenderBuffer,
+    pub hotspot: Point<f64, Logical>,
+    /// How long the frame stays on screen, ignored for single-frame cursors.
+    pub duration: Duration,
+}
+
+pub enum CustomCursor {
+    /// Prerendered frames, shown in order and looped.
+    Frames(Vec<CustomFrame>),
+    /// A vector renderer, rasterized at the output scale and looped.
+    Renderer(Rc<dyn VectorRenderer>),
+}
+
+impl CustomCursor {
+    pub fn is_animated(&self) -> bool {
+        match self {
+            CustomCursor::Frames(frames) => frames.len() > 1,
+            CustomCursor::Renderer(renderer) => renderer.total_frames() > 1,
+        }
+    }
+
+    /// Buffer and logical hotspot to show `elapsed` after the cursor was activated.
+    pub fn render(
+        &self,
+        elapsed: Duration,
+        scale: i32,
+    ) -> Result<(MemoryRenderBuffer, Point<f64, Logical>)> {
+        match self {
+            CustomCursor::Frames(frames) => {
+                ensure!(!frames.is_empty(), "custom cursor has no frames");
+                let durations: Vec<_> = frames.iter().map(|frame| frame.duration).collect();
+                let frame = &frames[frame_index(&durations, elapsed)];
+                Ok((frame.buffer.clone(), frame.hotspot))
+            }
+            CustomCursor::Renderer(renderer) => {
+                let duration = u128::from(renderer.frame_duration_ms().max(1));
+                let total_frames = u128::from(renderer.total_frames().max(1));
+                let frame = (elapsed.as_millis() / duration % total_frames) as u32;
+
+                let rendered = renderer.render_frame(frame, f64::from(scale))?;
+                let hotspot = hotspot::to_logical(rendered.hotspot, f64::from(scale));
+                Ok((rendered.buffer, hotspot))
+            }
+        }
+    }
+}
+
+/// Registered custom cursors and the stack of active ones.
+#[derive(Default)]
+pub struct CustomCursors {
+    cursors: HashMap<String, CustomCursor>,
+    /// Active cursor names with their activation times, the last one is shown.
+    active: Vec<(String, Duration)>,
+}
+
+impl CustomCursors {
+    /// Registers a cursor, replacing any cursor with the same name.
+    pub fn register(&mut self, name: String, cursor: CustomCursor) {
+        self.cursors.insert(name, cursor);
+    }
+
+    /// Unregisters a cursor, deactivating it.
+    pub fn unregister(&mut self, name: &str) -> Option<CustomCursor> {
+        self.remove(name);
+        self.cursors.remove(name)
+    }
+
+    /// Activates a registered cursor on top of the stack.
+    ///
+    /// A cursor that is already active moves to the top, keeping its animation time.
+    pub fn push(&mut self, name: &str, now: Duration) -> Result<()> {
+        ensure!(
+            self.cursors.contains_key(name),
+            "custom cursor '{name}' is not registered"
+        );
+
+        let activated = match self.active.iter().position(|(active, _)| active == name) {
+            Some(idx) => self.active.remove(idx).1,
+            None => now,
+        };
+        self.active.push((name.to_owned(), activated));
+        Ok(())
+    }
+
+    /// Deactivates a cursor wherever it is in the stack.
+    pub fn remove(&mut self, name: &str) {
+        self.active.retain(|(active, _)| active != name);
+    }
+
+    /// The cursor on top of the stack with the time it was activated at.
+    pub fn top(&self) -> Option<(&CustomCursor, Duration)> {
+        let (name, activated) = self.active.last()?;
+        Some((&self.cursors[name], *activated))
+    }
+}
+
+/// Index of the frame shown `elapsed` into a loop of frames with the given durations.
+fn frame_index(durations: &[Duration], elapsed: Duration) -> usize {
+    let total: Duration = durations.iter().sum();
+    if total.is_zero() {
+        return 0;
+    }
+
+    let mut into = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+    for (idx, duration) in durations.iter().enumerate() {
+        if into < *duration {
+            return idx;
+        }
+        into -= *duration;
+    }
+    durations.len() - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use smithay::backend::allocator::Fourcc;
+    use smithay::utils::Transform;
+
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn cursor() -> CustomCursor {
+        let buffer = MemoryRenderBuffer::from_slice(
+            &[0; 4],
+            Fourcc::Argb8888,
+            (1, 1),
+            1,
+            Transform::Normal,
+            None,
+        );
+        CustomCursor::Frames(vec![CustomFrame {
+            buffer,
+            hotspot: Point::from((0., 0.)),
+            duration: Duration::ZERO,
+        }])
+    }
+
+    #[test]
+    fn frame_index_loops() {
+        let durations = [ms(10), ms(20), ms(10)];
+        assert_eq!(frame_index(&durations, ms(0)), 0);
+        assert_eq!(frame_index(&durations, ms(10)), 1);
+        assert_eq!(frame_index(&durations, ms(29)), 1);
+        assert_eq!(frame_index(&durations, ms(35)), 2);
+        assert_eq!(frame_index(&durations, ms(45)), 0);
+        assert_eq!(frame_index(&[Duration::ZERO], ms(45)), 0);
+    }
+
+    #[test]
+    fn stack_order() {
+        let mut custom = CustomCursors::default();
+        assert!(custom.push("picker", ms(0)).is_err());
+
+        custom.register(String::from("picker"), cursor());
+        custom.register(String::from("busy"), cursor());
+        custom.push("picker", ms(1)).unwrap();
+        custom.push("busy", ms(2)).unwrap();
+        assert_eq!(custom.top().unwrap().1, ms(2));
+
+        // Re-activating keeps the original activation time.
+        custom.push("picker", ms(3)).unwrap();
+        assert_eq!(custom.top().unwrap().1, ms(1));
+
+        custom.remove("picker");
+        assert_eq!(custom.top().unwrap().1, ms(2));
+
+        custom.unregister("busy");
+        assert!(custom.top().is_none());
+    }
+}
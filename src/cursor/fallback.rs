@@ -0,0 +1,228 @@
+//! Last-resort cursor images, rendered at the requested size.
+
+use std::path::Path;
+
+use anyhow::Result;
+use tiny_skia::{
+    FillRule, FilterQuality, IntSize, LineJoin, Paint, PathBuilder, Pixmap, PixmapPaint, Stroke,
+    Transform,
+};
+use xcursor::parser::Image;
+
+use crate::cursor::hotspot::HotspotSpec;
+
+/// Some default looking `left_ptr` icon, straight alpha ARGB8888.
+static EMBEDDED: &[u8] = include_bytes!("../../resources/cursor.rgba");
+
+/// Width and height of [`EMBEDDED`].
+const EMBEDDED_SIZE: u32 = 64;
+
+/// Size of the grid [`ARROW`] is drawn on.
+const ARROW_GRID: f64 = 24.;
+
+/// Outline of the procedural arrow, starting at its tip.
+const ARROW: &[(f32, f32)] = &[
+    (1., 1.),
+    (1., 18.),
+    (5.5, 14.),
+    (8.5, 21.),
+    (11.5, 19.8),
+    (8.5, 13.),
+    (14., 13.),
+];
+
+/// The arrow image built into niri, scaled to `size` pixels wide and high.
+pub fn embedded(size: i32) -> Image {
+    let size = size.max(1) as u32;
+    let scale = f64::from(size) / f64::from(EMBEDDED_SIZE);
+    let canvas = f64::from(EMBEDDED_SIZE);
+    let hotspot = HotspotSpec::new((1., 1.), (canvas, canvas)).resolve(scale);
+
+    let pixels = if size == EMBEDDED_SIZE {
+        EMBEDDED.to_vec()
+    } else {
+        resample(EMBEDDED, EMBEDDED_SIZE, size)
+    };
+
+    Image {
+        size,
+        width: size,
+        height: size,
+        xhot: hotspot.x as u32,
+        yhot: hotspot.y as u32,
+        delay: 0,
+        pixels_rgba: pixels,
+        pixels_argb: vec![],
+    }
+}
+
+/// Scales square straight alpha pixels from `from` to `to` pixels wide.
+fn resample(data: &[u8], from: u32, to: u32) -> Vec<u8> {
+    // tiny-skia filters premultiplied pixels; the channel order doesn't matter for that.
+    let mut source = data.to_vec();
+    for pixel in source.chunks_exact_mut(4) {
+        let alpha = u16::from(pixel[3]);
+        for channel in &mut pixel[..3] {
+            *channel = ((u16::from(*channel) * alpha + 127) / 255) as u8;
+        }
+    }
+    let source = Pixmap::from_vec(source, IntSize::from_wh(from, from).unwrap()).unwrap();
+
+    let mut target = Pixmap::new(to, to).unwrap();
+    let scale = to as f32 / from as f32;
+    let paint = PixmapPaint {
+        quality: FilterQuality::Bicubic,
+        ..Default::default()
+    };
+    target.draw_pixmap(
+        0,
+        0,
+        source.as_ref(),
+        &paint,
+        Transform::from_scale(scale, scale),
+        None,
+    );
+
+    let mut pixels = target.take();
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = u16::from(pixel[3]);
+        if alpha == 0 {
+            continue;
+        }
+        for channel in &mut pixel[..3] {
+            *channel = ((u16::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+    pixels
+}
+
+/// Draws a black arrow with a white outline, `size` pixels wide and high.
+pub fn arrow(size: i32) -> Image {
+    let size = size.max(1);
+    let scale = f64::from(size) / ARROW_GRID;
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(ARROW[0].0, ARROW[0].1);
+    for &(x, y) in &ARROW[1..] {
+        builder.line_to(x, y);
+    }
+    builder.close();
+    let path = builder.finish().unwrap();
+
+    let mut pixmap = Pixmap::new(size as u32, size as u32).unwrap();
+    let transform = Transform::from_scale(scale as f32, scale as f32);
+
+    let mut paint = Paint::default();
+    paint.anti_alias = true;
+    paint.set_color_rgba8(0, 0, 0, 255);
+    pixmap.fill_path(&path, &paint, FillRule::Winding, transform, None);
+
+    paint.set_color_rgba8(255, 255, 255, 255);
+    let stroke = Stroke {
+        width: 1.5,
+        line_join: LineJoin::Round,
+        ..Default::default()
+    };
+    pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+
+    let tip = (f64::from(ARROW[0].0), f64::from(ARROW[0].1));
+    let hotspot = HotspotSpec::new(tip, (ARROW_GRID, ARROW_GRID)).resolve(scale);
+    image(pixmap, (hotspot.x as u32, hotspot.y as u32))
+}
+
+/// Renders an SVG file to fit `size` pixels, with the hotspot in the top-left corner.
+#[cfg(feature = "svg")]
+pub fn svg(path: &Path, size: i32) -> Result<Image> {
+    use anyhow::Context;
+
+    let data = std::fs::read(path)
+        .with_context(|| format!("error reading fallback cursor {}", path.display()))?;
+    let tree = usvg::Tree::from_data(
+        &data,
+        &usvg::Options::default(),
+        &fontdb::Database::default(),
+    )
+    .context("error parsing fallback cursor SVG")?;
+
+    let size = size.max(1);
+    let tree_size = tree.size();
+    let scale = size as f32 / tree_size.width().max(tree_size.height());
+
+    let mut pixmap = Pixmap::new(size as u32, size as u32).unwrap();
+    resvg::render(
+        &tree,
+        Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    Ok(image(pixmap, (0, 0)))
+}
+
+#[cfg(not(feature = "svg"))]
+pub fn svg(_path: &Path, _size: i32) -> Result<Image> {
+    anyhow::bail!("niri was built without SVG support")
+}
+
+fn image(pixmap: Pixmap, (xhot, yhot): (u32, u32)) -> Image {
+    let (width, height) = (pixmap.width(), pixmap.height());
+
+    // tiny-skia produces RGBA, cursor images are ARGB8888 (BGRA in memory).
+    let mut pixels = pixmap.take();
+    for chunk in pixels.chunks_exact_mut(4) {
+        chunk.swap(0, 2);
+    }
+
+    Image {
+        size: width,
+        width,
+        height,
+        xhot,
+        yhot,
+        delay: 0,
+        pixels_rgba: pixels,
+        pixels_argb: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_matches_requested_size() {
+        let image = embedded(64);
+        assert_eq!(image.pixels_rgba, EMBEDDED);
+        assert_eq!((image.xhot, image.yhot), (1, 1));
+
+        for (size, hot) in [(24, 0), (32, 1), (48, 1), (128, 2)] {
+            let image = embedded(size);
+            assert_eq!(
+                (image.size, image.width, image.height),
+                (size as u32, size as u32, size as u32)
+            );
+            assert_eq!((image.xhot, image.yhot), (hot, hot));
+            assert_eq!(image.pixels_rgba.len(), size as usize * size as usize * 4);
+        }
+    }
+
+    #[test]
+    fn arrow_matches_requested_size() {
+        for (size, hot) in [(24, 1), (48, 2), (36, 2)] {
+            let image = arrow(size);
+            assert_eq!(
+                (image.size, image.width, image.height),
+                (size as u32, size as u32, size as u32)
+            );
+            assert_eq!((image.xhot, image.yhot), (hot, hot));
+            assert_eq!(image.pixels_rgba.len(), size as usize * size as usize * 4);
+        }
+    }
+
+    #[test]
+    fn arrow_is_opaque_inside() {
+        let image = arrow(24);
+        let alpha = |x: usize, y: usize| image.pixels_rgba[(y * 24 + x) * 4 + 3];
+        assert_eq!(alpha(4, 10), 255);
+        assert_eq!(alpha(20, 4), 0);
+    }
+}
@@ -0,0 +1,115 @@
+//! Cursor hotspot coordinate conversions.
+//!
+//! [`RenderCursor`](super::RenderCursor) exposes hotspots in logical coordinates relative to the
+//! top-left corner of the cursor image, regardless of where the image came from. Cursor images
+//! themselves are rasterized at some buffer scale, and their native hotspots are in physical
+//! pixels of that buffer; these helpers are the only place where one is converted to the other.
+//!
+//! Vector cursors author their hotspot in asset units instead, described by [`HotspotSpec`] and
+//! resolved to buffer pixels for whatever scale the asset is rasterized at.
+
+use smithay::utils::{Logical, Physical, Point};
+
+/// Converts a hotspot in pixels of a buffer with the given scale to logical coordinates.
+pub fn to_logical(hotspot: Point<i32, Physical>, buffer_scale: f64) -> Point<f64, Logical> {
+    hotspot.to_f64().to_logical(buffer_scale)
+}
+
+/// Converts a logical hotspot to pixels of a buffer with the given scale.
+///
+/// Rounds to the nearest pixel, since buffer pixels can't address anything finer.
+pub fn to_physical(hotspot: Point<f64, Logical>, buffer_scale: f64) -> Point<i32, Physical> {
+    hotspot.to_physical_precise_round(buffer_scale)
+}
+
+/// Hotspot as authored in a vector cursor asset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HotspotSpec {
+    /// Hotspot position in asset units (SVG user units, Lottie composition pixels).
+    pub position: (f64, f64),
+    /// Size of the asset canvas in the same units.
+    pub canvas: (f64, f64),
+}
+
+impl HotspotSpec {
+    pub fn new(position: (f64, f64), canvas: (f64, f64)) -> Self {
+        Self { position, canvas }
+    }
+
+    /// Builds a spec from the integer hotspot in a cursor definition, defaulting to the top-left
+    /// corner.
+    pub fn from_config(hotspot: Option<(i32, i32)>, canvas: (f64, f64)) -> Self {
+        let (x, y) = hotspot.unwrap_or((0, 0));
+        Self::new((f64::from(x), f64::from(y)), canvas)
+    }
+
+    /// Resolves the hotspot to pixels of the asset rasterized at `scale`.
+    ///
+    /// The asset covers `ceil(canvas * scale)` pixels; the result is clamped to that area.
+    pub fn resolve(&self, scale: f64) -> Point<i32, Physical> {
+        let resolve = |position: f64, canvas: f64| {
+            let max = ((canvas * scale).ceil() - 1.).max(0.);
+            (position * scale).round().clamp(0., max) as i32
+        };
+
+        Point::from((
+            resolve(self.position.0, self.canvas.0),
+            resolve(self.position.1, self.canvas.1),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> HotspotSpec {
+        HotspotSpec::new((4., 6.), (24., 24.))
+    }
+
+    #[test]
+    fn resolve_integer_scales() {
+        assert_eq!(spec().resolve(1.), Point::from((4, 6)));
+        assert_eq!(spec().resolve(2.), Point::from((8, 12)));
+        assert_eq!(spec().resolve(3.), Point::from((12, 18)));
+    }
+
+    #[test]
+    fn resolve_fractional_scales() {
+        assert_eq!(spec().resolve(1.5), Point::from((6, 9)));
+        assert_eq!(spec().resolve(1.25), Point::from((5, 8)));
+        assert_eq!(spec().resolve(1.75), Point::from((7, 11)));
+    }
+
+    #[test]
+    fn resolved_hotspot_is_logically_stable() {
+        // Regression test: the hotspot used to be multiplied by the scale twice.
+        for scale in [1., 2., 3.] {
+            let physical = spec().resolve(scale);
+            assert_eq!(to_logical(physical, scale), Point::from((4., 6.)));
+        }
+
+        for scale in [1.25, 1.5, 1.75] {
+            let logical = to_logical(spec().resolve(scale), scale);
+            // Within half a buffer pixel of the authored position.
+            assert!((logical.x - 4.).abs() * scale <= 0.5 + 1e-9);
+            assert!((logical.y - 6.).abs() * scale <= 0.5 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn resolve_clamps_to_canvas() {
+        let spec = HotspotSpec::new((24., -3.), (24., 24.));
+        assert_eq!(spec.resolve(1.), Point::from((23, 0)));
+        assert_eq!(spec.resolve(2.), Point::from((47, 0)));
+        assert_eq!(spec.resolve(1.5), Point::from((35, 0)));
+    }
+
+    #[test]
+    fn physical_logical_roundtrip() {
+        let logical = Point::from((4., 6.));
+        for scale in [1., 2., 3.] {
+            assert_eq!(to_logical(to_physical(logical, scale), scale), logical);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Frame timing shared by XCursor and vector cursor animations.
+//!
+//! Both kinds of cursors are a sequence of frames with durations played according to a
+//! [`LoopMode`]. Answering "which frame is shown at this time, and until when" in one place keeps
+//! animated XCursors and vector cursors in lockstep with each other and with the compositor's
+//! redraw decisions. Lottie transitions play their animation through it too, once, with the
+//! frames spread over the transition; the eased progress that other transitions blend by is the
+//! animator's.
+
+use std::time::Duration;
+
+use crate::cursor::vector::types::LoopMode;
+
+/// Timing of one frame-based animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameScheduler {
+    /// Frames in playback order over one period, with the time each one starts at.
+    ///
+    /// For [`LoopMode::Bounce`] this is the forward pass followed by the backward pass.
+    steps: Vec<(usize, Duration)>,
+    /// Length of one period.
+    period: Duration,
+    loop_mode: LoopMode,
+}
+
+/// The frame shown at some point of an animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledFrame {
+    /// Index of the frame to show.
+    pub index: usize,
+    /// Time until a different frame is shown, `None` if the shown frame never changes again.
+    pub next_deadline: Option<Duration>,
+    /// Whether a [`LoopMode::Once`] animation has reached its end.
+    pub completed: bool,
+}
+
+impl FrameScheduler {
+    /// Creates a scheduler for frames with the given durations.
+    pub fn new(durations: &[Duration], loop_mode: LoopMode) -> Self {
+        let count = durations.len().max(1);
+        let duration = |index: usize| durations.get(index).copied().unwrap_or_default();
+
+        let mut order: Vec<usize> = (0..count).collect();
+        if let LoopMode::Bounce = loop_mode {
+            // The first and last frames are only shown once per period.
+            order.extend((1..count.saturating_sub(1)).rev());
+        }
+
+        let mut steps = Vec::with_capacity(order.len());
+        let mut period = Duration::ZERO;
+        for index in order {
+            steps.push((index, period));
+            period += duration(index);
+        }
+
+        Self {
+            steps,
+            period,
+            loop_mode,
+        }
+    }
+
+    /// Creates a scheduler for `frames` frames that each last `frame_duration`.
+    pub fn uniform(frames: u32, frame_duration: Duration, loop_mode: LoopMode) -> Self {
+        let durations = vec![frame_duration; frames.max(1) as usize];
+        Self::new(&durations, loop_mode)
+    }
+
+    /// Whether the shown frame ever changes.
+    pub fn is_animated(&self) -> bool {
+        self.steps.len() > 1 && !self.period.is_zero()
+    }
+
+    /// Length of one pass through the animation, forward and back for bounces.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Position within the current period, from 0 to 1.
+    ///
+    /// Completed [`LoopMode::Once`] animations stay at 1.
+    pub fn phase(&self, elapsed: Duration) -> f32 {
+        if !self.is_animated() {
+            return 0.;
+        }
+
+        let into = match self.loop_mode {
+            LoopMode::Once => elapsed.min(self.period),
+            LoopMode::Loop | LoopMode::Bounce => self.into_period(elapsed),
+        };
+        (into.as_secs_f64() / self.period.as_secs_f64()) as f32
+    }
+
+    /// The frame shown `elapsed` after the animation started.
+    pub fn frame_at(&self, elapsed: Duration) -> ScheduledFrame {
+        if !self.is_animated() {
+            return ScheduledFrame {
+                index: self.steps[0].0,
+                next_deadline: None,
+                completed: false,
+            };
+        }
+
+        if let LoopMode::Once = self.loop_mode {
+            if elapsed >= self.period {
+                return ScheduledFrame {
+                    index: self.steps[self.steps.len() - 1].0,
+                    next_deadline: None,
+                    completed: true,
+                };
+            }
+        }
+
+        let into = self.into_period(elapsed);
+        // The last step starting at or before `into`.
+        let step = self.steps.partition_point(|(_, start)| *start <= into) - 1;
+        let end = self
+            .steps
+            .get(step + 1)
+            .map_or(self.period, |(_, start)| *start);
+
+        ScheduledFrame {
+            index: self.steps[step].0,
+            next_deadline: Some(end - into),
+            completed: false,
+        }
+    }
+
+    fn into_period(&self, elapsed: Duration) -> Duration {
+        Duration::from_nanos((elapsed.as_nanos() % self.period.as_nanos()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn indices(scheduler: &FrameScheduler) -> Vec<usize> {
+        (0..9)
+            .map(|i| scheduler.frame_at(ms(i * 10)).index)
+            .collect()
+    }
+
+    #[test]
+    fn loop_modes() {
+        let uniform = |mode| FrameScheduler::uniform(4, ms(10), mode);
+
+        assert_eq!(
+            indices(&uniform(LoopMode::Loop)),
+            [0, 1, 2, 3, 0, 1, 2, 3, 0]
+        );
+        assert_eq!(
+            indices(&uniform(LoopMode::Once)),
+            [0, 1, 2, 3, 3, 3, 3, 3, 3]
+        );
+        assert_eq!(
+            indices(&uniform(LoopMode::Bounce)),
+            [0, 1, 2, 3, 2, 1, 0, 1, 2]
+        );
+        assert_eq!(uniform(LoopMode::Bounce).period(), ms(60));
+    }
+
+    #[test]
+    fn deadlines_and_completion() {
+        let scheduler = FrameScheduler::new(&[ms(10), ms(30), ms(20)], LoopMode::Loop);
+        assert_eq!(
+            scheduler.frame_at(ms(15)),
+            ScheduledFrame {
+                index: 1,
+                next_deadline: Some(ms(25)),
+                completed: false,
+            }
+        );
+        assert_eq!(scheduler.frame_at(ms(65)).index, 0);
+        assert_eq!(scheduler.phase(ms(75)), 0.25);
+
+        let scheduler = FrameScheduler::new(&[ms(10), ms(30)], LoopMode::Once);
+        assert_eq!(scheduler.frame_at(ms(39)).next_deadline, Some(ms(1)));
+        assert_eq!(
+            scheduler.frame_at(ms(40)),
+            ScheduledFrame {
+                index: 1,
+                next_deadline: None,
+                completed: true,
+            }
+        );
+        assert_eq!(scheduler.phase(ms(100)), 1.);
+    }
+
+    #[test]
+    fn static_frames() {
+        for scheduler in [
+            FrameScheduler::new(&[ms(10)], LoopMode::Bounce),
+            FrameScheduler::new(&[Duration::ZERO; 3], LoopMode::Loop),
+            FrameScheduler::new(&[], LoopMode::Once),
+        ] {
+            assert!(!scheduler.is_animated());
+            assert_eq!(scheduler.frame_at(ms(30)).index, 0);
+            assert_eq!(scheduler.frame_at(ms(30)).next_deadline, None);
+            assert_eq!(scheduler.phase(ms(30)), 0.);
+        }
+    }
+}
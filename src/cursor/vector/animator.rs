@@ -1,14 +1,68 @@
-use crate::cursor::vector::config::{CursorThemeConfig, EasingFunction};
-use crate::cursor::vector::types::{LoopMode, TransitionState};
+use crate::animation::CubicBezier;
+use crate::cursor::scheduler::FrameScheduler;
+use crate::cursor::vector::config::{
+    CursorDefinition, CursorThemeConfig, EasingFunction, SpringConfig,
+};
+use crate::cursor::vector::id::CursorId;
+use crate::cursor::vector::renderer::VectorRenderer;
+use crate::cursor::vector::types::{FrameRange, LoopMode, TransitionState};
 use anyhow::Result;
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::time::Instant;
+use parking_lot::{Mutex, RwLock, RwLockReadGuard};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
+/// Theme-independent animation state, used to carry an animation over to a new theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatorSnapshot {
+    /// The cursor being shown, or transitioned to.
+    pub cursor_id: CursorId,
+    /// Position within the cursor's animation loop, from 0 to 1.
+    pub phase: f32,
+    /// Source cursor and progress of an ongoing transition.
+    pub transition: Option<(CursorId, f32)>,
+}
+
+/// A point in a cursor animation the compositor may want to react to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationEvent {
+    /// A [`LoopMode::Once`] animation reached its final frame.
+    Completed { cursor_id: CursorId },
+    /// A looping or bouncing animation started its pass number `iteration` (counting from 1).
+    Looped { cursor_id: CursorId, iteration: u32 },
+    /// A transition from `from_id` to `to_id` started, also when an interrupted one turns
+    /// around.
+    TransitionStarted { from_id: CursorId, to_id: CursorId },
+    /// A transition finished and `to_id` is now shown.
+    TransitionFinished { from_id: CursorId, to_id: CursorId },
+}
+
+/// Callback receiving [`AnimationEvent`]s, see [`CursorAnimator::add_observer`].
+pub type AnimationObserver = Arc<dyn Fn(&AnimationEvent) + Send + Sync>;
+
+/// Plays the cursors of a theme and the transitions between them.
+///
+/// The animator is `Send + Sync`, like [`VectorRenderer`]: a render thread may query the state
+/// while another thread updates it. No lock is held while observers run.
 pub struct CursorAnimator {
-    config: Rc<CursorThemeConfig>,
-    current_state: RefCell<TransitionState>,
-    _last_update: RefCell<Instant>,
+    config: Arc<CursorThemeConfig>,
+    current_state: RwLock<TransitionState>,
+    /// Monotonic time the animation was last advanced to, see [`CursorAnimator::tick`].
+    last_update: Mutex<Option<Duration>>,
+    observers: RwLock<Vec<AnimationObserver>>,
+    /// Whether animations are frozen, see [`CursorAnimator::pause`].
+    paused: AtomicBool,
+    /// Whether cursors are shown still, see [`CursorAnimator::set_reduce_motion`].
+    reduce_motion: AtomicBool,
+    /// Bits of the pointer speed in logical pixels per second, see
+    /// [`CursorAnimator::set_pointer_velocity`].
+    pointer_velocity: AtomicU64,
+    /// Shortest time between two frames, see [`CursorAnimator::set_max_fps`].
+    frame_interval: Mutex<Duration>,
+    /// Cursor change waiting for `debounce_ms`, and the milliseconds it has waited.
+    pending: Mutex<Option<(CursorId, u32)>>,
+    /// Frame shown instead of the animation, see [`CursorAnimator::freeze_at_frame`].
+    frozen_frame: Mutex<Option<u32>>,
     base_size: u8,
 }
 
@@ -19,18 +73,14 @@ impl CursorAnimator {
         debug!("CursorAnimator created, initializing with default cursor");
 
         // Initialize with default cursor if available
-        if let Some(default_def) = config.cursors.get("default") {
-            let loop_mode = match default_def.loop_mode.as_deref() {
-                Some("once") => LoopMode::Once,
-                Some("loop") => LoopMode::Loop,
-                Some("bounce") => LoopMode::Bounce,
-                _ => LoopMode::Loop,
-            };
+        if let Some(default_id) = config.cursor_id("default") {
+            let loop_mode = Self::loop_mode(&config.cursors[&default_id]);
 
             state = TransitionState::Animated {
-                cursor_id: "default".to_string(),
+                cursor_id: default_id,
                 start_time_ms: 0,
                 loop_mode,
+                intro: false,
             };
 
             debug!("Initialized CursorAnimator with default cursor");
@@ -39,20 +89,275 @@ impl CursorAnimator {
         }
 
         Self {
-            config: Rc::new(config),
-            current_state: RefCell::new(state),
-            _last_update: RefCell::new(Instant::now()),
+            config: Arc::new(config),
+            current_state: RwLock::new(state),
+            last_update: Mutex::new(None),
+            observers: RwLock::new(Vec::new()),
+            paused: AtomicBool::new(false),
+            reduce_motion: AtomicBool::new(false),
+            pointer_velocity: AtomicU64::new(0f64.to_bits()),
+            frame_interval: Mutex::new(Duration::ZERO),
+            pending: Mutex::new(None),
+            frozen_frame: Mutex::new(None),
             base_size,
         }
     }
 
+    /// Switches to `cursor_id` for a client's cursor change, after the theme's `debounce_ms` if
+    /// the change starts a transition.
+    ///
+    /// A debounced change is replaced by changes to other cursors, and dropped if the shown
+    /// cursor is set again before it takes effect. Setting the pending cursor again keeps the
+    /// time it has waited.
     pub fn set_cursor(&self, cursor_id: &str) -> Result<()> {
+        if self.config.debounce_ms > 0 && !self.is_frozen() {
+            let target = self.target();
+            if target.as_ref().is_some_and(|target| *target == cursor_id) {
+                self.pending.lock().take();
+                return Ok(());
+            }
+            let transition =
+                target.is_some_and(|from| self.config.get_transition(&from, cursor_id).is_some());
+            if transition {
+                let mut pending = self.pending.lock();
+                if !pending.as_ref().is_some_and(|(id, _)| *id == cursor_id) {
+                    *pending = Some((CursorId::from(cursor_id), 0));
+                }
+                return Ok(());
+            }
+        }
+        self.set_cursor_now(cursor_id)
+    }
+
+    /// Switches to `cursor_id` right away, for changes niri makes itself like idle cursors and
+    /// theme switches, which don't flicker.
+    pub fn set_cursor_now(&self, cursor_id: &str) -> Result<()> {
+        self.pending.lock().take();
+
+        let event = self.switch(cursor_id);
+        if self.is_frozen() {
+            // A transition would stay frozen on the old cursor, show the new one right away.
+            self.skip_transition();
+            if self.reduce_motion.load(Ordering::Relaxed) {
+                self.rewind();
+            }
+            return Ok(());
+        }
+        self.emit(event.as_slice());
+        Ok(())
+    }
+
+    /// Freezes animated cursors at their current frame, until [`CursorAnimator::resume`].
+    ///
+    /// Time passing while paused is skipped rather than caught up on. Cursor changes still take
+    /// effect, without a transition.
+    pub fn pause(&self) {
+        debug!("pausing cursor animations");
+        self.paused.store(true, Ordering::Relaxed);
+        self.flush_pending();
+    }
+
+    /// Continues animations frozen by [`CursorAnimator::pause`] where they stopped.
+    pub fn resume(&self) {
+        debug!("resuming cursor animations");
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Freezes the shown cursor at `frame` of its animation, or lets it play again with `None`,
+    /// for debugging frames that render wrong.
+    ///
+    /// `frame` indexes all frames of the renderer, not only the played segment. Like
+    /// [`CursorAnimator::pause`], time stands still and cursor changes skip their transition;
+    /// the new cursor shows the same frame.
+    pub fn freeze_at_frame(&self, frame: Option<u32>) {
+        debug!("freezing cursor animations at frame {frame:?}");
+        *self.frozen_frame.lock() = frame;
+        if frame.is_some() {
+            self.flush_pending();
+            self.skip_transition();
+        }
+    }
+
+    /// Moves the frozen frame by `delta`, wrapping around the frames of `renderer`.
+    ///
+    /// Freezes the animation at its current frame first if it isn't.
+    pub fn step_frame(&self, delta: i32, renderer: &dyn VectorRenderer) {
+        let frame = self
+            .frozen_frame()
+            .unwrap_or_else(|| self.current_frame(renderer));
+        let total = i64::from(renderer.total_frames().max(1));
+        let frame = (i64::from(frame) + i64::from(delta)).rem_euclid(total);
+        self.freeze_at_frame(Some(frame as u32));
+    }
+
+    /// Frame the animation is frozen at, see [`CursorAnimator::freeze_at_frame`].
+    pub fn frozen_frame(&self) -> Option<u32> {
+        *self.frozen_frame.lock()
+    }
+
+    /// Caps the rate [`CursorAnimator::tick`] changes frames at, to save power; `None` or 0 for
+    /// no cap.
+    ///
+    /// Ticks coming sooner than a frame after the previous one are skipped, and the time in
+    /// between counts towards the next frame, so animations keep their speed.
+    pub fn set_max_fps(&self, max_fps: Option<u32>) {
+        debug!("capping cursor animations at {max_fps:?} fps");
+        let interval = max_fps
+            .filter(|fps| *fps > 0)
+            .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps);
+        *self.frame_interval.lock() = interval;
+    }
+
+    /// Shows every cursor still on its first frame and switches cursors without transitions,
+    /// for users sensitive to motion.
+    pub fn set_reduce_motion(&self, reduce: bool) {
+        debug!("setting reduced cursor motion to {reduce}");
+        self.reduce_motion.store(reduce, Ordering::Relaxed);
+        if !reduce {
+            return;
+        }
+
+        self.flush_pending();
+        self.skip_transition();
+        self.rewind();
+    }
+
+    /// Shows the first frame of the loop of an animated cursor, skipping its intro.
+    fn rewind(&self) {
+        if let TransitionState::Animated {
+            start_time_ms,
+            intro,
+            ..
+        } = &mut *self.current_state.write()
+        {
+            *start_time_ms = 0;
+            *intro = false;
+        }
+    }
+
+    /// Sets the pointer speed in logical pixels per second, which speeds up cursors with a
+    /// `velocity_speed` from the next update on.
+    pub fn set_pointer_velocity(&self, velocity: f64) {
+        self.pointer_velocity
+            .store(velocity.max(0.).to_bits(), Ordering::Relaxed);
+    }
+
+    /// The pointer speed last set with [`CursorAnimator::set_pointer_velocity`].
+    pub fn pointer_velocity(&self) -> f64 {
+        f64::from_bits(self.pointer_velocity.load(Ordering::Relaxed))
+    }
+
+    /// Playback speed of `cursor_id` at the current pointer speed.
+    ///
+    /// The elapsed time of animations is kept in playback time, so that loops and the end of
+    /// finishing animations line up with the frames shown at any speed.
+    fn playback_speed(&self, cursor_id: &str) -> f64 {
+        let Some(cursor) = self.config.get_cursor(cursor_id) else {
+            return 1.;
+        };
+        let velocity = cursor
+            .velocity_speed
+            .map_or(1., |velocity| velocity.speed(self.pointer_velocity()));
+        cursor.speed.unwrap_or(1.) * velocity
+    }
+
+    /// `elapsed_ms` of wall time in playback time of `cursor_id`.
+    fn playback_ms(&self, cursor_id: &str, elapsed_ms: u32) -> u32 {
+        let speed = self.playback_speed(cursor_id);
+        (f64::from(elapsed_ms) * speed)
+            .round()
+            .min(f64::from(u32::MAX)) as u32
+    }
+
+    /// Whether `cursor_id` is rendered between its frames, see
+    /// [`CursorAnimator::current_position`].
+    pub fn interpolates(&self, cursor_id: &str) -> bool {
+        self.config
+            .get_cursor(cursor_id)
+            .is_some_and(|cursor| cursor.interpolate)
+    }
+
+    /// Whether time stands still for the animations, paused, frozen at a frame or with reduced
+    /// motion.
+    fn is_frozen(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+            || self.reduce_motion.load(Ordering::Relaxed)
+            || self.frozen_frame.lock().is_some()
+    }
+
+    /// Carries out a debounced cursor change right away.
+    fn flush_pending(&self) {
+        let pending = self.pending.lock().take();
+        if let Some((cursor_id, _)) = pending {
+            let _ = self.set_cursor_now(&cursor_id);
+        }
+    }
+
+    /// Carries out a debounced cursor change once it has waited `elapsed_ms` more.
+    fn settle(&self, elapsed_ms: u32) -> Option<AnimationEvent> {
+        let mut pending = self.pending.lock();
+        let (_, waited) = pending.as_mut()?;
+        *waited = waited.saturating_add(elapsed_ms);
+        if *waited < self.config.debounce_ms {
+            return None;
+        }
+        let (cursor_id, _) = pending.take()?;
+        drop(pending);
+        self.switch(&cursor_id)
+    }
+
+    /// The cursor shown, or being switched to.
+    fn target(&self) -> Option<CursorId> {
+        match &*self.current_state.read() {
+            TransitionState::Static => None,
+            TransitionState::Animated { cursor_id, .. } => Some(cursor_id.clone()),
+            TransitionState::Transitioning { to_id, .. }
+            | TransitionState::Finishing { to_id, .. } => Some(to_id.clone()),
+        }
+    }
+
+    /// Ends an ongoing transition or finishing loop, showing its target.
+    fn skip_transition(&self) {
+        let mut state = self.current_state.write();
+        let to_id = match &*state {
+            TransitionState::Transitioning { to_id, .. }
+            | TransitionState::Finishing { to_id, .. } => to_id.clone(),
+            _ => return,
+        };
+        *state = self.enter(to_id);
+    }
+
+    /// State showing `cursor_id` from the start, with its intro if it has one.
+    fn enter(&self, cursor_id: CursorId) -> TransitionState {
+        match self.config.get_cursor(&cursor_id) {
+            Some(cursor) => TransitionState::Animated {
+                cursor_id,
+                start_time_ms: 0,
+                loop_mode: Self::loop_mode(cursor),
+                intro: cursor.intro.is_some(),
+            },
+            None => TransitionState::Static,
+        }
+    }
+
+    /// Changes the state for showing `cursor_id`, returning the event if a transition started.
+    fn switch(&self, cursor_id: &str) -> Option<AnimationEvent> {
         debug!(
             "CursorAnimator::set_cursor called with cursor_id: '{}'",
             cursor_id
         );
 
-        let mut state = self.current_state.borrow_mut();
+        // Unknown cursors only get an ID of their own to look up a transition with.
+        let target = self
+            .config
+            .cursor_id(cursor_id)
+            .unwrap_or_else(|| CursorId::from(cursor_id));
+
+        let mut state = self.current_state.write();
         let from_id = match &*state {
             TransitionState::Static => {
                 debug!("Current state is Static");
@@ -62,59 +367,223 @@ impl CursorAnimator {
                 debug!("Current state is Animated with cursor: '{}'", cursor_id);
                 Some(cursor_id.clone())
             }
-            TransitionState::Transitioning { to_id, .. } => {
-                debug!("Current state is Transitioning to cursor: '{}'", to_id);
-                Some(to_id.clone())
+            TransitionState::Transitioning {
+                from_id: from,
+                to_id,
+                progress,
+            } => {
+                debug!("Current state is Transitioning from '{from}' to '{to_id}'");
+                if to_id == cursor_id {
+                    return None;
+                }
+                let shown = self.eased_progress(from, to_id, *progress);
+                if from == cursor_id {
+                    // Interrupted, turn around from what is shown instead of starting over.
+                    let event = AnimationEvent::TransitionStarted {
+                        from_id: to_id.clone(),
+                        to_id: from.clone(),
+                    };
+                    *state = TransitionState::Transitioning {
+                        from_id: to_id.clone(),
+                        to_id: from.clone(),
+                        progress: self.progress_showing(to_id, from, 1. - shown),
+                    };
+                    return Some(event);
+                }
+                // Retargeted, continue from the cursor that is mostly shown.
+                Some(if shown < 0.5 {
+                    from.clone()
+                } else {
+                    to_id.clone()
+                })
+            }
+            TransitionState::Finishing {
+                cursor_id: from,
+                to_id,
+                start_time_ms,
+                loop_mode,
+                done,
+            } => {
+                debug!("Current state is Finishing '{from}' before '{to_id}'");
+                if to_id == cursor_id {
+                    return None;
+                }
+                if from == cursor_id {
+                    // Switched back, keep playing the loop unless the flourish already started.
+                    *state = if *done {
+                        self.enter(from.clone())
+                    } else {
+                        TransitionState::Animated {
+                            cursor_id: from.clone(),
+                            start_time_ms: *start_time_ms,
+                            loop_mode: *loop_mode,
+                            intro: false,
+                        }
+                    };
+                    return None;
+                }
+                Some(from.clone())
             }
         };
 
         if let Some(from) = from_id {
             if from == cursor_id {
                 debug!("Already showing cursor '{}', no change needed", cursor_id);
-                return Ok(());
+                return None;
             }
 
             debug!("Checking for transition from '{}' to '{}'", from, cursor_id);
-            if self.config.get_transition(&from, cursor_id).is_some() {
+            let transition = self.config.get_transition(&from, cursor_id);
+            let finishes_source = transition.is_some_and(|t| t.finishes_source());
+            let outro = self
+                .config
+                .get_cursor(&from)
+                .is_some_and(|cursor| cursor.outro.is_some());
+            if finishes_source || outro {
+                let finishing = match &*state {
+                    TransitionState::Animated {
+                        start_time_ms,
+                        loop_mode,
+                        intro: false,
+                        ..
+                    } if finishes_source => Some((*start_time_ms, *loop_mode, false)),
+                    // Cut short intros go straight to the outro.
+                    TransitionState::Animated { loop_mode, .. } => Some((0, *loop_mode, true)),
+                    TransitionState::Finishing {
+                        start_time_ms,
+                        loop_mode,
+                        done,
+                        ..
+                    } => Some((*start_time_ms, *loop_mode, *done)),
+                    _ => None,
+                };
+                if let Some((start_time_ms, loop_mode, done)) = finishing {
+                    debug!("Finishing '{from}' first");
+                    *state = TransitionState::Finishing {
+                        cursor_id: from,
+                        to_id: target,
+                        start_time_ms,
+                        loop_mode,
+                        done,
+                    };
+                    return None;
+                }
+            }
+
+            if transition.is_some() {
                 debug!("Found transition, setting state to Transitioning");
-                *state = TransitionState::Transitioning {
+                let event = AnimationEvent::TransitionStarted {
                     from_id: from.clone(),
-                    to_id: cursor_id.to_string(),
+                    to_id: target.clone(),
+                };
+                *state = TransitionState::Transitioning {
+                    from_id: from,
+                    to_id: target,
                     progress: 0.0,
                 };
-                return Ok(());
+                return Some(event);
             }
         }
 
         debug!("Looking up cursor definition for '{}'", cursor_id);
         if let Some(cursor_def) = self.config.get_cursor(cursor_id) {
-            let loop_mode = match cursor_def.loop_mode.as_deref() {
-                Some("once") => LoopMode::Once,
-                Some("loop") => LoopMode::Loop,
-                Some("bounce") => LoopMode::Bounce,
-                _ => LoopMode::Loop,
-            };
+            let loop_mode = Self::loop_mode(cursor_def);
 
             debug!(
                 "Found cursor definition, setting state to Animated with loop_mode: {:?}",
                 loop_mode
             );
             *state = TransitionState::Animated {
-                cursor_id: cursor_id.to_string(),
+                cursor_id: target,
                 start_time_ms: 0,
                 loop_mode,
+                intro: cursor_def.intro.is_some(),
             };
         } else {
             debug!("No cursor definition found, setting state to Static");
             *state = TransitionState::Static;
         }
 
-        Ok(())
+        None
+    }
+
+    /// Registers a callback for the events of this animator.
+    ///
+    /// Observers run from [`CursorAnimator::update`] and [`CursorAnimator::set_cursor`] after the
+    /// state was updated, so they may call [`CursorAnimator::set_cursor`].
+    pub fn add_observer(&self, observer: AnimationObserver) {
+        self.observers.write().push(observer);
+    }
+
+    /// Advances the animation by `elapsed_ms`.
+    ///
+    /// `renderer` renders the shown cursor; without it, loop and completion events of animated
+    /// cursors can't be detected.
+    pub fn update(&self, elapsed_ms: u32, renderer: Option<&dyn VectorRenderer>) {
+        if self.is_frozen() {
+            return;
+        }
+        let mut events = self.advance(elapsed_ms, renderer);
+        events.extend(self.settle(elapsed_ms));
+        self.emit(&events);
+    }
+
+    fn emit(&self, events: &[AnimationEvent]) {
+        // Clone the list so observers can add observers.
+        let observers = self.observers.read().clone();
+        for event in events {
+            debug!("cursor animation event: {event:?}");
+            for observer in &observers {
+                observer(event);
+            }
+        }
+    }
+
+    /// Advances the animation to `now`, a monotonic time, by the time since the previous call.
+    ///
+    /// The first call only starts the clock. Calls for the same `now`, like one per output for a
+    /// redraw, advance the animation once. Time is counted in whole milliseconds, and the
+    /// remainder carries over to the next call so that animations don't drift.
+    ///
+    /// Returns whether the animation advanced.
+    pub fn tick(&self, now: Duration, renderer: Option<&dyn VectorRenderer>) -> bool {
+        // Held until the time is taken, so ticks from several threads don't count it twice.
+        let mut last_update = self.last_update.lock();
+        let Some(last) = *last_update else {
+            *last_update = Some(now);
+            return false;
+        };
+        if self.is_frozen() {
+            // Restart the clock so that the pause isn't caught up on once resumed.
+            *last_update = Some(now);
+            return false;
+        }
+
+        let elapsed = now.saturating_sub(last);
+        // A millisecond of slack keeps presentation times, which rarely fall on whole
+        // milliseconds, from skipping one more frame than the cap asks for.
+        if elapsed + Duration::from_millis(1) < *self.frame_interval.lock() {
+            return false;
+        }
+        let elapsed_ms = elapsed.as_millis();
+        if elapsed_ms == 0 {
+            return false;
+        }
+        let elapsed_ms = u32::try_from(elapsed_ms).unwrap_or(u32::MAX);
+        *last_update = Some(last + Duration::from_millis(u64::from(elapsed_ms)));
+        drop(last_update);
+        self.update(elapsed_ms, renderer);
+        true
     }
 
-    pub fn update(&self, elapsed_ms: u32) {
-        let mut state = self.current_state.borrow_mut();
+    fn advance(
+        &self,
+        elapsed_ms: u32,
+        renderer: Option<&dyn VectorRenderer>,
+    ) -> Vec<AnimationEvent> {
+        let mut state = self.current_state.write();
         let mut new_state = None;
+        let mut events = Vec::new();
 
         match &*state {
             TransitionState::Transitioning {
@@ -122,31 +591,38 @@ impl CursorAnimator {
                 to_id,
                 progress,
             } => {
-                let config = match self.config.get_transition(from_id, to_id) {
-                    Some(c) => c,
+                let config = match self.config.get_transition_or_reverse(from_id, to_id) {
+                    Some((c, _)) => c,
                     None => {
                         *state = TransitionState::Static;
-                        return;
+                        return events;
                     }
                 };
 
-                let duration_ms = config.duration_ms;
+                let duration_ms = config.length_ms();
                 let delta_ms = elapsed_ms;
 
                 let new_progress = *progress + (delta_ms as f32 / duration_ms as f32);
 
                 if new_progress >= 1.0 {
+                    events.push(AnimationEvent::TransitionFinished {
+                        from_id: from_id.clone(),
+                        to_id: to_id.clone(),
+                    });
+                    // The target plays in its own loop mode, as if it had been switched to.
+                    let cursor = self.config.get_cursor(to_id);
                     new_state = Some(TransitionState::Animated {
                         cursor_id: to_id.clone(),
                         start_time_ms: 0,
-                        loop_mode: LoopMode::Loop,
+                        loop_mode: cursor.map_or(LoopMode::Loop, Self::loop_mode),
+                        intro: cursor.is_some_and(|cursor| cursor.intro.is_some()),
                     });
                 } else {
-                    let eased_progress = Self::apply_easing(new_progress, &config.easing);
+                    // Easing applies when the frame is composited, the state advances linearly.
                     *state = TransitionState::Transitioning {
                         from_id: from_id.clone(),
                         to_id: to_id.clone(),
-                        progress: eased_progress,
+                        progress: new_progress,
                     };
                 }
             }
@@ -154,27 +630,218 @@ impl CursorAnimator {
                 cursor_id,
                 start_time_ms,
                 loop_mode,
+                intro,
             } => {
-                if let Some(cursor_def) = self.config.get_cursor(cursor_id) {
-                    if cursor_def.format == crate::cursor::vector::config::CursorFormat::Lottie {
-                        let new_start = *start_time_ms + elapsed_ms;
-                        *state = TransitionState::Animated {
-                            cursor_id: cursor_id.clone(),
-                            start_time_ms: new_start,
-                            loop_mode: loop_mode.clone(),
-                        };
+                // Any format may animate, SVGs through SMIL.
+                if self.config.get_cursor(cursor_id).is_some() {
+                    let elapsed_ms = self.playback_ms(cursor_id, elapsed_ms);
+                    let mut from_ms = *start_time_ms;
+                    let mut new_start = start_time_ms.saturating_add(elapsed_ms);
+                    let mut intro = *intro;
+                    if let Some(renderer) = renderer {
+                        if intro {
+                            // Time past the end of the intro counts towards the loop.
+                            let length = self.intro_ms(cursor_id, renderer).unwrap_or(0);
+                            if new_start >= length {
+                                from_ms = 0;
+                                new_start -= length;
+                                intro = false;
+                            }
+                        }
+                        if !intro {
+                            let scheduler = self.scheduler(cursor_id, renderer, *loop_mode);
+                            events.extend(Self::crossed_event(
+                                cursor_id, &scheduler, from_ms, new_start,
+                            ));
+                            // A finished `once` animation stays on its last frame for good.
+                            if let LoopMode::Once = loop_mode {
+                                let period = scheduler.period().as_millis();
+                                new_start =
+                                    new_start.min(u32::try_from(period).unwrap_or(u32::MAX));
+                            }
+                        }
                     }
+                    *state = TransitionState::Animated {
+                        cursor_id: cursor_id.clone(),
+                        start_time_ms: new_start,
+                        loop_mode: *loop_mode,
+                        intro,
+                    };
                 }
             }
+            TransitionState::Finishing {
+                cursor_id,
+                to_id,
+                start_time_ms,
+                loop_mode,
+                done,
+            } => {
+                let new_start =
+                    start_time_ms.saturating_add(self.playback_ms(cursor_id, elapsed_ms));
+                let finishing = renderer.and_then(|renderer| {
+                    self.finish_step(
+                        cursor_id,
+                        to_id,
+                        renderer,
+                        *loop_mode,
+                        *done,
+                        *start_time_ms,
+                        new_start,
+                    )
+                });
+
+                new_state = Some(match finishing {
+                    Some((start_time_ms, done)) => TransitionState::Finishing {
+                        cursor_id: cursor_id.clone(),
+                        to_id: to_id.clone(),
+                        start_time_ms,
+                        loop_mode: *loop_mode,
+                        done,
+                    },
+                    // Played out, or there's no way to tell where the loop ends.
+                    None if self.config.get_transition(cursor_id, to_id).is_some() => {
+                        events.push(AnimationEvent::TransitionStarted {
+                            from_id: cursor_id.clone(),
+                            to_id: to_id.clone(),
+                        });
+                        TransitionState::Transitioning {
+                            from_id: cursor_id.clone(),
+                            to_id: to_id.clone(),
+                            progress: 0.,
+                        }
+                    }
+                    // Only the outro was played, switch without a transition.
+                    None => self.enter(to_id.clone()),
+                });
+            }
             TransitionState::Static => {}
         }
 
         if let Some(s) = new_state {
             *state = s;
         }
+
+        events
     }
 
-    fn apply_easing(t: f32, easing: &EasingFunction) -> f32 {
+    /// Advances a [`TransitionState::Finishing`] cursor from `from_ms` to `to_ms`.
+    ///
+    /// Returns the new elapsed time and whether the done segment is playing, or `None` once the
+    /// transition should start.
+    #[allow(clippy::too_many_arguments)]
+    fn finish_step(
+        &self,
+        cursor_id: &str,
+        to_id: &str,
+        renderer: &dyn VectorRenderer,
+        loop_mode: LoopMode,
+        done: bool,
+        from_ms: u32,
+        to_ms: u32,
+    ) -> Option<(u32, bool)> {
+        let done_segment = self.done_segment(cursor_id, to_id, renderer);
+
+        if done {
+            let scheduler = self.once_scheduler(done_segment?, renderer);
+            let elapsed = Duration::from_millis(u64::from(to_ms));
+            return (!scheduler.frame_at(elapsed).completed).then_some((to_ms, true));
+        }
+
+        let scheduler = self.scheduler(cursor_id, renderer, loop_mode);
+        let period = scheduler.period().as_millis() as u32;
+        if !scheduler.is_animated() || period == 0 {
+            return None;
+        }
+
+        let end = match loop_mode {
+            LoopMode::Once => period,
+            // The end of the loop the cursor was in when the switch was requested.
+            LoopMode::Loop | LoopMode::Bounce => (from_ms / period + 1) * period,
+        };
+        if to_ms < end {
+            return Some((to_ms, false));
+        }
+
+        // Time past the end of the loop counts towards the done segment.
+        done_segment.map(|_| (to_ms - end.max(from_ms), true))
+    }
+
+    /// The done segment of the transition from `cursor_id` to `to_id`, or else the outro of
+    /// `cursor_id`, if there is one.
+    fn done_segment(
+        &self,
+        cursor_id: &str,
+        to_id: &str,
+        renderer: &dyn VectorRenderer,
+    ) -> Option<FrameRange> {
+        let name = self
+            .config
+            .get_transition(cursor_id, to_id)
+            .and_then(|transition| transition.done_segment.as_deref())
+            .or_else(|| self.config.get_cursor(cursor_id)?.outro.as_deref())?;
+        Self::named_segment(cursor_id, name, renderer)
+    }
+
+    /// The intro segment of `cursor_id`, if it has one.
+    fn intro_segment(&self, cursor_id: &str, renderer: &dyn VectorRenderer) -> Option<FrameRange> {
+        let name = self.config.get_cursor(cursor_id)?.intro.as_deref()?;
+        Self::named_segment(cursor_id, name, renderer)
+    }
+
+    /// Length of the intro of `cursor_id` in playback time.
+    fn intro_ms(&self, cursor_id: &str, renderer: &dyn VectorRenderer) -> Option<u32> {
+        let segment = self.intro_segment(cursor_id, renderer)?;
+        let period = self.once_scheduler(segment, renderer).period();
+        Some(u32::try_from(period.as_millis()).unwrap_or(u32::MAX))
+    }
+
+    fn named_segment(
+        cursor_id: &str,
+        name: &str,
+        renderer: &dyn VectorRenderer,
+    ) -> Option<FrameRange> {
+        let segment = renderer.segment(name);
+        if segment.is_none() {
+            warn!("cursor '{cursor_id}' has no segment named '{name}', skipping it");
+        }
+        segment
+    }
+
+    /// Timing of `segment` played once, like done segments and intros.
+    fn once_scheduler(&self, segment: FrameRange, renderer: &dyn VectorRenderer) -> FrameScheduler {
+        let frame_duration = Duration::from_millis(u64::from(renderer.frame_duration_ms()));
+        segment.scheduler(frame_duration, LoopMode::Once)
+    }
+
+    /// Events of an animation whose elapsed time went from `from_ms` to `to_ms`.
+    fn crossed_event(
+        cursor_id: &CursorId,
+        scheduler: &FrameScheduler,
+        from_ms: u32,
+        to_ms: u32,
+    ) -> Option<AnimationEvent> {
+        let from = Duration::from_millis(u64::from(from_ms));
+        let to = Duration::from_millis(u64::from(to_ms));
+
+        if scheduler.frame_at(to).completed {
+            return (!scheduler.frame_at(from).completed).then(|| AnimationEvent::Completed {
+                cursor_id: cursor_id.clone(),
+            });
+        }
+
+        let period = scheduler.period().as_millis();
+        if !scheduler.is_animated() || period == 0 {
+            return None;
+        }
+        // Several wraps within one update are reported as the latest one.
+        let iteration = u128::from(to_ms) / period;
+        (iteration > u128::from(from_ms) / period).then(|| AnimationEvent::Looped {
+            cursor_id: cursor_id.clone(),
+            iteration: iteration as u32,
+        })
+    }
+
+    fn apply_easing(t: f32, easing: &EasingFunction, spring: &SpringConfig) -> f32 {
         let t = t.clamp(0.0, 1.0);
         match easing {
             EasingFunction::Linear => t,
@@ -206,6 +873,388 @@ impl CursorAnimator {
                     (2.0f32).powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
                 }
             }
+            EasingFunction::Spring => spring.value_at(t),
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => {
+                let curve = CubicBezier::new(*x1, *y1, *x2, *y2);
+                keyframe::EasingFunction::y(&curve, f64::from(t)) as f32
+            }
+        }
+    }
+
+    /// Captures the current animation state.
+    ///
+    /// `renderer` renders the shown cursor and is used to turn the elapsed time into a phase.
+    pub fn snapshot(&self, renderer: Option<&dyn VectorRenderer>) -> Option<AnimatorSnapshot> {
+        match &*self.current_state.read() {
+            TransitionState::Static => None,
+            TransitionState::Transitioning {
+                from_id,
+                to_id,
+                progress,
+            } => Some(AnimatorSnapshot {
+                cursor_id: to_id.clone(),
+                phase: 0.,
+                transition: Some((from_id.clone(), *progress)),
+            }),
+            // The source cursor only plays out, carry over the switch itself.
+            TransitionState::Finishing {
+                cursor_id, to_id, ..
+            } => Some(AnimatorSnapshot {
+                cursor_id: to_id.clone(),
+                phase: 0.,
+                transition: Some((cursor_id.clone(), 0.)),
+            }),
+            TransitionState::Animated {
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                intro,
+            } => {
+                let elapsed = Duration::from_millis(u64::from(*start_time_ms));
+                // Intros aren't carried over, the loop picks up from its start.
+                let phase = match renderer {
+                    Some(r) if !intro => self.scheduler(cursor_id, r, *loop_mode).phase(elapsed),
+                    _ => 0.,
+                };
+
+                Some(AnimatorSnapshot {
+                    cursor_id: cursor_id.clone(),
+                    phase,
+                    transition: None,
+                })
+            }
+        }
+    }
+
+    /// Continues an animation captured with [`CursorAnimator::snapshot`], possibly from another
+    /// theme.
+    ///
+    /// Cursor IDs must already be translated to this theme. Cursors this theme doesn't define
+    /// leave the state unchanged, and transitions it doesn't define are skipped.
+    pub fn restore(&self, snapshot: &AnimatorSnapshot, renderer: Option<&dyn VectorRenderer>) {
+        // Re-interned, the snapshot's IDs may come from another theme.
+        let Some(cursor_id) = self.config.cursor_id(&snapshot.cursor_id) else {
+            debug!(
+                "cursor '{}' is not in the new theme, not restoring its animation",
+                snapshot.cursor_id
+            );
+            return;
+        };
+        let cursor_def = &self.config.cursors[&cursor_id];
+
+        if let Some((from_id, progress)) = &snapshot.transition {
+            if self.config.get_transition(from_id, &cursor_id).is_some() {
+                let from_id = self
+                    .config
+                    .cursor_id(from_id)
+                    .unwrap_or_else(|| from_id.clone());
+                *self.current_state.write() = TransitionState::Transitioning {
+                    from_id,
+                    to_id: cursor_id,
+                    progress: *progress,
+                };
+                return;
+            }
+        }
+
+        let loop_mode = Self::loop_mode(cursor_def);
+        let period = renderer.map_or(Duration::ZERO, |r| {
+            self.scheduler(&cursor_id, r, loop_mode).period()
+        });
+        *self.current_state.write() = TransitionState::Animated {
+            cursor_id,
+            start_time_ms: period
+                .mul_f64(f64::from(snapshot.phase.clamp(0., 1.)))
+                .as_millis() as u32,
+            loop_mode,
+            intro: false,
+        };
+    }
+
+    /// Timing of the frames played for `cursor_id`, relative to the start of its frame range.
+    pub fn scheduler(
+        &self,
+        cursor_id: &str,
+        renderer: &dyn VectorRenderer,
+        loop_mode: LoopMode,
+    ) -> FrameScheduler {
+        let frame_duration = Duration::from_millis(u64::from(renderer.frame_duration_ms()));
+        self.frame_range(cursor_id, renderer)
+            .scheduler(frame_duration, loop_mode)
+    }
+
+    fn loop_mode(cursor_def: &CursorDefinition) -> LoopMode {
+        match cursor_def.loop_mode.as_deref() {
+            Some("once") => LoopMode::Once,
+            Some("loop") => LoopMode::Loop,
+            Some("bounce") => LoopMode::Bounce,
+            _ => LoopMode::Loop,
+        }
+    }
+
+    /// Frame range played for `cursor_id`: its configured segment, or the whole animation.
+    pub fn frame_range(&self, cursor_id: &str, renderer: &dyn VectorRenderer) -> FrameRange {
+        let full = FrameRange::new(0, renderer.total_frames());
+
+        let Some(segment) = self
+            .config
+            .get_cursor(cursor_id)
+            .and_then(|def| def.segment.as_deref())
+        else {
+            return full;
+        };
+
+        renderer.segment(segment).unwrap_or_else(|| {
+            warn!("cursor '{cursor_id}' has no segment named '{segment}', playing all frames");
+            full
+        })
+    }
+
+    /// First frame shown for `cursor_id` when switching to it: the start of its intro, or of its
+    /// frame range.
+    pub fn entry_frame(&self, cursor_id: &str, renderer: &dyn VectorRenderer) -> u32 {
+        match self.intro_segment(cursor_id, renderer) {
+            Some(segment) => segment.start,
+            None => self.frame_range(cursor_id, renderer).start,
+        }
+    }
+
+    /// Last frame shown for `cursor_id` before transitioning to `to_id`: the end of its done
+    /// segment or outro, or the start of its frame range.
+    pub fn exit_frame(&self, cursor_id: &str, to_id: &str, renderer: &dyn VectorRenderer) -> u32 {
+        match self.done_segment(cursor_id, to_id, renderer) {
+            Some(segment) => segment.end - 1,
+            None => self.frame_range(cursor_id, renderer).start,
+        }
+    }
+
+    /// Frame of `renderer` to show for the current animation state.
+    pub fn current_frame(&self, renderer: &dyn VectorRenderer) -> u32 {
+        if let Some(frame) = self.frozen_frame() {
+            return frame.min(renderer.total_frames().saturating_sub(1));
+        }
+        match self.playing(renderer) {
+            Some((range, scheduler, elapsed)) => range.frame_at(&scheduler, elapsed),
+            None => 0,
+        }
+    }
+
+    /// Position between the frames of `renderer` for the current animation state, for cursors
+    /// that `interpolate`.
+    ///
+    /// Advances smoothly from [`CursorAnimator::current_frame`] towards the frame shown next,
+    /// except where the animation jumps, like back to the start of a loop.
+    pub fn current_position(&self, renderer: &dyn VectorRenderer) -> f32 {
+        if self.frozen_frame().is_some() {
+            return self.current_frame(renderer) as f32;
+        }
+        let Some((range, scheduler, elapsed)) = self.playing(renderer) else {
+            return 0.;
+        };
+        let frame = scheduler.frame_at(elapsed);
+        let index = frame.index as f32;
+        let frame_duration = Duration::from_millis(u64::from(renderer.frame_duration_ms()));
+        let position = match frame.next_deadline {
+            Some(until) if !frame_duration.is_zero() => {
+                let next = scheduler.frame_at(elapsed + until).index as f32;
+                let t = 1. - until.as_secs_f32() / frame_duration.as_secs_f32();
+                if (next - index).abs() == 1. {
+                    index + (next - index) * t.clamp(0., 1.)
+                } else {
+                    index
+                }
+            }
+            _ => index,
+        };
+        range.start as f32 + position
+    }
+
+    /// Range of frames played for the current animation state, its timing and the time into
+    /// it, or `None` when no cursor is animated.
+    fn playing(
+        &self,
+        renderer: &dyn VectorRenderer,
+    ) -> Option<(FrameRange, FrameScheduler, Duration)> {
+        let state = self.current_state.read();
+        let (cursor_id, start_time_ms, loop_mode, segment) = match &*state {
+            TransitionState::Animated {
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                intro,
+            } => (
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                intro.then(|| self.intro_segment(cursor_id, renderer)),
+            ),
+            TransitionState::Finishing {
+                cursor_id,
+                to_id,
+                start_time_ms,
+                loop_mode,
+                done,
+            } => (
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                done.then(|| self.done_segment(cursor_id, to_id, renderer)),
+            ),
+            _ => return None,
+        };
+        let elapsed = Duration::from_millis(u64::from(*start_time_ms));
+
+        Some(match segment {
+            // Intros and done segments play once.
+            Some(Some(segment)) => (segment, self.once_scheduler(segment, renderer), elapsed),
+            // Missing ones hold the start of the loop.
+            Some(None) => {
+                let start = self.frame_range(cursor_id, renderer).start;
+                let still = FrameRange::new(start, start);
+                (still, self.once_scheduler(still, renderer), elapsed)
+            }
+            None => {
+                let range = self.frame_range(cursor_id, renderer);
+                let scheduler = self.scheduler(cursor_id, renderer, *loop_mode);
+                (range, scheduler, elapsed)
+            }
+        })
+    }
+
+    /// Eased progress of the ongoing transition, what the frames of both cursors are blended by.
+    pub fn transition_progress(&self) -> Option<f32> {
+        let state = self.current_state.read();
+        let TransitionState::Transitioning {
+            from_id,
+            to_id,
+            progress,
+        } = &*state
+        else {
+            return None;
+        };
+        Some(self.eased_progress(from_id, to_id, *progress))
+    }
+
+    /// Eased `progress` of the transition from `from_id` to `to_id`.
+    fn eased_progress(&self, from_id: &str, to_id: &str, progress: f32) -> f32 {
+        match self.config.get_transition_or_reverse(from_id, to_id) {
+            Some((transition, _)) => {
+                Self::apply_easing(progress, &transition.easing, &transition.spring)
+            }
+            None => progress.clamp(0., 1.),
+        }
+    }
+
+    /// Linear progress at which the transition from `from_id` to `to_id` shows `eased` of the
+    /// target cursor.
+    fn progress_showing(&self, from_id: &str, to_id: &str, eased: f32) -> f32 {
+        let Some((transition, _)) = self.config.get_transition_or_reverse(from_id, to_id) else {
+            return eased.clamp(0., 1.);
+        };
+        // Easing functions rise from 0 to 1, mostly monotonically.
+        let (mut low, mut high) = (0f32, 1f32);
+        for _ in 0..24 {
+            let mid = (low + high) / 2.;
+            if Self::apply_easing(mid, &transition.easing, &transition.spring) < eased {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.
+    }
+
+    /// Whether the shown frame will still change without a cursor switch, so the cursor needs
+    /// redraws.
+    ///
+    /// `renderer` renders the shown cursor; without it, animated cursors are assumed to be still.
+    pub fn is_animating(&self, renderer: Option<&dyn VectorRenderer>) -> bool {
+        if self.is_frozen() {
+            return false;
+        }
+        // A debounced change is coming up.
+        if self.pending.lock().is_some() {
+            return true;
+        }
+        match &*self.current_state.read() {
+            TransitionState::Static => false,
+            TransitionState::Transitioning { .. }
+            | TransitionState::Finishing { .. }
+            | TransitionState::Animated { intro: true, .. } => true,
+            TransitionState::Animated {
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                ..
+            } => renderer.is_some_and(|renderer| {
+                let scheduler = self.scheduler(cursor_id, renderer, *loop_mode);
+                let elapsed = Duration::from_millis(u64::from(*start_time_ms));
+                scheduler.is_animated() && !scheduler.frame_at(elapsed).completed
+            }),
+        }
+    }
+
+    /// Monotonic time the shown frame changes next, like the times passed to
+    /// [`CursorAnimator::tick`], or `None` if it doesn't change on its own.
+    ///
+    /// Transitions change every frame, so their deadline is the time the animation was last
+    /// advanced to, or one frame of [`CursorAnimator::set_max_fps`] later. Before the clock
+    /// starts, animated cursors are due right away. A debounced cursor change is due once it
+    /// has waited `debounce_ms`.
+    pub fn next_frame_deadline(&self, renderer: Option<&dyn VectorRenderer>) -> Option<Duration> {
+        if !self.is_animating(renderer) {
+            return None;
+        }
+        let Some(last) = *self.last_update.lock() else {
+            return Some(Duration::ZERO);
+        };
+
+        let pending = self.pending.lock().as_ref().map(|(_, waited)| {
+            let wait = self.config.debounce_ms.saturating_sub(*waited);
+            last + Duration::from_millis(u64::from(wait))
+        });
+        let frame = self.frame_deadline(last, renderer);
+        frame.into_iter().chain(pending).min()
+    }
+
+    /// Time the shown frame changes next, see [`CursorAnimator::next_frame_deadline`].
+    fn frame_deadline(
+        &self,
+        last: Duration,
+        renderer: Option<&dyn VectorRenderer>,
+    ) -> Option<Duration> {
+        match &*self.current_state.read() {
+            TransitionState::Static => None,
+            TransitionState::Animated {
+                cursor_id,
+                start_time_ms,
+                loop_mode,
+                intro,
+            } => {
+                // Interpolated cursors change with every output frame, like transitions.
+                if self.interpolates(cursor_id) {
+                    return Some(last + *self.frame_interval.lock());
+                }
+                let renderer = renderer?;
+                let elapsed = Duration::from_millis(u64::from(*start_time_ms));
+                let until = match self.intro_segment(cursor_id, renderer) {
+                    // The loop starts when the intro ends.
+                    Some(segment) if *intro => self
+                        .once_scheduler(segment, renderer)
+                        .frame_at(elapsed)
+                        .next_deadline
+                        .unwrap_or_default(),
+                    _ => {
+                        let scheduler = self.scheduler(cursor_id, renderer, *loop_mode);
+                        scheduler.frame_at(elapsed).next_deadline?
+                    }
+                };
+                let speed = self.playback_speed(cursor_id);
+                let until =
+                    Duration::try_from_secs_f64(until.as_secs_f64() / speed).unwrap_or(until);
+                Some(last + until.max(*self.frame_interval.lock()))
+            }
+            _ => Some(last + *self.frame_interval.lock()),
         }
     }
 
@@ -213,7 +1262,757 @@ impl CursorAnimator {
         self.base_size
     }
 
-    pub fn current_state(&self) -> std::cell::Ref<'_, TransitionState> {
-        self.current_state.borrow()
+    pub fn current_state(&self) -> RwLockReadGuard<'_, TransitionState> {
+        self.current_state.read()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+    use smithay::utils::{Physical, Point};
+
+    use super::*;
+    use crate::cursor::vector::types::RenderedFrame;
+
+    struct FakeRenderer {
+        total_frames: u32,
+    }
+
+    impl VectorRenderer for FakeRenderer {
+        fn render_frame(&self, _frame: u32, _scale: f64) -> Result<RenderedFrame> {
+            bail!("not rendered in tests")
+        }
+
+        fn hotspot(&self) -> Point<i32, Physical> {
+            Point::from((0, 0))
+        }
+
+        fn total_frames(&self) -> u32 {
+            self.total_frames
+        }
+
+        fn frame_duration_ms(&self) -> u32 {
+            10
+        }
+
+        fn segment(&self, name: &str) -> Option<FrameRange> {
+            match name {
+                "done" => Some(FrameRange::new(0, 5)),
+                "intro" => Some(FrameRange::new(0, 3)),
+                "spin" => Some(FrameRange::new(3, 8)),
+                "outro" => Some(FrameRange::new(8, 10)),
+                _ => None,
+            }
+        }
+    }
+
+    fn animator(theme: &str) -> CursorAnimator {
+        CursorAnimator::new(CursorThemeConfig::from_toml(theme).unwrap(), 24)
+    }
+
+    const THEME: &str = r#"
+[cursors.default]
+format = "lottie"
+file = "default.json"
+
+[cursors.wait]
+format = "lottie"
+file = "wait.json"
+
+[transitions."default->wait"]
+duration_ms = 100
+"#;
+
+    #[test]
+    fn tick_advances_by_elapsed_time() {
+        let animator = animator(THEME);
+        let renderer = FakeRenderer { total_frames: 40 };
+        let start_time = || match &*animator.current_state() {
+            TransitionState::Animated { start_time_ms, .. } => *start_time_ms,
+            state => panic!("unexpected state {state:?}"),
+        };
+
+        // The first tick starts the clock.
+        assert!(!animator.tick(Duration::from_secs(5), Some(&renderer)));
+        assert_eq!(start_time(), 0);
+        assert_eq!(animator.current_frame(&renderer), 0);
+
+        // A second tick for the same time, like for another output, doesn't advance.
+        assert!(animator.tick(Duration::from_micros(5_016_700), Some(&renderer)));
+        assert!(!animator.tick(Duration::from_micros(5_016_700), Some(&renderer)));
+        assert_eq!(start_time(), 16);
+        // The 0.7 ms left over count towards the next tick.
+        animator.tick(Duration::from_micros(5_033_400), Some(&renderer));
+        assert_eq!(start_time(), 33);
+        assert_eq!(animator.current_frame(&renderer), 3);
+    }
+
+    #[test]
+    fn restore_keeps_phase() {
+        let old = animator(THEME);
+        old.set_cursor("wait").unwrap();
+        *old.current_state.write() = TransitionState::Animated {
+            cursor_id: "wait".into(),
+            start_time_ms: 250,
+            loop_mode: LoopMode::Loop,
+            intro: false,
+        };
+
+        // 25 of 40 frames into the loop.
+        let snapshot = old
+            .snapshot(Some(&FakeRenderer { total_frames: 40 }))
+            .unwrap();
+        assert_eq!(snapshot.cursor_id, "wait");
+        assert_eq!(snapshot.phase, 0.625);
+
+        // The same phase of a 20-frame loop.
+        let new = animator(THEME);
+        new.restore(&snapshot, Some(&FakeRenderer { total_frames: 20 }));
+        assert!(matches!(
+            &*new.current_state(),
+            TransitionState::Animated { cursor_id, start_time_ms: 125, .. } if cursor_id == "wait"
+        ));
+    }
+
+    #[test]
+    fn next_frame_deadlines() {
+        let animator = animator(THEME);
+        let renderer = FakeRenderer { total_frames: 40 };
+        assert_eq!(animator.next_frame_deadline(None), None);
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::ZERO)
+        );
+
+        // Frames last 10 ms.
+        animator.tick(Duration::from_secs(1), Some(&renderer));
+        animator.tick(Duration::from_micros(1_004_500), Some(&renderer));
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::from_millis(1010))
+        );
+
+        animator.set_cursor("wait").unwrap();
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::from_millis(1004))
+        );
+
+        let still = FakeRenderer { total_frames: 1 };
+        animator.update(100, None);
+        assert_eq!(animator.next_frame_deadline(Some(&still)), None);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let animator = Arc::new(animator(THEME));
+        let reader = {
+            let animator = animator.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    assert!(!matches!(
+                        &*animator.current_state(),
+                        TransitionState::Static
+                    ));
+                }
+            })
+        };
+        for _ in 0..100 {
+            animator.set_cursor("wait").unwrap();
+            animator.update(10, None);
+            animator.set_cursor("default").unwrap();
+        }
+        reader.join().unwrap();
+    }
+
+    #[test]
+    fn capped_frame_rate() {
+        let animator = animator(THEME);
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.set_max_fps(Some(20));
+        animator.tick(Duration::from_secs(1), Some(&renderer));
+
+        // Frames last 10 ms, but only every 50 ms one is shown.
+        animator.tick(Duration::from_millis(1030), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 0);
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::from_millis(1050))
+        );
+        animator.tick(Duration::from_millis(1050), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+
+        animator.set_max_fps(None);
+        animator.tick(Duration::from_millis(1060), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 6);
+    }
+
+    #[test]
+    fn interpolated_positions() {
+        let animator = animator(
+            r#"
+[cursors.default]
+format = "lottie"
+file = "default.json"
+interpolate = true
+"#,
+        );
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.tick(Duration::from_secs(1), Some(&renderer));
+        animator.tick(Duration::from_millis(1025), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 2);
+        assert!((animator.current_position(&renderer) - 2.5).abs() < 1e-4);
+        // Every output frame shows a new position.
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::from_millis(1025))
+        );
+
+        // The loop jumps back to its start instead of rewinding.
+        animator.update(370, Some(&renderer));
+        assert_eq!(animator.current_position(&renderer), 39.);
+    }
+
+    #[test]
+    fn cursors_play_at_their_speed() {
+        let theme = THEME.replace("file = \"wait.json\"", "file = \"wait.json\"\nspeed = 2");
+        let animator = animator(&theme);
+        let events = record_events(&animator);
+        let renderer = FakeRenderer { total_frames: 10 };
+        animator.update(20, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 2);
+
+        animator.set_cursor("wait").unwrap();
+        animator.update(100, Some(&renderer));
+        events.lock().clear();
+        animator.update(20, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 4);
+
+        // The 100 ms loop takes 50 ms.
+        animator.update(29, Some(&renderer));
+        assert!(events.lock().is_empty());
+        animator.update(1, Some(&renderer));
+        assert_eq!(
+            *events.lock(),
+            [AnimationEvent::Looped {
+                cursor_id: "wait".into(),
+                iteration: 1,
+            }]
+        );
+        animator.tick(Duration::ZERO, Some(&renderer));
+        assert_eq!(
+            animator.next_frame_deadline(Some(&renderer)),
+            Some(Duration::from_millis(5))
+        );
+    }
+
+    #[test]
+    fn pointer_motion_speeds_up_playback() {
+        let theme = THEME.replace(
+            "file = \"default.json\"",
+            "file = \"default.json\"\nvelocity_speed = { speedup = 0.5, max = 2 }",
+        );
+        let animator = animator(&theme);
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.update(20, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 2);
+
+        animator.set_pointer_velocity(1000.);
+        animator.update(20, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+        // The next frame is due in 10 ms of playback time.
+        animator.tick(Duration::ZERO, Some(&renderer));
+        let deadline = animator.next_frame_deadline(Some(&renderer)).unwrap();
+        assert!((deadline.as_secs_f64() - 0.01 / 1.5).abs() < 1e-6);
+
+        // Capped at `max`.
+        animator.set_pointer_velocity(10_000.);
+        animator.update(20, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 9);
+    }
+
+    #[test]
+    fn paused_animations_stay_put() {
+        let animator = animator(THEME);
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.tick(Duration::from_secs(1), Some(&renderer));
+        animator.tick(Duration::from_millis(1050), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+
+        animator.pause();
+        assert!(!animator.is_animating(Some(&renderer)));
+        animator.tick(Duration::from_secs(2), Some(&renderer));
+        animator.update(100, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+
+        // Cursor changes don't wait for the frozen transition.
+        animator.set_cursor("wait").unwrap();
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, start_time_ms: 0, .. } if cursor_id == "wait"
+        ));
+
+        // The paused time is skipped.
+        animator.resume();
+        animator.tick(Duration::from_millis(2030), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 3);
+        assert!(animator.is_animating(Some(&renderer)));
+    }
+
+    #[test]
+    fn frozen_frames_step() {
+        let animator = animator(THEME);
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.tick(Duration::from_secs(1), Some(&renderer));
+        animator.tick(Duration::from_millis(1050), Some(&renderer));
+
+        // Stepping freezes at the current frame first.
+        animator.step_frame(1, &renderer);
+        assert_eq!(animator.frozen_frame(), Some(6));
+        animator.tick(Duration::from_secs(2), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 6);
+        assert!(!animator.is_animating(Some(&renderer)));
+
+        animator.step_frame(-7, &renderer);
+        assert_eq!(animator.current_frame(&renderer), 39);
+        animator.freeze_at_frame(Some(50));
+        assert_eq!(animator.current_frame(&renderer), 39);
+        assert_eq!(animator.current_position(&renderer), 39.);
+
+        // Thawing continues where the animation was frozen.
+        animator.freeze_at_frame(None);
+        animator.tick(Duration::from_millis(2010), Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 6);
+    }
+
+    #[test]
+    fn reduced_motion_shows_first_frames() {
+        let animator = animator(THEME);
+        let events = record_events(&animator);
+        let renderer = FakeRenderer { total_frames: 40 };
+        animator.set_cursor("wait").unwrap();
+        animator.update(50, Some(&renderer));
+        events.lock().clear();
+
+        animator.set_reduce_motion(true);
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, start_time_ms: 0, .. } if cursor_id == "wait"
+        ));
+        animator.update(50, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 0);
+        assert!(!animator.is_animating(Some(&renderer)));
+
+        animator.set_cursor("default").unwrap();
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, .. } if cursor_id == "default"
+        ));
+        assert!(events.lock().is_empty());
+
+        animator.set_reduce_motion(false);
+        animator.update(50, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+    }
+
+    fn record_events(animator: &CursorAnimator) -> Arc<Mutex<Vec<AnimationEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_ = events.clone();
+        animator.add_observer(Arc::new(move |event: &AnimationEvent| {
+            events_.lock().push(event.clone())
+        }));
+        events
+    }
+
+    #[test]
+    fn flicker_is_debounced() {
+        let animator = animator(&format!("debounce_ms = 30\n{THEME}"));
+        let events = record_events(&animator);
+
+        // Hovering for less than the debounce time doesn't start a transition.
+        animator.set_cursor("wait").unwrap();
+        animator.update(10, None);
+        animator.set_cursor("default").unwrap();
+        animator.update(50, None);
+        assert!(events.lock().is_empty());
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, .. } if cursor_id == "default"
+        ));
+
+        // Otherwise the change takes effect after waiting.
+        animator.tick(Duration::from_secs(1), None);
+        animator.set_cursor("wait").unwrap();
+        animator.tick(Duration::from_millis(1020), None);
+        assert_eq!(animator.transition_progress(), None);
+        assert_eq!(
+            animator.next_frame_deadline(None),
+            Some(Duration::from_millis(1030))
+        );
+        animator.tick(Duration::from_millis(1030), None);
+        assert!(matches!(
+            events.lock().as_slice(),
+            [AnimationEvent::TransitionStarted { to_id, .. }] if to_id == "wait"
+        ));
+    }
+
+    #[test]
+    fn only_transitions_are_debounced() {
+        let animator = animator(&format!("debounce_ms = 30\n{THEME}"));
+        let target = || animator.target().map(|id| id.to_string());
+        animator.tick(Duration::from_secs(1), None);
+
+        // Setting the pending cursor again doesn't restart the wait.
+        animator.set_cursor("wait").unwrap();
+        animator.tick(Duration::from_millis(1020), None);
+        animator.set_cursor("wait").unwrap();
+        animator.tick(Duration::from_millis(1030), None);
+        assert_eq!(target().as_deref(), Some("wait"));
+
+        // There's no transition back, so nothing to debounce.
+        animator.set_cursor("default").unwrap();
+        assert_eq!(target().as_deref(), Some("default"));
+
+        // Neither are cursors niri sets itself.
+        animator.set_cursor_now("wait").unwrap();
+        assert_eq!(target().as_deref(), Some("wait"));
+    }
+
+    #[test]
+    fn transition_and_loop_events() {
+        let animator = animator(THEME);
+        let events = record_events(&animator);
+        let renderer = FakeRenderer { total_frames: 10 };
+
+        animator.set_cursor("wait").unwrap();
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            [AnimationEvent::TransitionStarted {
+                from_id: "default".into(),
+                to_id: "wait".into(),
+            }]
+        );
+        animator.update(60, Some(&renderer));
+        assert!(events.lock().is_empty());
+        animator.update(60, Some(&renderer));
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            [AnimationEvent::TransitionFinished {
+                from_id: "default".into(),
+                to_id: "wait".into(),
+            }]
+        );
+
+        // 10 frames of 10 ms loop every 100 ms.
+        animator.update(90, Some(&renderer));
+        assert!(events.lock().is_empty());
+        animator.update(20, Some(&renderer));
+        assert_eq!(
+            events.lock().drain(..).collect::<Vec<_>>(),
+            [AnimationEvent::Looped {
+                cursor_id: "wait".into(),
+                iteration: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn once_completes_once() {
+        let animator = animator(
+            r#"
+[cursors.default]
+format = "lottie"
+file = "default.json"
+loop_mode = "once"
+"#,
+        );
+        let events = record_events(&animator);
+        let renderer = FakeRenderer { total_frames: 10 };
+
+        animator.update(50, Some(&renderer));
+        assert!(animator.is_animating(Some(&renderer)));
+        animator.update(50, Some(&renderer));
+        animator.update(50, Some(&renderer));
+        assert_eq!(
+            *events.lock(),
+            [AnimationEvent::Completed {
+                cursor_id: "default".into(),
+            }]
+        );
+
+        // The last frame stays up without needing redraws.
+        assert_eq!(animator.current_frame(&renderer), 9);
+        assert!(!animator.is_animating(Some(&renderer)));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated {
+                start_time_ms: 100,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn transitions_ease_their_progress() {
+        let theme = THEME.replace(
+            "duration_ms = 100",
+            "duration_ms = 100\neasing = \"easein\"",
+        );
+        let animator = animator(&theme);
+        assert_eq!(animator.transition_progress(), None);
+
+        animator.set_cursor("wait").unwrap();
+        animator.update(50, None);
+        assert_eq!(animator.transition_progress(), Some(0.25));
+        animator.update(25, None);
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Transitioning { progress, .. } if *progress == 0.75
+        ));
+        assert_eq!(animator.transition_progress(), Some(0.5625));
+
+        let spring = SpringConfig::default();
+        let linear = EasingFunction::CubicBezier(0., 0., 1., 1.);
+        assert!((CursorAnimator::apply_easing(0.3, &linear, &spring) - 0.3).abs() < 1e-4);
+        let overshoot = EasingFunction::CubicBezier(0.3, 1.5, 0.7, 1.5);
+        assert!(CursorAnimator::apply_easing(0.8, &overshoot, &spring) > 1.);
+    }
+
+    #[test]
+    fn springs_bounce_into_place() {
+        let spring = SpringConfig {
+            stiffness: 800.,
+            damping_ratio: 0.3,
+        };
+        let values: Vec<f32> = (0..=20)
+            .map(|i| CursorAnimator::apply_easing(i as f32 / 20., &EasingFunction::Spring, &spring))
+            .collect();
+        assert!(values[0].abs() < 1e-4);
+        assert!(values.iter().any(|&value| value > 1.05));
+        assert_eq!(values[20], 1.);
+
+        // The transition lasts until the spring settles.
+        let theme = THEME.replace(
+            "duration_ms = 100",
+            "easing = \"spring\"\nspring = { stiffness = 800, damping_ratio = 0.3 }",
+        );
+        let animator = animator(&theme);
+        animator.set_cursor("wait").unwrap();
+        let length = spring.duration().as_millis() as u32;
+        animator.update(length - 10, None);
+        assert!(animator.transition_progress().is_some());
+        animator.update(20, None);
+        assert!(animator.transition_progress().is_none());
+    }
+
+    #[test]
+    fn interrupted_transitions_turn_around() {
+        let theme = format!(
+            "{THEME}\n[cursors.pointer]\nformat = \"lottie\"\nfile = \"pointer.json\"\n\
+             [transitions.\"default->pointer\"]\n"
+        );
+        let animator = animator(&theme);
+        let transition = || match &*animator.current_state() {
+            TransitionState::Transitioning {
+                from_id,
+                to_id,
+                progress,
+            } => (from_id.to_string(), to_id.to_string(), *progress),
+            state => panic!("unexpected state {state:?}"),
+        };
+
+        // Reversed at 0.3 of the way, 0.18 eased, the way back shows as much.
+        animator.set_cursor("wait").unwrap();
+        animator.update(30, None);
+        animator.set_cursor("default").unwrap();
+        let (from_id, to_id, progress) = transition();
+        assert_eq!((from_id.as_str(), to_id.as_str()), ("wait", "default"));
+        assert!((progress - 0.7).abs() < 1e-4);
+        assert!((animator.transition_progress().unwrap() - 0.82).abs() < 1e-4);
+
+        // It finishes with the duration of the transition it reverses.
+        animator.update(70, None);
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, .. } if cursor_id == "default"
+        ));
+
+        // Retargeted early, the transition continues from the source cursor.
+        animator.set_cursor("wait").unwrap();
+        animator.update(20, None);
+        animator.set_cursor("pointer").unwrap();
+        assert_eq!(
+            transition(),
+            (String::from("default"), String::from("pointer"), 0.)
+        );
+
+        // Retargeted late, from the target, which has no transition to the new cursor.
+        animator.set_cursor("wait").unwrap();
+        animator.update(60, None);
+        animator.set_cursor("pointer").unwrap();
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, .. } if cursor_id == "pointer"
+        ));
+    }
+
+    #[test]
+    fn bounce_after_transition() {
+        let theme = THEME.replace(
+            "file = \"wait.json\"",
+            "file = \"wait.json\"\nloop_mode = \"bounce\"",
+        );
+        let animator = animator(&theme);
+        let renderer = FakeRenderer { total_frames: 4 };
+
+        animator.set_cursor("wait").unwrap();
+        animator.update(100, Some(&renderer));
+        let mut frames = Vec::new();
+        for _ in 0..8 {
+            frames.push(animator.current_frame(&renderer));
+            animator.update(10, Some(&renderer));
+        }
+        assert_eq!(frames, [0, 1, 2, 3, 2, 1, 0, 1]);
+    }
+
+    const FINISHING_THEME: &str = r#"
+[cursors.default]
+format = "lottie"
+file = "default.json"
+
+[cursors.progress]
+format = "lottie"
+file = "progress.json"
+
+[transitions."progress->default"]
+duration_ms = 100
+finish_loop = true
+done_segment = "done"
+"#;
+
+    #[test]
+    fn finishes_loop_and_plays_done_segment() {
+        let animator = animator(FINISHING_THEME);
+        let renderer = FakeRenderer { total_frames: 10 };
+        animator.set_cursor("progress").unwrap();
+        animator.update(250, Some(&renderer));
+
+        // The switch waits for the loop to end at 300 ms.
+        animator.set_cursor("default").unwrap();
+        animator.update(40, Some(&renderer));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Finishing {
+                done: false,
+                start_time_ms: 290,
+                ..
+            }
+        ));
+        assert_eq!(animator.current_frame(&renderer), 9);
+
+        // Then the done segment plays once.
+        animator.update(20, Some(&renderer));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Finishing {
+                done: true,
+                start_time_ms: 10,
+                ..
+            }
+        ));
+        assert_eq!(animator.current_frame(&renderer), 1);
+
+        animator.update(50, Some(&renderer));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Transitioning { from_id, to_id, .. }
+                if from_id == "progress" && to_id == "default"
+        ));
+    }
+
+    #[test]
+    fn intro_loop_and_outro() {
+        let animator = animator(
+            r#"
+[cursors.default]
+format = "lottie"
+file = "default.json"
+
+[cursors.wait]
+format = "lottie"
+file = "wait.json"
+intro = "intro"
+segment = "spin"
+outro = "outro"
+"#,
+        );
+        let renderer = FakeRenderer { total_frames: 10 };
+
+        // The intro plays once, then the loop takes over.
+        animator.set_cursor("wait").unwrap();
+        assert_eq!(animator.current_frame(&renderer), 0);
+        animator.update(25, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 2);
+        animator.update(10, Some(&renderer));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated {
+                start_time_ms: 5,
+                intro: false,
+                ..
+            }
+        ));
+        assert_eq!(animator.current_frame(&renderer), 3);
+        animator.update(70, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 5);
+
+        // Leaving the cursor plays its outro first, even without a transition.
+        animator.set_cursor("default").unwrap();
+        assert_eq!(animator.current_frame(&renderer), 8);
+        animator.update(10, Some(&renderer));
+        assert_eq!(animator.current_frame(&renderer), 9);
+        animator.update(20, Some(&renderer));
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated { cursor_id, intro: false, .. } if cursor_id == "default"
+        ));
+    }
+
+    #[test]
+    fn switching_back_resumes_the_loop() {
+        let animator = animator(FINISHING_THEME);
+        let renderer = FakeRenderer { total_frames: 10 };
+        animator.set_cursor("progress").unwrap();
+        animator.update(250, Some(&renderer));
+
+        animator.set_cursor("default").unwrap();
+        animator.set_cursor("progress").unwrap();
+        assert!(matches!(
+            &*animator.current_state(),
+            TransitionState::Animated {
+                start_time_ms: 250,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn restore_keeps_transition() {
+        let old = animator(THEME);
+        old.set_cursor("wait").unwrap();
+        old.update(50, None);
+        let snapshot = old.snapshot(None).unwrap();
+        assert_eq!(
+            snapshot.transition.as_ref().map(|(from, _)| from.as_str()),
+            Some("default")
+        );
+
+        let new = animator(THEME);
+        new.restore(&snapshot, None);
+        assert!(matches!(
+            &*new.current_state(),
+            TransitionState::Transitioning { to_id, .. } if to_id == "wait"
+        ));
     }
 }
@@ -0,0 +1,162 @@
+//! Benchmarking entry points for vector cursor rendering.
+//!
+//! These are shaped after criterion's `Bencher::iter` model: set up a [`RenderBench`] once, then
+//! call [`RenderBench::iter`] in the measured closure. [`RenderBench::run`] provides a
+//! self-contained timing loop for harness-less bench targets, and [`RenderBench::run_gpu`] the
+//! same loop with every frame also uploaded and drawn with a [`GlesRenderer`], like the cursor is
+//! on screen.
+
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBufferRenderElement;
+use smithay::backend::renderer::element::Kind;
+use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::utils::{Point, Scale, Transform};
+
+use crate::cursor::vector::config::CursorThemeConfig;
+use crate::cursor::vector::renderer::VectorRenderer;
+use crate::cursor::vector::stats::DurationHistogram;
+use crate::cursor::vector::store::VectorCursorStore;
+use crate::cursor::vector::types::RenderedFrame;
+use crate::render_helpers::render_to_texture;
+
+/// Loads a vector cursor theme directory for benchmarking.
+pub fn load_store(theme_dir: &Path, base_size: u8) -> Result<VectorCursorStore> {
+    let config_path = theme_dir.join("theme.toml");
+    let config_str = std::fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    let config = CursorThemeConfig::from_toml(&config_str)?;
+    VectorCursorStore::new(theme_dir.to_path_buf(), config, base_size)
+}
+
+/// Renders consecutive frames of one cursor at one scale.
+pub struct RenderBench {
+    renderer: Rc<dyn VectorRenderer>,
+    scale: f64,
+    frame: u32,
+}
+
+/// Timings collected by [`RenderBench::run`].
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub frames: u32,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub histogram: DurationHistogram,
+}
+
+impl RenderBench {
+    pub fn new(store: &VectorCursorStore, cursor_id: &str, scale: f64) -> Result<Self> {
+        let renderer = store.get_renderer(cursor_id)?;
+        Ok(Self::with_renderer(renderer, scale))
+    }
+
+    /// Benchmarks an arbitrary renderer, e.g. to compare backends on the same asset.
+    pub fn with_renderer(renderer: Rc<dyn VectorRenderer>, scale: f64) -> Self {
+        Self {
+            renderer,
+            scale,
+            frame: 0,
+        }
+    }
+
+    /// Renders the next frame, wrapping around at the end of the animation.
+    pub fn iter(&mut self) -> Result<RenderedFrame> {
+        let total_frames = self.renderer.total_frames().max(1);
+        let frame = self.frame;
+        self.frame = (self.frame + 1) % total_frames;
+        self.renderer.render_frame(frame, self.scale)
+    }
+
+    /// Renders `frames` frames and reports their timings.
+    pub fn run(&mut self, frames: u32) -> Result<BenchReport> {
+        self.run_with(frames, |_| Ok(()))
+    }
+
+    /// Renders `frames` frames, uploads each to a texture and draws it with `renderer`, then
+    /// reports their timings.
+    ///
+    /// Every frame waits for the GPU to finish, so the timings cover the whole path from
+    /// rasterizing to the cursor being drawn.
+    pub fn run_gpu(&mut self, renderer: &mut GlesRenderer, frames: u32) -> Result<BenchReport> {
+        self.run_with(frames, |rendered| {
+            let element = MemoryRenderBufferRenderElement::from_buffer(
+                renderer,
+                Point::from((0., 0.)),
+                &rendered.buffer,
+                None,
+                None,
+                None,
+                Kind::Cursor,
+            )
+            .context("error importing the cursor buffer")?;
+
+            let size = rendered
+                .size
+                .to_logical(1, Transform::Normal)
+                .to_physical(1);
+            let (_texture, sync_point) = render_to_texture(
+                renderer,
+                size,
+                Scale::from(1.),
+                Transform::Normal,
+                Fourcc::Abgr8888,
+                std::iter::once(element),
+            )?;
+            sync_point
+                .wait()
+                .map_err(|_| anyhow::anyhow!("interrupted waiting for the GPU"))
+        })
+    }
+
+    fn run_with(
+        &mut self,
+        frames: u32,
+        mut present: impl FnMut(&RenderedFrame) -> Result<()>,
+    ) -> Result<BenchReport> {
+        let mut total = Duration::ZERO;
+        let mut min = Duration::MAX;
+        let mut max = Duration::ZERO;
+        let mut histogram = DurationHistogram::new();
+
+        for _ in 0..frames {
+            let start = Instant::now();
+            let rendered = self.iter()?;
+            present(&rendered)?;
+            let elapsed = start.elapsed();
+            drop(rendered);
+
+            total += elapsed;
+            min = min.min(elapsed);
+            max = max.max(elapsed);
+            histogram.record(elapsed);
+        }
+
+        if frames == 0 {
+            min = Duration::ZERO;
+        }
+
+        Ok(BenchReport {
+            frames,
+            total,
+            min,
+            max,
+            histogram,
+        })
+    }
+}
+
+impl BenchReport {
+    pub fn mean(&self) -> Duration {
+        if self.frames == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.frames
+        }
+    }
+}
@@ -0,0 +1,534 @@
+//! Conversion of cursor colors to the color space of the display.
+//!
+//! Cursors and tints are authored in sRGB, but the compositor hands pixels to wide-gamut displays
+//! unmanaged, so an sRGB red shows up as the display's much more saturated native red. A
+//! [`ColorTransform`] re-encodes sRGB pixels for a display described by an ICC profile or by its
+//! primaries, so a tint matches the accent color the user picked.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::expand_home;
+
+type Matrix = [[f64; 3]; 3];
+
+/// CIE xy chromaticity.
+pub type Xy = (f64, f64);
+
+const D50: Xy = (0.3457, 0.3585);
+const D65: Xy = (0.3127, 0.3290);
+
+/// Chromaticities of a display's primaries and white point.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Primaries {
+    pub red: Xy,
+    pub green: Xy,
+    pub blue: Xy,
+    #[serde(default = "d65")]
+    pub white: Xy,
+}
+
+fn d65() -> Xy {
+    D65
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NamedPrimaries {
+    Srgb,
+    DisplayP3,
+    AdobeRgb,
+    Bt2020,
+}
+
+impl From<niri_config::output::Primaries> for NamedPrimaries {
+    fn from(primaries: niri_config::output::Primaries) -> Self {
+        use niri_config::output::Primaries as Config;
+        match primaries {
+            Config::Srgb => NamedPrimaries::Srgb,
+            Config::DisplayP3 => NamedPrimaries::DisplayP3,
+            Config::AdobeRgb => NamedPrimaries::AdobeRgb,
+            Config::Bt2020 => NamedPrimaries::Bt2020,
+        }
+    }
+}
+
+impl NamedPrimaries {
+    pub fn primaries(self) -> Primaries {
+        let (red, green, blue) = match self {
+            NamedPrimaries::Srgb => ((0.64, 0.33), (0.30, 0.60), (0.15, 0.06)),
+            NamedPrimaries::DisplayP3 => ((0.680, 0.320), (0.265, 0.690), (0.150, 0.060)),
+            NamedPrimaries::AdobeRgb => ((0.64, 0.33), (0.21, 0.71), (0.15, 0.06)),
+            NamedPrimaries::Bt2020 => ((0.708, 0.292), (0.170, 0.797), (0.131, 0.046)),
+        };
+        Primaries {
+            red,
+            green,
+            blue,
+            white: D65,
+        }
+    }
+}
+
+/// Transfer function from encoded values to linear light.
+#[derive(Debug, Clone, PartialEq)]
+enum Curve {
+    Srgb,
+    Gamma(f64),
+    /// ICC parametric curve, `[g, a, b, c, d, e, f]`.
+    Parametric([f64; 7]),
+    /// Samples evenly spaced over the encoded range.
+    Table(Vec<f64>),
+}
+
+impl Curve {
+    fn linear(&self, v: f64) -> f64 {
+        match self {
+            Curve::Srgb => {
+                if v <= 0.04045 {
+                    v / 12.92
+                } else {
+                    ((v + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            Curve::Gamma(gamma) => v.powf(*gamma),
+            Curve::Parametric([g, a, b, c, d, e, f]) => {
+                if v >= *d {
+                    (a * v + b).max(0.).powf(*g) + e
+                } else {
+                    c * v + f
+                }
+            }
+            Curve::Table(table) => {
+                let pos = v.clamp(0., 1.) * (table.len() - 1) as f64;
+                let idx = (pos.floor() as usize).min(table.len() - 2);
+                let t = pos - idx as f64;
+                table[idx] + (table[idx + 1] - table[idx]) * t
+            }
+        }
+    }
+}
+
+/// Conversion of sRGB pixels to the encoding of a display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorTransform {
+    /// Linear sRGB to linear display RGB.
+    matrix: Matrix,
+    /// Linear light of every 8-bit sRGB value.
+    decode: Vec<f64>,
+    /// Linear light of every 8-bit display value, per channel, for finding the closest encoding.
+    encode: [Vec<f64>; 3],
+}
+
+impl ColorTransform {
+    /// Transform to a display with the given primaries and the sRGB transfer function, or a pure
+    /// power curve if `gamma` is set.
+    pub fn from_primaries(primaries: &Primaries, gamma: Option<f64>) -> Result<Self> {
+        let display = rgb_to_xyz(primaries)?;
+        let srgb = rgb_to_xyz(&NamedPrimaries::Srgb.primaries())?;
+        // Relative colorimetric: sRGB white maps to the display white.
+        let adapt = bradford(D65, primaries.white);
+        let matrix = mul(&invert(&display)?, &mul(&adapt, &srgb));
+
+        let curve = gamma.map_or(Curve::Srgb, Curve::Gamma);
+        Ok(Self::new(matrix, [curve.clone(), curve.clone(), curve]))
+    }
+
+    /// Transform to the display of an output with `cursor-color` in the niri config.
+    pub fn load(config: &niri_config::output::CursorColor) -> Result<Self> {
+        if let Some(icc) = &config.icc {
+            let path = expand_home(Path::new(icc))
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| PathBuf::from(icc));
+            let data = fs::read(&path)
+                .with_context(|| format!("Failed to read ICC profile: {}", path.display()))?;
+            return Self::from_icc(&data)
+                .with_context(|| format!("Failed to load ICC profile: {}", path.display()));
+        }
+
+        match config.primaries {
+            Some(primaries) => Self::from_primaries(
+                &NamedPrimaries::from(primaries).primaries(),
+                config.gamma.map(|gamma| gamma.0),
+            ),
+            None => bail!("cursor-color needs either `icc` or `primaries`"),
+        }
+    }
+
+    /// Transform to a display described by an RGB matrix/TRC ICC profile.
+    ///
+    /// LUT-based profiles aren't supported.
+    pub fn from_icc(data: &[u8]) -> Result<Self> {
+        let profile = IccProfile::parse(data)?;
+
+        // ICC colorants are adapted to the D50 connection space.
+        let srgb = rgb_to_xyz(&NamedPrimaries::Srgb.primaries())?;
+        let srgb_d50 = mul(&bradford(D65, D50), &srgb);
+        let matrix = mul(&invert(&profile.colorants)?, &srgb_d50);
+
+        Ok(Self::new(matrix, profile.curves))
+    }
+
+    fn new(matrix: Matrix, curves: [Curve; 3]) -> Self {
+        let levels = |curve: &Curve| -> Vec<f64> {
+            (0..=255)
+                .map(|v| curve.linear(f64::from(v) / 255.))
+                .collect()
+        };
+
+        let [r, g, b] = &curves;
+        Self {
+            matrix,
+            decode: levels(&Curve::Srgb),
+            encode: [levels(r), levels(g), levels(b)],
+        }
+    }
+
+    /// Converts straight (not premultiplied) 8-bit sRGB to the display encoding.
+    pub fn convert(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let linear = rgb.map(|c| self.decode[usize::from(c)]);
+
+        std::array::from_fn(|channel| {
+            let row = self.matrix[channel];
+            let value = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+
+            // Curves are monotonic, pick the closest encoded level.
+            let levels = &self.encode[channel];
+            let idx = levels.partition_point(|level| *level < value).min(255);
+            let closer_below = idx > 0 && value - levels[idx - 1] < levels[idx] - value;
+            (idx - usize::from(closer_below)) as u8
+        })
+    }
+
+    /// Converts premultiplied ARGB8888 pixels in place.
+    #[cfg(feature = "effects")]
+    pub fn apply(&self, pixels: &mut [u8]) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            let alpha = u16::from(pixel[3]);
+            if alpha == 0 {
+                continue;
+            }
+
+            // ARGB8888 is BGRA in memory.
+            let straight = |c: u8| (u16::from(c) * 255 / alpha).min(255) as u8;
+            let [r, g, b] =
+                self.convert([straight(pixel[2]), straight(pixel[1]), straight(pixel[0])]);
+            let premultiply = |c: u8| ((u16::from(c) * alpha + 127) / 255) as u8;
+            pixel[0] = premultiply(b);
+            pixel[1] = premultiply(g);
+            pixel[2] = premultiply(r);
+        }
+    }
+}
+
+/// The parts of an RGB display profile needed for converting to it.
+struct IccProfile {
+    /// Columns are the D50 XYZ of the red, green and blue colorants.
+    colorants: Matrix,
+    curves: [Curve; 3],
+}
+
+impl IccProfile {
+    fn parse(data: &[u8]) -> Result<Self> {
+        ensure!(data.len() >= 132, "ICC profile is truncated");
+        ensure!(&data[36..40] == b"acsp", "not an ICC profile");
+        ensure!(
+            &data[16..20] == b"RGB ",
+            "only RGB ICC profiles are supported"
+        );
+
+        let tag = |signature| find_tag(data, signature);
+
+        let mut colorants = [[0.; 3]; 3];
+        for (column, signature) in [b"rXYZ", b"gXYZ", b"bXYZ"].into_iter().enumerate() {
+            let xyz = parse_xyz(tag(signature)?)?;
+            for (row, value) in xyz.into_iter().enumerate() {
+                colorants[row][column] = value;
+            }
+        }
+
+        let curves = [
+            parse_curve(tag(b"rTRC")?)?,
+            parse_curve(tag(b"gTRC")?)?,
+            parse_curve(tag(b"bTRC")?)?,
+        ];
+
+        Ok(Self { colorants, curves })
+    }
+}
+
+/// Data of the tag with the given signature.
+fn find_tag<'a>(data: &'a [u8], signature: &[u8; 4]) -> Result<&'a [u8]> {
+    let tag_count = be_u32(data, 128)? as usize;
+    let entry = (0..tag_count)
+        .map(|i| 132 + i * 12)
+        .find(|&entry| data.get(entry..entry + 4) == Some(&signature[..]))
+        .with_context(|| {
+            format!(
+                "ICC profile has no {} tag, only matrix/TRC profiles are supported",
+                String::from_utf8_lossy(signature)
+            )
+        })?;
+
+    let offset = be_u32(data, entry + 4)? as usize;
+    let size = be_u32(data, entry + 8)? as usize;
+    data.get(offset..offset.saturating_add(size))
+        .context("ICC tag is out of bounds")
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .context("ICC data is truncated")?;
+    Ok(u16::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .context("ICC data is truncated")?;
+    Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+fn s15_fixed16(data: &[u8], offset: usize) -> Result<f64> {
+    Ok(f64::from(be_u32(data, offset)? as i32) / 65536.)
+}
+
+fn parse_xyz(tag: &[u8]) -> Result<[f64; 3]> {
+    ensure!(
+        tag.get(..4) == Some(&b"XYZ "[..]),
+        "ICC colorant is not an XYZ tag"
+    );
+    Ok([
+        s15_fixed16(tag, 8)?,
+        s15_fixed16(tag, 12)?,
+        s15_fixed16(tag, 16)?,
+    ])
+}
+
+fn parse_curve(tag: &[u8]) -> Result<Curve> {
+    match tag.get(..4) {
+        Some(b"curv") => {
+            let count = be_u32(tag, 8)? as usize;
+            match count {
+                0 => Ok(Curve::Gamma(1.)),
+                1 => Ok(Curve::Gamma(f64::from(be_u16(tag, 12)?) / 256.)),
+                _ => {
+                    let table = (0..count)
+                        .map(|i| Ok(f64::from(be_u16(tag, 12 + i * 2)?) / 65535.))
+                        .collect::<Result<_>>()?;
+                    Ok(Curve::Table(table))
+                }
+            }
+        }
+        Some(b"para") => {
+            let kind = be_u16(tag, 8)?;
+            let count = match kind {
+                0 => 1,
+                1 => 3,
+                2 => 4,
+                3 => 5,
+                4 => 7,
+                _ => bail!("unknown ICC parametric curve type {kind}"),
+            };
+            let mut params = [0.; 7];
+            for (i, param) in params.iter_mut().enumerate().take(count) {
+                *param = s15_fixed16(tag, 12 + i * 4)?;
+            }
+            // Types 1 and 2 divide by `a` for the start of the curve.
+            ensure!(
+                !matches!(kind, 1 | 2) || params[1] != 0.,
+                "ICC parametric curve type {kind} has a = 0"
+            );
+
+            // Express every type as type 4: Y = (aX + b)^g + e for X >= d, else cX + f.
+            let [g, a, b, c, d, e, f] = params;
+            Ok(Curve::Parametric(match kind {
+                0 => [g, 1., 0., 0., 0., 0., 0.],
+                1 => [g, a, b, 0., -b / a, 0., 0.],
+                2 => [g, a, b, 0., -b / a, c, c],
+                3 => [g, a, b, c, d, 0., 0.],
+                _ => [g, a, b, c, d, e, f],
+            }))
+        }
+        _ => bail!("unsupported ICC tone curve"),
+    }
+}
+
+fn xyz(xy: Xy) -> [f64; 3] {
+    let (x, y) = xy;
+    [x / y, 1., (1. - x - y) / y]
+}
+
+/// Matrix converting linear RGB with the given primaries to XYZ.
+fn rgb_to_xyz(primaries: &Primaries) -> Result<Matrix> {
+    let columns = [primaries.red, primaries.green, primaries.blue].map(xyz);
+    let unscaled: Matrix = std::array::from_fn(|row| std::array::from_fn(|col| columns[col][row]));
+
+    // Scale the primaries so that full RGB is the white point.
+    let scale = apply(&invert(&unscaled)?, xyz(primaries.white));
+    Ok(std::array::from_fn(|row| {
+        std::array::from_fn(|col| unscaled[row][col] * scale[col])
+    }))
+}
+
+/// Bradford chromatic adaptation between two white points.
+fn bradford(from: Xy, to: Xy) -> Matrix {
+    const BRADFORD: Matrix = [
+        [0.8951, 0.2664, -0.1614],
+        [-0.7502, 1.7135, 0.0367],
+        [0.0389, -0.0685, 1.0296],
+    ];
+    let inverse = invert(&BRADFORD).unwrap();
+
+    let from = apply(&BRADFORD, xyz(from));
+    let to = apply(&BRADFORD, xyz(to));
+    let scale: Matrix = std::array::from_fn(|row| {
+        std::array::from_fn(|col| if row == col { to[row] / from[row] } else { 0. })
+    });
+
+    mul(&inverse, &mul(&scale, &BRADFORD))
+}
+
+fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    std::array::from_fn(|row| {
+        std::array::from_fn(|col| (0..3).map(|i| a[row][i] * b[i][col]).sum())
+    })
+}
+
+fn apply(m: &Matrix, v: [f64; 3]) -> [f64; 3] {
+    std::array::from_fn(|row| (0..3).map(|i| m[row][i] * v[i]).sum())
+}
+
+fn invert(m: &Matrix) -> Result<Matrix> {
+    let cofactor = |row: usize, col: usize| {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((col + 1) % 3, (col + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+
+    let det = (0..3).map(|col| m[0][col] * cofactor(0, col)).sum::<f64>();
+    ensure!(det.abs() > 1e-12, "color primaries are degenerate");
+
+    Ok(std::array::from_fn(|row| {
+        std::array::from_fn(|col| cofactor(col, row) / det)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an ICC profile with the given colorants and a `curv` gamma.
+    fn icc_profile(colorants: &Matrix, gamma: f64) -> Vec<u8> {
+        let mut tags: Vec<(&[u8; 4], Vec<u8>)> = Vec::new();
+        for (col, signature) in [b"rXYZ", b"gXYZ", b"bXYZ"].into_iter().enumerate() {
+            let mut tag = b"XYZ \0\0\0\0".to_vec();
+            for row in colorants {
+                tag.extend(((row[col] * 65536.).round() as i32).to_be_bytes());
+            }
+            tags.push((signature, tag));
+        }
+        for signature in [b"rTRC", b"gTRC", b"bTRC"] {
+            let mut tag = b"curv\0\0\0\0".to_vec();
+            tag.extend(1u32.to_be_bytes());
+            tag.extend(((gamma * 256.).round() as u16).to_be_bytes());
+            tags.push((signature, tag));
+        }
+
+        let mut data = vec![0; 128];
+        data[16..20].copy_from_slice(b"RGB ");
+        data[36..40].copy_from_slice(b"acsp");
+        data.extend((tags.len() as u32).to_be_bytes());
+
+        let mut offset = 132 + tags.len() * 12;
+        for (signature, tag) in &tags {
+            data.extend(*signature);
+            data.extend((offset as u32).to_be_bytes());
+            data.extend((tag.len() as u32).to_be_bytes());
+            offset += tag.len();
+        }
+        for (_, tag) in tags {
+            data.extend(tag);
+        }
+        data
+    }
+
+    fn close(actual: [u8; 3], expected: [u8; 3]) {
+        let ok = actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 1);
+        assert!(ok, "{actual:?} vs {expected:?}");
+    }
+
+    #[test]
+    fn srgb_is_identity() {
+        let transform =
+            ColorTransform::from_primaries(&NamedPrimaries::Srgb.primaries(), None).unwrap();
+        for rgb in [[255, 0, 0], [0, 128, 255], [12, 200, 77], [255, 255, 255]] {
+            close(transform.convert(rgb), rgb);
+        }
+    }
+
+    #[test]
+    fn display_p3_desaturates() {
+        let transform =
+            ColorTransform::from_primaries(&NamedPrimaries::DisplayP3.primaries(), None).unwrap();
+        close(transform.convert([255, 0, 0]), [234, 51, 35]);
+        close(transform.convert([255, 255, 255]), [255, 255, 255]);
+        close(transform.convert([0, 0, 0]), [0, 0, 0]);
+    }
+
+    #[test]
+    fn icc_matrix_trc() {
+        let srgb = rgb_to_xyz(&NamedPrimaries::Srgb.primaries()).unwrap();
+        let srgb_d50 = mul(&bradford(D65, D50), &srgb);
+        let transform = ColorTransform::from_icc(&icc_profile(&srgb_d50, 2.2)).unwrap();
+
+        // Same primaries, only the tone curve differs from sRGB.
+        close(transform.convert([255, 0, 0]), [255, 0, 0]);
+        let [r, g, b] = transform.convert([128, 128, 128]);
+        assert_eq!((r, g), (b, b));
+        assert!(b.abs_diff(128) <= 3, "{b}");
+
+        assert!(ColorTransform::from_icc(b"not a profile").is_err());
+        let mut lut_profile = icc_profile(&srgb_d50, 2.2);
+        lut_profile[132..136].copy_from_slice(b"A2B0");
+        assert!(ColorTransform::from_icc(&lut_profile).is_err());
+    }
+
+    #[test]
+    fn parametric_curves() {
+        let para = |kind: u16, params: &[f64]| {
+            let mut tag = b"para\0\0\0\0".to_vec();
+            tag.extend(kind.to_be_bytes());
+            tag.extend([0, 0]);
+            for param in params {
+                tag.extend(((param * 65536.).round() as i32).to_be_bytes());
+            }
+            parse_curve(&tag)
+        };
+
+        let curve = para(1, &[2., 1., 0.]).unwrap();
+        assert_eq!(curve.linear(0.5), 0.25);
+        assert!(para(1, &[2., 0., 0.]).is_err());
+        assert!(para(2, &[2., 0., 0., 0.]).is_err());
+        // Type 3 has no division, `a` may be 0.
+        assert!(para(3, &[2., 0., 0., 1., 0.]).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "effects")]
+    fn apply_keeps_premultiplied_alpha() {
+        let transform =
+            ColorTransform::from_primaries(&NamedPrimaries::DisplayP3.primaries(), None).unwrap();
+        // Half-transparent sRGB red and a transparent pixel, BGRA.
+        let mut pixels = [0, 0, 128, 128, 0, 0, 0, 0];
+        transform.apply(&mut pixels);
+        assert_eq!(pixels[3], 128);
+        assert!(pixels[2].abs_diff(118) <= 1, "{pixels:?}");
+        assert_eq!(pixels[4..], [0, 0, 0, 0]);
+    }
+}
@@ -1,22 +1,312 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::animation::{Spring, SpringParams};
+use crate::cursor::vector::id::CursorId;
+use crate::cursor::vector::integrity::IntegrityMode;
+#[cfg(feature = "lottie")]
+use crate::cursor::vector::renderer::lottie::validate::Limits;
+#[cfg(feature = "svg")]
+use crate::cursor::vector::renderer::svg_limits;
+use crate::cursor::vector::scale::ScaleSnapping;
+
+/// Key of the transition used between cursors without a transition of their own.
+pub const DEFAULT_TRANSITION: &str = "default";
+
+/// Playback speeds a cursor can have, including the speedup while the pointer moves.
+pub const SPEEDS: RangeInclusive<f64> = 0.01..=100.;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CursorThemeConfig {
-    pub cursors: HashMap<String, CursorDefinition>,
+    /// The cursors of the theme, whose keys are the registry of its [`CursorId`]s.
+    pub cursors: HashMap<CursorId, CursorDefinition>,
+    /// Transitions by `"<from>-><to>"`, where either side may be a `@group`, and the theme-wide
+    /// one under [`DEFAULT_TRANSITION`].
     #[serde(default)]
     pub transitions: HashMap<String, TransitionConfig>,
+    #[serde(default)]
+    pub scale_snapping: ScaleSnapping,
+    #[serde(default)]
+    pub groups: HashMap<String, CursorGroup>,
+    #[cfg(feature = "lottie")]
+    #[serde(default)]
+    pub lottie_limits: Limits,
+    #[cfg(feature = "svg")]
+    #[serde(default)]
+    pub svg_limits: svg_limits::Limits,
+    /// Rhai script reacting to cursor events, relative to the theme directory.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// What to do with cursor files that don't match their `sha256`.
+    #[serde(default)]
+    pub integrity: IntegrityMode,
+    /// Named colors SVG cursors can use as `var(--name)`, like `accent = "#3584e4"`.
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+    /// Color of `currentColor` in SVG cursors, for symbolic cursor sets.
+    #[serde(default)]
+    pub foreground: Option<String>,
+    /// CSS added to every SVG cursor, before the `stylesheet` of the cursor.
+    #[serde(default)]
+    pub stylesheet: Option<String>,
+    /// Font files or directories for text in SVG cursors, relative to the theme directory, in
+    /// addition to the system fonts.
+    #[serde(default)]
+    pub fonts: Vec<String>,
+    /// Smearing of the cursor along the direction of fast pointer motion.
+    #[serde(default)]
+    pub motion_blur: Option<MotionBlurConfig>,
+    /// Mirror every cursor horizontally, for left-handed use.
+    #[serde(default)]
+    pub mirror: bool,
+    /// Cursors shown while the pointer rests.
+    #[serde(default)]
+    pub idle: IdleConfig,
+    /// Time a cursor change waits before taking effect, so that changes undone sooner, like
+    /// brief hovers, don't start transitions. 0 switches right away.
+    #[serde(default)]
+    pub debounce_ms: u32,
+}
+
+/// Idle variants of cursors, shown after a while without pointer motion.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IdleConfig {
+    /// Time without pointer motion before cursors switch to their idle variant.
+    pub after_ms: u32,
+    /// Idle variant of the cursors without an `idle` of their own.
+    pub cursor: Option<String>,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            after_ms: 30_000,
+            cursor: None,
+        }
+    }
+}
+
+/// Motion blur of the cursor while the pointer moves fast.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MotionBlurConfig {
+    pub enabled: bool,
+    /// Pointer speed in logical pixels per second above which the cursor smears.
+    pub threshold: f64,
+    /// Length of the smear in logical pixels per 1000 px/s above the threshold.
+    pub strength: f64,
+    /// Longest smear in logical pixels.
+    pub max_length: f64,
+}
+
+impl Default for MotionBlurConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1500.,
+            strength: 8.,
+            max_length: 24.,
+        }
+    }
 }
 
+impl MotionBlurConfig {
+    /// Length of the smear in logical pixels at `speed`, 0 when the cursor doesn't smear.
+    pub fn length(&self, speed: f64) -> f64 {
+        if !self.enabled || speed <= self.threshold {
+            return 0.;
+        }
+        ((speed - self.threshold) / 1000. * self.strength).clamp(0., self.max_length)
+    }
+}
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CursorDefinition {
     pub format: CursorFormat,
+    /// Path of the source, relative to the theme directory. Empty for cursors with `data`.
+    #[serde(default)]
     pub file: String,
+    /// The SVG or Lottie JSON source itself, instead of a `file`.
+    #[serde(default)]
+    pub data: Option<String>,
+    /// SVG artwork for a light desktop color scheme, instead of `file`.
+    #[serde(default)]
+    pub file_light: Option<String>,
+    /// SVG artwork for a dark desktop color scheme, instead of `file`.
+    #[serde(default)]
+    pub file_dark: Option<String>,
     #[serde(default)]
     pub hotspot: Option<(i32, i32)>,
     #[serde(default)]
     pub loop_mode: Option<String>,
+    /// Name of the Lottie marker to play instead of the whole animation.
+    #[serde(default)]
+    pub segment: Option<String>,
+    /// Name of the Lottie marker played once when switching to the cursor, before `segment`
+    /// starts looping.
+    #[serde(default)]
+    pub intro: Option<String>,
+    /// Name of the Lottie marker played once when switching away from the cursor, unless the
+    /// transition has a `done_segment`.
+    #[serde(default)]
+    pub outro: Option<String>,
+    /// `#rrggbb` or `#rrggbbaa` color multiplied into the cursor.
+    #[serde(default)]
+    pub tint: Option<String>,
+    /// Factor the cursor is drawn larger or smaller by, relative to the cursor size.
+    #[serde(default)]
+    pub scale_bias: Option<f64>,
+    /// Hex SHA-256 the contents of `file` must have.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Hex SHA-256 the contents of `file_light` must have.
+    #[serde(default)]
+    pub sha256_light: Option<String>,
+    /// Hex SHA-256 the contents of `file_dark` must have.
+    #[serde(default)]
+    pub sha256_dark: Option<String>,
+    /// SVG artwork drawn for other sizes than the base size, e.g. simplified for small cursors.
+    #[serde(default)]
+    pub sizes: Vec<SizeVariant>,
+    /// CSS added to the SVG artwork, overriding its own styles.
+    #[serde(default)]
+    pub stylesheet: Option<String>,
+    /// Clockwise rotation of the SVG artwork, e.g. to derive `ew-resize` from `ns-resize`.
+    #[serde(default)]
+    pub rotate_deg: Option<f64>,
+    /// Cursor shown instead once the pointer rests for `idle.after_ms`.
+    #[serde(default)]
+    pub idle: Option<String>,
+    /// Playback speed of the animation, 2 plays it twice as fast, within [`SPEEDS`].
+    #[serde(default)]
+    pub speed: Option<f64>,
+    /// Faster playback while the pointer moves.
+    #[serde(default)]
+    pub velocity_speed: Option<VelocitySpeed>,
+    /// Renders Lottie animations between their frames at the time each output frame is shown,
+    /// smoother than the animation's own frame rate on high refresh rate outputs.
+    #[serde(default)]
+    pub interpolate: bool,
+}
+
+/// Playback speed of an animated cursor growing with the pointer speed.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct VelocitySpeed {
+    /// Speed added per 1000 logical pixels per second of pointer motion, 0.5 plays at 1.5x
+    /// while the pointer moves at 1000 px/s.
+    pub speedup: f64,
+    /// Fastest playback speed, within [`SPEEDS`].
+    #[serde(default = "default_max_velocity_speed")]
+    pub max: f64,
+}
+
+fn default_max_velocity_speed() -> f64 {
+    4.
+}
+
+impl VelocitySpeed {
+    /// Playback speed while the pointer moves at `velocity` logical pixels per second.
+    pub fn speed(&self, velocity: f64) -> f64 {
+        (1. + self.speedup * velocity / 1000.).clamp(1., self.max.max(1.))
+    }
+}
+
+/// Artwork of an SVG cursor for one nominal size.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SizeVariant {
+    /// Cursor size in pixels the artwork is drawn for, like 24, 32 or 48.
+    pub size: u32,
+    pub file: String,
+    /// Hotspot in the units of `file`, the cursor's hotspot scaled to `size` if unset.
+    #[serde(default)]
+    pub hotspot: Option<(i32, i32)>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// Color scheme of the desktop, which picks between light and dark cursor artwork.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl CursorDefinition {
+    /// The artwork for `scheme`, if the cursor has one other than `file`.
+    pub fn scheme_file(&self, scheme: ColorScheme) -> Option<&str> {
+        match scheme {
+            ColorScheme::Light => self.file_light.as_deref(),
+            ColorScheme::Dark => self.file_dark.as_deref(),
+        }
+    }
+
+    /// The hash of [`Self::scheme_file`].
+    pub fn scheme_sha256(&self, scheme: ColorScheme) -> Option<&str> {
+        match scheme {
+            ColorScheme::Light => self.sha256_light.as_deref(),
+            ColorScheme::Dark => self.sha256_dark.as_deref(),
+        }
+    }
+
+    /// Whether the cursor looks different depending on the color scheme.
+    pub fn has_scheme_files(&self) -> bool {
+        self.file_light.is_some() || self.file_dark.is_some()
+    }
+
+    /// Artwork files the cursor is drawn from, relative to the theme directory.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        (!self.file.is_empty())
+            .then_some(self.file.as_str())
+            .into_iter()
+            .chain(self.file_light.as_deref())
+            .chain(self.file_dark.as_deref())
+            .chain(self.sizes.iter().map(|variant| variant.file.as_str()))
+    }
+
+    /// Picks the size variant closest to `base_size` at `scale`, `None` if `file` (drawn for
+    /// the base size) is closest.
+    ///
+    /// Sizes are compared by ratio, and on a tie the larger artwork wins since scaling down
+    /// looks better than scaling up.
+    pub fn size_variant(&self, base_size: u8, scale: f64) -> Option<&SizeVariant> {
+        let target = f64::from(base_size) * scale;
+        let distance = |size: u32| (f64::from(size.max(1)) / target).ln().abs();
+
+        let best = self.sizes.iter().min_by(|a, b| {
+            distance(a.size)
+                .total_cmp(&distance(b.size))
+                .then(b.size.cmp(&a.size))
+        })?;
+
+        let base = u32::from(base_size);
+        let base_distance = distance(base);
+        match distance(best.size).total_cmp(&base_distance) {
+            std::cmp::Ordering::Less => Some(best),
+            std::cmp::Ordering::Equal if best.size > base => Some(best),
+            _ => None,
+        }
+    }
+}
+
+/// Settings shared by several cursors.
+///
+/// Members inherit every setting they don't define themselves. Transitions can refer to a group
+/// as `@name`, e.g. `"@resize->default"` applies to every resize cursor.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CursorGroup {
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub loop_mode: Option<String>,
+    #[serde(default)]
+    pub tint: Option<String>,
+    #[serde(default)]
+    pub scale_bias: Option<f64>,
+    #[serde(default)]
+    pub speed: Option<f64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -26,6 +316,16 @@ pub enum CursorFormat {
     Lottie,
 }
 
+impl CursorFormat {
+    /// Whether niri was built with support for this format.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            CursorFormat::Svg => cfg!(feature = "svg"),
+            CursorFormat::Lottie => cfg!(feature = "lottie"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TransitionConfig {
     #[serde(default = "default_transition_type")]
@@ -34,8 +334,108 @@ pub struct TransitionConfig {
     pub duration_ms: u32,
     #[serde(default = "default_easing")]
     pub easing: EasingFunction,
+    /// Lottie animation played by a `lottie` transition, stretched to `duration_ms`.
     #[serde(default)]
     pub file: Option<String>,
+    /// Hex SHA-256 the contents of `file` must have.
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Hotspot of the animation in `file`, in the units of the composition, moving from the
+    /// source cursor's hotspot to the target's if unset.
+    #[serde(default)]
+    pub hotspot: Option<(i32, i32)>,
+    /// Name of a registered [`TransitionEffect`] compositing both cursors, overriding
+    /// `transition_type`.
+    ///
+    /// [`TransitionEffect`]: crate::cursor::vector::transition::TransitionEffect
+    #[serde(default)]
+    pub effect: Option<String>,
+    /// Plays the rest of the source cursor's current loop before transitioning, so e.g. a
+    /// spinner doesn't stop mid-turn.
+    #[serde(default)]
+    pub finish_loop: bool,
+    /// Segment of the source cursor played once before transitioning, after the rest of its
+    /// loop, like a "done" flourish when leaving the progress cursor.
+    #[serde(default)]
+    pub done_segment: Option<String>,
+    /// Clockwise turn of a `transform` transition: the source cursor turns by this much on its
+    /// way out and the target cursor the rest of the way in.
+    #[serde(default)]
+    pub rotate_deg: f64,
+    /// Spring of `easing = "spring"`.
+    #[serde(default)]
+    pub spring: SpringConfig,
+}
+
+impl TransitionConfig {
+    /// Whether the source cursor keeps playing for a while before the transition starts.
+    pub fn finishes_source(&self) -> bool {
+        self.finish_loop || self.done_segment.is_some()
+    }
+
+    /// Length of the transition in milliseconds: `duration_ms`, or the time the spring takes to
+    /// settle with `easing = "spring"`.
+    pub fn length_ms(&self) -> u32 {
+        match self.easing {
+            EasingFunction::Spring => {
+                u32::try_from(self.spring.duration().as_millis()).map_or(u32::MAX, |ms| ms.max(1))
+            }
+            _ => self.duration_ms,
+        }
+    }
+}
+
+/// Damping ratios a transition spring can have. Lower ones take too long to settle, higher ones
+/// barely move.
+pub const SPRING_DAMPING_RATIOS: RangeInclusive<f64> = 0.1..=10.;
+
+/// Lowest stiffness of a transition spring, softer ones take too long to settle.
+pub const MIN_SPRING_STIFFNESS: f64 = 1.;
+
+/// A damped spring pulling a transition from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpringConfig {
+    /// Strength of the spring, higher values settle sooner. At least [`MIN_SPRING_STIFFNESS`].
+    pub stiffness: f64,
+    /// 1 settles without overshooting, lower values bounce around the target. Within
+    /// [`SPRING_DAMPING_RATIOS`].
+    pub damping_ratio: f64,
+}
+
+impl Default for SpringConfig {
+    fn default() -> Self {
+        Self {
+            stiffness: 800.,
+            damping_ratio: 0.5,
+        }
+    }
+}
+
+impl SpringConfig {
+    fn spring(&self) -> Spring {
+        Spring {
+            from: 0.,
+            to: 1.,
+            initial_velocity: 0.,
+            params: SpringParams::new(self.damping_ratio, self.stiffness, 0.001),
+        }
+    }
+
+    /// Time until the spring is at rest.
+    pub fn duration(&self) -> Duration {
+        self.spring().duration()
+    }
+
+    /// Position of the spring `t` of the way through its duration, which overshoots 1 when
+    /// it bounces.
+    pub fn value_at(&self, t: f32) -> f32 {
+        if t >= 1. {
+            return 1.;
+        }
+        let elapsed = self.duration().mul_f64(f64::from(t.max(0.)));
+        self.spring().value_at(elapsed) as f32
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -59,8 +459,10 @@ fn default_easing() -> EasingFunction {
     EasingFunction::EaseInOut
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-#[serde(rename_all = "lowercase")]
+/// Written as the lowercase name of a curve, like `"easeinout"`, or as a CSS
+/// `"cubic-bezier(x1, y1, x2, y2)"`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum EasingFunction {
     Linear,
     EaseIn,
@@ -70,13 +472,184 @@ pub enum EasingFunction {
     EaseOutQuad,
     EaseInOutQuad,
     Elastic,
+    /// Follows the `spring` of the transition, which also sets its length.
+    Spring,
+    /// A cubic bezier from 0,0 to 1,1 with the control points `x1, y1` and `x2, y2`.
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl TryFrom<String> for EasingFunction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let easing = match value.trim() {
+            "linear" => EasingFunction::Linear,
+            "easein" => EasingFunction::EaseIn,
+            "easeout" => EasingFunction::EaseOut,
+            "easeinout" => EasingFunction::EaseInOut,
+            "easeinquad" => EasingFunction::EaseInQuad,
+            "easeoutquad" => EasingFunction::EaseOutQuad,
+            "easeinoutquad" => EasingFunction::EaseInOutQuad,
+            "elastic" => EasingFunction::Elastic,
+            "spring" => EasingFunction::Spring,
+            curve => {
+                let args = curve
+                    .strip_prefix("cubic-bezier(")
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .with_context(|| format!("unknown easing function {value:?}"))?;
+                let points = args
+                    .split(',')
+                    .map(|arg| arg.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("invalid easing function {value:?}"))?;
+                let [x1, y1, x2, y2] = points[..] else {
+                    anyhow::bail!("{value:?} needs 4 numbers");
+                };
+                if !(0. ..=1.).contains(&x1)
+                    || !(0. ..=1.).contains(&x2)
+                    || !y1.is_finite()
+                    || !y2.is_finite()
+                {
+                    anyhow::bail!("{value:?} needs x1 and x2 from 0 to 1 and finite y1 and y2");
+                }
+                EasingFunction::CubicBezier(x1, y1, x2, y2)
+            }
+        };
+        Ok(easing)
+    }
+}
+
+impl From<EasingFunction> for String {
+    fn from(easing: EasingFunction) -> Self {
+        let name = match easing {
+            EasingFunction::Linear => "linear",
+            EasingFunction::EaseIn => "easein",
+            EasingFunction::EaseOut => "easeout",
+            EasingFunction::EaseInOut => "easeinout",
+            EasingFunction::EaseInQuad => "easeinquad",
+            EasingFunction::EaseOutQuad => "easeoutquad",
+            EasingFunction::EaseInOutQuad => "easeinoutquad",
+            EasingFunction::Elastic => "elastic",
+            EasingFunction::Spring => "spring",
+            EasingFunction::CubicBezier(x1, y1, x2, y2) => {
+                return format!("cubic-bezier({x1}, {y1}, {x2}, {y2})");
+            }
+        };
+        String::from(name)
+    }
+}
+
+/// Whether `color` can be used as the foreground of SVG cursors, which writes it into an
+/// attribute as it is.
+pub fn is_valid_foreground(color: &str) -> bool {
+    !color.trim().is_empty() && !color.contains(['"', '\'', '<', '>', '&'])
 }
 
 impl CursorThemeConfig {
     pub fn from_toml(toml_str: &str) -> Result<Self> {
         debug!("Parsing cursor theme config from TOML...");
-        let config: CursorThemeConfig =
+        let mut config: CursorThemeConfig =
             toml::from_str(toml_str).context("Failed to parse cursor theme config")?;
+        config.apply_groups();
+        for name in config.colors.keys() {
+            let valid = name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if name.is_empty() || !valid {
+                anyhow::bail!("invalid color name {name:?}: use letters, digits, - and _");
+            }
+        }
+        #[cfg(feature = "svg")]
+        if let Some(name) = crate::cursor::vector::renderer::vars::find_cycle(&config.colors) {
+            anyhow::bail!("color {name:?} refers to itself");
+        }
+        if let Some(color) = &config.foreground {
+            if !is_valid_foreground(color) {
+                anyhow::bail!("invalid foreground color {color:?}");
+            }
+        }
+        for (cursor_id, cursor) in &config.cursors {
+            match (cursor.file.is_empty(), cursor.data.is_some()) {
+                (true, false) => anyhow::bail!("cursor '{cursor_id}' needs a `file` or `data`"),
+                (false, true) => anyhow::bail!("cursor '{cursor_id}' has both `file` and `data`"),
+                _ => {}
+            }
+            if let Some(speed) = cursor.speed {
+                if !SPEEDS.contains(&speed) {
+                    anyhow::bail!(
+                        "cursor '{cursor_id}' has invalid speed {speed}: use {} to {}",
+                        SPEEDS.start(),
+                        SPEEDS.end()
+                    );
+                }
+            }
+            if let Some(velocity) = &cursor.velocity_speed {
+                let valid = velocity.speedup.is_finite() && velocity.speedup >= 0.;
+                if !valid || !(1.0..=*SPEEDS.end()).contains(&velocity.max) {
+                    anyhow::bail!(
+                        "cursor '{cursor_id}' needs a non-negative velocity_speed.speedup and a \
+                         max from 1 to {}",
+                        SPEEDS.end()
+                    );
+                }
+            }
+            if let Some(degrees) = cursor.rotate_deg {
+                if cursor.format != CursorFormat::Svg {
+                    anyhow::bail!("cursor '{cursor_id}': only SVG cursors can be rotated");
+                }
+                if !degrees.is_finite() {
+                    anyhow::bail!("cursor '{cursor_id}' has invalid rotate_deg {degrees}");
+                }
+            }
+        }
+        let idle_cursors = config
+            .cursors
+            .values()
+            .filter_map(|cursor| cursor.idle.as_deref())
+            .chain(config.idle.cursor.as_deref());
+        for name in idle_cursors {
+            if !config.cursors.contains_key(name) {
+                anyhow::bail!("idle cursor '{name}' is not defined");
+            }
+        }
+        if config.idle.after_ms == 0 {
+            anyhow::bail!("idle.after_ms must be positive");
+        }
+        for (key, transition) in &config.transitions {
+            if matches!(transition.transition_type, TransitionType::Lottie)
+                && transition.effect.is_none()
+                && transition.file.is_none()
+            {
+                anyhow::bail!("transition '{key}' plays a Lottie animation but has no `file`");
+            }
+            let spring = &transition.spring;
+            if !(spring.stiffness.is_finite() && spring.stiffness >= MIN_SPRING_STIFFNESS)
+                || !SPRING_DAMPING_RATIOS.contains(&spring.damping_ratio)
+            {
+                anyhow::bail!(
+                    "transition '{key}' needs a spring stiffness of at least \
+                     {MIN_SPRING_STIFFNESS} and a damping_ratio from {} to {}",
+                    SPRING_DAMPING_RATIOS.start(),
+                    SPRING_DAMPING_RATIOS.end()
+                );
+            }
+            if !transition.rotate_deg.is_finite() {
+                anyhow::bail!(
+                    "transition '{key}' has invalid rotate_deg {}",
+                    transition.rotate_deg
+                );
+            }
+        }
+        let stylesheets = config
+            .cursors
+            .values()
+            .filter_map(|cursor| cursor.stylesheet.as_deref())
+            .chain(config.stylesheet.as_deref());
+        for css in stylesheets {
+            if css.contains("]]>") {
+                anyhow::bail!("invalid stylesheet: it can't contain `]]>`");
+            }
+        }
         debug!(
             "Config parsed successfully with {} cursors defined",
             config.cursors.len()
@@ -85,6 +658,21 @@ impl CursorThemeConfig {
         Ok(config)
     }
 
+    /// The interned ID of a cursor the theme defines, `None` for unknown names.
+    pub fn cursor_id(&self, name: &str) -> Option<CursorId> {
+        self.cursors.get_key_value(name).map(|(id, _)| id.clone())
+    }
+
+    /// Cursor `cursor_id` turns into while the pointer rests, `None` if it stays as it is.
+    pub fn idle_variant(&self, cursor_id: &str) -> Option<CursorId> {
+        let cursor = self.cursors.get(cursor_id)?;
+        let variant = cursor.idle.as_deref().or(self.idle.cursor.as_deref())?;
+        if variant == cursor_id {
+            return None;
+        }
+        self.cursor_id(variant)
+    }
+
     pub fn get_cursor(&self, cursor_id: &str) -> Option<&CursorDefinition> {
         debug!("Looking up cursor: '{}'", cursor_id);
         let result = self.cursors.get(cursor_id);
@@ -96,15 +684,380 @@ impl CursorThemeConfig {
         result
     }
 
+    /// Looks up the transition shown between two cursors: the one from `from_id` to `to_id`, or
+    /// else the one the other way, which a reversed transition plays backwards.
+    ///
+    /// Also returns whether the transition is the reversed one.
+    pub fn get_transition_or_reverse(
+        &self,
+        from_id: &str,
+        to_id: &str,
+    ) -> Option<(&TransitionConfig, bool)> {
+        match self.get_transition(from_id, to_id) {
+            Some(transition) => Some((transition, false)),
+            None => self
+                .get_explicit_transition(to_id, from_id)
+                .map(|transition| (transition, true)),
+        }
+    }
+
+    /// Looks up the transition between two cursors.
+    ///
+    /// Transitions between the cursors themselves take precedence over transitions involving
+    /// their groups, and those over `[transitions.default]`.
     pub fn get_transition(&self, from_id: &str, to_id: &str) -> Option<&TransitionConfig> {
-        let key = format!("{}->{}", from_id, to_id);
-        debug!("Looking up transition: '{}'", key);
-        let result = self.transitions.get(&key);
-        if result.is_some() {
-            debug!("Found transition: '{}'", key);
-        } else {
-            debug!("Transition not found: '{}'", key);
+        self.get_explicit_transition(from_id, to_id)
+            .or_else(|| self.get_default_transition(from_id, to_id))
+    }
+
+    /// The theme-wide transition, used between cursors the theme defines.
+    fn get_default_transition(&self, from_id: &str, to_id: &str) -> Option<&TransitionConfig> {
+        if !(self.cursors.contains_key(from_id) && self.cursors.contains_key(to_id)) {
+            return None;
         }
-        result
+        self.transitions.get(DEFAULT_TRANSITION)
+    }
+
+    /// Looks up a transition the theme defines for two cursors or their groups.
+    fn get_explicit_transition(&self, from_id: &str, to_id: &str) -> Option<&TransitionConfig> {
+        let group_key = |id: &str| self.group_of(id).map(|group| format!("@{group}"));
+        let from_group = group_key(from_id);
+        let to_group = group_key(to_id);
+
+        let candidates = [
+            Some((from_id, to_id)),
+            to_group.as_deref().map(|to| (from_id, to)),
+            from_group.as_deref().map(|from| (from, to_id)),
+            from_group.as_deref().zip(to_group.as_deref()),
+        ];
+
+        for (from, to) in candidates.into_iter().flatten() {
+            let key = format!("{}->{}", from, to);
+            debug!("Looking up transition: '{}'", key);
+            if let Some(transition) = self.transitions.get(&key) {
+                debug!("Found transition: '{}'", key);
+                return Some(transition);
+            }
+        }
+
+        debug!("Transition not found: '{}->{}'", from_id, to_id);
+        None
+    }
+
+    /// Name of the group the cursor belongs to.
+    ///
+    /// If the cursor is listed in several groups, the alphabetically first one wins.
+    pub fn group_of(&self, cursor_id: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .filter(|(_, group)| group.members.iter().any(|member| member == cursor_id))
+            .map(|(name, _)| name.as_str())
+            .min()
+    }
+
+    /// Copies group settings into the member cursors that don't override them.
+    fn apply_groups(&mut self) {
+        for (cursor_id, cursor) in &mut self.cursors {
+            let Some(group) = self
+                .groups
+                .iter()
+                .filter(|(_, group)| {
+                    group
+                        .members
+                        .iter()
+                        .any(|member| cursor_id.as_str() == member)
+                })
+                .min_by_key(|(name, _)| name.as_str())
+                .map(|(_, group)| group)
+            else {
+                continue;
+            };
+
+            cursor.loop_mode = cursor.loop_mode.take().or_else(|| group.loop_mode.clone());
+            cursor.tint = cursor.tint.take().or_else(|| group.tint.clone());
+            cursor.scale_bias = cursor.scale_bias.or(group.scale_bias);
+            cursor.speed = cursor.speed.or(group.speed);
+        }
+
+        for (name, group) in &self.groups {
+            for member in &group.members {
+                if !self.cursors.contains_key(member.as_str()) {
+                    warn!("cursor group '{name}' lists unknown cursor '{member}'");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THEME: &str = r##"
+[cursors.default]
+format = "svg"
+file = "default.svg"
+
+[cursors.ew-resize]
+format = "svg"
+file = "ew-resize.svg"
+
+[cursors.ns-resize]
+format = "svg"
+file = "ns-resize.svg"
+tint = "#00ff00"
+
+[groups.resize]
+members = ["ew-resize", "ns-resize"]
+tint = "#ff0000"
+scale_bias = 1.5
+
+[transitions."@resize->default"]
+duration_ms = 100
+
+[transitions."ns-resize->default"]
+duration_ms = 300
+"##;
+
+    #[test]
+    fn groups_apply_to_members() {
+        let config = CursorThemeConfig::from_toml(THEME).unwrap();
+
+        let ew = config.get_cursor("ew-resize").unwrap();
+        assert_eq!(ew.tint.as_deref(), Some("#ff0000"));
+        assert_eq!(ew.scale_bias, Some(1.5));
+
+        let ns = config.get_cursor("ns-resize").unwrap();
+        assert_eq!(ns.tint.as_deref(), Some("#00ff00"));
+        assert_eq!(ns.scale_bias, Some(1.5));
+
+        let default = config.get_cursor("default").unwrap();
+        assert_eq!(default.tint, None);
+        assert_eq!(config.group_of("default"), None);
+    }
+
+    #[test]
+    fn group_transitions() {
+        let config = CursorThemeConfig::from_toml(THEME).unwrap();
+
+        let duration = |from, to| config.get_transition(from, to).map(|t| t.duration_ms);
+        assert_eq!(duration("ew-resize", "default"), Some(100));
+        assert_eq!(duration("ns-resize", "default"), Some(300));
+        assert_eq!(duration("default", "ew-resize"), None);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn color_cycles_are_rejected() {
+        let theme = |colors: &str| {
+            format!(
+                r#"
+                [colors]
+                {colors}
+
+                [cursors.default]
+                format = "svg"
+                data = "<svg/>"
+                "#
+            )
+        };
+        assert!(CursorThemeConfig::from_toml(&theme(r#"a = "var(--b, red)""#)).is_ok());
+        assert!(
+            CursorThemeConfig::from_toml(&theme("a = \"var(--b)\"\nb = \"var(--a)\"")).is_err()
+        );
+    }
+
+    #[test]
+    fn speed_limits() {
+        let theme = |speed: &str| {
+            CursorThemeConfig::from_toml(&format!(
+                "[cursors.default]\nformat = \"lottie\"\nfile = \"default.json\"\n{speed}"
+            ))
+        };
+        assert!(theme("speed = 0.01").is_ok());
+        assert!(theme("speed = 100").is_ok());
+        assert!(theme("speed = 0.001").is_err());
+        assert!(theme("speed = 1000").is_err());
+
+        assert!(theme("velocity_speed = { speedup = 1, max = 100 }").is_ok());
+        assert!(theme("velocity_speed = { speedup = 1, max = 1e9 }").is_err());
+    }
+
+    #[test]
+    fn inline_data() {
+        let config = CursorThemeConfig::from_toml(
+            r#"
+            [cursors.default]
+            format = "svg"
+            data = "<svg/>"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.cursors["default"].data.as_deref(), Some("<svg/>"));
+
+        let both = r#"
+            [cursors.default]
+            format = "svg"
+            file = "default.svg"
+            data = "<svg/>"
+        "#;
+        assert!(CursorThemeConfig::from_toml(both).is_err());
+        let neither = "[cursors.default]\nformat = \"svg\"";
+        assert!(CursorThemeConfig::from_toml(neither).is_err());
+    }
+
+    #[test]
+    fn rotation_is_svg_only() {
+        let svg = "[cursors.ew-resize]\nformat = \"svg\"\nfile = \"resize.svg\"\nrotate_deg = 90";
+        let config = CursorThemeConfig::from_toml(svg).unwrap();
+        assert_eq!(config.cursors["ew-resize"].rotate_deg, Some(90.));
+
+        let lottie = svg.replace("\"svg\"", "\"lottie\"");
+        assert!(CursorThemeConfig::from_toml(&lottie).is_err());
+    }
+
+    #[test]
+    fn default_transition() {
+        let config = CursorThemeConfig::from_toml(
+            r#"
+[cursors.default]
+format = "svg"
+file = "default.svg"
+
+[cursors.pointer]
+format = "svg"
+file = "pointer.svg"
+
+[cursors.text]
+format = "svg"
+file = "text.svg"
+
+[transitions.default]
+transition_type = "crossfade"
+duration_ms = 150
+
+[transitions."pointer->default"]
+duration_ms = 50
+"#,
+        )
+        .unwrap();
+        let duration = |from, to| config.get_transition(from, to).map(|t| t.duration_ms);
+
+        assert_eq!(duration("default", "text"), Some(150));
+        assert_eq!(duration("pointer", "default"), Some(50));
+        // Cursors the theme doesn't define have no transitions.
+        assert_eq!(duration("default", "wait"), None);
+        // The default transition applies before reversing another one.
+        let (transition, reversed) = config
+            .get_transition_or_reverse("default", "pointer")
+            .unwrap();
+        assert_eq!((transition.duration_ms, reversed), (150, false));
+    }
+
+    #[test]
+    fn cubic_bezier_easing() {
+        let easing = |value: &str| {
+            toml::from_str::<TransitionConfig>(&format!("easing = {value:?}")).map(|t| t.easing)
+        };
+        assert_eq!(easing("easeinout").unwrap(), EasingFunction::EaseInOut);
+        assert_eq!(
+            easing("cubic-bezier(0.4, 0, 0.2, 1)").unwrap(),
+            EasingFunction::CubicBezier(0.4, 0., 0.2, 1.)
+        );
+        assert_eq!(
+            String::from(EasingFunction::CubicBezier(0.4, 0., 0.2, 1.)),
+            "cubic-bezier(0.4, 0, 0.2, 1)"
+        );
+        // Overshooting is fine, going back in time isn't.
+        assert!(easing("cubic-bezier(0.3, -0.5, 0.7, 1.5)").is_ok());
+        assert!(easing("cubic-bezier(1.2, 0, 0.2, 1)").is_err());
+        assert!(easing("cubic-bezier(0.4, 0, 0.2)").is_err());
+        assert!(easing("ease-out").is_err());
+    }
+
+    #[test]
+    fn idle_variants() {
+        let theme = format!(
+            "{THEME}[cursors.sleepy]\nformat = \"svg\"\nfile = \"sleepy.svg\"\n\
+             [cursors.pointer]\nformat = \"svg\"\nfile = \"pointer.svg\"\nidle = \"default\"\n\
+             [idle]\ncursor = \"sleepy\"\n"
+        );
+        let config = CursorThemeConfig::from_toml(&theme).unwrap();
+        assert_eq!(config.idle.after_ms, 30_000);
+        assert_eq!(config.idle_variant("default").as_deref(), Some("sleepy"));
+        assert_eq!(config.idle_variant("pointer").as_deref(), Some("default"));
+        assert_eq!(config.idle_variant("sleepy"), None);
+        assert_eq!(config.idle_variant("missing"), None);
+
+        let theme = theme.replace("cursor = \"sleepy\"", "cursor = \"missing\"");
+        assert!(CursorThemeConfig::from_toml(&theme).is_err());
+    }
+
+    #[test]
+    fn springs_set_the_length() {
+        let transition: TransitionConfig = toml::from_str("duration_ms = 100").unwrap();
+        assert_eq!(transition.length_ms(), 100);
+
+        let transition: TransitionConfig =
+            toml::from_str("easing = \"spring\"\nspring = { stiffness = 200, damping_ratio = 1 }")
+                .unwrap();
+        let stiff = SpringConfig {
+            stiffness: 800.,
+            ..transition.spring
+        };
+        assert!(transition.length_ms() > stiff.duration().as_millis() as u32);
+
+        let theme = "[transitions.\"default->wait\"]\nspring = { damping_ratio = 0 }";
+        assert!(CursorThemeConfig::from_toml(theme).is_err());
+    }
+
+    #[test]
+    fn spring_limits() {
+        let theme = |spring: &str| {
+            CursorThemeConfig::from_toml(&format!(
+                "[transitions.\"default->wait\"]\nspring = {{ {spring} }}"
+            ))
+        };
+        assert!(theme("stiffness = 1, damping_ratio = 0.1").is_ok());
+        assert!(theme("stiffness = 1e6, damping_ratio = 10").is_ok());
+        assert!(theme("stiffness = 0.5").is_err());
+        assert!(theme("damping_ratio = 0.05").is_err());
+        assert!(theme("damping_ratio = 11").is_err());
+        assert!(theme("stiffness = inf").is_err());
+    }
+
+    #[test]
+    fn lottie_transitions_need_a_file() {
+        let theme = "[transitions.\"default->wait\"]\ntransition_type = \"lottie\"\n";
+        assert!(CursorThemeConfig::from_toml(theme).is_err());
+
+        let theme = format!("{theme}file = \"melt.json\"\nhotspot = [2, 2]");
+        let config = CursorThemeConfig::from_toml(&theme).unwrap();
+        assert_eq!(config.transitions["default->wait"].hotspot, Some((2, 2)));
+    }
+
+    #[test]
+    fn size_variants() {
+        let config = CursorThemeConfig::from_toml(
+            r#"
+            [cursors.default]
+            format = "svg"
+            file = "default.svg"
+            sizes = [
+                { size = 16, file = "default-16.svg" },
+                { size = 48, file = "default-48.svg" },
+            ]
+            "#,
+        )
+        .unwrap();
+        let cursor = &config.cursors["default"];
+        let variant = |scale| cursor.size_variant(24, scale).map(|v| v.size);
+
+        assert_eq!(variant(0.6), Some(16));
+        assert_eq!(variant(1.), None);
+        assert_eq!(variant(1.25), None);
+        assert_eq!(variant(1.5), Some(48));
+        assert_eq!(variant(2.), Some(48));
+        assert_eq!(variant(3.), Some(48));
     }
 }
@@ -0,0 +1,245 @@
+//! Structural and pixel-level comparison of two vector cursor themes.
+//!
+//! Meant for reviewing theme updates: which cursors were added or removed, and for cursors in
+//! both themes, what changed about their hotspot, size, animation and first frame.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use smithay::utils::{Buffer, Physical, Point, Size};
+
+use crate::cursor::vector::bench::load_store;
+use crate::cursor::vector::config::CursorFormat;
+use crate::cursor::vector::store::VectorCursorStore;
+use crate::cursor::vector::types::RenderedFrame;
+use crate::utils::write_png_rgba8;
+
+/// Differences between an old and a new theme.
+#[derive(Debug, Default)]
+pub struct ThemeDiff {
+    /// Cursors only defined by the new theme.
+    pub added: Vec<String>,
+    /// Cursors only defined by the old theme.
+    pub removed: Vec<String>,
+    /// Cursors defined by both themes that look or behave differently.
+    pub changed: Vec<CursorDiff>,
+}
+
+/// Differences of one cursor, as `(old, new)` pairs of the values that changed.
+#[derive(Debug, Default)]
+pub struct CursorDiff {
+    pub cursor_id: String,
+    pub format: Option<(CursorFormat, CursorFormat)>,
+    pub hotspot: Option<(Point<i32, Physical>, Point<i32, Physical>)>,
+    pub size: Option<(Size<i32, Buffer>, Size<i32, Buffer>)>,
+    pub total_frames: Option<(u32, u32)>,
+    pub frame_duration_ms: Option<(u32, u32)>,
+    /// Changes of the first frame, if both frames have the same size.
+    pub pixels: Option<PixelDiff>,
+    /// Why one of the cursors couldn't be rendered.
+    pub error: Option<String>,
+}
+
+/// Pixel-level comparison of two frames of the same size.
+#[derive(Debug)]
+pub struct PixelDiff {
+    pub size: Size<i32, Buffer>,
+    /// Number of pixels with a channel that differs by more than the tolerance.
+    pub changed: usize,
+    /// Largest channel difference over all pixels.
+    pub max_delta: u8,
+    /// RGBA8 image of the new frame, faded to gray, with the changed pixels in red.
+    pub image: Vec<u8>,
+}
+
+impl ThemeDiff {
+    /// Loads two theme directories and compares them at `scale`.
+    pub fn load(old_dir: &Path, new_dir: &Path, base_size: u8, scale: f64) -> Result<Self> {
+        let old = load_store(old_dir, base_size)
+            .with_context(|| format!("error loading theme {}", old_dir.display()))?;
+        let new = load_store(new_dir, base_size)
+            .with_context(|| format!("error loading theme {}", new_dir.display()))?;
+        Ok(Self::compare(&old, &new, scale))
+    }
+
+    /// Compares two loaded themes at `scale`.
+    pub fn compare(old: &VectorCursorStore, new: &VectorCursorStore, scale: f64) -> Self {
+        let old_ids: BTreeSet<_> = old.get_config().cursors.keys().collect();
+        let new_ids: BTreeSet<_> = new.get_config().cursors.keys().collect();
+
+        let added = new_ids
+            .difference(&old_ids)
+            .map(|id| id.to_string())
+            .collect();
+        let removed = old_ids
+            .difference(&new_ids)
+            .map(|id| id.to_string())
+            .collect();
+        let changed = old_ids
+            .intersection(&new_ids)
+            .map(|id| CursorDiff::compare(old, new, id, scale))
+            .filter(|diff| !diff.is_empty())
+            .collect();
+
+        Self {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl CursorDiff {
+    fn compare(
+        old: &VectorCursorStore,
+        new: &VectorCursorStore,
+        cursor_id: &str,
+        scale: f64,
+    ) -> Self {
+        let mut diff = Self {
+            cursor_id: cursor_id.to_owned(),
+            ..Self::default()
+        };
+
+        let old_def = &old.get_config().cursors[cursor_id];
+        let new_def = &new.get_config().cursors[cursor_id];
+        diff.format = changed(old_def.format.clone(), new_def.format.clone());
+
+        let render = |store: &VectorCursorStore| {
+            let renderer = store.get_renderer(cursor_id)?;
+            let frame = renderer.render_frame(0, scale)?;
+            Result::<_>::Ok((renderer, frame))
+        };
+        let rendered = render(old).context("old theme").and_then(|old| {
+            let new = render(new).context("new theme")?;
+            Ok((old, new))
+        });
+        let ((old_renderer, old_frame), (new_renderer, new_frame)) = match rendered {
+            Ok(rendered) => rendered,
+            Err(err) => {
+                diff.error = Some(format!("{err:#}"));
+                return diff;
+            }
+        };
+
+        diff.hotspot = changed(old_frame.hotspot, new_frame.hotspot);
+        diff.size = changed(old_frame.size, new_frame.size);
+        diff.total_frames = changed(old_renderer.total_frames(), new_renderer.total_frames());
+        diff.frame_duration_ms = changed(
+            old_renderer.frame_duration_ms(),
+            new_renderer.frame_duration_ms(),
+        );
+        if diff.size.is_none() {
+            diff.pixels = PixelDiff::compare(&old_frame, &new_frame, 0);
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.format.is_none()
+            && self.hotspot.is_none()
+            && self.size.is_none()
+            && self.total_frames.is_none()
+            && self.frame_duration_ms.is_none()
+            && self.pixels.is_none()
+            && self.error.is_none()
+    }
+}
+
+impl PixelDiff {
+    /// Compares two frames, `None` if they have different sizes or no channel differs by more
+    /// than `tolerance`.
+    pub fn compare(old: &RenderedFrame, new: &RenderedFrame, tolerance: u8) -> Option<Self> {
+        if old.size != new.size {
+            return None;
+        }
+        let diff = Self::from_pixels(&old.read_pixels(), &new.read_pixels(), new.size, tolerance);
+        (diff.changed > 0).then_some(diff)
+    }
+
+    /// Compares two images of `size` with four 8-bit channels per pixel, in any order, counting
+    /// the pixels with a channel that differs by more than `tolerance`.
+    pub fn from_pixels(old: &[u8], new: &[u8], size: Size<i32, Buffer>, tolerance: u8) -> Self {
+        let mut changed = 0;
+        let mut max_delta = 0;
+        let mut image = Vec::with_capacity(new.len());
+
+        for (old, new) in old.chunks_exact(4).zip(new.chunks_exact(4)) {
+            let delta = new
+                .iter()
+                .zip(old)
+                .map(|(a, b)| a.abs_diff(*b))
+                .max()
+                .unwrap_or(0);
+            max_delta = max_delta.max(delta);
+            if delta > tolerance {
+                changed += 1;
+                image.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                // Fade unchanged pixels to a translucent gray to keep them as context.
+                let gray = ((u16::from(new[0]) + u16::from(new[1]) + u16::from(new[2])) / 3) as u8;
+                image.extend_from_slice(&[gray, gray, gray, new[3] / 4]);
+            }
+        }
+
+        Self {
+            size,
+            changed,
+            max_delta,
+            image,
+        }
+    }
+
+    /// Writes the diff image to a PNG file.
+    pub fn write_png(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("error creating {}", path.display()))?;
+        write_png_rgba8(
+            BufWriter::new(file),
+            self.size.w as u32,
+            self.size.h as u32,
+            &self.image,
+        )
+        .with_context(|| format!("error encoding {}", path.display()))
+    }
+}
+
+fn changed<T: PartialEq>(old: T, new: T) -> Option<(T, T)> {
+    (old != new).then_some((old, new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_diff() {
+        let size = Size::from((2, 1));
+        let old = [10, 20, 30, 255, 0, 0, 0, 0];
+        let same = PixelDiff::from_pixels(&old, &old, size, 0);
+        assert_eq!((same.changed, same.max_delta), (0, 0));
+
+        let new = [11, 20, 30, 255, 0, 0, 255, 255];
+        let diff = PixelDiff::from_pixels(&old, &new, size, 0);
+        assert_eq!((diff.changed, diff.max_delta), (2, 255));
+        assert_eq!(diff.image, [255, 0, 0, 255, 255, 0, 0, 255]);
+
+        let diff = PixelDiff::from_pixels(&old, &new, size, 2);
+        assert_eq!(diff.changed, 1);
+        assert_eq!(diff.image, [20, 20, 20, 63, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn changed_pairs() {
+        assert_eq!(changed(1, 1), None);
+        assert_eq!(changed(1, 2), Some((1, 2)));
+    }
+}
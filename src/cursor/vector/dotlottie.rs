@@ -0,0 +1,292 @@
+//! dotLottie (`.lottie`) container support.
+//!
+//! A dotLottie file is a zip archive with a `manifest.json`, one or more animations (under
+//! `animations/` in version 1, `a/` in version 2) and the images they reference (under `images/`
+//! or `i/`). Extraction returns the animation JSON with its images inlined as data URLs, which is
+//! what a plain Lottie file with embedded assets looks like.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, ensure, Context, Result};
+use serde_json::Value;
+
+/// Upper bound for the decompressed size of a single archive entry.
+const MAX_ENTRY_SIZE: usize = 64 * 1024 * 1024;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Returns whether `data` looks like a zip archive.
+pub fn is_dotlottie(data: &[u8]) -> bool {
+    data.starts_with(&LOCAL_HEADER_SIGNATURE.to_le_bytes())
+}
+
+/// Extracts the animation JSON from a dotLottie archive.
+///
+/// Picks the manifest's active animation if it names one, otherwise the first animation.
+pub fn extract_animation(data: &[u8]) -> Result<String> {
+    let mut entries = read_zip(data).context("Failed to read dotLottie archive")?;
+
+    let manifest = entries
+        .get("manifest.json")
+        .context("dotLottie archive has no manifest.json")?;
+    let manifest: Value =
+        serde_json::from_slice(manifest).context("Failed to parse dotLottie manifest")?;
+
+    let animations = manifest
+        .get("animations")
+        .and_then(|v| v.as_array())
+        .context("dotLottie manifest lists no animations")?;
+    let id = manifest
+        .get("activeAnimationId")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            animations
+                .iter()
+                .find_map(|animation| animation.get("id")?.as_str())
+        })
+        .context("dotLottie manifest lists no animations")?;
+
+    let animation = [format!("animations/{id}.json"), format!("a/{id}.json")]
+        .into_iter()
+        .find_map(|path| entries.remove(&path))
+        .with_context(|| format!("dotLottie archive has no data for animation '{id}'"))?;
+    let mut animation: Value =
+        serde_json::from_slice(&animation).context("Failed to parse dotLottie animation")?;
+
+    inline_images(&mut animation, &entries);
+
+    Ok(animation.to_string())
+}
+
+/// Replaces references to archive images with data URLs.
+fn inline_images(animation: &mut Value, entries: &HashMap<String, Vec<u8>>) {
+    let Some(assets) = animation.get_mut("assets").and_then(|v| v.as_array_mut()) else {
+        return;
+    };
+
+    for asset in assets {
+        let Some(name) = asset.get("p").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if asset.get("e").and_then(|v| v.as_i64()) == Some(1) || name.starts_with("data:") {
+            continue;
+        }
+
+        let Some(data) = ["images/", "i/"]
+            .iter()
+            .find_map(|dir| entries.get(&format!("{dir}{name}")))
+        else {
+            warn!("dotLottie archive is missing image '{name}'");
+            continue;
+        };
+
+        let mime = match name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+        {
+            Some(ext) if ext == "jpg" || ext == "jpeg" => "image/jpeg",
+            Some(ext) if ext == "webp" => "image/webp",
+            Some(ext) if ext == "svg" => "image/svg+xml",
+            _ => "image/png",
+        };
+        let url = format!("data:{mime};base64,{}", base64(data));
+
+        asset["u"] = Value::from("");
+        asset["p"] = Value::from(url);
+        asset["e"] = Value::from(1);
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .context("unexpected end of archive")?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .context("unexpected end of archive")?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Reads all file entries of a zip archive.
+fn read_zip(data: &[u8]) -> Result<HashMap<String, Vec<u8>>> {
+    // The end of central directory record is at the very end, followed by an optional comment.
+    let eocd = (0..data.len().saturating_sub(21))
+        .rev()
+        .take(u16::MAX as usize + 1)
+        .find(|&offset| u32_at(data, offset).ok() == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .context("missing end of central directory")?;
+
+    let count = u16_at(data, eocd + 10)?;
+    let mut offset = u32_at(data, eocd + 16)? as usize;
+
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        ensure!(
+            u32_at(data, offset)? == CENTRAL_HEADER_SIGNATURE,
+            "corrupt central directory"
+        );
+
+        let method = u16_at(data, offset + 10)?;
+        let compressed_size = u32_at(data, offset + 20)? as usize;
+        let size = u32_at(data, offset + 24)? as usize;
+        let name_len = usize::from(u16_at(data, offset + 28)?);
+        let extra_len = usize::from(u16_at(data, offset + 30)?);
+        let comment_len = usize::from(u16_at(data, offset + 32)?);
+        let local_offset = u32_at(data, offset + 42)? as usize;
+
+        let name = data
+            .get(offset + 46..offset + 46 + name_len)
+            .context("unexpected end of archive")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with('/') {
+            continue;
+        }
+
+        ensure!(
+            u32_at(data, local_offset)? == LOCAL_HEADER_SIGNATURE,
+            "corrupt local header for {name}"
+        );
+        let start = local_offset
+            + 30
+            + usize::from(u16_at(data, local_offset + 26)?)
+            + usize::from(u16_at(data, local_offset + 28)?);
+        let compressed = data
+            .get(start..start + compressed_size)
+            .with_context(|| format!("unexpected end of archive in {name}"))?;
+
+        ensure!(size <= MAX_ENTRY_SIZE, "{name} is too large");
+        let contents = match method {
+            0 => compressed.to_vec(),
+            8 => miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, MAX_ENTRY_SIZE)
+                .map_err(|err| anyhow::anyhow!("failed to inflate {name}: {err:?}"))?,
+            _ => bail!("{name} uses unsupported compression method {method}"),
+        };
+
+        entries.insert(name, contents);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an uncompressed zip archive.
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+
+        for (name, contents) in files {
+            let offset = data.len() as u32;
+            let len = contents.len() as u32;
+
+            data.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend([0; 14]);
+            data.extend(len.to_le_bytes());
+            data.extend(len.to_le_bytes());
+            data.extend((name.len() as u16).to_le_bytes());
+            data.extend(0u16.to_le_bytes());
+            data.extend(name.as_bytes());
+            data.extend(*contents);
+
+            central.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            central.extend([0; 16]);
+            central.extend(len.to_le_bytes());
+            central.extend(len.to_le_bytes());
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend([0; 12]);
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+        }
+
+        let central_offset = data.len() as u32;
+        let central_len = central.len() as u32;
+        data.extend(central);
+        data.extend(END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend((files.len() as u16).to_le_bytes());
+        data.extend(central_len.to_le_bytes());
+        data.extend(central_offset.to_le_bytes());
+        data.extend(0u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn extracts_animation_with_images() {
+        let manifest = br#"{"animations": [{"id": "spin"}]}"#;
+        let animation = br#"{"w": 24, "h": 24, "assets": [{"id": "img", "u": "/images/", "p": "dot.png", "e": 0}], "layers": []}"#;
+        let data = zip(&[
+            ("manifest.json", &manifest[..]),
+            ("animations/spin.json", &animation[..]),
+            ("images/dot.png", &b"png"[..]),
+        ]);
+
+        assert!(is_dotlottie(&data));
+        let json: Value = serde_json::from_str(&extract_animation(&data).unwrap()).unwrap();
+        assert_eq!(json["w"], 24);
+        assert_eq!(json["assets"][0]["p"], "data:image/png;base64,cG5n");
+        assert_eq!(json["assets"][0]["e"], 1);
+    }
+
+    #[test]
+    fn version_2_layout_and_active_animation() {
+        let manifest = br#"{"activeAnimationId": "b", "animations": [{"id": "a"}, {"id": "b"}]}"#;
+        let data = zip(&[
+            ("manifest.json", &manifest[..]),
+            ("a/a.json", &br#"{"nm": "a"}"#[..]),
+            ("a/b.json", &br#"{"nm": "b"}"#[..]),
+        ]);
+
+        let json: Value = serde_json::from_str(&extract_animation(&data).unwrap()).unwrap();
+        assert_eq!(json["nm"], "b");
+    }
+
+    #[test]
+    fn rejects_invalid_archives() {
+        assert!(!is_dotlottie(b"{}"));
+        assert!(extract_animation(b"PK\x03\x04garbage").is_err());
+        assert!(extract_animation(&zip(&[("manifest.json", &b"{}"[..])])).is_err());
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+    }
+}
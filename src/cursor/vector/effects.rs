@@ -0,0 +1,172 @@
+//! Post-processing applied to rasterized cursor pixels.
+//!
+//! Effects run on the premultiplied ARGB8888 pixels of a frame right before they are wrapped in a
+//! buffer, so they work the same for every renderer and backend.
+
+use anyhow::{bail, ensure, Context, Result};
+use smithay::utils::{Physical, Point};
+
+/// Color multiplied into every pixel, recoloring white and light parts of a cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tint {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+/// The post-processing chain of one cursor.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Effects {
+    pub tint: Option<Tint>,
+    /// Whether to stamp the debug watermark, see [`watermark`](super::watermark).
+    pub watermark: bool,
+    /// Whether to mirror the frame horizontally, for left-handed use.
+    ///
+    /// Unlike the color effects, mirroring doesn't need the `effects` feature.
+    pub mirror: bool,
+}
+
+impl Tint {
+    /// Parses `#rrggbb` or `#rrggbbaa`.
+    pub fn parse(color: &str) -> Result<Self> {
+        let hex = color
+            .strip_prefix('#')
+            .with_context(|| format!("tint '{color}' must start with '#'"))?;
+        ensure!(
+            hex.is_ascii() && (hex.len() == 6 || hex.len() == 8),
+            "tint '{color}' must be #rrggbb or #rrggbbaa"
+        );
+
+        let channel = |i: usize| -> Result<f32> {
+            match u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16) {
+                Ok(value) => Ok(f32::from(value) / 255.),
+                Err(_) => bail!("tint '{color}' is not a hex color"),
+            }
+        };
+
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(1)?,
+            b: channel(2)?,
+            a: if hex.len() == 8 { channel(3)? } else { 1. },
+        })
+    }
+
+    #[cfg(feature = "effects")]
+    fn apply(&self, pixels: &mut [u8]) {
+        // ARGB8888 is BGRA in memory. Scaling every premultiplied channel keeps it premultiplied.
+        let factors = [self.b * self.a, self.g * self.a, self.r * self.a, self.a];
+        for pixel in pixels.chunks_exact_mut(4) {
+            for (channel, factor) in pixel.iter_mut().zip(factors) {
+                *channel = (f32::from(*channel) * factor).round() as u8;
+            }
+        }
+    }
+}
+
+impl Effects {
+    /// Applies the effects to premultiplied ARGB8888 pixels in place.
+    ///
+    /// Does nothing when niri is built without the `effects` feature.
+    pub fn apply(&self, pixels: &mut [u8]) {
+        #[cfg(feature = "effects")]
+        {
+            if let Some(tint) = &self.tint {
+                tint.apply(pixels);
+            }
+        }
+        #[cfg(not(feature = "effects"))]
+        let _ = pixels;
+    }
+
+    /// Mirrors the pixels of a frame `width` pixels wide in place, if [`Self::mirror`] is set.
+    pub fn apply_mirror(&self, pixels: &mut [u8], width: i32) {
+        if !self.mirror || width <= 0 {
+            return;
+        }
+        for row in pixels.chunks_exact_mut(width as usize * 4) {
+            let pixels = row.len() / 4;
+            for x in 0..pixels / 2 {
+                let (left, right) = row.split_at_mut((pixels - 1 - x) * 4);
+                left[x * 4..x * 4 + 4].swap_with_slice(&mut right[..4]);
+            }
+        }
+    }
+
+    /// Where the hotspot of a frame `width` pixels wide ends up with [`Self::apply_mirror`].
+    ///
+    /// The hotspot is a point between pixels, so its distance from the left edge becomes the
+    /// distance from the right edge, kept on the frame.
+    pub fn mirror_hotspot(
+        &self,
+        hotspot: Point<i32, Physical>,
+        width: i32,
+    ) -> Point<i32, Physical> {
+        if !self.mirror {
+            return hotspot;
+        }
+        Point::from(((width - hotspot.x).clamp(0, (width - 1).max(0)), hotspot.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tint() {
+        let tint = Tint::parse("#ff8000").unwrap();
+        assert_eq!((tint.r, tint.b, tint.a), (1., 0., 1.));
+        assert!((tint.g - 128. / 255.).abs() < 1e-6);
+
+        assert_eq!(Tint::parse("#00000000").unwrap().a, 0.);
+        assert!(Tint::parse("ff8000").is_err());
+        assert!(Tint::parse("#ff80").is_err());
+        assert!(Tint::parse("#gg8000").is_err());
+    }
+
+    #[test]
+    fn mirror_flips_rows_and_hotspot() {
+        let effects = Effects {
+            mirror: true,
+            ..Default::default()
+        };
+
+        // Two rows of three pixels, one channel value per pixel.
+        let mut pixels: Vec<u8> = [1, 2, 3, 4, 5, 6]
+            .into_iter()
+            .flat_map(|v| [v; 4])
+            .collect();
+        effects.apply_mirror(&mut pixels, 3);
+        let firsts: Vec<_> = pixels.chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(firsts, [3, 2, 1, 6, 5, 4]);
+
+        assert_eq!(
+            effects.mirror_hotspot(Point::from((1, 2)), 3),
+            Point::from((2, 2))
+        );
+        assert_eq!(
+            effects.mirror_hotspot(Point::from((0, 0)), 3),
+            Point::from((2, 0))
+        );
+        assert_eq!(
+            Effects::default().mirror_hotspot(Point::from((1, 2)), 3),
+            Point::from((1, 2))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "effects")]
+    fn tint_multiplies_premultiplied_pixels() {
+        let effects = Effects {
+            tint: Some(Tint::parse("#ff800080").unwrap()),
+            ..Default::default()
+        };
+
+        // Opaque white and half-transparent white, BGRA.
+        let mut pixels = [255, 255, 255, 255, 128, 128, 128, 128];
+        effects.apply(&mut pixels);
+        assert_eq!(pixels, [0, 64, 128, 128, 0, 32, 64, 64]);
+    }
+}
@@ -0,0 +1,208 @@
+//! Memoization of rendered frames.
+//!
+//! Looping animations show the same frames over and over. Caching them by frame and scale means a
+//! 30-frame loop is rasterized 30 times in total rather than once per vblank.
+
+use std::collections::HashMap;
+
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Default byte budget of a renderer's frame cache.
+pub const DEFAULT_BUDGET: usize = 4 * 1024 * 1024;
+
+/// Least recently used cache of rendered frames, bounded by the size of their pixels.
+pub struct FrameCache {
+    budget: usize,
+    used: usize,
+    /// Incremented on every access, for finding the least recently used entry.
+    clock: u64,
+    /// Keyed by frame and scale in 1/120 units.
+    entries: HashMap<(u32, u32), Entry>,
+}
+
+struct Entry {
+    frame: RenderedFrame,
+    bytes: usize,
+    last_used: u64,
+}
+
+impl FrameCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            used: 0,
+            clock: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the cached `frame` at `scale`, marking it as recently used.
+    pub fn get(&mut self, frame: u32, scale: f64) -> Option<RenderedFrame> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(&(frame, scale_key(scale)))?;
+        entry.last_used = self.clock;
+        Some(entry.frame.clone())
+    }
+
+    /// Caches a rendered frame, evicting the least recently used frames to stay in budget.
+    ///
+    /// Frames larger than the whole budget are not cached. Returns the dropped frames that nothing
+    /// else holds on to, so their buffers can be reused without changing a frame on screen.
+    pub fn insert(
+        &mut self,
+        frame: u32,
+        scale: f64,
+        rendered: RenderedFrame,
+    ) -> Vec<RenderedFrame> {
+        let key = (frame, scale_key(scale));
+        let bytes = rendered.size.w.max(0) as usize * rendered.size.h.max(0) as usize * 4;
+        if bytes > self.budget {
+            return Vec::new();
+        }
+
+        let mut dropped = Vec::new();
+        if let Some(old) = self.entries.remove(&key) {
+            self.used -= old.bytes;
+            dropped.push(old.frame);
+        }
+
+        while self.used + bytes > self.budget {
+            // Cursor animations have few frames, a linear scan is fine.
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            let entry = self.entries.remove(&oldest).unwrap();
+            self.used -= entry.bytes;
+            dropped.push(entry.frame);
+        }
+
+        self.clock += 1;
+        self.used += bytes;
+        self.entries.insert(
+            key,
+            Entry {
+                frame: rendered,
+                bytes,
+                last_used: self.clock,
+            },
+        );
+        dropped.retain(RenderedFrame::is_unique);
+        dropped
+    }
+
+    /// Drops every frame except the most recently used one, which is likely still on screen.
+    pub fn trim(&mut self) {
+        let newest = self
+            .entries
+            .iter()
+            .max_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
+        self.entries.retain(|key, _| Some(*key) == newest);
+        self.used = self.entries.values().map(|entry| entry.bytes).sum();
+    }
+
+    /// Drops every frame, for when the frames would look different now.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.used = 0;
+    }
+
+    /// Total size of the cached pixels in bytes.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn scale_key(scale: f64) -> u32 {
+    (scale * 120.).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::vector::test_frames::solid_frame;
+
+    fn frame(size: i32) -> RenderedFrame {
+        solid_frame((size, size), (0, 0), [0; 4])
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        // Room for three 8×8 frames.
+        let mut cache = FrameCache::new(3 * 8 * 8 * 4);
+        cache.insert(0, 1., frame(8));
+        cache.insert(1, 1., frame(8));
+        cache.insert(2, 1., frame(8));
+        assert!(cache.get(0, 1.).is_some());
+
+        assert_eq!(cache.insert(3, 1., frame(8)).len(), 1);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.get(1, 1.).is_none());
+        assert!(cache.get(0, 1.).is_some());
+        assert!(cache.get(3, 1.).is_some());
+        assert_eq!(cache.used(), 3 * 8 * 8 * 4);
+    }
+
+    #[test]
+    fn keys_include_scale() {
+        let mut cache = FrameCache::new(DEFAULT_BUDGET);
+        cache.insert(0, 1., frame(8));
+        assert!(cache.get(0, 2.).is_none());
+        assert!(cache.get(0, 1.25).is_none());
+
+        cache.insert(0, 1., frame(8));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.used(), 8 * 8 * 4);
+    }
+
+    #[test]
+    fn trim_keeps_newest() {
+        let mut cache = FrameCache::new(DEFAULT_BUDGET);
+        cache.insert(0, 1., frame(8));
+        cache.insert(1, 1., frame(8));
+        cache.insert(2, 1., frame(8));
+        cache.get(1, 1.);
+
+        cache.trim();
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(1, 1.).is_some());
+        assert_eq!(cache.used(), 8 * 8 * 4);
+    }
+
+    #[test]
+    fn skips_frames_over_budget() {
+        let mut cache = FrameCache::new(16);
+        assert!(cache.insert(0, 1., frame(8)).is_empty());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn returns_only_unused_frames() {
+        // Room for one 8×8 frame.
+        let mut cache = FrameCache::new(8 * 8 * 4);
+        cache.insert(0, 1., frame(8));
+        let shown = cache.get(0, 1.).unwrap();
+
+        // The evicted frame is still on screen, so its buffer must not be reused.
+        assert!(cache.insert(1, 1., frame(8)).is_empty());
+        drop(shown);
+
+        let rendered = frame(8);
+        assert_eq!(cache.insert(2, 1., rendered.clone()).len(), 1);
+        assert!(!rendered.is_unique());
+        assert!(cache.insert(3, 1., rendered).is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+//! Golden-image checks of vector cursors, for visual regression tests of themes.
+//!
+//! A theme repository keeps reference PNGs of its cursors at chosen frames and scales. A small
+//! companion binary built on [`run`] renders each case with this crate and compares it against
+//! its reference, writing diff images for the cases that changed:
+//!
+//! ```ignore
+//! let cases = [Case::new("default", 0, 1.), Case::new("wait", 12, 2.)];
+//! let report = golden::run(theme_dir, 24, &cases, &Options::new(refs_dir))?;
+//! for failure in report.failures() {
+//!     eprintln!("{failure}");
+//! }
+//! std::process::exit(if report.passed() { 0 } else { 1 });
+//! ```
+//!
+//! References are named after their case, like `wait@2x-12.png`, and are (re)created by running
+//! with [`Options::update`] set.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use smithay::utils::{Buffer, Size};
+
+use crate::cursor::vector::bench::load_store;
+use crate::cursor::vector::diff::PixelDiff;
+use crate::cursor::vector::store::VectorCursorStore;
+use crate::cursor::vector::types::RenderedFrame;
+use crate::utils::write_png_rgba8;
+
+/// An RGBA8 image with straight alpha, as stored in PNG files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub size: Size<i32, Buffer>,
+    pub pixels: Vec<u8>,
+}
+
+/// One cursor frame to check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub cursor_id: String,
+    pub frame: u32,
+    pub scale: f64,
+}
+
+/// How much a rendering may differ from its reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerance {
+    /// Largest difference of a color or alpha channel for a pixel to count as unchanged.
+    pub channel: u8,
+    /// Number of changed pixels that are still accepted.
+    pub pixels: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        // Allows for rounding differences between rasterizer versions.
+        Self {
+            channel: 2,
+            pixels: 0,
+        }
+    }
+}
+
+/// Result of comparing a rendering with its reference.
+#[derive(Debug)]
+pub struct Comparison {
+    /// Sizes of the rendering and the reference, if they differ.
+    pub size_mismatch: Option<(Size<i32, Buffer>, Size<i32, Buffer>)>,
+    /// Number of pixels that differ by more than the channel tolerance.
+    pub changed: usize,
+    /// Largest channel difference over all pixels.
+    pub max_delta: u8,
+    /// The rendering faded to gray with the changed pixels in red, if any changed.
+    pub diff: Option<PixelDiff>,
+    pub passed: bool,
+}
+
+/// Where [`run`] finds references and puts diffs.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub reference_dir: PathBuf,
+    /// Directory for diff images of failed cases, none are written if unset.
+    pub diff_dir: Option<PathBuf>,
+    /// Writes the renderings as new references instead of comparing.
+    pub update: bool,
+    pub tolerance: Tolerance,
+}
+
+/// Outcome of one case of [`run`].
+#[derive(Debug)]
+pub struct Outcome {
+    pub case: Case,
+    pub result: Result<Comparison>,
+}
+
+/// Outcomes of all cases of [`run`].
+#[derive(Debug, Default)]
+pub struct Report {
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Image {
+    /// Converts a rendered frame from premultiplied ARGB8888.
+    pub fn from_frame(frame: &RenderedFrame) -> Self {
+        let mut pixels = frame.read_pixels();
+        for pixel in pixels.chunks_exact_mut(4) {
+            let [b, g, r, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            let unpremultiply = |c: u8| match a {
+                0 => 0,
+                a => ((u16::from(c) * 255 + u16::from(a) / 2) / u16::from(a)).min(255) as u8,
+            };
+            pixel.copy_from_slice(&[unpremultiply(r), unpremultiply(g), unpremultiply(b), a]);
+        }
+        Self {
+            size: frame.size,
+            pixels,
+        }
+    }
+
+    /// Reads a PNG file of any color type.
+    pub fn read_png(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("error opening {}", path.display()))?;
+        let mut decoder = png::Decoder::new(BufReader::new(file));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder
+            .read_info()
+            .with_context(|| format!("error decoding {}", path.display()))?;
+
+        let (width, height) = (reader.info().width, reader.info().height);
+        let mut buffer = vec![0; width as usize * height as usize * 4];
+        let info = reader
+            .next_frame(&mut buffer)
+            .with_context(|| format!("error decoding {}", path.display()))?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+            png::ColorType::Indexed => bail!("{}: palette wasn't expanded", path.display()),
+        };
+
+        Ok(Self {
+            size: Size::from((width as i32, height as i32)),
+            pixels,
+        })
+    }
+
+    pub fn write_png(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("error creating {}", path.display()))?;
+        write_png_rgba8(
+            BufWriter::new(file),
+            self.size.w as u32,
+            self.size.h as u32,
+            &self.pixels,
+        )
+        .with_context(|| format!("error encoding {}", path.display()))
+    }
+}
+
+impl Case {
+    pub fn new(cursor_id: impl Into<String>, frame: u32, scale: f64) -> Self {
+        Self {
+            cursor_id: cursor_id.into(),
+            frame,
+            scale,
+        }
+    }
+
+    /// File name of the case's reference, like `wait@1.5x-12.png`.
+    pub fn file_name(&self) -> String {
+        format!("{}@{}x-{}.png", self.cursor_id, self.scale, self.frame)
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} frame {} at {}x",
+            self.cursor_id, self.frame, self.scale
+        )
+    }
+}
+
+impl Options {
+    pub fn new(reference_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            reference_dir: reference_dir.into(),
+            diff_dir: None,
+            update: false,
+            tolerance: Tolerance::default(),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Err(err) => write!(f, "{}: {err:#}", self.case),
+            Ok(comparison) => match comparison.size_mismatch {
+                Some((rendered, reference)) => write!(
+                    f,
+                    "{}: rendered {}×{}, reference is {}×{}",
+                    self.case, rendered.w, rendered.h, reference.w, reference.h
+                ),
+                None if comparison.passed => write!(f, "{}: ok", self.case),
+                None => write!(
+                    f,
+                    "{}: {} pixels changed, by up to {}",
+                    self.case, comparison.changed, comparison.max_delta
+                ),
+            },
+        }
+    }
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Cases that errored or didn't match their reference.
+    pub fn failures(&self) -> impl Iterator<Item = &Outcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| !matches!(&outcome.result, Ok(comparison) if comparison.passed))
+    }
+}
+
+/// Renders one frame of a cursor.
+pub fn render(store: &VectorCursorStore, cursor_id: &str, frame: u32, scale: f64) -> Result<Image> {
+    let renderer = store.get_renderer(cursor_id)?;
+    let frame = renderer
+        .render_frame(frame, scale)
+        .with_context(|| format!("error rendering {cursor_id}"))?;
+    Ok(Image::from_frame(&frame))
+}
+
+/// Compares a rendering with its reference.
+pub fn compare(rendered: &Image, reference: &Image, tolerance: Tolerance) -> Comparison {
+    if rendered.size != reference.size {
+        return Comparison {
+            size_mismatch: Some((rendered.size, reference.size)),
+            changed: 0,
+            max_delta: 0,
+            diff: None,
+            passed: false,
+        };
+    }
+
+    let diff = PixelDiff::from_pixels(
+        &reference.pixels,
+        &rendered.pixels,
+        rendered.size,
+        tolerance.channel,
+    );
+    Comparison {
+        size_mismatch: None,
+        changed: diff.changed,
+        max_delta: diff.max_delta,
+        passed: diff.changed <= tolerance.pixels,
+        diff: (diff.changed > 0).then_some(diff),
+    }
+}
+
+/// Renders each case from a theme directory and checks it against its reference.
+///
+/// Fails only if the theme can't be loaded, problems with single cases are in their outcome.
+pub fn run(theme_dir: &Path, base_size: u8, cases: &[Case], options: &Options) -> Result<Report> {
+    let store = load_store(theme_dir, base_size)
+        .with_context(|| format!("error loading theme {}", theme_dir.display()))?;
+
+    let outcomes = cases
+        .iter()
+        .map(|case| Outcome {
+            case: case.clone(),
+            result: run_case(&store, case, options),
+        })
+        .collect();
+    Ok(Report { outcomes })
+}
+
+fn run_case(store: &VectorCursorStore, case: &Case, options: &Options) -> Result<Comparison> {
+    let rendered = render(store, &case.cursor_id, case.frame, case.scale)?;
+    let reference_path = options.reference_dir.join(case.file_name());
+
+    if options.update {
+        rendered.write_png(&reference_path)?;
+        return Ok(compare(&rendered, &rendered, options.tolerance));
+    }
+
+    let reference = Image::read_png(&reference_path)?;
+    let comparison = compare(&rendered, &reference, options.tolerance);
+    if let (Some(diff_dir), Some(diff)) = (&options.diff_dir, &comparison.diff) {
+        if !comparison.passed {
+            diff.write_png(&diff_dir.join(case.file_name()))?;
+        }
+    }
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: &[u8]) -> Image {
+        Image {
+            size: Size::from((pixels.len() as i32 / 4, 1)),
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn compares_within_tolerance() {
+        let reference = image(&[10, 20, 30, 255, 0, 0, 0, 0]);
+        let rendered = image(&[11, 20, 30, 255, 0, 0, 255, 255]);
+
+        let comparison = compare(&rendered, &reference, Tolerance::default());
+        assert!(!comparison.passed);
+        assert_eq!(comparison.changed, 1);
+        assert_eq!(comparison.max_delta, 255);
+        assert_eq!(
+            comparison.diff.unwrap().image,
+            [20, 20, 20, 63, 255, 0, 0, 255]
+        );
+
+        let tolerance = Tolerance {
+            channel: 2,
+            pixels: 1,
+        };
+        assert!(compare(&rendered, &reference, tolerance).passed);
+        assert!(compare(&reference, &reference, Tolerance::default())
+            .diff
+            .is_none());
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let comparison = compare(&image(&[0; 4]), &image(&[0; 8]), Tolerance::default());
+        assert!(!comparison.passed);
+        assert_eq!(
+            comparison.size_mismatch,
+            Some((Size::from((1, 1)), Size::from((2, 1))))
+        );
+    }
+
+    #[test]
+    fn case_file_names() {
+        assert_eq!(Case::new("wait", 12, 1.5).file_name(), "wait@1.5x-12.png");
+        assert_eq!(Case::new("default", 0, 2.).file_name(), "default@2x-0.png");
+    }
+}
@@ -0,0 +1,186 @@
+//! Ring of recently shown cursor frames, for debugging glitches.
+//!
+//! With `NIRI_CURSOR_HISTORY=<n>` in the environment, the cursor manager keeps the last `n`
+//! distinct vector cursor frames it showed. [`CursorHistory::strip`] lays them out side by side
+//! with their hotspots on one line, so a cursor that flashed the wrong icon for a single frame, or
+//! jumped by a few pixels, stands out in one image.
+
+use std::collections::VecDeque;
+use std::env;
+use std::time::Duration;
+
+use smithay::utils::{Physical, Point, Size};
+
+use crate::cursor::vector::golden::Image;
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Width of the gap between the cells of a strip.
+const GAP: i32 = 1;
+
+/// Opaque gray of the gaps, RGBA.
+const GAP_COLOR: [u8; 4] = [0x80, 0x80, 0x80, 0xff];
+
+/// Number of frames to keep requested through the environment, 0 if history is off.
+pub fn capacity_from_env() -> usize {
+    env::var("NIRI_CURSOR_HISTORY")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// A frame as it was shown.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Monotonic time the frame was first shown.
+    pub time: Duration,
+    pub cursor_id: String,
+    pub frame: u32,
+    pub scale: f64,
+    pub hotspot: Point<i32, Physical>,
+    /// Copy of the pixels, since frame buffers get reused.
+    pub image: Image,
+}
+
+/// The last frames shown, oldest first.
+#[derive(Debug, Default)]
+pub struct CursorHistory {
+    capacity: usize,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl CursorHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            snapshots: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Records a shown frame, unless it looks the same as the previous one.
+    pub fn record(
+        &mut self,
+        time: Duration,
+        cursor_id: &str,
+        frame: u32,
+        rendered: &RenderedFrame,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let image = Image::from_frame(rendered);
+        if let Some(last) = self.snapshots.back() {
+            if last.hotspot == rendered.hotspot && last.image == image {
+                return;
+            }
+        }
+
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(Snapshot {
+            time,
+            cursor_id: cursor_id.to_owned(),
+            frame,
+            scale: rendered.scale,
+            hotspot: rendered.hotspot,
+            image,
+        });
+    }
+
+    pub fn snapshots(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Composes the snapshots into one image, oldest on the left, with their hotspots aligned.
+    ///
+    /// Cells are as large as needed to fit every snapshot and separated by gray lines. `None` if
+    /// there are no snapshots.
+    pub fn strip(&self) -> Option<Image> {
+        if self.snapshots.is_empty() {
+            return None;
+        }
+
+        let extent = |f: fn(&Snapshot) -> i32| self.snapshots.iter().map(f).max().unwrap_or(0);
+        let left = extent(|s| s.hotspot.x);
+        let top = extent(|s| s.hotspot.y);
+        let right = extent(|s| s.image.size.w - s.hotspot.x);
+        let bottom = extent(|s| s.image.size.h - s.hotspot.y);
+        let cell = Size::from(((left + right).max(1), (top + bottom).max(1)));
+
+        let count = self.snapshots.len() as i32;
+        let size = Size::from((count * cell.w + (count - 1) * GAP, cell.h));
+        let mut pixels = vec![0; size.w as usize * size.h as usize * 4];
+
+        for (i, snapshot) in self.snapshots.iter().enumerate() {
+            let x0 = i as i32 * (cell.w + GAP);
+            if i > 0 {
+                for y in 0..size.h {
+                    let at = ((y * size.w + x0 - GAP) * 4) as usize;
+                    pixels[at..at + 4].copy_from_slice(&GAP_COLOR);
+                }
+            }
+
+            let image = &snapshot.image;
+            let dx = x0 + left - snapshot.hotspot.x;
+            let dy = top - snapshot.hotspot.y;
+            let row = image.size.w as usize * 4;
+            for y in 0..image.size.h {
+                let src = (y * image.size.w * 4) as usize;
+                let dst = (((y + dy) * size.w + dx) * 4) as usize;
+                pixels[dst..dst + row].copy_from_slice(&image.pixels[src..src + row]);
+            }
+        }
+
+        Some(Image { size, pixels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::vector::test_frames::solid_frame as frame;
+
+    #[test]
+    fn keeps_last_distinct_frames() {
+        let mut history = CursorHistory::new(2);
+        let red = frame((1, 1), (0, 0), [0, 0, 255, 255]);
+        let blue = frame((1, 1), (0, 0), [255, 0, 0, 255]);
+
+        history.record(Duration::from_millis(1), "a", 0, &red);
+        history.record(Duration::from_millis(2), "a", 0, &red);
+        history.record(Duration::from_millis(3), "b", 0, &blue);
+        history.record(Duration::from_millis(4), "a", 0, &red);
+
+        let times: Vec<_> = history.snapshots().map(|s| s.time.as_millis()).collect();
+        assert_eq!(times, [3, 4]);
+        assert!(CursorHistory::new(0).strip().is_none());
+    }
+
+    #[test]
+    fn strip_aligns_hotspots() {
+        let mut history = CursorHistory::new(4);
+        history.record(Duration::ZERO, "a", 0, &frame((1, 1), (0, 0), [255; 4]));
+        history.record(
+            Duration::ZERO,
+            "b",
+            0,
+            &frame((2, 1), (1, 0), [0, 0, 0, 255]),
+        );
+
+        let strip = history.strip().unwrap();
+        // Cells of 2×1 pixels with a gap between them.
+        assert_eq!(strip.size, Size::from((5, 1)));
+        let alpha: Vec<u8> = strip.pixels.chunks_exact(4).map(|p| p[3]).collect();
+        assert_eq!(alpha, [0, 255, 255, 255, 255]);
+        assert_eq!(&strip.pixels[8..12], &GAP_COLOR);
+    }
+}
@@ -0,0 +1,98 @@
+//! Verification of theme assets against the hashes in `theme.toml`.
+//!
+//! Themes installed from third-party sources can pin the SHA-256 of every cursor file, so assets
+//! that were corrupted or swapped after the theme was published are caught when they're loaded.
+
+use std::fmt;
+
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What to do with an asset that doesn't match its hash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityMode {
+    /// Don't load the cursor.
+    #[default]
+    Refuse,
+    /// Log the mismatch and load the cursor anyway.
+    Warn,
+}
+
+/// An asset whose contents don't match the configured hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashMismatch {
+    pub file: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for HashMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} has SHA-256 {}, but the theme expects {}",
+            self.file, self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for HashMismatch {}
+
+/// Lowercase hex SHA-256 of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Checks `data` of `file` against the expected hex SHA-256.
+///
+/// Fails with a [`HashMismatch`] if the hash differs, or with a plain error if `expected` isn't a
+/// SHA-256 hash at all.
+pub fn verify(file: &str, data: &[u8], expected: &str) -> Result<()> {
+    ensure!(
+        expected.len() == 64 && expected.bytes().all(|b| b.is_ascii_hexdigit()),
+        "sha256 of {file} must be 64 hex digits"
+    );
+
+    let actual = sha256_hex(data);
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(HashMismatch {
+            file: file.to_owned(),
+            expected: expected.to_ascii_lowercase(),
+            actual,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn hashes() {
+        assert_eq!(sha256_hex(b"abc"), ABC);
+    }
+
+    #[test]
+    fn verification() {
+        verify("a.svg", b"abc", ABC).unwrap();
+        verify("a.svg", b"abc", &ABC.to_ascii_uppercase()).unwrap();
+
+        let err = verify("a.svg", b"abd", ABC).unwrap_err();
+        let mismatch = err.downcast_ref::<HashMismatch>().unwrap();
+        assert_eq!(mismatch.expected, ABC);
+        assert_eq!(mismatch.actual, sha256_hex(b"abd"));
+
+        let err = verify("a.svg", b"abc", "abc").unwrap_err();
+        assert!(err.downcast_ref::<HashMismatch>().is_none());
+    }
+}
@@ -1,11 +1,43 @@
 pub mod animator;
+pub mod bench;
+pub mod color;
 pub mod config;
+pub mod diff;
+#[cfg(feature = "lottie")]
+pub mod dotlottie;
+pub mod effects;
+pub mod frame_cache;
+pub mod golden;
+pub mod history;
+pub mod id;
+pub mod integrity;
+pub mod morph;
+pub mod motion_blur;
+pub mod pixel_pool;
+pub mod remote;
 pub mod renderer;
+pub mod scale;
+pub mod script;
+pub mod stats;
 pub mod store;
+#[cfg(test)]
+mod test_frames;
+pub mod transition;
 pub mod types;
+pub mod watcher;
+pub mod watermark;
 
-pub use animator::CursorAnimator;
-pub use config::{CursorThemeConfig, TransitionConfig};
-pub use renderer::{LottieRenderer, SvgRenderer, VectorRenderer};
+pub use animator::{AnimationEvent, AnimationObserver, CursorAnimator};
+pub use config::{CursorGroup, CursorThemeConfig, TransitionConfig};
+pub use effects::{Effects, Tint};
+pub use id::CursorId;
+#[cfg(feature = "lottie")]
+pub use renderer::LottieRenderer;
+#[cfg(feature = "svg")]
+pub use renderer::SvgRenderer;
+pub use renderer::VectorRenderer;
+pub use scale::{RenderScale, ScaleSnapping};
+pub use stats::{DurationHistogram, RenderStats, StatsSummary};
 pub use store::VectorCursorStore;
+pub use transition::{TransitionEffect, TransitionEffects};
 pub use types::{LoopMode, RenderedFrame, TransitionState, VectorCursorData};
@@ -0,0 +1,298 @@
+//! Morph transitions, which turn the outline of one cursor into the other's.
+//!
+//! Renderers that know the geometry of their artwork provide an [`Outline`]: the filled shapes
+//! of a frame, each resampled to the same number of points along its contour. Shapes of both
+//! cursors are paired by area, the points of a pair are matched up starting from the closest
+//! ones, and every pair is drawn at its interpolated points and color. Shapes without a partner
+//! shrink into, or grow out of, their center.
+//!
+//! Outlines only have fills of a single color: while morphing, strokes are left out, gradients
+//! take the color of their first stop, and holes are filled.
+
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Point, Size, Transform};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap};
+
+use crate::cursor::vector::scale;
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Points every contour is resampled to.
+pub const POINTS: usize = 64;
+
+/// Filled shapes of a frame, in buffer pixels relative to its hotspot.
+#[derive(Debug, Clone, Default)]
+pub struct Outline {
+    /// Shapes in the order they are drawn.
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Shape {
+    /// [`POINTS`] points evenly spaced along the contour, all shapes wound the same way.
+    pub points: Vec<(f32, f32)>,
+    /// Straight RGBA, each from 0 to 1.
+    pub color: [f32; 4],
+}
+
+impl Shape {
+    /// Resamples a closed polygon, `None` if it encloses nothing.
+    pub fn new(polygon: &[(f32, f32)], color: [f32; 4]) -> Option<Self> {
+        if polygon.len() < 3 || signed_area(polygon).abs() < f32::EPSILON {
+            return None;
+        }
+
+        let mut points = resample(polygon, POINTS);
+        if signed_area(&points) < 0. {
+            points.reverse();
+        }
+        Some(Self { points, color })
+    }
+
+    fn area(&self) -> f32 {
+        signed_area(&self.points).abs()
+    }
+
+    /// The shape shrunk into its center and faded out.
+    fn collapsed(&self) -> Self {
+        let (x, y) = self
+            .points
+            .iter()
+            .fold((0., 0.), |(x, y), point| (x + point.0, y + point.1));
+        let count = self.points.len() as f32;
+        let [r, g, b, _] = self.color;
+        Self {
+            points: vec![(x / count, y / count); self.points.len()],
+            color: [r, g, b, 0.],
+        }
+    }
+
+    fn interpolate(&self, other: &Shape, t: f32) -> Shape {
+        let offset = alignment(&self.points, &other.points);
+        let count = self.points.len();
+        let points = (0..count)
+            .map(|i| {
+                let (ax, ay) = self.points[i];
+                let (bx, by) = other.points[(i + offset) % count];
+                (ax + (bx - ax) * t, ay + (by - ay) * t)
+            })
+            .collect();
+        let mut color = self.color;
+        for (a, b) in color.iter_mut().zip(other.color) {
+            *a += (b - *a) * t;
+        }
+        Shape { points, color }
+    }
+}
+
+/// Draws the morph from `from` to `to` at `progress`, from 0 to 1.
+///
+/// Shapes are drawn largest first, so small details stay on top of the body of a cursor.
+pub fn morph(from: &Outline, to: &Outline, progress: f32, scale: f64) -> RenderedFrame {
+    let t = progress.clamp(0., 1.);
+    let by_area = |outline: &Outline| {
+        let mut shapes = outline.shapes.clone();
+        shapes.sort_by(|a, b| b.area().total_cmp(&a.area()));
+        shapes
+    };
+    let from = by_area(from);
+    let to = by_area(to);
+
+    let shapes: Vec<Shape> = (0..from.len().max(to.len()))
+        .filter_map(|i| match (from.get(i), to.get(i)) {
+            (Some(a), Some(b)) => Some(a.interpolate(b, t)),
+            (Some(a), None) => Some(a.interpolate(&a.collapsed(), t)),
+            (None, Some(b)) => Some(b.collapsed().interpolate(b, t)),
+            (None, None) => None,
+        })
+        .collect();
+    rasterize(&shapes, scale)
+}
+
+/// Fills the shapes onto a canvas that just fits them.
+fn rasterize(shapes: &[Shape], scale: f64) -> RenderedFrame {
+    let points = shapes.iter().flat_map(|shape| &shape.points);
+    let (left, top, right, bottom) = points.fold(
+        (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+        |(left, top, right, bottom), &(x, y)| {
+            (left.min(x), top.min(y), right.max(x), bottom.max(y))
+        },
+    );
+    let (left, top) = if shapes.is_empty() {
+        (0, 0)
+    } else {
+        (left.floor() as i32, top.floor() as i32)
+    };
+    let size = Size::from((
+        (right.ceil() as i32 - left).max(1),
+        (bottom.ceil() as i32 - top).max(1),
+    ));
+
+    let mut pixmap = Pixmap::new(size.w as u32, size.h as u32).expect("canvas is not empty");
+    for shape in shapes {
+        let mut builder = PathBuilder::new();
+        let mut points = shape
+            .points
+            .iter()
+            .map(|&(x, y)| (x - left as f32, y - top as f32));
+        let Some((x, y)) = points.next() else {
+            continue;
+        };
+        builder.move_to(x, y);
+        for (x, y) in points {
+            builder.line_to(x, y);
+        }
+        builder.close();
+        // Collapsed shapes have no area left to fill.
+        let Some(path) = builder.finish() else {
+            continue;
+        };
+
+        let [r, g, b, a] = shape.color.map(|c| c.clamp(0., 1.));
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(
+            (r * 255.).round() as u8,
+            (g * 255.).round() as u8,
+            (b * 255.).round() as u8,
+            (a * 255.).round() as u8,
+        );
+        paint.anti_alias = true;
+        pixmap.fill_path(
+            &path,
+            &paint,
+            FillRule::Winding,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+
+    // tiny-skia produces RGBA, the buffer wants ARGB8888 (BGRA in memory).
+    let mut pixels = pixmap.take();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    let buffer = MemoryRenderBuffer::from_slice(
+        &pixels,
+        Fourcc::Argb8888,
+        (size.w, size.h),
+        scale::memory_buffer_scale(scale),
+        Transform::Normal,
+        None,
+    );
+    RenderedFrame::new(buffer, size, scale, Point::from((-left, -top)), None)
+}
+
+/// Area of a polygon, negative if it's wound counterclockwise on screen.
+fn signed_area(polygon: &[(f32, f32)]) -> f32 {
+    let next = polygon.iter().cycle().skip(1);
+    polygon
+        .iter()
+        .zip(next)
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f32>()
+        / 2.
+}
+
+/// `count` points evenly spaced along the contour of a closed polygon, starting at its first
+/// point.
+fn resample(polygon: &[(f32, f32)], count: usize) -> Vec<(f32, f32)> {
+    let next = polygon.iter().cycle().skip(1);
+    let edges: Vec<_> = polygon
+        .iter()
+        .zip(next)
+        .map(|(&a, &b)| (a, b, (b.0 - a.0).hypot(b.1 - a.1)))
+        .collect();
+    let perimeter: f32 = edges.iter().map(|edge| edge.2).sum();
+
+    let mut index = 0;
+    let mut start = 0.;
+    (0..count)
+        .map(|i| {
+            let distance = perimeter * i as f32 / count as f32;
+            while index + 1 < edges.len() && start + edges[index].2 < distance {
+                start += edges[index].2;
+                index += 1;
+            }
+            let (a, b, length) = edges[index];
+            let t = if length > 0. {
+                ((distance - start) / length).clamp(0., 1.)
+            } else {
+                0.
+            };
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+        })
+        .collect()
+}
+
+/// Rotation of `to`'s points that brings them closest to `from`'s, so that shapes don't twist
+/// while morphing.
+fn alignment(from: &[(f32, f32)], to: &[(f32, f32)]) -> usize {
+    let count = from.len().min(to.len());
+    let cost = |offset: usize| -> f32 {
+        (0..count)
+            .map(|i| {
+                let (ax, ay) = from[i];
+                let (bx, by) = to[(i + offset) % count];
+                (bx - ax).powi(2) + (by - ay).powi(2)
+            })
+            .sum()
+    };
+    (0..count)
+        .min_by(|&a, &b| cost(a).total_cmp(&cost(b)))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 4] = [1., 0., 0., 1.];
+    const BLUE: [f32; 4] = [0., 0., 1., 1.];
+
+    fn square(x: f32, y: f32, size: f32, color: [f32; 4]) -> Shape {
+        let polygon = [(x, y), (x + size, y), (x + size, y + size), (x, y + size)];
+        Shape::new(&polygon, color).unwrap()
+    }
+
+    #[test]
+    fn resamples_evenly() {
+        let shape = square(0., 0., 16., RED);
+        assert_eq!(shape.points.len(), POINTS);
+        assert_eq!(shape.points[1], (1., 0.));
+        assert_eq!(shape.points[POINTS / 4], (16., 0.));
+        assert_eq!(shape.area(), 256.);
+
+        // Winding is normalized.
+        let reversed = Shape::new(&[(0., 0.), (0., 4.), (4., 4.), (4., 0.)], RED).unwrap();
+        assert!(signed_area(&reversed.points) > 0.);
+        assert!(Shape::new(&[(0., 0.), (4., 4.), (8., 8.)], RED).is_none());
+    }
+
+    #[test]
+    fn morphs_between_outlines() {
+        let from = Outline {
+            shapes: vec![square(-2., -2., 10., RED)],
+        };
+        let to = Outline {
+            shapes: vec![square(0., 0., 20., BLUE), square(2., 2., 4., RED)],
+        };
+
+        let frame = morph(&from, &to, 0., 1.);
+        assert_eq!(frame.size, Size::from((10, 10)));
+        assert_eq!(frame.hotspot, Point::from((2, 2)));
+
+        let frame = morph(&from, &to, 0.5, 1.);
+        assert_eq!(frame.size, Size::from((15, 15)));
+        assert_eq!(frame.hotspot, Point::from((1, 1)));
+        let pixels = frame.read_pixels();
+        let center = &pixels[(7 * 15 + 7) * 4..][..4];
+        assert!(center[0] > 0 && center[2] > 0 && center[3] == 255);
+
+        let frame = morph(&from, &to, 1., 1.);
+        assert_eq!(frame.size, Size::from((20, 20)));
+        assert_eq!(frame.hotspot, Point::from((0, 0)));
+        // The small square grew out of its center on top of the large one.
+        let pixels = frame.read_pixels();
+        assert_eq!(&pixels[(3 * 20 + 3) * 4..][..4], [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Motion blur of vector cursors while the pointer moves fast.
+//!
+//! The rendered frame is smeared behind the direction of motion: fading copies of it trail the
+//! cursor, and the frame itself is drawn sharp on top. The canvas grows to fit the trail with
+//! the hotspot kept on the same pixel of the artwork, so the cursor doesn't shift.
+
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Buffer, Point, Size, Transform};
+
+use crate::cursor::vector::scale;
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Most copies drawn along a trail.
+const MAX_COPIES: usize = 32;
+
+/// Opacity of the copy closest to the cursor.
+const TRAIL_OPACITY: f32 = 0.5;
+
+/// Smears `frame` over `length` buffer pixels opposite to `direction`, a unit vector.
+pub fn smear(frame: &RenderedFrame, direction: (f64, f64), length: f64) -> RenderedFrame {
+    let (tx, ty) = (-direction.0 * length, -direction.1 * length);
+    if !(tx.is_finite() && ty.is_finite()) || length < 1. {
+        return frame.clone();
+    }
+
+    let (w, h) = (frame.size.w, frame.size.h);
+    let left = (tx.round() as i32).min(0);
+    let top = (ty.round() as i32).min(0);
+    let right = w + (tx.round() as i32).max(0);
+    let bottom = h + (ty.round() as i32).max(0);
+    let size = Size::from((right - left, bottom - top));
+
+    let pixels = frame.read_pixels();
+    let mut canvas = vec![0; size.w as usize * size.h as usize * 4];
+
+    // Farthest copy first, so nearer ones end up on top.
+    let copies = (length.ceil() as usize).clamp(1, MAX_COPIES);
+    for k in (1..=copies).rev() {
+        let t = k as f64 / copies as f64;
+        let offset = (
+            (tx * t).round() as i32 - left,
+            (ty * t).round() as i32 - top,
+        );
+        let opacity = TRAIL_OPACITY * (1. - t as f32 + 1. / copies as f32);
+        draw_over(&mut canvas, size, &pixels, frame.size, offset, opacity);
+    }
+    draw_over(&mut canvas, size, &pixels, frame.size, (-left, -top), 1.);
+
+    let buffer = MemoryRenderBuffer::from_slice(
+        &canvas,
+        Fourcc::Argb8888,
+        (size.w, size.h),
+        scale::memory_buffer_scale(frame.scale),
+        Transform::Normal,
+        None,
+    );
+    let hotspot = Point::from((frame.hotspot.x - left, frame.hotspot.y - top));
+    let mut smeared = RenderedFrame::new(buffer, size, frame.scale, hotspot, frame.duration);
+    smeared.opacity = frame.opacity;
+    smeared
+}
+
+/// Draws premultiplied `pixels` of `size` over `canvas` at `offset` with `opacity`.
+fn draw_over(
+    canvas: &mut [u8],
+    canvas_size: Size<i32, Buffer>,
+    pixels: &[u8],
+    size: Size<i32, Buffer>,
+    offset: (i32, i32),
+    opacity: f32,
+) {
+    for y in 0..size.h {
+        for x in 0..size.w {
+            let (cx, cy) = (x + offset.0, y + offset.1);
+            if cx < 0 || cy < 0 || cx >= canvas_size.w || cy >= canvas_size.h {
+                continue;
+            }
+
+            let src = ((y * size.w + x) * 4) as usize;
+            let dst = ((cy * canvas_size.w + cx) * 4) as usize;
+            let Some(src) = pixels.get(src..src + 4) else {
+                continue;
+            };
+            let alpha = f32::from(src[3]) / 255. * opacity;
+            for (dst, src) in canvas[dst..dst + 4].iter_mut().zip(src) {
+                let value = f32::from(*src) * opacity + f32::from(*dst) * (1. - alpha);
+                *dst = value.round().min(255.) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::vector::config::MotionBlurConfig;
+
+    #[test]
+    fn smears_behind_motion() {
+        let buffer = MemoryRenderBuffer::from_slice(
+            &[255; 4],
+            Fourcc::Argb8888,
+            (1, 1),
+            1,
+            Transform::Normal,
+            None,
+        );
+        let frame = RenderedFrame::new(buffer, Size::from((1, 1)), 1., Point::from((0, 0)), None);
+
+        let smeared = smear(&frame, (1., 0.), 2.);
+        assert_eq!(smeared.size, Size::from((3, 1)));
+        assert_eq!(smeared.hotspot, Point::from((2, 0)));
+
+        let alpha: Vec<u8> = smeared
+            .read_pixels()
+            .chunks_exact(4)
+            .map(|pixel| pixel[3])
+            .collect();
+        assert_eq!(alpha[2], 255);
+        assert!(
+            0 < alpha[0] && alpha[0] < alpha[1] && alpha[1] < 255,
+            "{alpha:?}"
+        );
+    }
+
+    #[test]
+    fn smear_length() {
+        let config = MotionBlurConfig::default();
+        assert_eq!(config.length(1000.), 0.);
+        assert_eq!(config.length(2500.), 8.);
+        assert_eq!(config.length(100_000.), config.max_length);
+
+        let disabled = MotionBlurConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(disabled.length(2500.), 0.);
+    }
+}
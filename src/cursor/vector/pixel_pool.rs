@@ -0,0 +1,163 @@
+//! Reuse of pixel memory between rendered frames.
+//!
+//! Animations that don't fit in the frame cache are rasterized again on every frame. Keeping the
+//! scratch pixels and the buffers of evicted frames around means playing them back doesn't
+//! allocate a new buffer per frame.
+
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Buffer, Rectangle, Size, Transform};
+
+use crate::cursor::vector::scale;
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Number of buffers of each kind kept around.
+const CAPACITY: usize = 4;
+
+/// Pool of scratch pixels and render buffers of one renderer.
+#[derive(Default)]
+pub struct PixelPool {
+    scratch: Vec<Vec<u8>>,
+    buffers: Vec<PooledBuffer>,
+}
+
+struct PooledBuffer {
+    buffer: MemoryRenderBuffer,
+    size: Size<i32, Buffer>,
+    /// Buffer scale, see [`scale::memory_buffer_scale`].
+    scale: i32,
+}
+
+impl PixelPool {
+    /// Returns zeroed scratch pixels of `len` bytes.
+    pub fn scratch(&mut self, len: usize) -> Vec<u8> {
+        // Prefer a vector that doesn't need to grow.
+        let pixels = match self.scratch.iter().position(|v| v.capacity() >= len) {
+            Some(idx) => Some(self.scratch.swap_remove(idx)),
+            None => self.scratch.pop(),
+        };
+
+        let mut pixels = pixels.unwrap_or_default();
+        pixels.clear();
+        pixels.resize(len, 0);
+        pixels
+    }
+
+    /// Returns scratch pixels to the pool.
+    pub fn recycle_scratch(&mut self, pixels: Vec<u8>) {
+        if self.scratch.len() < CAPACITY {
+            self.scratch.push(pixels);
+        }
+    }
+
+    /// Returns the buffer of a frame that was evicted from the frame cache to the pool.
+    pub fn recycle(&mut self, frame: RenderedFrame) {
+        if self.buffers.len() < CAPACITY {
+            self.buffers.push(PooledBuffer {
+                buffer: frame.buffer,
+                size: frame.size,
+                scale: scale::memory_buffer_scale(frame.scale),
+            });
+        }
+    }
+
+    /// Creates a buffer holding `pixels` rasterized at `scale`, writing into a pooled buffer of
+    /// the same size if there is one.
+    pub fn buffer(
+        &mut self,
+        pixels: &[u8],
+        size: Size<i32, Buffer>,
+        scale: f64,
+    ) -> MemoryRenderBuffer {
+        let scale = scale::memory_buffer_scale(scale);
+        let pooled = self
+            .buffers
+            .iter()
+            .position(|pooled| pooled.size == size && pooled.scale == scale);
+
+        let Some(idx) = pooled else {
+            return MemoryRenderBuffer::from_slice(
+                pixels,
+                Fourcc::Argb8888,
+                (size.w, size.h),
+                scale,
+                Transform::Normal,
+                None,
+            );
+        };
+
+        let mut buffer = self.buffers.swap_remove(idx).buffer;
+        let _ = buffer.render().draw(|dst| {
+            dst.copy_from_slice(pixels);
+            Result::<_, ()>::Ok(vec![Rectangle::from_size(size)])
+        });
+        buffer
+    }
+
+    /// Frees all pooled memory.
+    pub fn clear(&mut self) {
+        self.scratch = Vec::new();
+        self.buffers = Vec::new();
+    }
+
+    /// Number of pooled scratch vectors and render buffers.
+    pub fn pooled(&self) -> (usize, usize) {
+        (self.scratch.len(), self.buffers.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(size: i32) -> RenderedFrame {
+        let pool = &mut PixelPool::default();
+        let pixels = vec![0; size as usize * size as usize * 4];
+        let size = Size::from((size, size));
+        RenderedFrame::new(
+            pool.buffer(&pixels, size, 1.),
+            size,
+            1.,
+            (0, 0).into(),
+            None,
+        )
+    }
+
+    #[test]
+    fn scratch_is_zeroed_and_reused() {
+        let mut pool = PixelPool::default();
+        let mut pixels = pool.scratch(16);
+        pixels.fill(0xff);
+        let ptr = pixels.as_ptr();
+        pool.recycle_scratch(pixels);
+
+        let pixels = pool.scratch(8);
+        assert_eq!(pixels.as_ptr(), ptr);
+        assert_eq!(pixels, [0; 8]);
+        assert_eq!(pool.pooled(), (0, 0));
+    }
+
+    #[test]
+    fn buffers_are_reused_by_size() {
+        let mut pool = PixelPool::default();
+        pool.recycle(frame(4));
+        pool.recycle(frame(8));
+
+        pool.buffer(&[0; 2 * 2 * 4], Size::from((2, 2)), 1.);
+        assert_eq!(pool.pooled(), (0, 2));
+        pool.buffer(&[0; 8 * 8 * 4], Size::from((8, 8)), 2.);
+        assert_eq!(pool.pooled(), (0, 2));
+        pool.buffer(&[0; 8 * 8 * 4], Size::from((8, 8)), 1.);
+        assert_eq!(pool.pooled(), (0, 1));
+    }
+
+    #[test]
+    fn capacity_is_bounded() {
+        let mut pool = PixelPool::default();
+        for _ in 0..CAPACITY + 2 {
+            pool.recycle_scratch(Vec::new());
+            pool.recycle(frame(1));
+        }
+        assert_eq!(pool.pooled(), (CAPACITY, CAPACITY));
+    }
+}
@@ -0,0 +1,848 @@
+//! Rendering vector cursors in a separate process.
+//!
+//! `niri cursor-server` loads a theme and renders its frames on request over a Unix socket, so
+//! the SVG and Lottie parsers never run inside the compositor. The compositor uses it when
+//! `NIRI_CURSOR_SERVER` is set to the socket path.
+//!
+//! Requests and replies are JSON lines. Pixels don't go over the socket: the client creates a
+//! file in the runtime directory (tmpfs, so in shared memory) which the server writes every
+//! rendered frame into.
+//!
+//! The compositor never waits on the socket while drawing. A worker thread talks to the server;
+//! answers that don't arrive within [`WAIT`] are picked up by a later request, and in the
+//! meantime a renderer shows its previous frame. A server that fails is retried with
+//! exponential backoff.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{FileExt, FileTypeExt, OpenOptionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+use anyhow::{bail, ensure, Context, Result};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Physical, Point, Size, Transform};
+
+use crate::cursor::vector::bench::load_store;
+use crate::cursor::vector::renderer::VectorRenderer;
+use crate::cursor::vector::scale;
+use crate::cursor::vector::store::VectorCursorStore;
+use crate::cursor::vector::types::{FrameRange, RenderedFrame};
+
+/// Environment variable with the socket of the cursor server the compositor should use.
+pub const SERVER_ENV: &str = "NIRI_CURSOR_SERVER";
+
+/// How long the worker waits for the server to take or answer a request before reconnecting.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long drawing waits for an answer before going on without it.
+const WAIT: Duration = Duration::from_millis(4);
+
+/// Wait before reconnecting after the first failure, doubled with every further one.
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Largest width and height of frames accepted from the server without SVG support, the default
+/// `max_raster_size` of SVG cursors.
+pub const DEFAULT_MAX_FRAME_SIZE: i32 = 1024;
+
+/// Start and end of the names of shared frame files, the only files the server writes to.
+const SHM_PREFIX: &str = "niri-cursor-";
+const SHM_SUFFIX: &str = ".shm";
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Request {
+    /// Sets the file frames are written to.
+    Hello {
+        shm_path: PathBuf,
+    },
+    Load {
+        cursor_id: String,
+    },
+    Render {
+        cursor_id: String,
+        frame: u32,
+        scale: f64,
+    },
+    Segment {
+        cursor_id: String,
+        name: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Reply {
+    Ok,
+    Loaded {
+        total_frames: u32,
+        frame_duration_ms: u32,
+        hotspot: (i32, i32),
+    },
+    /// A frame whose ARGB8888 pixels were written to the start of the shared file.
+    Frame {
+        size: (i32, i32),
+        scale: f64,
+        hotspot: (i32, i32),
+        duration_ms: Option<u64>,
+    },
+    Segment(Option<(u32, u32)>),
+    Error(String),
+}
+
+impl Request {
+    /// Identifies the answers that satisfy the request. Any frame of a cursor at a scale
+    /// satisfies a render request, so that a late frame beats an even older one.
+    fn key(&self) -> String {
+        match self {
+            Request::Render {
+                cursor_id, scale, ..
+            } => format!("render {cursor_id} {scale}"),
+            request => serde_json::to_string(request).unwrap_or_default(),
+        }
+    }
+}
+
+/// Directory of the shared frame files, the runtime directory if there is one.
+fn runtime_dir() -> PathBuf {
+    BaseDirs::new()
+        .as_ref()
+        .and_then(|dirs| dirs.runtime_dir())
+        .map(Path::to_owned)
+        .unwrap_or_else(env::temp_dir)
+}
+
+/// Runs the cursor server until it fails to accept connections.
+///
+/// Clients are served one at a time, there's normally only the compositor.
+pub fn serve(theme_dir: &Path, base_size: u8, socket_path: &Path) -> Result<()> {
+    let store = load_store(theme_dir, base_size)?;
+    let runtime_dir = runtime_dir();
+
+    let listener = bind(socket_path)?;
+    info!("cursor server listening on {}", socket_path.display());
+
+    for stream in listener.incoming() {
+        let stream = stream.context("error accepting cursor server client")?;
+        debug!("cursor server client connected");
+        if let Err(err) = handle_client(&store, &runtime_dir, stream) {
+            warn!("cursor server client error: {err:#}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Listens on `socket_path`, accessible only to the user.
+///
+/// A socket left behind by a previous server is replaced; any other file, or a socket a server
+/// still listens on, is left alone.
+fn bind(socket_path: &Path) -> Result<UnixListener> {
+    match fs::symlink_metadata(socket_path) {
+        Ok(metadata) => {
+            ensure!(
+                metadata.file_type().is_socket(),
+                "{} exists and isn't a socket",
+                socket_path.display()
+            );
+            ensure!(
+                UnixStream::connect(socket_path).is_err(),
+                "a cursor server is already listening on {}",
+                socket_path.display()
+            );
+            fs::remove_file(socket_path)
+                .with_context(|| format!("error removing {}", socket_path.display()))?;
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => {
+            return Err(err).with_context(|| format!("error checking {}", socket_path.display()))
+        }
+    }
+
+    // The socket gets the permissions the umask leaves, so there's no window in which others
+    // could connect.
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(socket_path);
+    unsafe { libc::umask(umask) };
+    listener.with_context(|| format!("error binding {}", socket_path.display()))
+}
+
+fn handle_client(store: &VectorCursorStore, runtime_dir: &Path, stream: UnixStream) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut shm = None;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+
+        let request = serde_json::from_str(&line).context("error parsing request")?;
+        let reply = handle_request(store, runtime_dir, &mut shm, request)
+            .unwrap_or_else(|err| Reply::Error(format!("{err:#}")));
+        write_message(&mut writer, &reply)?;
+    }
+}
+
+fn handle_request(
+    store: &VectorCursorStore,
+    runtime_dir: &Path,
+    shm: &mut Option<File>,
+    request: Request,
+) -> Result<Reply> {
+    match request {
+        Request::Hello { shm_path } => {
+            check_shm_path(&shm_path, runtime_dir)?;
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NOFOLLOW)
+                .open(&shm_path)
+                .with_context(|| format!("error opening {}", shm_path.display()))?;
+            *shm = Some(file);
+            Ok(Reply::Ok)
+        }
+        Request::Load { cursor_id } => {
+            let renderer = store.get_renderer(&cursor_id)?;
+            let hotspot = renderer.hotspot();
+            Ok(Reply::Loaded {
+                total_frames: renderer.total_frames(),
+                frame_duration_ms: renderer.frame_duration_ms(),
+                hotspot: (hotspot.x, hotspot.y),
+            })
+        }
+        Request::Render {
+            cursor_id,
+            frame,
+            scale,
+        } => {
+            let shm = shm.as_ref().context("no Hello before Render")?;
+            let rendered = store.get_renderer(&cursor_id)?.render_frame(frame, scale)?;
+            shm.write_all_at(&rendered.read_pixels(), 0)
+                .context("error writing frame")?;
+
+            Ok(Reply::Frame {
+                size: (rendered.size.w, rendered.size.h),
+                scale: rendered.scale,
+                hotspot: (rendered.hotspot.x, rendered.hotspot.y),
+                duration_ms: rendered.duration.map(|d| d.as_millis() as u64),
+            })
+        }
+        Request::Segment { cursor_id, name } => {
+            let range = store.get_renderer(&cursor_id)?.segment(&name);
+            Ok(Reply::Segment(range.map(|range| (range.start, range.end))))
+        }
+    }
+}
+
+/// Checks that `path` names a shared frame file directly in `runtime_dir`, so that clients
+/// can't make the server write anywhere else.
+fn check_shm_path(path: &Path, runtime_dir: &Path) -> Result<()> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    ensure!(
+        name.starts_with(SHM_PREFIX) && name.ends_with(SHM_SUFFIX),
+        "{} isn't a cursor frame file",
+        path.display()
+    );
+
+    let parent = path
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .with_context(|| format!("error resolving the directory of {}", path.display()))?;
+    let runtime_dir = runtime_dir
+        .canonicalize()
+        .with_context(|| format!("error resolving {}", runtime_dir.display()))?;
+    ensure!(
+        parent == runtime_dir,
+        "{} isn't in {}",
+        path.display(),
+        runtime_dir.display()
+    );
+    Ok(())
+}
+
+fn write_message(stream: &mut UnixStream, message: &impl Serialize) -> Result<()> {
+    let mut buf = serde_json::to_vec(message)?;
+    buf.push(b'\n');
+    stream.write_all(&buf)?;
+    Ok(())
+}
+
+/// Bytes of the ARGB8888 pixels of a `width`×`height` frame, refusing sizes no renderer within
+/// `max_size` produces.
+fn frame_len((width, height): (i32, i32), max_size: i32) -> Result<usize> {
+    ensure!(
+        (0..=max_size).contains(&width) && (0..=max_size).contains(&height),
+        "cursor server sent a {width}×{height} frame, larger than {max_size}×{max_size}"
+    );
+    Ok(width as usize * height as usize * 4)
+}
+
+/// The compositor's side of a cursor server connection.
+///
+/// Requests go through a worker thread, which connects lazily and reconnects after errors, so a
+/// restarted server is picked up again.
+pub struct RemoteConnection {
+    socket_path: PathBuf,
+    jobs: mpsc::Sender<Job>,
+    shared: Arc<Shared>,
+}
+
+/// State of the connection shared with its worker thread.
+#[derive(Default)]
+struct Shared {
+    backoff: parking_lot::Mutex<Backoff>,
+    /// Whether the worker is still on a request.
+    busy: AtomicBool,
+    /// Answer that arrived after its request stopped waiting, by [`Request::key`].
+    late: parking_lot::Mutex<Option<(String, Answer)>>,
+}
+
+struct Job {
+    request: Request,
+    /// Largest width and height of a frame in the answer.
+    max_size: i32,
+    answer: mpsc::Sender<Result<Answer>>,
+}
+
+/// A reply, with the pixels of a `Frame` reply copied out of the shared file.
+#[derive(Debug)]
+struct Answer {
+    reply: Reply,
+    pixels: Vec<u8>,
+}
+
+/// Exponential backoff of reconnection attempts.
+#[derive(Debug, Default)]
+struct Backoff {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Backoff {
+    /// Fails while the server shouldn't be tried again yet.
+    fn check(&self, now: Instant) -> Result<()> {
+        match self.retry_at {
+            Some(retry_at) if now < retry_at => {
+                bail!(
+                    "cursor server unavailable, retrying in {:?}",
+                    retry_at - now
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn fail(&mut self, now: Instant) {
+        let delay = MIN_BACKOFF
+            .saturating_mul(1 << self.failures.min(16))
+            .min(MAX_BACKOFF);
+        self.failures = self.failures.saturating_add(1);
+        self.retry_at = Some(now + delay);
+    }
+
+    fn succeed(&mut self) {
+        *self = Self::default();
+    }
+}
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    shm: File,
+    shm_path: PathBuf,
+}
+
+impl RemoteConnection {
+    pub fn new(socket_path: PathBuf) -> Self {
+        let (jobs, rx) = mpsc::channel();
+        let shared = Arc::new(Shared::default());
+
+        let path = socket_path.clone();
+        let worker_shared = shared.clone();
+        thread::Builder::new()
+            .name(format!("Cursor Server Client for {socket_path:?}"))
+            .spawn(move || work(&path, rx, &worker_shared))
+            .unwrap();
+
+        Self {
+            socket_path,
+            jobs,
+            shared,
+        }
+    }
+
+    /// Connection to the server set in the environment, if any.
+    pub fn from_env() -> Option<Arc<Self>> {
+        let socket_path = env::var_os(SERVER_ENV)?;
+        Some(Arc::new(Self::new(socket_path.into())))
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Sends `request` and returns the answer if it arrives within [`WAIT`].
+    ///
+    /// Otherwise the answer is kept for the next request with the same [`Request::key`], and
+    /// requests fail right away while the worker is still on one, or while the server is backed
+    /// off.
+    fn call(&self, request: Request, max_size: i32) -> Result<Answer> {
+        let key = request.key();
+        {
+            let mut late = self.shared.late.lock();
+            if late.as_ref().is_some_and(|(late_key, _)| *late_key == key) {
+                let (_, answer) = late.take().unwrap();
+                return Self::check(answer);
+            }
+        }
+
+        ensure!(
+            !self.shared.busy.load(Ordering::Relaxed),
+            "cursor server is still on the previous request"
+        );
+        self.shared.backoff.lock().check(Instant::now())?;
+
+        let (answer, rx) = mpsc::channel();
+        self.shared.busy.store(true, Ordering::Relaxed);
+        let job = Job {
+            request,
+            max_size,
+            answer,
+        };
+        if self.jobs.send(job).is_err() {
+            self.shared.busy.store(false, Ordering::Relaxed);
+            bail!("cursor server worker exited");
+        }
+
+        match rx.recv_timeout(WAIT) {
+            Ok(answer) => Self::check(answer?),
+            Err(_) => bail!("cursor server didn't answer in time"),
+        }
+    }
+
+    fn check(answer: Answer) -> Result<Answer> {
+        match answer.reply {
+            Reply::Error(err) => bail!("cursor server: {err}"),
+            _ => Ok(answer),
+        }
+    }
+}
+
+/// Runs the jobs of a connection until the connection is dropped.
+fn work(socket_path: &Path, jobs: mpsc::Receiver<Job>, shared: &Shared) {
+    let mut connection = None;
+    for job in jobs {
+        let answer = run_job(socket_path, &mut connection, &job);
+        match &answer {
+            Ok(_) => shared.backoff.lock().succeed(),
+            Err(err) => {
+                warn!("error talking to cursor server: {err:#}");
+                shared.backoff.lock().fail(Instant::now());
+            }
+        }
+
+        if let Err(mpsc::SendError(Ok(answer))) = job.answer.send(answer) {
+            *shared.late.lock() = Some((job.request.key(), answer));
+        }
+        shared.busy.store(false, Ordering::Relaxed);
+    }
+    debug!("exiting cursor server client for {socket_path:?}");
+}
+
+fn run_job(socket_path: &Path, connection: &mut Option<Connection>, job: &Job) -> Result<Answer> {
+    let conn = match connection {
+        Some(conn) => conn,
+        None => connection.insert(Connection::open(socket_path)?),
+    };
+
+    let answer = conn.call(&job.request).and_then(|reply| {
+        let pixels = match &reply {
+            Reply::Frame { size, .. } => conn.read_frame(*size, job.max_size)?,
+            _ => Vec::new(),
+        };
+        Ok(Answer { reply, pixels })
+    });
+    if answer.is_err() {
+        // The stream may be out of sync now, start over with the next request.
+        *connection = None;
+    }
+    answer
+}
+
+impl Connection {
+    fn open(socket_path: &Path) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let stream = UnixStream::connect(socket_path).with_context(|| {
+            format!(
+                "error connecting to cursor server {}",
+                socket_path.display()
+            )
+        })?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let shm_path = runtime_dir().join(format!(
+            "{SHM_PREFIX}{}-{}{SHM_SUFFIX}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let shm = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&shm_path)
+            .with_context(|| format!("error creating {}", shm_path.display()))?;
+
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            shm,
+            shm_path,
+        };
+        let shm_path = connection.shm_path.clone();
+        match connection.call(&Request::Hello { shm_path })? {
+            Reply::Ok => Ok(connection),
+            Reply::Error(err) => bail!("cursor server: {err}"),
+            reply => bail!("unexpected cursor server reply: {reply:?}"),
+        }
+    }
+
+    fn call(&mut self, request: &Request) -> Result<Reply> {
+        write_message(&mut self.writer, request)?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            bail!("cursor server closed the connection");
+        }
+        serde_json::from_str(&line).context("error parsing cursor server reply")
+    }
+
+    fn read_frame(&self, size: (i32, i32), max_size: i32) -> Result<Vec<u8>> {
+        let mut pixels = vec![0; frame_len(size, max_size)?];
+        self.shm
+            .read_exact_at(&mut pixels, 0)
+            .context("error reading frame from cursor server")?;
+        Ok(pixels)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.shm_path);
+    }
+}
+
+/// A cursor rendered by the cursor server.
+pub struct RemoteRenderer {
+    connection: Arc<RemoteConnection>,
+    cursor_id: String,
+    total_frames: u32,
+    frame_duration_ms: u32,
+    hotspot: Point<i32, Physical>,
+    /// Largest width and height of a frame, the `max_raster_size` of SVG cursors.
+    max_size: i32,
+    /// Frame shown while the server doesn't deliver a new one.
+    fallback: parking_lot::Mutex<Option<RenderedFrame>>,
+    /// Segments the server looked up.
+    segments: parking_lot::Mutex<Vec<(String, Option<FrameRange>)>>,
+}
+
+impl RemoteRenderer {
+    /// Makes the server load the cursor.
+    ///
+    /// Fails while the server hasn't loaded it yet; loading again later picks up the answer.
+    pub fn load(connection: Arc<RemoteConnection>, cursor_id: &str, max_size: i32) -> Result<Self> {
+        let request = Request::Load {
+            cursor_id: cursor_id.to_owned(),
+        };
+        let Reply::Loaded {
+            total_frames,
+            frame_duration_ms,
+            hotspot,
+        } = connection.call(request, max_size)?.reply
+        else {
+            bail!("unexpected cursor server reply");
+        };
+
+        Ok(Self {
+            connection,
+            cursor_id: cursor_id.to_owned(),
+            total_frames,
+            frame_duration_ms,
+            hotspot: hotspot.into(),
+            max_size,
+            fallback: parking_lot::Mutex::new(None),
+            segments: parking_lot::Mutex::new(Vec::new()),
+        })
+    }
+
+    fn fetch_frame(&self, frame: u32, scale: f64) -> Result<RenderedFrame> {
+        let request = Request::Render {
+            cursor_id: self.cursor_id.clone(),
+            frame,
+            scale,
+        };
+        let answer = self.connection.call(request, self.max_size)?;
+        let Reply::Frame {
+            size: (width, height),
+            scale,
+            hotspot,
+            duration_ms,
+        } = answer.reply
+        else {
+            bail!("unexpected cursor server reply: {:?}", answer.reply);
+        };
+        ensure!(
+            answer.pixels.len() == frame_len((width, height), self.max_size)?,
+            "cursor server sent a frame of the wrong size"
+        );
+
+        let buffer = MemoryRenderBuffer::from_slice(
+            &answer.pixels,
+            Fourcc::Argb8888,
+            (width, height),
+            scale::memory_buffer_scale(scale),
+            Transform::Normal,
+            None,
+        );
+        Ok(RenderedFrame::new(
+            buffer,
+            Size::from((width, height)),
+            scale,
+            hotspot.into(),
+            duration_ms.map(Duration::from_millis),
+        ))
+    }
+}
+
+impl VectorRenderer for RemoteRenderer {
+    /// Shows the previous frame instead while the server doesn't deliver this one.
+    fn render_frame(&self, frame: u32, scale: f64) -> Result<RenderedFrame> {
+        match self.fetch_frame(frame, scale) {
+            Ok(rendered) => {
+                *self.fallback.lock() = Some(rendered.clone());
+                Ok(rendered)
+            }
+            Err(err) => {
+                let fallback = self.fallback.lock().clone();
+                fallback.ok_or(err)
+            }
+        }
+    }
+
+    fn hotspot(&self) -> Point<i32, Physical> {
+        self.hotspot
+    }
+
+    fn total_frames(&self) -> u32 {
+        self.total_frames
+    }
+
+    fn frame_duration_ms(&self) -> u32 {
+        self.frame_duration_ms
+    }
+
+    fn segment(&self, name: &str) -> Option<FrameRange> {
+        if let Some((_, range)) = self.segments.lock().iter().find(|(known, _)| known == name) {
+            return *range;
+        }
+
+        let request = Request::Segment {
+            cursor_id: self.cursor_id.clone(),
+            name: name.to_owned(),
+        };
+        let answer = self.connection.call(request, self.max_size);
+        match answer.map(|answer| answer.reply) {
+            Ok(Reply::Segment(range)) => {
+                let range = range.map(|(start, end)| FrameRange::new(start, end));
+                self.segments.lock().push((name.to_owned(), range));
+                range
+            }
+            Ok(reply) => {
+                warn!("unexpected cursor server reply: {reply:?}");
+                None
+            }
+            Err(err) => {
+                debug!("error getting cursor segment '{name}': {err:#}");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use xshell::{Shell, TempDir};
+
+    use super::*;
+
+    fn temp_dir() -> TempDir {
+        Shell::new().unwrap().create_temp_dir().unwrap()
+    }
+
+    #[test]
+    fn messages_are_single_lines() {
+        let request = Request::Render {
+            cursor_id: String::from("wait"),
+            frame: 3,
+            scale: 1.5,
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(!json.contains('\n'));
+        assert!(matches!(
+            serde_json::from_str(&json).unwrap(),
+            Request::Render { frame: 3, .. }
+        ));
+
+        let reply: Reply = serde_json::from_str(r#"{"Segment":[10,20]}"#).unwrap();
+        assert!(matches!(reply, Reply::Segment(Some((10, 20)))));
+    }
+
+    #[test]
+    fn oversized_frames_are_refused() {
+        assert_eq!(frame_len((24, 32), 64).unwrap(), 24 * 32 * 4);
+        assert!(frame_len((65, 1), 64).is_err());
+        assert!(frame_len((1, -1), 64).is_err());
+        assert!(frame_len((i32::MAX, i32::MAX), 64).is_err());
+    }
+
+    #[test]
+    fn shm_files_stay_in_the_runtime_dir() {
+        let dir = temp_dir();
+        let other = temp_dir();
+        let runtime_dir = dir.path();
+
+        assert!(check_shm_path(&runtime_dir.join("niri-cursor-1-0.shm"), runtime_dir).is_ok());
+        assert!(check_shm_path(&runtime_dir.join("wayland-1"), runtime_dir).is_err());
+        assert!(check_shm_path(&other.path().join("niri-cursor-1-0.shm"), runtime_dir).is_err());
+        let escaping = runtime_dir.join("..").join("niri-cursor-1-0.shm");
+        assert!(check_shm_path(&escaping, runtime_dir).is_err());
+    }
+
+    #[test]
+    fn binding_replaces_only_stale_sockets() {
+        let dir = temp_dir();
+
+        let file = dir.path().join("file");
+        fs::write(&file, "keep").unwrap();
+        assert!(bind(&file).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "keep");
+
+        let socket = dir.path().join("socket");
+        let listener = bind(&socket).unwrap();
+        let mode = fs::metadata(&socket).unwrap().permissions();
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&mode) & 0o777,
+            0o600
+        );
+        // Another server can't take over a live socket.
+        assert!(bind(&socket).is_err());
+
+        drop(listener);
+        assert!(bind(&socket).is_ok());
+    }
+
+    #[test]
+    fn failures_back_off_exponentially() {
+        let now = Instant::now();
+        let mut backoff = Backoff::default();
+        assert!(backoff.check(now).is_ok());
+
+        backoff.fail(now);
+        assert!(backoff.check(now + MIN_BACKOFF / 2).is_err());
+        assert!(backoff.check(now + MIN_BACKOFF).is_ok());
+
+        backoff.fail(now);
+        assert!(backoff.check(now + MIN_BACKOFF).is_err());
+        assert!(backoff.check(now + MIN_BACKOFF * 2).is_ok());
+
+        for _ in 0..40 {
+            backoff.fail(now);
+        }
+        assert!(backoff.check(now + MAX_BACKOFF).is_ok());
+
+        backoff.succeed();
+        assert!(backoff.check(now).is_ok());
+    }
+
+    /// A renderer of the server at `socket_path` that has loaded a 1×1 cursor.
+    fn renderer(socket_path: &Path) -> RemoteRenderer {
+        RemoteRenderer {
+            connection: Arc::new(RemoteConnection::new(socket_path.to_owned())),
+            cursor_id: String::from("default"),
+            total_frames: 1,
+            frame_duration_ms: 0,
+            hotspot: Point::from((0, 0)),
+            max_size: 64,
+            fallback: parking_lot::Mutex::new(None),
+            segments: parking_lot::Mutex::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn missing_server_fails_without_waiting() {
+        let dir = temp_dir();
+        let renderer = renderer(&dir.path().join("missing"));
+
+        let start = Instant::now();
+        assert!(renderer.render_frame(0, 1.).is_err());
+        assert!(renderer.segment("spin").is_none());
+        assert!(start.elapsed() < TIMEOUT);
+    }
+
+    #[test]
+    fn stuck_server_shows_the_previous_frame() {
+        let dir = temp_dir();
+        let socket = dir.path().join("socket");
+        // Accepts connections and reads requests, but never answers them.
+        let listener = UnixListener::bind(&socket).unwrap();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                thread::spawn(move || {
+                    let mut buf = [0; 1024];
+                    while stream.read(&mut buf).is_ok_and(|read| read > 0) {}
+                });
+            }
+        });
+
+        let renderer = renderer(&socket);
+        let previous = RenderedFrame::new(
+            MemoryRenderBuffer::from_slice(
+                &[0; 4],
+                Fourcc::Argb8888,
+                (1, 1),
+                1,
+                Transform::Normal,
+                None,
+            ),
+            Size::from((1, 1)),
+            1.,
+            Point::from((0, 0)),
+            None,
+        );
+        *renderer.fallback.lock() = Some(previous);
+
+        for _ in 0..3 {
+            let start = Instant::now();
+            let frame = renderer.render_frame(0, 1.).unwrap();
+            assert_eq!(frame.size, Size::from((1, 1)));
+            assert!(start.elapsed() < TIMEOUT);
+        }
+    }
+}
@@ -0,0 +1,1034 @@
+//! Sampling of CSS keyframe animations in SVG documents.
+//!
+//! Rules in `<style>` elements and `style` attributes that run an `animation` on an element are
+//! resolved against the document's `@keyframes`, and the animated `transform` and `opacity` are
+//! written to the element's attributes at the frame's time. Selectors may combine type, class and
+//! id selectors with descendant combinators; specificity is ignored, so later rules win.
+//!
+//! `transform-origin` is honored relative to the view box, or with `transform-box: fill-box` to
+//! the bounds of basic shapes. Transform lists interpolate when their functions line up and
+//! switch halfway otherwise.
+
+use std::f64::consts::PI;
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use roxmltree::{Document, Node};
+
+use super::rewrite::{self, Rewrite};
+
+/// The CSS animations of an SVG document.
+pub struct Animation {
+    running: Vec<Running>,
+    period: f64,
+}
+
+/// One animation applied to one element.
+struct Running {
+    /// Byte range of the element's start tag.
+    target: Range<usize>,
+    keyframes: Vec<Keyframe>,
+    timing: Timing,
+    delay: f64,
+    duration: f64,
+    iterations: f64,
+    direction: Direction,
+    fill: Fill,
+    /// Point transforms are applied around.
+    origin: (f64, f64),
+    /// Values of the element when keyframes don't specify a property at 0% or 100%.
+    underlying_transform: Vec<Function>,
+    underlying_opacity: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Keyframe {
+    offset: f64,
+    transform: Option<Vec<Function>>,
+    opacity: Option<f64>,
+    timing: Option<Timing>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Timing {
+    Linear,
+    CubicBezier(f64, f64, f64, f64),
+    Steps { steps: u32, start: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Direction {
+    #[default]
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Fill {
+    #[default]
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+/// A transform function in SVG terms, with angles in degrees and lengths in user units.
+#[derive(Debug, Clone, PartialEq)]
+struct Function {
+    name: &'static str,
+    args: Vec<f64>,
+}
+
+/// Animation properties declared for an element, after the cascade.
+#[derive(Debug, Default, Clone)]
+struct Declared {
+    names: Vec<String>,
+    durations: Vec<f64>,
+    delays: Vec<f64>,
+    iterations: Vec<f64>,
+    directions: Vec<Direction>,
+    timings: Vec<Timing>,
+    fills: Vec<Fill>,
+    origin: Option<String>,
+    fill_box: bool,
+}
+
+impl Animation {
+    /// Finds the CSS animations of a document, `None` if it has none.
+    pub fn parse(document: &Document) -> Option<Self> {
+        let mut css = String::new();
+        for node in document.descendants() {
+            if node.has_tag_name("style") {
+                for text in node
+                    .descendants()
+                    .filter(Node::is_text)
+                    .filter_map(|n| n.text())
+                {
+                    css.push_str(text);
+                }
+                css.push('\n');
+            }
+        }
+        let css = strip_comments(&css);
+
+        let mut rules = Vec::new();
+        let mut keyframes = Vec::new();
+        for (prelude, body) in blocks(&css) {
+            if let Some(at_rule) = prelude.strip_prefix('@') {
+                let (keyword, name) = at_rule
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((at_rule, ""));
+                if keyword.trim_start_matches("-webkit-") == "keyframes" {
+                    let name = name.trim().trim_matches(['"', '\'']).to_owned();
+                    keyframes.push((name, parse_keyframes(body)));
+                }
+                continue;
+            }
+            rules.push((prelude, declarations(body)));
+        }
+        if keyframes.is_empty() {
+            return None;
+        }
+
+        let view_box = view_box(document.root_element());
+        let mut running = Vec::new();
+        for node in document.descendants().filter(Node::is_element) {
+            let mut declared = Declared::default();
+            for (selectors, declarations) in &rules {
+                if selectors
+                    .split(',')
+                    .any(|selector| matches(selector.trim(), node))
+                {
+                    declared.apply(declarations);
+                }
+            }
+            if let Some(style) = node.attribute("style") {
+                declared.apply(&declarations(style));
+            }
+            if declared.names.is_empty() {
+                continue;
+            }
+
+            let bounds = if declared.fill_box {
+                shape_bounds(node).unwrap_or_else(|| {
+                    debug!(
+                        "can't compute the fill box of <{}>, using the view box",
+                        node.tag_name().name()
+                    );
+                    view_box
+                })
+            } else {
+                view_box
+            };
+            let origin = declared
+                .origin
+                .as_deref()
+                .map_or((0., 0.), |origin| transform_origin(origin, bounds));
+
+            let target = rewrite::start_tag(document.input_text(), node.range().start);
+            let underlying_transform = node
+                .attribute("transform")
+                .and_then(parse_transform)
+                .unwrap_or_default();
+            let underlying_opacity = node
+                .attribute("opacity")
+                .and_then(|opacity| opacity.trim().parse().ok())
+                .unwrap_or(1.);
+
+            for (i, name) in declared.names.iter().enumerate() {
+                let Some((_, frames)) = keyframes.iter().rev().find(|(n, _)| n == name) else {
+                    if name != "none" {
+                        debug!("no @keyframes named {name}");
+                    }
+                    continue;
+                };
+                let duration = cycle(&declared.durations, i).unwrap_or(0.);
+                if duration <= 0. || frames.is_empty() {
+                    continue;
+                }
+
+                running.push(Running {
+                    target: target.clone(),
+                    keyframes: frames.clone(),
+                    timing: cycle(&declared.timings, i).unwrap_or(EASE),
+                    delay: cycle(&declared.delays, i).unwrap_or(0.),
+                    duration,
+                    iterations: cycle(&declared.iterations, i).unwrap_or(1.),
+                    direction: cycle(&declared.directions, i).unwrap_or_default(),
+                    fill: cycle(&declared.fills, i).unwrap_or_default(),
+                    origin,
+                    underlying_transform: underlying_transform.clone(),
+                    underlying_opacity,
+                });
+            }
+        }
+
+        if running.is_empty() {
+            return None;
+        }
+
+        let period = running
+            .iter()
+            .map(|running| {
+                let active = if running.iterations.is_finite() {
+                    running.duration * running.iterations
+                } else if matches!(
+                    running.direction,
+                    Direction::Alternate | Direction::AlternateReverse
+                ) {
+                    // There and back again.
+                    running.duration * 2.
+                } else {
+                    running.duration
+                };
+                running.delay + active
+            })
+            .fold(0., f64::max);
+
+        Some(Self { running, period })
+    }
+
+    /// Length of one pass through all animations in seconds, which the cursor loops.
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    /// Applies the animations at `time` seconds to a frame.
+    pub fn apply(&self, time: f64, rewrite: &mut Rewrite) {
+        for running in &self.running {
+            let Some(progress) = running.progress(time) else {
+                continue;
+            };
+            if let Some(transform) = running.transform(progress) {
+                rewrite.set(&running.target, "transform", transform);
+            }
+            if let Some(opacity) = running.opacity(progress) {
+                rewrite.set(&running.target, "opacity", opacity.to_string());
+            }
+        }
+    }
+}
+
+const EASE: Timing = Timing::CubicBezier(0.25, 0.1, 0.25, 1.);
+
+fn cycle<T: Copy>(list: &[T], i: usize) -> Option<T> {
+    (!list.is_empty()).then(|| list[i % list.len()])
+}
+
+impl Running {
+    /// Directed progress through the current iteration, `None` while the animation doesn't apply.
+    fn progress(&self, time: f64) -> Option<f64> {
+        let local = time - self.delay;
+        let active = self.duration * self.iterations;
+
+        let (iteration, progress) = if local < 0. {
+            if !matches!(self.fill, Fill::Backwards | Fill::Both) {
+                return None;
+            }
+            (0., 0.)
+        } else if local >= active {
+            if !matches!(self.fill, Fill::Forwards | Fill::Both) {
+                return None;
+            }
+            let rest = self.iterations.fract();
+            if rest == 0. {
+                (self.iterations - 1., 1.)
+            } else {
+                (self.iterations.floor(), rest)
+            }
+        } else {
+            let iterations = local / self.duration;
+            (iterations.floor(), iterations.fract())
+        };
+
+        let odd = iteration % 2. == 1.;
+        let reversed = match self.direction {
+            Direction::Normal => false,
+            Direction::Reverse => true,
+            Direction::Alternate => odd,
+            Direction::AlternateReverse => !odd,
+        };
+        Some(if reversed { 1. - progress } else { progress })
+    }
+
+    /// The keyframes around `progress` that specify a property, with the eased position between.
+    fn interval<T: Clone>(
+        &self,
+        progress: f64,
+        value: impl Fn(&Keyframe) -> Option<T>,
+        underlying: T,
+    ) -> Option<(T, T, f64)> {
+        let mut frames: Vec<(f64, T, Timing)> = self
+            .keyframes
+            .iter()
+            .filter_map(|keyframe| {
+                let timing = keyframe.timing.unwrap_or(self.timing);
+                value(keyframe).map(|value| (keyframe.offset, value, timing))
+            })
+            .collect();
+        if frames.is_empty() {
+            return None;
+        }
+        // Missing 0% and 100% keyframes use the element's own value.
+        if frames[0].0 > 0. {
+            frames.insert(0, (0., underlying.clone(), self.timing));
+        }
+        if frames[frames.len() - 1].0 < 1. {
+            frames.push((1., underlying, self.timing));
+        }
+
+        let index = frames
+            .iter()
+            .rposition(|(offset, _, _)| *offset <= progress)
+            .unwrap_or(0)
+            .min(frames.len() - 2);
+        let (from_offset, from, timing) = &frames[index];
+        let (to_offset, to, _) = &frames[index + 1];
+        let span = to_offset - from_offset;
+        let t = if span > 0. {
+            ((progress - from_offset) / span).clamp(0., 1.)
+        } else {
+            1.
+        };
+        Some((from.clone(), to.clone(), timing.ease(t)))
+    }
+
+    fn transform(&self, progress: f64) -> Option<String> {
+        let (from, to, t) = self.interval(
+            progress,
+            |keyframe| keyframe.transform.clone(),
+            self.underlying_transform.clone(),
+        )?;
+        let functions = interpolate_transform(&from, &to, t);
+
+        let mut value = String::new();
+        let (x, y) = self.origin;
+        let around = (x, y) != (0., 0.) && !functions.is_empty();
+        if around {
+            let _ = write!(value, "translate({x} {y}) ");
+        }
+        for function in &functions {
+            let args: Vec<String> = function.args.iter().map(f64::to_string).collect();
+            let _ = write!(value, "{}({}) ", function.name, args.join(" "));
+        }
+        if around {
+            let _ = write!(value, "translate({} {})", -x, -y);
+        }
+        Some(value.trim_end().to_owned())
+    }
+
+    fn opacity(&self, progress: f64) -> Option<f64> {
+        let (from, to, t) = self.interval(
+            progress,
+            |keyframe| keyframe.opacity,
+            self.underlying_opacity,
+        )?;
+        Some((from + (to - from) * t).clamp(0., 1.))
+    }
+}
+
+impl Timing {
+    fn parse(value: &str) -> Option<Self> {
+        let timing = match value {
+            "linear" => Self::Linear,
+            "ease" => EASE,
+            "ease-in" => Self::CubicBezier(0.42, 0., 1., 1.),
+            "ease-out" => Self::CubicBezier(0., 0., 0.58, 1.),
+            "ease-in-out" => Self::CubicBezier(0.42, 0., 0.58, 1.),
+            "step-start" => Self::Steps {
+                steps: 1,
+                start: true,
+            },
+            "step-end" => Self::Steps {
+                steps: 1,
+                start: false,
+            },
+            _ => {
+                let (name, args) = value.strip_suffix(')')?.split_once('(')?;
+                let args: Vec<&str> = args.split(',').map(str::trim).collect();
+                match (name.trim(), args.as_slice()) {
+                    ("cubic-bezier", [x1, y1, x2, y2]) => Self::CubicBezier(
+                        x1.parse().ok()?,
+                        y1.parse().ok()?,
+                        x2.parse().ok()?,
+                        y2.parse().ok()?,
+                    ),
+                    ("steps", [steps, rest @ ..]) => Self::Steps {
+                        steps: steps.parse::<u32>().ok()?.max(1),
+                        start: matches!(rest.first(), Some(&"start" | &"jump-start")),
+                    },
+                    _ => return None,
+                }
+            }
+        };
+        Some(timing)
+    }
+
+    fn ease(self, t: f64) -> f64 {
+        match self {
+            Self::Linear => t,
+            Self::Steps { steps, start } => {
+                let steps = f64::from(steps);
+                let step = if start {
+                    (t * steps).ceil()
+                } else {
+                    (t * steps).floor()
+                };
+                (step / steps).clamp(0., 1.)
+            }
+            Self::CubicBezier(x1, y1, x2, y2) => {
+                let bezier = |a: f64, b: f64, s: f64| {
+                    3. * a * s * (1. - s).powi(2) + 3. * b * s.powi(2) * (1. - s) + s.powi(3)
+                };
+                // Bisect for the curve parameter at `t`, the curve is monotonic in x.
+                let (mut low, mut high) = (0., 1.);
+                let mut s = t;
+                for _ in 0..32 {
+                    let x = bezier(x1, x2, s);
+                    if (x - t).abs() < 1e-6 {
+                        break;
+                    }
+                    if x < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+                    s = (low + high) / 2.;
+                }
+                bezier(y1, y2, s)
+            }
+        }
+    }
+}
+
+impl Declared {
+    fn apply(&mut self, declarations: &[(String, String)]) {
+        for (name, value) in declarations {
+            let name = name.trim_start_matches("-webkit-");
+            let list = || split_top_level(value, ',');
+            match name {
+                "animation" => self.apply_shorthand(value),
+                "animation-name" => self.names = list().map(str::to_owned).collect(),
+                "animation-duration" => self.durations = list().filter_map(time).collect(),
+                "animation-delay" => self.delays = list().filter_map(time).collect(),
+                "animation-iteration-count" => {
+                    self.iterations = list().filter_map(iteration_count).collect()
+                }
+                "animation-direction" => self.directions = list().filter_map(direction).collect(),
+                "animation-timing-function" => {
+                    self.timings = list().filter_map(Timing::parse).collect()
+                }
+                "animation-fill-mode" => self.fills = list().filter_map(fill).collect(),
+                "transform-origin" => self.origin = Some(value.clone()),
+                "transform-box" => self.fill_box = value == "fill-box",
+                _ => {}
+            }
+        }
+    }
+
+    /// Applies an `animation` shorthand, which resets every animation longhand.
+    fn apply_shorthand(&mut self, value: &str) {
+        *self = Self {
+            origin: self.origin.take(),
+            fill_box: self.fill_box,
+            ..Self::default()
+        };
+
+        for animation in split_top_level(value, ',') {
+            let mut name = String::from("none");
+            let mut times = Vec::new();
+            let (mut timing, mut iterations) = (EASE, 1.);
+            let (mut animation_direction, mut fill_mode) = (Direction::Normal, Fill::None);
+
+            for token in split_top_level(animation, ' ') {
+                if let Some(value) = time(token) {
+                    times.push(value);
+                } else if let Some(value) = Timing::parse(token) {
+                    timing = value;
+                } else if let Some(value) = iteration_count(token) {
+                    iterations = value;
+                } else if let Some(value) = direction(token) {
+                    animation_direction = value;
+                } else if let Some(value) = fill(token) {
+                    fill_mode = value;
+                } else if !matches!(token, "running" | "paused") {
+                    name = token.to_owned();
+                }
+            }
+
+            self.names.push(name);
+            self.durations.push(times.first().copied().unwrap_or(0.));
+            self.delays.push(times.get(1).copied().unwrap_or(0.));
+            self.timings.push(timing);
+            self.iterations.push(iterations);
+            self.directions.push(animation_direction);
+            self.fills.push(fill_mode);
+        }
+    }
+}
+
+fn time(value: &str) -> Option<f64> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse::<f64>().ok().map(|ms| ms / 1000.)
+    } else {
+        value.strip_suffix('s')?.parse().ok()
+    }
+}
+
+fn iteration_count(value: &str) -> Option<f64> {
+    match value {
+        "infinite" => Some(f64::INFINITY),
+        _ => value.parse().ok().filter(|count: &f64| *count >= 0.),
+    }
+}
+
+fn direction(value: &str) -> Option<Direction> {
+    Some(match value {
+        "normal" => Direction::Normal,
+        "reverse" => Direction::Reverse,
+        "alternate" => Direction::Alternate,
+        "alternate-reverse" => Direction::AlternateReverse,
+        _ => return None,
+    })
+}
+
+fn fill(value: &str) -> Option<Fill> {
+    Some(match value {
+        "none" => Fill::None,
+        "forwards" => Fill::Forwards,
+        "backwards" => Fill::Backwards,
+        "both" => Fill::Both,
+        _ => return None,
+    })
+}
+
+fn parse_keyframes(body: &str) -> Vec<Keyframe> {
+    let mut keyframes: Vec<Keyframe> = Vec::new();
+    for (selectors, body) in blocks(body) {
+        let declarations = declarations(body);
+        for selector in selectors.split(',') {
+            let offset = match selector.trim() {
+                "from" => 0.,
+                "to" => 1.,
+                percent => match percent
+                    .strip_suffix('%')
+                    .and_then(|p| p.parse::<f64>().ok())
+                {
+                    Some(percent) => (percent / 100.).clamp(0., 1.),
+                    None => continue,
+                },
+            };
+
+            // Keyframes at the same offset merge, later ones win.
+            let index = match keyframes.iter().position(|k| k.offset == offset) {
+                Some(index) => index,
+                None => {
+                    keyframes.push(Keyframe {
+                        offset,
+                        ..Keyframe::default()
+                    });
+                    keyframes.len() - 1
+                }
+            };
+            let keyframe = &mut keyframes[index];
+            for (name, value) in &declarations {
+                match name.as_str() {
+                    "transform" => match parse_transform(value) {
+                        Some(transform) => keyframe.transform = Some(transform),
+                        None => debug!("unsupported CSS transform in keyframes: {value}"),
+                    },
+                    "opacity" => keyframe.opacity = value.parse().ok(),
+                    "animation-timing-function" => keyframe.timing = Timing::parse(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+    keyframes.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    keyframes
+}
+
+/// Parses a CSS or SVG transform list, `None` if it uses unsupported functions or units.
+fn parse_transform(value: &str) -> Option<Vec<Function>> {
+    let value = value.trim();
+    if value == "none" {
+        return Some(Vec::new());
+    }
+
+    let mut functions = Vec::new();
+    let mut rest = value;
+    while !rest.trim().is_empty() {
+        let (name, after) = rest.split_once('(')?;
+        let (args, after) = after.split_once(')')?;
+        rest = after.trim_start_matches([' ', ',', '\n', '\t']);
+
+        let name = name.trim().trim_start_matches(',').trim();
+        let angle = name.starts_with("rotate") || name.starts_with("skew");
+        let args: Vec<f64> = args
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| if angle { angle_value(arg) } else { length(arg) })
+            .collect::<Option<_>>()?;
+
+        let function = |name, args| Function { name, args };
+        match (name, args.as_slice()) {
+            ("translate", [x]) => functions.push(function("translate", vec![*x, 0.])),
+            ("translate", [x, y]) => functions.push(function("translate", vec![*x, *y])),
+            ("translateX", [x]) => functions.push(function("translate", vec![*x, 0.])),
+            ("translateY", [y]) => functions.push(function("translate", vec![0., *y])),
+            ("scale", [s]) => functions.push(function("scale", vec![*s, *s])),
+            ("scale", [x, y]) => functions.push(function("scale", vec![*x, *y])),
+            ("scaleX", [x]) => functions.push(function("scale", vec![*x, 1.])),
+            ("scaleY", [y]) => functions.push(function("scale", vec![1., *y])),
+            ("rotate" | "rotateZ", [_] | [_, _, _]) => {
+                functions.push(function("rotate", args.clone()))
+            }
+            ("skewX", [_]) => functions.push(function("skewX", args.clone())),
+            ("skewY", [_]) => functions.push(function("skewY", args.clone())),
+            ("skew", [x]) => functions.push(function("skewX", vec![*x])),
+            ("skew", [x, y]) => {
+                functions.push(function("skewX", vec![*x]));
+                functions.push(function("skewY", vec![*y]));
+            }
+            ("matrix", [_, _, _, _, _, _]) => functions.push(function("matrix", args.clone())),
+            _ => return None,
+        }
+    }
+    Some(functions)
+}
+
+fn angle_value(value: &str) -> Option<f64> {
+    let (number, factor) = if let Some(deg) = value.strip_suffix("deg") {
+        (deg, 1.)
+    } else if let Some(grad) = value.strip_suffix("grad") {
+        (grad, 0.9)
+    } else if let Some(rad) = value.strip_suffix("rad") {
+        (rad, 180. / PI)
+    } else if let Some(turn) = value.strip_suffix("turn") {
+        (turn, 360.)
+    } else {
+        (value, 1.)
+    };
+    number.parse::<f64>().ok().map(|n| n * factor)
+}
+
+fn length(value: &str) -> Option<f64> {
+    value.strip_suffix("px").unwrap_or(value).parse().ok()
+}
+
+fn identity(function: &Function) -> Function {
+    let args = match function.name {
+        "scale" => vec![1., 1.],
+        "matrix" => vec![1., 0., 0., 1., 0., 0.],
+        _ => vec![0.; function.args.len()],
+    };
+    Function {
+        name: function.name,
+        args,
+    }
+}
+
+fn interpolate_transform(from: &[Function], to: &[Function], t: f64) -> Vec<Function> {
+    let from: Vec<Function> = if from.is_empty() {
+        to.iter().map(identity).collect()
+    } else {
+        from.to_vec()
+    };
+    let to: Vec<Function> = if to.is_empty() {
+        from.iter().map(identity).collect()
+    } else {
+        to.to_vec()
+    };
+
+    let compatible = from.len() == to.len()
+        && from
+            .iter()
+            .zip(&to)
+            .all(|(a, b)| a.name == b.name && a.args.len() == b.args.len());
+    if !compatible {
+        return if t < 0.5 { from } else { to };
+    }
+
+    from.iter()
+        .zip(&to)
+        .map(|(a, b)| Function {
+            name: a.name,
+            args: a
+                .args
+                .iter()
+                .zip(&b.args)
+                .map(|(a, b)| a + (b - a) * t)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Resolves `transform-origin` against a reference box of `(x, y, width, height)`.
+fn transform_origin(value: &str, (x, y, width, height): (f64, f64, f64, f64)) -> (f64, f64) {
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    // A single vertical keyword applies to y.
+    let (horizontal, vertical) = match tokens.as_slice() {
+        [] => return (x, y),
+        [single @ ("top" | "bottom")] => ("center", *single),
+        [single] => (*single, "center"),
+        [first @ ("top" | "bottom"), second, ..] => (*second, *first),
+        [first, second, ..] => (*first, *second),
+    };
+
+    let resolve = |token: &str, size: f64| match token {
+        "left" | "top" => 0.,
+        "center" => size / 2.,
+        "right" | "bottom" => size,
+        _ => match token.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().unwrap_or(0.) / 100. * size,
+            None => length(token).unwrap_or(0.),
+        },
+    };
+    (
+        x + resolve(horizontal, width),
+        y + resolve(vertical, height),
+    )
+}
+
+fn view_box(root: Node) -> (f64, f64, f64, f64) {
+    let numbers = |value: &str| -> Option<Vec<f64>> {
+        value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(length)
+            .collect()
+    };
+    if let Some(&[x, y, width, height]) = root.attribute("viewBox").and_then(numbers).as_deref() {
+        return (x, y, width, height);
+    }
+
+    let size = |name: &str| root.attribute(name).and_then(length).unwrap_or(0.);
+    (0., 0., size("width"), size("height"))
+}
+
+/// Bounds of a basic shape from its attributes.
+fn shape_bounds(node: Node) -> Option<(f64, f64, f64, f64)> {
+    let number = |name: &str| node.attribute(name).and_then(length);
+    let or_zero = |name: &str| number(name).unwrap_or(0.);
+
+    match node.tag_name().name() {
+        "rect" | "image" | "use" | "svg" => Some((
+            or_zero("x"),
+            or_zero("y"),
+            number("width")?,
+            number("height")?,
+        )),
+        "circle" => {
+            let r = number("r")?;
+            Some((or_zero("cx") - r, or_zero("cy") - r, r * 2., r * 2.))
+        }
+        "ellipse" => {
+            let (rx, ry) = (number("rx")?, number("ry")?);
+            Some((or_zero("cx") - rx, or_zero("cy") - ry, rx * 2., ry * 2.))
+        }
+        "line" => {
+            let (x1, y1, x2, y2) = (or_zero("x1"), or_zero("y1"), or_zero("x2"), or_zero("y2"));
+            Some((x1.min(x2), y1.min(y2), (x2 - x1).abs(), (y2 - y1).abs()))
+        }
+        _ => None,
+    }
+}
+
+/// Whether a selector of compound selectors and descendant combinators matches `node`.
+fn matches(selector: &str, node: Node) -> bool {
+    if selector.is_empty() || selector.contains(['+', '~', '[', ':']) {
+        return false;
+    }
+
+    let compounds: Vec<&str> = selector
+        .split(|c: char| c.is_whitespace() || c == '>')
+        .filter(|compound| !compound.is_empty())
+        .collect();
+    let Some((last, ancestors)) = compounds.split_last() else {
+        return false;
+    };
+    if !matches_compound(last, node) {
+        return false;
+    }
+
+    let mut ancestor = node.parent_element();
+    for compound in ancestors.iter().rev() {
+        loop {
+            let Some(candidate) = ancestor else {
+                return false;
+            };
+            ancestor = candidate.parent_element();
+            if matches_compound(compound, candidate) {
+                break;
+            }
+        }
+    }
+    true
+}
+
+fn matches_compound(compound: &str, node: Node) -> bool {
+    let type_end = compound.find(['.', '#']).unwrap_or(compound.len());
+    let type_name = &compound[..type_end];
+    if !type_name.is_empty() && type_name != "*" && type_name != node.tag_name().name() {
+        return false;
+    }
+
+    let classes = node.attribute("class").unwrap_or_default();
+    let mut rest = &compound[type_end..];
+    while !rest.is_empty() {
+        let kind = &rest[..1];
+        rest = &rest[1..];
+        let end = rest.find(['.', '#']).unwrap_or(rest.len());
+        let name = &rest[..end];
+        rest = &rest[end..];
+
+        let matched = match kind {
+            "." => classes.split_whitespace().any(|class| class == name),
+            _ => node.attribute("id") == Some(name),
+        };
+        if !matched {
+            return false;
+        }
+    }
+    true
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .split_once("*/")
+            .map_or("", |(_, after)| after);
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Top-level `prelude { body }` blocks of a style sheet, skipping statements like `@import`.
+fn blocks(css: &str) -> Vec<(&str, &str)> {
+    let mut blocks = Vec::new();
+    let mut rest = css;
+    while let Some(open) = rest.find('{') {
+        let prelude = &rest[..open];
+        let prelude = prelude.rsplit_once(';').map_or(prelude, |(_, p)| p).trim();
+
+        let mut depth = 0;
+        let mut close = rest.len();
+        for (i, c) in rest[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = open + i;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        blocks.push((prelude, &rest[(open + 1).min(close)..close]));
+        rest = rest.get(close + 1..).unwrap_or_default();
+    }
+    blocks
+}
+
+/// Declarations of a block, with lowercase names and `!important` dropped.
+fn declarations(body: &str) -> Vec<(String, String)> {
+    split_top_level(body, ';')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let value = value.trim();
+            let value = value.strip_suffix("!important").unwrap_or(value).trim();
+            Some((name.trim().to_ascii_lowercase(), value.to_owned()))
+        })
+        .collect()
+}
+
+/// Splits on `separator` outside parentheses, trimming and skipping empty parts.
+fn split_top_level(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && (c == separator || separator == ' ' && c.is_whitespace()) => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPINNER: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+<style>
+/* Spins around the center. */
+@keyframes spin { from { transform: rotate(0deg) } to { transform: rotate(1turn) } }
+@keyframes pulse { 50% { opacity: 0.5 } }
+.spinner { animation: spin 1s linear infinite; transform-origin: center }
+g #dot { animation: pulse 2s linear infinite }
+</style>
+<path class="spinner" d="M0 0h4v4z"/>
+<g><circle id="dot" r="2" opacity="1"/></g>
+</svg>"#;
+
+    fn sample(svg: &str, time: f64) -> String {
+        let document = Document::parse(svg).unwrap();
+        let mut rewrite = Rewrite::new(svg);
+        Animation::parse(&document)
+            .unwrap()
+            .apply(time, &mut rewrite);
+        rewrite.finish()
+    }
+
+    #[test]
+    fn samples_keyframes() {
+        let document = Document::parse(SPINNER).unwrap();
+        assert_eq!(Animation::parse(&document).unwrap().period(), 2.);
+
+        let frame = sample(SPINNER, 0.25);
+        assert!(
+            frame.contains(r#"transform="translate(12 12) rotate(90) translate(-12 -12)""#),
+            "{frame}"
+        );
+        assert!(frame.contains(r#"opacity="0.875""#), "{frame}");
+        Document::parse(&frame).unwrap();
+    }
+
+    #[test]
+    fn documents_without_animations() {
+        let svg =
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><style>rect { fill: red }</style></svg>"#;
+        assert!(Animation::parse(&Document::parse(svg).unwrap()).is_none());
+    }
+
+    #[test]
+    fn directions_and_fill() {
+        let running = Running {
+            target: 0..0,
+            keyframes: Vec::new(),
+            timing: Timing::Linear,
+            delay: 1.,
+            duration: 2.,
+            iterations: 2.,
+            direction: Direction::Alternate,
+            fill: Fill::Forwards,
+            origin: (0., 0.),
+            underlying_transform: Vec::new(),
+            underlying_opacity: 1.,
+        };
+
+        assert_eq!(running.progress(0.5), None);
+        assert_eq!(running.progress(2.), Some(0.5));
+        assert_eq!(running.progress(4.), Some(0.5));
+        assert_eq!(running.progress(10.), Some(0.));
+    }
+
+    #[test]
+    fn transforms() {
+        assert_eq!(
+            parse_transform("translateX(2px) scale(2) rotate(0.5turn)"),
+            Some(vec![
+                Function {
+                    name: "translate",
+                    args: vec![2., 0.],
+                },
+                Function {
+                    name: "scale",
+                    args: vec![2., 2.],
+                },
+                Function {
+                    name: "rotate",
+                    args: vec![180.],
+                },
+            ])
+        );
+        assert_eq!(parse_transform("translate(50%)"), None);
+
+        let scale = parse_transform("scale(3)").unwrap();
+        assert_eq!(interpolate_transform(&[], &scale, 0.5)[0].args, [2., 2.]);
+    }
+
+    #[test]
+    fn timing_functions() {
+        assert_eq!(Timing::parse("steps(4, start)").unwrap().ease(0.1), 0.25);
+        assert_eq!(Timing::parse("steps(4)").unwrap().ease(0.1), 0.);
+        let ease = Timing::parse("ease-in-out").unwrap();
+        assert!((ease.ease(0.5) - 0.5).abs() < 1e-3);
+        assert!(ease.ease(0.1) < 0.1);
+    }
+
+    #[test]
+    fn selectors() {
+        let svg = r#"<svg><g class="a b"><rect id="r"/></g></svg>"#;
+        let document = Document::parse(svg).unwrap();
+        let rect = document
+            .descendants()
+            .find(|n| n.has_tag_name("rect"))
+            .unwrap();
+
+        assert!(matches("rect", rect));
+        assert!(matches("#r", rect));
+        assert!(matches("g.b rect#r", rect));
+        assert!(matches(".a > *", rect));
+        assert!(!matches(".c rect", rect));
+        assert!(!matches("rect:hover", rect));
+    }
+}
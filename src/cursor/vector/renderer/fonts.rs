@@ -0,0 +1,48 @@
+//! Font database shared by the renderers that draw text.
+//!
+//! Loading system fonts scans every font directory, so it happens once rather than per cursor,
+//! on the thread the cursor store loads the theme's fonts on. Themes can bundle fonts of their
+//! own, which are added to a copy of the system database.
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use fontdb::Database;
+
+/// The shared database, with the system fonts loaded.
+pub fn database() -> Arc<Database> {
+    static DATABASE: OnceLock<Arc<Database>> = OnceLock::new();
+
+    DATABASE
+        .get_or_init(|| {
+            let mut database = Database::new();
+            database.load_system_fonts();
+            debug!("loaded {} font faces for cursor text", database.len());
+            Arc::new(database)
+        })
+        .clone()
+}
+
+/// The system fonts and the font files or directories in `bundled`.
+///
+/// Without bundled fonts this is the shared database itself.
+pub fn with_bundled(bundled: &[PathBuf]) -> Arc<Database> {
+    let system = database();
+    if bundled.is_empty() {
+        return system;
+    }
+
+    let mut database = Database::clone(&system);
+    for path in bundled {
+        if path.is_dir() {
+            database.load_fonts_dir(path);
+        } else if let Err(err) = database.load_font_file(path) {
+            warn!("error loading cursor theme font {}: {err}", path.display());
+        }
+    }
+    debug!(
+        "loaded {} bundled font faces for cursor text",
+        database.len() - system.len()
+    );
+    Arc::new(database)
+}
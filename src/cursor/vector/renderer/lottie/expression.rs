@@ -0,0 +1,506 @@
+//! A small whitelisted subset of Lottie property expressions.
+//!
+//! Expressions are JavaScript in the property's `x` field. Instead of running them, the source is
+//! tokenized and the few calls common in cursor animations are recognized by name:
+//!
+//! - `loopOut(type, n)` and `loopIn(type, n)` repeat the keyframes after the last or before the
+//!   first one, with `type` being `"cycle"` (the default), `"pingpong"` or `"offset"`. They are
+//!   evaluated by the property evaluator through [`loop_frame`].
+//! - `wiggle(frequency, amplitude)` on a static property is baked into keyframes by [`bake`] when
+//!   the composition is loaded, since it needs the frame rate.
+//!
+//! Anything else is ignored, leaving the property at its keyframed value.
+
+use serde_json::{json, Map, Value};
+
+use super::property::numbers;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expression {
+    Loop {
+        /// `loopOut` if true, `loopIn` otherwise.
+        out: bool,
+        kind: LoopKind,
+        /// Number of keyframes to loop, 0 for all of them.
+        keyframes: usize,
+    },
+    Wiggle {
+        frequency: f32,
+        amplitude: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopKind {
+    Cycle,
+    PingPong,
+    Offset,
+}
+
+/// Frame to sample a looping property at, and the number of whole loops skipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopedFrame {
+    pub frame: f32,
+    /// Signed number of loop periods between `frame` and the requested frame, used by
+    /// [`LoopKind::Offset`].
+    pub cycles: i32,
+    pub kind: LoopKind,
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Recognizes the expression of a property.
+///
+/// The source is tokenized, and a supported call is only recognized at the top level of a
+/// statement, like `loopOut()` or `$bm_rt = loopIn('pingpong', 2);`, with literal arguments.
+/// Calls nested in other calls or taking computed arguments, and text in strings or comments, are
+/// not mistaken for one.
+pub fn parse(source: &str) -> Option<Expression> {
+    let tokens = tokenize(source)?;
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('(') => depth += 1,
+            Token::Punct(')') => depth = depth.saturating_sub(1),
+            Token::Ident(name @ ("loopOut" | "loopIn" | "wiggle"))
+                if tokens.get(i + 1) == Some(&Token::Punct('(')) =>
+            {
+                if depth > 0 {
+                    debug!("unsupported expression, {name} is nested in another call");
+                    return None;
+                }
+                // Methods of other properties, like `thisComp.layer(1).position.loopOut()`,
+                // don't loop this one.
+                let receiver = i.checked_sub(1).map(|prev| &tokens[prev]);
+                let object = i.checked_sub(2).map(|prev| &tokens[prev]);
+                if receiver == Some(&Token::Punct('.'))
+                    && object != Some(&Token::Ident("thisProperty"))
+                {
+                    debug!("unsupported expression, {name} of another property");
+                    return None;
+                }
+                let Some(args) = arguments(&tokens[i + 2..]) else {
+                    debug!("unsupported expression, {name} has arguments that aren't literals");
+                    return None;
+                };
+                return recognize(name, &args);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn recognize(name: &str, args: &[Literal]) -> Option<Expression> {
+    match name {
+        "loopOut" | "loopIn" => {
+            let kind = match args.first() {
+                None => LoopKind::Cycle,
+                Some(Literal::Str(kind)) if kind == "cycle" => LoopKind::Cycle,
+                Some(Literal::Str(kind)) if kind == "pingpong" => LoopKind::PingPong,
+                Some(Literal::Str(kind)) if kind == "offset" => LoopKind::Offset,
+                Some(other) => {
+                    debug!("unsupported {name} type: {other:?}");
+                    return None;
+                }
+            };
+            let keyframes = match args.get(1) {
+                None => 0,
+                Some(Literal::Number(n)) if *n >= 0. => *n as usize,
+                Some(_) => return None,
+            };
+            Some(Expression::Loop {
+                out: name == "loopOut",
+                kind,
+                keyframes,
+            })
+        }
+        "wiggle" => match args {
+            [Literal::Number(frequency), Literal::Number(amplitude), ..] => {
+                Some(Expression::Wiggle {
+                    frequency: *frequency,
+                    amplitude: *amplitude,
+                })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Number(f32),
+    Str(String),
+    Punct(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Number(f32),
+    Str(String),
+}
+
+/// Splits JavaScript source into tokens, leaving out whitespace and comments.
+///
+/// Returns `None` for source that doesn't tokenize, like an unterminated string.
+fn tokenize(source: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.next_if(|&(_, next)| next == '/').is_some() => {
+                while chars.next_if(|&(_, next)| next != '\n').is_some() {}
+            }
+            '/' if chars.next_if(|&(_, next)| next == '*').is_some() => {
+                let mut last = None;
+                loop {
+                    let (_, c) = chars.next()?;
+                    if last == Some('*') && c == '/' {
+                        break;
+                    }
+                    last = Some(c);
+                }
+            }
+            '"' | '\'' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next()? {
+                        (_, '\\') => string.push(chars.next()?.1),
+                        (_, quote) if quote == c => break,
+                        (_, c) => string.push(c),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.peek().is_some_and(|(_, next)| next.is_ascii_digit())) =>
+            {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|&(_, next)| next.is_ascii_alphanumeric() || next == '.')
+                {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Number(source[start..end].parse().ok()?));
+            }
+            c if is_ident(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, next)| is_ident(next)) {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Ident(&source[start..end]));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+
+    Some(tokens)
+}
+
+/// Parses the literal arguments of a call, from the tokens after its opening parenthesis.
+///
+/// Returns `None` if an argument isn't a single string or number literal.
+fn arguments(tokens: &[Token]) -> Option<Vec<Literal>> {
+    let mut args = Vec::new();
+    let mut tokens = tokens.iter();
+    loop {
+        let arg = match tokens.next()? {
+            Token::Punct(')') if args.is_empty() => return Some(args),
+            Token::Number(n) => Literal::Number(*n),
+            Token::Punct('-') => match tokens.next()? {
+                Token::Number(n) => Literal::Number(-n),
+                _ => return None,
+            },
+            Token::Str(string) => Literal::Str(string.clone()),
+            _ => return None,
+        };
+        args.push(arg);
+
+        match tokens.next()? {
+            Token::Punct(',') => {}
+            Token::Punct(')') => return Some(args),
+            _ => return None,
+        }
+    }
+}
+
+/// Maps `frame` into the keyframed range of a property with a loop expression.
+pub fn loop_frame(prop: &Value, keyframes: &[Value], frame: f32) -> Option<LoopedFrame> {
+    let source = prop.get("x")?.as_str()?;
+    let Some(Expression::Loop {
+        out,
+        kind,
+        keyframes: count,
+    }) = parse(source)
+    else {
+        return None;
+    };
+
+    let time = |keyframe: &Value| keyframe.get("t").and_then(|t| t.as_f64()).unwrap_or(0.) as f32;
+    let last = keyframes.len().checked_sub(1)?;
+    let (start, end) = match (out, count) {
+        (_, 0) => (time(&keyframes[0]), time(&keyframes[last])),
+        (true, n) => (
+            time(&keyframes[last.saturating_sub(n)]),
+            time(&keyframes[last]),
+        ),
+        (false, n) => (time(&keyframes[0]), time(&keyframes[n.min(last)])),
+    };
+
+    let duration = end - start;
+    if duration <= 0. || (out && frame <= end) || (!out && frame >= start) {
+        return None;
+    }
+
+    // Distance past the keyframed range, in the direction of the loop.
+    let past = if out { frame - end } else { start - frame };
+    let cycles = (past / duration).floor();
+    let into = past - cycles * duration;
+
+    let looped = match kind {
+        LoopKind::PingPong if cycles as i32 % 2 == 0 => {
+            if out {
+                end - into
+            } else {
+                start + into
+            }
+        }
+        _ => {
+            if out {
+                start + into
+            } else {
+                end - into
+            }
+        }
+    };
+
+    let cycles = cycles as i32 + 1;
+    Some(LoopedFrame {
+        frame: looped,
+        cycles: if out { cycles } else { -cycles },
+        kind,
+        start,
+        end,
+    })
+}
+
+/// Replaces `wiggle` expressions on static properties with equivalent keyframes.
+///
+/// `ip` and `op` are the composition's in and out points.
+pub fn bake(composition: &mut Value, frame_rate: f32, ip: f32, op: f32) {
+    let mut seed = 0;
+    bake_value(composition, frame_rate, ip, op, &mut seed);
+}
+
+fn bake_value(value: &mut Value, frame_rate: f32, ip: f32, op: f32, seed: &mut u32) {
+    match value {
+        Value::Object(object) => {
+            let expression = object.get("x").and_then(|x| x.as_str()).and_then(parse);
+            if let Some(Expression::Wiggle {
+                frequency,
+                amplitude,
+            }) = expression
+            {
+                *seed = seed.wrapping_add(1);
+                bake_wiggle(object, frequency, amplitude, frame_rate, ip, op, *seed);
+            }
+
+            for child in object.values_mut() {
+                bake_value(child, frame_rate, ip, op, seed);
+            }
+        }
+        Value::Array(array) => {
+            for child in array {
+                bake_value(child, frame_rate, ip, op, seed);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn bake_wiggle(
+    prop: &mut Map<String, Value>,
+    frequency: f32,
+    amplitude: f32,
+    frame_rate: f32,
+    ip: f32,
+    op: f32,
+    seed: u32,
+) {
+    if prop.get("a").and_then(|a| a.as_i64()) == Some(1) {
+        debug!("wiggle on keyframed properties is not supported");
+        return;
+    }
+    let Some(base) = prop.get("k").and_then(numbers) else {
+        return;
+    };
+    if frequency <= 0. || frame_rate <= 0. || op <= ip {
+        return;
+    }
+
+    // Keyframes can't be too dense, or a malformed file would blow up in size.
+    let step = (frame_rate / frequency).max(1.);
+    let count = (((op - ip) / step).ceil() as usize + 1).min(1024);
+
+    let keyframes: Vec<Value> = (0..count)
+        .map(|i| {
+            let value: Vec<f32> = base
+                .iter()
+                .enumerate()
+                .map(|(dim, base)| base + amplitude * noise(seed, i as u32, dim as u32))
+                .collect();
+
+            json!({
+                "t": ip + i as f32 * step,
+                "s": value,
+                "o": {"x": [0.33], "y": [0.]},
+                "i": {"x": [0.67], "y": [1.]},
+            })
+        })
+        .collect();
+
+    prop.insert("a".to_owned(), Value::from(1));
+    prop.insert("k".to_owned(), Value::from(keyframes));
+    prop.remove("x");
+}
+
+/// Deterministic pseudo-random value in `-1.0..=1.0`.
+fn noise(seed: u32, index: u32, dim: u32) -> f32 {
+    let mut x = seed
+        .wrapping_mul(0x9e37_79b9)
+        .wrapping_add(index.wrapping_mul(0x85eb_ca6b))
+        .wrapping_add(dim.wrapping_mul(0xc2b2_ae35));
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    (x as f32 / u32::MAX as f32) * 2. - 1.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_expressions() {
+        assert_eq!(
+            parse("loopOut()"),
+            Some(Expression::Loop {
+                out: true,
+                kind: LoopKind::Cycle,
+                keyframes: 0
+            })
+        );
+        assert_eq!(
+            parse("var $bm_rt;\n$bm_rt = loopIn('pingpong', 2);"),
+            Some(Expression::Loop {
+                out: false,
+                kind: LoopKind::PingPong,
+                keyframes: 2
+            })
+        );
+        assert_eq!(
+            parse("wiggle(2, 10)"),
+            Some(Expression::Wiggle {
+                frequency: 2.,
+                amplitude: 10.
+            })
+        );
+        assert_eq!(parse("loopOut(\"continue\")"), None);
+        assert_eq!(parse("time * 360"), None);
+    }
+
+    #[test]
+    fn parse_ignores_strings_comments_and_nested_calls() {
+        // Calls in strings and comments aren't expressions.
+        assert_eq!(
+            parse("var s = \"loopOut('pingpong')\"; // loopIn()\nwiggle(2, 10)"),
+            Some(Expression::Wiggle {
+                frequency: 2.,
+                amplitude: 10.
+            })
+        );
+        assert_eq!(parse("/* wiggle(1, 2) */ time * 360"), None);
+
+        // The value of nested calls isn't the call's.
+        assert_eq!(parse("Math.min(loopOut(), 10)"), None);
+        assert_eq!(parse("loopOut(wiggle(1, 2))"), None);
+        assert_eq!(parse("loopOut('cycle', numKeys - 1)"), None);
+        assert_eq!(parse("thisComp.layer(1).position.loopOut()"), None);
+
+        // Names only match whole.
+        assert_eq!(parse("myloopOut()"), None);
+
+        assert_eq!(
+            parse("thisProperty.loopOut( \"offset\" , 1 )"),
+            Some(Expression::Loop {
+                out: true,
+                kind: LoopKind::Offset,
+                keyframes: 1
+            })
+        );
+        assert_eq!(
+            parse("wiggle(.5, -3)"),
+            Some(Expression::Wiggle {
+                frequency: 0.5,
+                amplitude: -3.
+            })
+        );
+        assert_eq!(parse("wiggle(2, 10"), None);
+        assert_eq!(parse("loopOut('cycle)"), None);
+    }
+
+    #[test]
+    fn loop_out() {
+        let keyframes = [json!({"t": 10}), json!({"t": 20})];
+        let cycle = json!({"x": "loopOut('cycle')"});
+        let pingpong = json!({"x": "loopOut('pingpong')"});
+
+        assert_eq!(loop_frame(&cycle, &keyframes, 15.), None);
+
+        let frame = |prop, frame| loop_frame(prop, &keyframes, frame).unwrap().frame;
+        assert_eq!(frame(&cycle, 23.), 13.);
+        assert_eq!(frame(&cycle, 35.), 15.);
+        assert_eq!(frame(&pingpong, 23.), 17.);
+        assert_eq!(frame(&pingpong, 33.), 13.);
+
+        assert_eq!(loop_frame(&cycle, &keyframes, 35.).unwrap().cycles, 2);
+    }
+
+    #[test]
+    fn loop_in() {
+        let keyframes = [json!({"t": 10}), json!({"t": 20})];
+        let cycle = json!({"x": "loopIn()"});
+
+        let looped = loop_frame(&cycle, &keyframes, 7.).unwrap();
+        assert_eq!(looped.frame, 17.);
+        assert_eq!(looped.cycles, -1);
+    }
+
+    #[test]
+    fn bake_wiggle_into_keyframes() {
+        let mut composition = json!({"layers": [{"ks": {"p": {
+            "a": 0, "k": [12, 12], "x": "wiggle(2, 3)"
+        }}}]});
+        bake(&mut composition, 30., 0., 60.);
+
+        let prop = &composition["layers"][0]["ks"]["p"];
+        assert_eq!(prop["a"], 1);
+        assert!(prop.get("x").is_none());
+
+        let keyframes = prop["k"].as_array().unwrap();
+        assert_eq!(keyframes.len(), 5);
+        for keyframe in keyframes {
+            for value in keyframe["s"].as_array().unwrap() {
+                let value = value.as_f64().unwrap();
+                assert!((9. ..=15.).contains(&value), "{value}");
+            }
+        }
+    }
+}
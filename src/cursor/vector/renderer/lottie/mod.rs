@@ -0,0 +1,1228 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use smithay::utils::{Physical, Point, Size};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, PixmapMut, Stroke, StrokeDash,
+};
+
+mod expression;
+mod property;
+#[cfg(feature = "svg")]
+mod text;
+mod timeline;
+pub mod validate;
+
+#[cfg(feature = "svg")]
+use super::fonts;
+#[cfg(feature = "rlottie")]
+use super::rlottie::RlottieEngine;
+use super::VectorRenderer;
+use crate::cursor::hotspot::HotspotSpec;
+use crate::cursor::vector::effects::Effects;
+use crate::cursor::vector::frame_cache::{self, FrameCache};
+use crate::cursor::vector::pixel_pool::PixelPool;
+use crate::cursor::vector::types::{FrameRange, RenderedFrame};
+use crate::cursor::vector::watermark::{self, Producer};
+use validate::{Issue, LayerType, Limits, ValidationError};
+
+/// Engine producing the pixels of a frame.
+enum Backend {
+    /// The built-in software rasterizer.
+    Builtin,
+    #[cfg(feature = "rlottie")]
+    Rlottie(RlottieEngine),
+}
+
+pub struct LottieRenderer {
+    _cursor_id: String,
+    _lottie_data: String,
+    hotspot: HotspotSpec,
+    _base_size: u8,
+    width: f32,
+    height: f32,
+    frame_rate: f32,
+    total_frames: u32,
+    composition: Arc<Value>,
+    markers: HashMap<String, FrameRange>,
+    effects: Effects,
+    backend: Backend,
+    issues: Vec<Issue>,
+    limits: Limits,
+    /// Paths of shapes without keyframes, keyed by the address of their `ks` property.
+    static_paths: parking_lot::RwLock<HashMap<usize, Option<Path>>>,
+    /// Laid out text, keyed by the address of the text layer, the document keyframe and the
+    /// layer opacity.
+    #[cfg(feature = "svg")]
+    texts: parking_lot::RwLock<HashMap<(usize, usize, u8), Option<usvg::Tree>>>,
+    /// Fonts text layers are drawn with, the system fonts if unset.
+    #[cfg(feature = "svg")]
+    fonts: Option<Arc<fontdb::Database>>,
+    frames: parking_lot::Mutex<FrameCache>,
+    /// Pixels of frames that aren't cached, reused instead of allocated per frame.
+    pool: parking_lot::Mutex<PixelPool>,
+    /// For every frame, the first frame of the static run it belongs to.
+    static_runs: Vec<u32>,
+}
+
+impl LottieRenderer {
+    pub fn new(
+        cursor_id: String,
+        lottie_data: String,
+        hotspot: Option<(i32, i32)>,
+        base_size: u8,
+        limits: &Limits,
+    ) -> Result<Self> {
+        let mut json: Value =
+            serde_json::from_str(&lottie_data).context("Failed to parse Lottie JSON")?;
+
+        let (fatal, issues): (Vec<_>, Vec<_>) = validate::validate(&json, limits)
+            .into_iter()
+            .partition(Issue::is_fatal);
+        if !fatal.is_empty() {
+            return Err(ValidationError(fatal).into());
+        }
+
+        let width = json.get("w").and_then(|v| v.as_f64()).unwrap_or(24.0) as f32;
+
+        let height = json.get("h").and_then(|v| v.as_f64()).unwrap_or(24.0) as f32;
+
+        let frame_rate = json.get("fr").and_then(|v| v.as_f64()).unwrap_or(60.0) as f32;
+
+        let total_frames = json.get("op").and_then(|v| v.as_f64()).unwrap_or(0.0) as u32;
+
+        let markers = parse_markers(&json);
+
+        let in_point = json.get("ip").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        expression::bake(&mut json, frame_rate, in_point, total_frames as f32);
+        let static_runs = timeline::static_runs(&json, total_frames);
+
+        #[cfg(feature = "rlottie")]
+        let backend = match RlottieEngine::new(&cursor_id, &lottie_data) {
+            Some(engine) => Backend::Rlottie(engine),
+            None => {
+                warn!("rlottie failed to load cursor '{cursor_id}', using the built-in rasterizer");
+                Backend::Builtin
+            }
+        };
+        #[cfg(not(feature = "rlottie"))]
+        let backend = Backend::Builtin;
+
+        // rlottie handles everything the built-in rasterizer doesn't.
+        #[cfg(feature = "rlottie")]
+        let issues = match backend {
+            Backend::Rlottie(_) => Vec::new(),
+            Backend::Builtin => issues,
+        };
+        for issue in &issues {
+            warn!("Lottie cursor '{cursor_id}': {issue}");
+        }
+
+        Ok(Self {
+            _cursor_id: cursor_id,
+            _lottie_data: lottie_data,
+            hotspot: HotspotSpec::from_config(hotspot, (f64::from(width), f64::from(height))),
+            _base_size: base_size,
+            width,
+            height,
+            frame_rate,
+            total_frames,
+            composition: Arc::new(json),
+            markers,
+            effects: Effects::default(),
+            backend,
+            issues,
+            limits: *limits,
+            static_paths: Default::default(),
+            #[cfg(feature = "svg")]
+            texts: Default::default(),
+            #[cfg(feature = "svg")]
+            fonts: None,
+            frames: parking_lot::Mutex::new(FrameCache::new(frame_cache::DEFAULT_BUDGET)),
+            pool: parking_lot::Mutex::new(PixelPool::default()),
+            static_runs,
+        })
+    }
+
+    /// Non-fatal problems found in the composition, such as unsupported features.
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// Sets the post-processing applied to every rendered frame.
+    pub fn with_effects(mut self, effects: Effects) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Draws text layers with the fonts in `fonts`, like the system fonts and the fonts bundled
+    /// with the theme.
+    #[cfg(feature = "svg")]
+    pub fn with_fonts(mut self, fonts: Arc<fontdb::Database>) -> Self {
+        self.fonts = Some(fonts);
+        self.texts.get_mut().clear();
+        self
+    }
+
+    /// Whether the composition draws text, which needs fonts.
+    pub fn has_text(&self) -> bool {
+        self.composition
+            .get("layers")
+            .and_then(|v| v.as_array())
+            .is_some_and(|layers| {
+                layers
+                    .iter()
+                    .any(|layer| matches!(LayerType::of(layer), LayerType::Text))
+            })
+    }
+
+    /// Collects the paths and paint operations of a shape item list.
+    ///
+    /// Returns the paths defined in this list (including nested groups), which styles of the
+    /// enclosing group also apply to, and the draw operations in bottom-to-top order. Both are in
+    /// the coordinates of the enclosing group, i.e. with the list's `tr` transform applied.
+    fn parse_shapes(&self, items: &[Value], frame: f32) -> (Vec<Path>, Vec<Draw>) {
+        enum Entry {
+            Style { style: ShapeStyle, paths: usize },
+            Group(Vec<Draw>),
+        }
+
+        let mut paths = Vec::new();
+        let mut entries = Vec::new();
+        let mut transform = None;
+
+        for item in items {
+            let Some(ty) = item.get("ty").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if validate::is_hidden(item) {
+                continue;
+            }
+
+            match ty {
+                "gr" => {
+                    let Some(group_items) = item.get("it").and_then(|v| v.as_array()) else {
+                        continue;
+                    };
+                    let (group_paths, group_draws) = self.parse_shapes(group_items, frame);
+                    paths.extend(group_paths);
+                    entries.push(Entry::Group(group_draws));
+                }
+                "sh" => {
+                    if let Some(path) = item.get("ks").and_then(|ks| self.shape_path(ks, frame)) {
+                        paths.push(path);
+                    }
+                }
+                "fl" => {
+                    if let Some(style) = parse_fill(item, frame) {
+                        entries.push(Entry::Style {
+                            style,
+                            paths: paths.len(),
+                        });
+                    }
+                }
+                "st" => {
+                    if let Some(style) = parse_stroke(item, frame) {
+                        entries.push(Entry::Style {
+                            style,
+                            paths: paths.len(),
+                        });
+                    }
+                }
+                "tr" => transform = Some(item),
+                _ => {}
+            }
+        }
+
+        // Items listed first are drawn on top, so walk the list backwards.
+        let mut draws = Vec::new();
+        for entry in entries.into_iter().rev() {
+            match entry {
+                Entry::Style {
+                    style,
+                    paths: count,
+                } => draws.push(Draw {
+                    paths: paths[..count].to_vec(),
+                    style,
+                    transform: tiny_skia::Transform::identity(),
+                }),
+                Entry::Group(group_draws) => draws.extend(group_draws),
+            }
+        }
+
+        // The transform applies to the whole list, wherever the `tr` item is.
+        if let Some(tr) = transform {
+            let matrix = parse_transform(tr, frame);
+            paths = paths
+                .into_iter()
+                .filter_map(|path| path.transform(matrix))
+                .collect();
+
+            let opacity = tr.get("o").and_then(|o| property::scalar(o, frame));
+            for draw in &mut draws {
+                // Styles of this list draw in its coordinates, so strokes scale and skew too.
+                draw.transform = matrix.pre_concat(draw.transform);
+                if let Some(opacity) = opacity {
+                    draw.style.apply_opacity((opacity / 100.).clamp(0., 1.));
+                }
+            }
+        }
+
+        (paths, draws)
+    }
+
+    /// Builds the path of a shape at `frame`, reusing it across frames if it's static.
+    fn shape_path(&self, ks: &Value, frame: f32) -> Option<Path> {
+        if property::is_animated(ks) {
+            return parse_bezier_path(ks, frame);
+        }
+
+        // The composition is immutable and lives as long as the renderer, so addresses of its
+        // values are stable identifiers.
+        let key = ks as *const Value as usize;
+        if let Some(path) = self.static_paths.read().get(&key) {
+            return path.clone();
+        }
+
+        let path = parse_bezier_path(ks, frame);
+        self.static_paths.write().insert(key, path.clone());
+        path
+    }
+
+    /// Hotspot at `frame`.
+    ///
+    /// A layer named `hotspot` (usually a null layer) overrides the configured hotspot with its
+    /// position, so the hotspot can follow the animation.
+    fn hotspot_at(&self, frame: f32) -> HotspotSpec {
+        let layer = self
+            .composition
+            .get("layers")
+            .and_then(|v| v.as_array())
+            .and_then(|layers| {
+                layers
+                    .iter()
+                    .find(|layer| layer.get("nm").and_then(|v| v.as_str()) == Some("hotspot"))
+            });
+
+        let position = layer.and_then(|layer| {
+            let frame = transform_time(layer, frame);
+            layer_position(layer.get("ks")?, frame)
+        });
+
+        match position {
+            Some((x, y)) => HotspotSpec::new((f64::from(x), f64::from(y)), self.hotspot.canvas),
+            None => self.hotspot,
+        }
+    }
+
+    fn render_frame_to_buffer(&self, frame: f32, scale: f64) -> Result<RenderedFrame> {
+        let scaled_width = (f64::from(self.width) * scale).ceil() as i32;
+        let scaled_height = (f64::from(self.height) * scale).ceil() as i32;
+        self.limits
+            .check_raster(f64::from(scaled_width), f64::from(scaled_height))
+            .map_err(|issue| ValidationError(vec![issue]))?;
+
+        let (mut pixels, producer) = match &self.backend {
+            Backend::Builtin => {
+                let len = scaled_width.max(0) as usize * scaled_height.max(0) as usize * 4;
+                let mut pixels = self.pool.lock().scratch(len);
+                self.rasterize(frame, &mut pixels, scaled_width, scaled_height, scale);
+                (pixels, Producer::Lottie)
+            }
+            #[cfg(feature = "rlottie")]
+            Backend::Rlottie(engine) => (
+                engine.render(frame as u32, scaled_width, scaled_height),
+                Producer::Rlottie,
+            ),
+        };
+
+        self.effects.apply(&mut pixels);
+        self.effects.apply_mirror(&mut pixels, scaled_width);
+        if self.effects.watermark {
+            watermark::stamp(&mut pixels, scaled_width, producer);
+        }
+
+        let size = Size::from((scaled_width, scaled_height));
+        let buffer = {
+            let mut pool = self.pool.lock();
+            let buffer = pool.buffer(&pixels, size, scale);
+            pool.recycle_scratch(pixels);
+            buffer
+        };
+
+        let duration = (self.total_frames > 1)
+            .then(|| Duration::from_millis(u64::from(self.frame_duration_ms())));
+
+        Ok(RenderedFrame::new(
+            buffer,
+            size,
+            scale,
+            self.effects
+                .mirror_hotspot(self.hotspot_at(frame).resolve(scale), scaled_width),
+            duration,
+        ))
+    }
+
+    /// Draws `frame` into zeroed `pixels` of `width`×`height`.
+    fn rasterize(&self, frame: f32, pixels: &mut [u8], width: i32, height: i32, scale: f64) {
+        let Some(mut pixmap) = PixmapMut::from_bytes(pixels, width as u32, height as u32) else {
+            return;
+        };
+
+        let transform = tiny_skia::Transform::from_scale(scale as f32, scale as f32);
+
+        if let Some(layers) = self.composition.get("layers").and_then(|v| v.as_array()) {
+            // The first layer is the topmost one.
+            for layer in layers.iter().rev() {
+                if validate::is_hidden(layer) || !layer_visible_at(layer, frame) {
+                    continue;
+                }
+
+                let layer_frame = layer_time(layer, frame, self.frame_rate);
+                match LayerType::of(layer) {
+                    LayerType::Shape => {
+                        let Some(shapes) = layer.get("shapes").and_then(|v| v.as_array()) else {
+                            continue;
+                        };
+
+                        let (_, draws) = self.parse_shapes(shapes, layer_frame);
+                        for draw in &draws {
+                            draw.render(&mut pixmap, transform);
+                        }
+                    }
+                    #[cfg(feature = "svg")]
+                    LayerType::Text => self.render_text(layer, frame, &mut pixmap, transform),
+                    // Other layer types were reported by validation and are skipped rather than
+                    // drawn with whatever shapes they happen to carry.
+                    _ => {}
+                }
+            }
+        }
+
+        // tiny-skia produces RGBA, the buffer wants ARGB8888 (BGRA in memory).
+        for chunk in pixmap.data_mut().chunks_exact_mut(4) {
+            chunk.swap(0, 2);
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+impl LottieRenderer {
+    /// Draws a text layer at composition `frame`, positioned by its `ks` transform.
+    fn render_text(
+        &self,
+        layer: &Value,
+        frame: f32,
+        pixmap: &mut PixmapMut,
+        transform: tiny_skia::Transform,
+    ) {
+        let content_frame = layer_time(layer, frame, self.frame_rate);
+        let Some((keyframe, document)) = text::document_at(layer, content_frame) else {
+            return;
+        };
+
+        let frame = transform_time(layer, frame);
+        let ks = layer.get("ks");
+        let opacity = ks
+            .and_then(|ks| ks.get("o"))
+            .and_then(|o| property::scalar(o, frame))
+            .map_or(1., |o| (o / 100.).clamp(0., 1.));
+        let matrix = ks.map_or(tiny_skia::Transform::identity(), |ks| {
+            parse_transform(ks, frame)
+        });
+
+        // The composition lives as long as the renderer, see `shape_path`.
+        let key = (
+            layer as *const Value as usize,
+            keyframe,
+            (opacity * 255.).round() as u8,
+        );
+        if !self.texts.read().contains_key(&key) {
+            let fonts = self.fonts.clone().unwrap_or_else(fonts::database);
+            let tree = text::layout(document, &self.composition, opacity, &fonts);
+            self.texts.write().insert(key, tree);
+        }
+
+        if let Some(tree) = &self.texts.read()[&key] {
+            resvg::render(tree, transform.pre_concat(matrix), pixmap);
+        }
+    }
+}
+
+/// Paint applied to the paths collected before it in a shape group.
+enum ShapeStyle {
+    Fill { color: Color, rule: FillRule },
+    Stroke { color: Color, stroke: Stroke },
+}
+
+impl ShapeStyle {
+    fn apply_opacity(&mut self, opacity: f32) {
+        match self {
+            ShapeStyle::Fill { color, .. } | ShapeStyle::Stroke { color, .. } => {
+                color.apply_opacity(opacity)
+            }
+        }
+    }
+}
+
+/// A style applied to a set of paths.
+struct Draw {
+    paths: Vec<Path>,
+    style: ShapeStyle,
+    /// Transform of the group the style belongs to.
+    transform: tiny_skia::Transform,
+}
+
+impl Draw {
+    fn render(&self, pixmap: &mut PixmapMut, transform: tiny_skia::Transform) {
+        let transform = transform.pre_concat(self.transform);
+        let mut paint = Paint::default();
+        paint.anti_alias = true;
+
+        match &self.style {
+            ShapeStyle::Fill { color, rule } => {
+                paint.set_color(*color);
+                for path in &self.paths {
+                    pixmap.fill_path(path, &paint, *rule, transform, None);
+                }
+            }
+            ShapeStyle::Stroke { color, stroke } => {
+                paint.set_color(*color);
+                for path in &self.paths {
+                    pixmap.stroke_path(path, &paint, stroke, transform, None);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether `frame` is within the layer's `ip`..`op` range.
+fn layer_visible_at(layer: &Value, frame: f32) -> bool {
+    let bound = |key: &str| layer.get(key).and_then(|v| v.as_f64()).map(|v| v as f32);
+
+    if bound("ip").is_some_and(|ip| frame < ip) {
+        return false;
+    }
+    if bound("op").is_some_and(|op| frame >= op) {
+        return false;
+    }
+    true
+}
+
+/// Collects the named frame ranges of the composition's `markers`.
+fn parse_markers(composition: &Value) -> HashMap<String, FrameRange> {
+    let Some(markers) = composition.get("markers").and_then(|v| v.as_array()) else {
+        return HashMap::new();
+    };
+
+    markers
+        .iter()
+        .filter_map(|marker| {
+            let comment = marker.get("cm")?.as_str()?;
+            // After Effects exports marker comments as JSON with the name inside.
+            let name = serde_json::from_str::<Value>(comment)
+                .ok()
+                .and_then(|v| v.get("name")?.as_str().map(str::to_owned))
+                .unwrap_or_else(|| comment.to_owned());
+
+            let start = marker.get("tm")?.as_f64()?.max(0.);
+            let duration = marker.get("dr").and_then(|v| v.as_f64()).unwrap_or(0.);
+            let range = FrameRange::new(start as u32, (start + duration).ceil() as u32);
+            Some((name, range))
+        })
+        .collect()
+}
+
+/// Maps a composition frame to the frame the layer's content is evaluated at.
+///
+/// Layers are offset by their start time `st`. A time-remapped layer instead samples its `tm`
+/// property, which gives the content time in seconds and can slow down, reverse or hold the
+/// layer's animation.
+fn layer_time(layer: &Value, frame: f32, frame_rate: f32) -> f32 {
+    let frame = transform_time(layer, frame);
+
+    if let Some(seconds) = layer.get("tm").and_then(|tm| property::scalar(tm, frame)) {
+        return seconds * frame_rate;
+    }
+
+    frame
+}
+
+/// Maps a composition frame to the frame the layer's own transform `ks` is evaluated at.
+///
+/// Like in After Effects, time remapping only applies to the content, so the transform keeps
+/// animating, offset by `st`, when `tm` holds the content still.
+fn transform_time(layer: &Value, frame: f32) -> f32 {
+    frame - layer.get("st").and_then(|v| v.as_f64()).unwrap_or(0.) as f32
+}
+
+/// Builds the matrix of a transform (`tr` item) at `frame`.
+///
+/// Like After Effects, this is position · rotation · skew · scale · -anchor, where the skew
+/// shears along the skew axis.
+fn parse_transform(tr: &Value, frame: f32) -> tiny_skia::Transform {
+    let pair = |key: &str, default: f32| {
+        let value = tr.get(key).and_then(|v| property::value(v, frame));
+        let value = value.as_deref().unwrap_or_default();
+        let x = value.first().copied().unwrap_or(default);
+        (x, value.get(1).copied().unwrap_or(x))
+    };
+    let scalar = |key: &str| {
+        tr.get(key)
+            .and_then(|v| property::scalar(v, frame))
+            .unwrap_or(0.)
+    };
+
+    let (x, y) = layer_position(tr, frame).unwrap_or((0., 0.));
+    let (anchor_x, anchor_y) = pair("a", 0.);
+    let (scale_x, scale_y) = pair("s", 100.);
+    // Shearing by 90° or more is degenerate, After Effects limits skew to ±85°.
+    let skew = scalar("sk").clamp(-85., 85.);
+    let skew_axis = scalar("sa");
+
+    let mut matrix = tiny_skia::Transform::from_translate(x, y)
+        .pre_concat(tiny_skia::Transform::from_rotate(scalar("r")));
+    if skew != 0. {
+        let shear = tiny_skia::Transform::from_row(1., 0., (-skew).to_radians().tan(), 1., 0., 0.);
+        matrix = matrix
+            .pre_concat(tiny_skia::Transform::from_rotate(skew_axis))
+            .pre_concat(shear)
+            .pre_concat(tiny_skia::Transform::from_rotate(-skew_axis));
+    }
+    matrix
+        .pre_scale(scale_x / 100., scale_y / 100.)
+        .pre_translate(-anchor_x, -anchor_y)
+}
+
+/// Evaluates the position of a transform, which may have its dimensions split into `x` and `y`.
+fn layer_position(transform: &Value, frame: f32) -> Option<(f32, f32)> {
+    let position = transform.get("p")?;
+
+    if position.get("s").and_then(|v| v.as_bool()) == Some(true) {
+        let x = property::scalar(position.get("x")?, frame)?;
+        let y = property::scalar(position.get("y")?, frame)?;
+        return Some((x, y));
+    }
+
+    let value = property::value(position, frame)?;
+    Some((*value.first()?, *value.get(1)?))
+}
+
+fn prop_color(prop: &Value, frame: f32) -> Option<Color> {
+    // Overshooting easing curves can leave the 0 to 1 range.
+    let [r, g, b, a] = property::color(prop, frame)?.map(|c| c.clamp(0., 1.));
+    Color::from_rgba(r, g, b, a)
+}
+
+/// Color of a fill or stroke at `frame`, with the item's `o` opacity (0 to 100) applied.
+fn paint_color(item: &Value, frame: f32) -> Option<Color> {
+    let mut color = prop_color(item.get("c")?, frame)?;
+    if let Some(opacity) = item.get("o").and_then(|o| property::scalar(o, frame)) {
+        color.apply_opacity((opacity / 100.).clamp(0., 1.));
+    }
+    Some(color)
+}
+
+/// Builds a path from a Lottie bezier shape property.
+fn parse_bezier_path(ks: &Value, frame: f32) -> Option<Path> {
+    let shape = property::shape(ks, frame)?;
+    let vertices = &shape.vertices;
+    let (first, rest) = vertices.split_first()?;
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(first.0, first.1);
+
+    let segment = |builder: &mut PathBuilder, from: usize, to: usize| {
+        let (fx, fy) = vertices[from];
+        let (tx, ty) = vertices[to];
+        let (ox, oy) = shape.out_tangents[from];
+        let (ix, iy) = shape.in_tangents[to];
+        if (ox, oy) == (0., 0.) && (ix, iy) == (0., 0.) {
+            builder.line_to(tx, ty);
+        } else {
+            builder.cubic_to(fx + ox, fy + oy, tx + ix, ty + iy, tx, ty);
+        }
+    };
+
+    for idx in 1..=rest.len() {
+        segment(&mut builder, idx - 1, idx);
+    }
+    if shape.closed {
+        segment(&mut builder, vertices.len() - 1, 0);
+        builder.close();
+    }
+
+    builder.finish()
+}
+
+fn parse_fill(item: &Value, frame: f32) -> Option<ShapeStyle> {
+    let color = paint_color(item, frame)?;
+    let rule = match item.get("r").and_then(|v| v.as_i64()) {
+        Some(2) => FillRule::EvenOdd,
+        _ => FillRule::Winding,
+    };
+    Some(ShapeStyle::Fill { color, rule })
+}
+
+fn parse_stroke(item: &Value, frame: f32) -> Option<ShapeStyle> {
+    let color = paint_color(item, frame)?;
+
+    let mut stroke = Stroke {
+        width: item
+            .get("w")
+            .and_then(|w| property::scalar(w, frame))
+            .unwrap_or(1.),
+        ..Default::default()
+    };
+
+    stroke.line_cap = match item.get("lc").and_then(|v| v.as_i64()) {
+        Some(2) => LineCap::Round,
+        Some(3) => LineCap::Square,
+        _ => LineCap::Butt,
+    };
+    stroke.line_join = match item.get("lj").and_then(|v| v.as_i64()) {
+        Some(2) => LineJoin::Round,
+        Some(3) => LineJoin::Bevel,
+        _ => LineJoin::Miter,
+    };
+    if let Some(ml) = item.get("ml").and_then(|v| v.as_f64()) {
+        stroke.miter_limit = ml as f32;
+    } else if let Some(ml) = item.get("ml2").and_then(|ml2| property::scalar(ml2, frame)) {
+        stroke.miter_limit = ml;
+    }
+
+    if let Some(dashes) = item.get("d").and_then(|v| v.as_array()) {
+        stroke.dash = parse_dash(dashes, frame);
+    }
+
+    Some(ShapeStyle::Stroke { color, stroke })
+}
+
+/// Parses a Lottie dash list: alternating `d` (dash) and `g` (gap) entries plus an `o` offset.
+fn parse_dash(dashes: &[Value], frame: f32) -> Option<StrokeDash> {
+    let mut array = Vec::new();
+    let mut offset = 0.;
+
+    for dash in dashes {
+        let Some(value) = dash.get("v").and_then(|v| property::scalar(v, frame)) else {
+            continue;
+        };
+        match dash.get("n").and_then(|v| v.as_str()) {
+            Some("d") | Some("g") => array.push(value.max(0.)),
+            Some("o") => offset = value,
+            _ => {}
+        }
+    }
+
+    // Like SVG, an odd number of entries is repeated to make it even.
+    if array.len() % 2 == 1 {
+        array.extend_from_within(..);
+    }
+
+    StrokeDash::new(array, offset)
+}
+
+impl VectorRenderer for LottieRenderer {
+    fn render_frame(&self, frame: u32, scale: f64) -> Result<RenderedFrame> {
+        let actual_frame = if self.total_frames > 0 {
+            frame % self.total_frames
+        } else {
+            0
+        };
+        // Frames where nothing changed look like the first frame of their static run.
+        let actual_frame = self
+            .static_runs
+            .get(actual_frame as usize)
+            .copied()
+            .unwrap_or(actual_frame);
+
+        if let Some(rendered) = self.frames.lock().get(actual_frame, scale) {
+            return Ok(rendered);
+        }
+
+        let rendered = self.render_frame_to_buffer(actual_frame as f32, scale)?;
+        let dropped = self
+            .frames
+            .lock()
+            .insert(actual_frame, scale, rendered.clone());
+        let mut pool = self.pool.lock();
+        for frame in dropped {
+            pool.recycle(frame);
+        }
+        Ok(rendered)
+    }
+
+    fn render_frame_at(&self, frame: f32, scale: f64) -> Result<RenderedFrame> {
+        // Whole frames come from the cache, and rlottie only renders those.
+        let builtin = matches!(self.backend, Backend::Builtin);
+        if !builtin || frame.fract() == 0. || self.total_frames == 0 {
+            return self.render_frame(frame as u32, scale);
+        }
+        // In-between frames are only shown once, they aren't cached.
+        self.render_frame_to_buffer(frame % self.total_frames as f32, scale)
+    }
+
+    fn hotspot(&self) -> Point<i32, Physical> {
+        let width = self.width.ceil() as i32;
+        self.effects
+            .mirror_hotspot(self.hotspot_at(0.).resolve(1.), width)
+    }
+
+    fn frame_hotspot(&self, frame: u32, scale: f64) -> Point<i32, Physical> {
+        let frame = frame.checked_rem(self.total_frames).unwrap_or(0);
+        let scaled_width = (f64::from(self.width) * scale).ceil() as i32;
+        self.effects
+            .mirror_hotspot(self.hotspot_at(frame as f32).resolve(scale), scaled_width)
+    }
+
+    fn total_frames(&self) -> u32 {
+        self.total_frames
+    }
+
+    fn frame_duration_ms(&self) -> u32 {
+        if self.frame_rate > 0.0 {
+            (1000.0 / self.frame_rate) as u32
+        } else {
+            16
+        }
+    }
+
+    fn trim_caches(&self) {
+        self.frames.lock().trim();
+        self.pool.lock().clear();
+    }
+
+    fn segment(&self, name: &str) -> Option<FrameRange> {
+        self.markers.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn markers() {
+        let composition = json!({"markers": [
+            {"cm": "enter", "tm": 0, "dr": 10},
+            {"cm": "{\"name\":\"loop\"}", "tm": 10, "dr": 29.5},
+            {"cm": "tick", "tm": 45},
+        ]});
+        let markers = parse_markers(&composition);
+        assert_eq!(markers["enter"], FrameRange::new(0, 10));
+        assert_eq!(markers["loop"], FrameRange::new(10, 40));
+        assert_eq!(markers["tick"], FrameRange::new(45, 46));
+    }
+
+    #[test]
+    fn position() {
+        let transform = json!({"p": {"a": 0, "k": [3, 4, 0]}});
+        assert_eq!(layer_position(&transform, 0.), Some((3., 4.)));
+
+        let split = json!({"p": {"s": true, "x": {"a": 0, "k": 5}, "y": {"a": 0, "k": 6}}});
+        assert_eq!(layer_position(&split, 0.), Some((5., 6.)));
+
+        assert_eq!(layer_position(&json!({}), 0.), None);
+    }
+
+    #[test]
+    fn layer_time_offsets_by_start() {
+        let layer = json!({"st": 10});
+        assert_eq!(layer_time(&layer, 15., 60.), 5.);
+        assert_eq!(layer_time(&json!({}), 15., 60.), 15.);
+    }
+
+    #[test]
+    fn layer_time_remap() {
+        // Plays the first second of content backwards over 60 frames.
+        let layer = json!({"tm": {"a": 1, "k": [
+            {"t": 0, "s": [1], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 60, "s": [0]},
+        ]}});
+        assert_eq!(layer_time(&layer, 0., 30.), 30.);
+        assert_eq!(layer_time(&layer, 30., 30.), 15.);
+        assert_eq!(layer_time(&layer, 60., 30.), 0.);
+
+        // Holds a single content frame.
+        let layer = json!({"tm": {"a": 0, "k": 0.5}});
+        assert_eq!(layer_time(&layer, 42., 30.), 15.);
+    }
+
+    #[test]
+    fn time_remap_leaves_the_transform() {
+        // The content holds still, while the hotspot layer keeps moving.
+        let composition = json!({"w": 20, "h": 20, "fr": 30, "ip": 0, "op": 20, "layers": [
+            {"ty": 3, "nm": "hotspot", "st": 5, "tm": {"a": 0, "k": 0}, "ks": {"p": {"a": 1, "k": [
+                {"t": 0, "s": [0, 0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+                {"t": 10, "s": [10, 10]},
+            ]}}},
+        ]});
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(layer_time(&composition["layers"][0], 10., 30.), 0.);
+        assert_eq!(renderer.hotspot_at(5.).resolve(1.), Point::from((0, 0)));
+        assert_eq!(renderer.hotspot_at(10.).resolve(1.), Point::from((5, 5)));
+        assert_eq!(renderer.hotspot_at(15.).resolve(1.), Point::from((10, 10)));
+    }
+
+    #[test]
+    fn transforms() {
+        let close = |actual: f32, expected: f32| {
+            assert!((actual - expected).abs() < 1e-4, "{actual} vs {expected}");
+        };
+
+        let tr = json!({
+            "a": {"a": 0, "k": [5, 5]},
+            "p": {"a": 0, "k": [10, 20]},
+            "s": {"a": 0, "k": [200, 50]},
+        });
+        let matrix = parse_transform(&tr, 0.);
+        let mut point = [tiny_skia::Point::from_xy(5., 5.)];
+        matrix.map_points(&mut point);
+        assert_eq!((point[0].x, point[0].y), (10., 20.));
+        close(matrix.sx, 2.);
+        close(matrix.sy, 0.5);
+
+        // Skewing along the x axis shears x by y.
+        let skew = json!({"sk": {"a": 0, "k": 45}, "sa": {"a": 0, "k": 0}});
+        let matrix = parse_transform(&skew, 0.);
+        close(matrix.kx, -1.);
+        close(matrix.ky, 0.);
+        close(matrix.sx, 1.);
+        close(matrix.sy, 1.);
+    }
+
+    #[test]
+    fn group_transforms() {
+        let square = json!({
+            "v": [[0, 0], [10, 0], [10, 10], [0, 10]],
+            "i": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "o": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "c": true,
+        });
+        let group = json!({"ty": "gr", "it": [
+            {"ty": "sh", "ks": {"a": 0, "k": square}},
+            {"ty": "st", "c": {"a": 0, "k": [1, 1, 1]}, "w": {"a": 0, "k": 1}},
+            {"ty": "tr", "p": {"a": 0, "k": [20, 0]}, "o": {"a": 0, "k": 50}},
+        ]});
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            json!({"w": 40, "h": 40, "fr": 30, "ip": 0, "op": 1, "layers": []}).to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        let (paths, draws) = renderer.parse_shapes(&[group], 0.);
+        let bounds = paths[0].bounds();
+        assert_eq!((bounds.left(), bounds.right()), (20., 30.));
+
+        // The group's own stroke keeps local paths and draws with the group transform.
+        assert_eq!(draws[0].paths[0].bounds().left(), 0.);
+        assert_eq!(draws[0].transform.tx, 20.);
+        let ShapeStyle::Stroke { color, .. } = &draws[0].style else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(color.alpha(), 0.5);
+    }
+
+    /// Rasterizes a shape layer with `shapes` into a `size`×`size` composition at `scale`.
+    fn rasterize_shapes(shapes: Value, size: i32, scale: f64) -> Vec<u8> {
+        let composition = json!({
+            "w": size, "h": size, "fr": 30, "ip": 0, "op": 1,
+            "layers": [{"ty": 4, "shapes": shapes}],
+        });
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        let width = (f64::from(size) * scale) as i32;
+        let mut pixels = vec![0; width as usize * width as usize * 4];
+        renderer.rasterize(0., &mut pixels, width, width, scale);
+        pixels
+    }
+
+    /// The BGRA pixel at `(x, y)` of a square frame.
+    fn pixel(pixels: &[u8], (x, y): (usize, usize)) -> [u8; 4] {
+        let width = ((pixels.len() / 4) as f64).sqrt() as usize;
+        let i = (y * width + x) * 4;
+        pixels[i..i + 4].try_into().unwrap()
+    }
+
+    fn rect(x: f32, y: f32, size: f32) -> Value {
+        json!({
+            "v": [[x, y], [x + size, y], [x + size, y + size], [x, y + size]],
+            "i": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "o": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "c": true,
+        })
+    }
+
+    #[test]
+    fn fill_pixels() {
+        let pixels = rasterize_shapes(
+            json!([
+                {"ty": "sh", "ks": {"a": 0, "k": rect(2., 2., 4.)}},
+                {"ty": "fl", "c": {"a": 0, "k": [1, 0, 0, 1]}},
+            ]),
+            8,
+            1.,
+        );
+
+        for inside in [(2, 2), (3, 4), (5, 5)] {
+            assert_eq!(pixel(&pixels, inside), [0, 0, 255, 255], "{inside:?}");
+        }
+        for outside in [(1, 1), (6, 3), (3, 6), (7, 7)] {
+            assert_eq!(pixel(&pixels, outside), [0; 4], "{outside:?}");
+        }
+    }
+
+    #[test]
+    fn stroke_pixels() {
+        // A 2 px stroke centered on the outline covers one pixel on either side of it.
+        let pixels = rasterize_shapes(
+            json!([
+                {"ty": "sh", "ks": {"a": 0, "k": rect(2., 2., 4.)}},
+                {"ty": "st", "c": {"a": 0, "k": [0, 1, 0]}, "w": {"a": 0, "k": 2}},
+            ]),
+            8,
+            1.,
+        );
+
+        for edge in [(1, 1), (2, 4), (6, 4), (4, 6)] {
+            assert_eq!(pixel(&pixels, edge), [0, 255, 0, 255], "{edge:?}");
+        }
+        for off in [(0, 4), (3, 3), (4, 4), (7, 4)] {
+            assert_eq!(pixel(&pixels, off), [0; 4], "{off:?}");
+        }
+    }
+
+    #[test]
+    fn transform_pixels() {
+        // A 2×2 square moved to (2, 2) and scaled to 4×4, rendered at scale 2.
+        let pixels = rasterize_shapes(
+            json!([{"ty": "gr", "it": [
+                {"ty": "sh", "ks": {"a": 0, "k": rect(0., 0., 2.)}},
+                {"ty": "fl", "c": {"a": 0, "k": [0, 0, 1, 1]}},
+                {"ty": "tr", "p": {"a": 0, "k": [2, 2]}, "s": {"a": 0, "k": [200, 200]}},
+            ]}]),
+            8,
+            2.,
+        );
+
+        for inside in [(4, 4), (8, 8), (11, 11)] {
+            assert_eq!(pixel(&pixels, inside), [255, 0, 0, 255], "{inside:?}");
+        }
+        for outside in [(0, 0), (3, 3), (12, 12), (4, 12)] {
+            assert_eq!(pixel(&pixels, outside), [0; 4], "{outside:?}");
+        }
+    }
+
+    #[test]
+    fn layer_in_and_out_points() {
+        // `ip` is inclusive and `op` exclusive.
+        let layer = json!({"ip": 10, "op": 20});
+        assert!(!layer_visible_at(&layer, 9.5));
+        assert!(layer_visible_at(&layer, 10.));
+        assert!(layer_visible_at(&layer, 19.5));
+        assert!(!layer_visible_at(&layer, 20.));
+
+        // A missing bound leaves that side open.
+        assert!(layer_visible_at(&json!({"op": 5}), 0.));
+        assert!(layer_visible_at(&json!({"ip": 5}), 1000.));
+        assert!(layer_visible_at(&json!({}), 1000.));
+    }
+
+    #[test]
+    fn layers_draw_within_their_range() {
+        let fill = |color: [u8; 3]| {
+            json!([
+                {"ty": "sh", "ks": {"a": 0, "k": rect(0., 0., 4.)}},
+                {"ty": "fl", "c": {"a": 0, "k": color}},
+            ])
+        };
+        // An intro layer followed by a loop layer. The loop layer is parented to a null layer
+        // that ends before it, which doesn't hide it: parents only lend their transform.
+        let composition = json!({"w": 4, "h": 4, "fr": 30, "ip": 0, "op": 10, "layers": [
+            {"ty": 4, "ind": 1, "ip": 0, "op": 5, "shapes": fill([1, 0, 0])},
+            {"ty": 4, "ind": 2, "parent": 3, "ip": 5, "op": 10, "shapes": fill([0, 0, 1])},
+            {"ty": 3, "ind": 3, "ip": 0, "op": 5},
+        ]});
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        let color_at = |frame| {
+            let mut pixels = vec![0; 4 * 4 * 4];
+            renderer.rasterize(frame, &mut pixels, 4, 4, 1.);
+            pixel(&pixels, (2, 2))
+        };
+        assert_eq!(color_at(0.), [0, 0, 255, 255]);
+        assert_eq!(color_at(4.5), [0, 0, 255, 255]);
+        assert_eq!(color_at(5.), [255, 0, 0, 255]);
+        assert_eq!(color_at(9.), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn hidden_and_unsupported_layers_are_skipped() {
+        let square = json!({
+            "v": [[0, 0], [4, 0], [4, 4], [0, 4]],
+            "i": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "o": [[0, 0], [0, 0], [0, 0], [0, 0]],
+            "c": true,
+        });
+        let shapes = json!([
+            {"ty": "sh", "ks": {"a": 0, "k": square}},
+            {"ty": "fl", "c": {"a": 0, "k": [1, 0, 0, 1]}},
+        ]);
+        let hidden_fill = json!([
+            {"ty": "sh", "ks": {"a": 0, "k": square}},
+            {"ty": "fl", "c": {"a": 0, "k": [1, 0, 0, 1]}, "hd": true},
+        ]);
+        let composition = json!({"w": 4, "h": 4, "fr": 30, "ip": 0, "op": 1, "layers": [
+            {"ty": 4, "hd": true, "shapes": shapes},
+            {"ty": 1, "shapes": shapes},
+            {"ty": 4, "shapes": hidden_fill},
+        ]});
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        let frame = renderer.render_frame(0, 1.).unwrap();
+        assert!(frame.read_pixels().iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn paint_opacity() {
+        let fill = json!({
+            "ty": "fl",
+            "c": {"a": 0, "k": [1, 0, 0, 0.5]},
+            "o": {"a": 1, "k": [
+                {"t": 0, "s": [0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+                {"t": 10, "s": [100]},
+            ]},
+        });
+        let alpha = |frame| paint_color(&fill, frame).unwrap().alpha();
+        assert_eq!(alpha(0.), 0.);
+        assert_eq!(alpha(5.), 0.25);
+        assert_eq!(alpha(10.), 0.5);
+
+        let stroke = json!({"ty": "st", "c": {"a": 0, "k": [1, 1, 1]}});
+        assert_eq!(paint_color(&stroke, 0.).unwrap().alpha(), 1.);
+    }
+
+    #[test]
+    fn static_shapes_are_cached() {
+        let triangle = |x: f32| {
+            json!({
+                "v": [[x, 0], [10, 0], [10, 10]],
+                "i": [[0, 0], [0, 0], [0, 0]],
+                "o": [[0, 0], [0, 0], [0, 0]],
+                "c": true,
+            })
+        };
+        let composition = json!({
+            "w": 10, "h": 10, "fr": 30, "ip": 0, "op": 10,
+            "layers": [{"ty": 4, "shapes": [
+                {"ty": "sh", "ks": {"a": 0, "k": triangle(0.)}},
+                {"ty": "sh", "ks": {"a": 1, "k": [
+                    {"t": 0, "s": [triangle(0.)]},
+                    {"t": 10, "s": [triangle(5.)]},
+                ]}},
+                {"ty": "fl", "c": {"a": 0, "k": [1, 0, 0, 1]}, "o": {"a": 0, "k": 100}},
+            ]}],
+        });
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+
+        let rasterize = |frame| {
+            let mut pixels = vec![0; 10 * 10 * 4];
+            renderer.rasterize(frame, &mut pixels, 10, 10, 1.);
+            pixels
+        };
+        let first = rasterize(0.);
+        rasterize(5.);
+        assert_eq!(renderer.static_paths.read().len(), 1);
+        assert_eq!(rasterize(0.), first);
+    }
+
+    #[test]
+    #[cfg(feature = "svg")]
+    fn text_uses_the_given_fonts() {
+        let composition = json!({
+            "w": 10, "h": 10, "fr": 30, "ip": 0, "op": 1,
+            "layers": [{
+                "ty": 5,
+                "ks": {"p": {"a": 0, "k": [0, 10]}},
+                "t": {"d": {"k": [{"t": 0, "s": {"t": "8", "s": 10, "fc": [1, 0, 0]}}]}},
+            }],
+        });
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            composition.to_string(),
+            None,
+            24,
+            &Limits::default(),
+        )
+        .unwrap();
+        assert!(renderer.has_text());
+
+        // Without any fonts, the digit has no glyph to draw.
+        let renderer = renderer.with_fonts(Arc::new(fontdb::Database::new()));
+        let mut pixels = vec![0; 10 * 10 * 4];
+        renderer.rasterize(0., &mut pixels, 10, 10, 1.);
+        assert!(pixels.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn max_size_applies_to_scaled_frames() {
+        let limits = Limits {
+            max_size: 32.,
+            ..Limits::default()
+        };
+        let renderer = LottieRenderer::new(
+            String::from("test"),
+            json!({"w": 24, "h": 24, "fr": 30, "ip": 0, "op": 1, "layers": []}).to_string(),
+            None,
+            24,
+            &limits,
+        )
+        .unwrap();
+
+        assert!(renderer.render_frame(0, 1.).is_ok());
+        assert!(renderer.render_frame(0, 2.).is_err());
+    }
+}
@@ -0,0 +1,401 @@
+//! Evaluation of animatable Lottie properties.
+//!
+//! A property is either static (`{"a": 0, "k": value}`) or keyframed (`{"a": 1, "k": [...]}`).
+//! Between two keyframes the value follows the cubic bezier easing defined by the `o` (out) and
+//! `i` (in) tangents of the starting keyframe, optionally per dimension. Loop expressions repeat
+//! the keyframes outside of their range, see [`expression`].
+
+use keyframe::EasingFunction;
+use serde_json::Value;
+
+use super::expression::{self, LoopKind};
+use crate::animation::CubicBezier;
+
+/// A Lottie bezier shape with tangents relative to their vertices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BezierShape {
+    pub vertices: Vec<(f32, f32)>,
+    pub in_tangents: Vec<(f32, f32)>,
+    pub out_tangents: Vec<(f32, f32)>,
+    pub closed: bool,
+}
+
+/// Keyframe lookup result for a given frame.
+enum Sample<'a> {
+    Value(&'a Value),
+    Between {
+        keyframe: &'a Value,
+        from: &'a Value,
+        to: &'a Value,
+        t: f32,
+    },
+}
+
+/// Returns whether the property is keyframed.
+pub fn is_animated(prop: &Value) -> bool {
+    if let Some(a) = prop.get("a").and_then(|v| v.as_i64()) {
+        return a == 1;
+    }
+
+    // Some exporters omit `a`, detect keyframes by their shape instead.
+    prop.get("k")
+        .and_then(|k| k.as_array())
+        .and_then(|k| k.first())
+        .is_some_and(|first| first.get("t").is_some())
+}
+
+/// Evaluates a numeric (scalar or vector) property at `frame`.
+pub fn value(prop: &Value, frame: f32) -> Option<Vec<f32>> {
+    let k = prop.get("k")?;
+    if !is_animated(prop) {
+        return numbers(k);
+    }
+
+    let keyframes = k.as_array()?;
+    let Some(looped) = expression::loop_frame(prop, keyframes, frame) else {
+        return keyframed_value(keyframes, frame);
+    };
+
+    let mut value = keyframed_value(keyframes, looped.frame)?;
+    if looped.kind == LoopKind::Offset {
+        // Every loop continues from where the previous one ended.
+        let start = keyframed_value(keyframes, looped.start)?;
+        let end = keyframed_value(keyframes, looped.end)?;
+        for ((value, start), end) in value.iter_mut().zip(&start).zip(&end) {
+            *value += (end - start) * looped.cycles as f32;
+        }
+    }
+    Some(value)
+}
+
+fn keyframed_value(keyframes: &[Value], frame: f32) -> Option<Vec<f32>> {
+    match sample(keyframes, frame)? {
+        Sample::Value(value) => numbers(value),
+        Sample::Between {
+            keyframe,
+            from,
+            to,
+            t,
+        } => {
+            let from = numbers(from)?;
+            let to = numbers(to)?;
+            Some(
+                from.iter()
+                    .zip(&to)
+                    .enumerate()
+                    .map(|(dim, (a, b))| a + (b - a) * ease(keyframe, dim, t))
+                    .collect(),
+            )
+        }
+    }
+}
+
+/// Evaluates a scalar property at `frame`, taking the first component of vector values.
+pub fn scalar(prop: &Value, frame: f32) -> Option<f32> {
+    value(prop, frame)?.first().copied()
+}
+
+/// Evaluates a color property at `frame` as RGBA components from 0 to 1.
+///
+/// Colors without alpha are opaque, and keyframes with and without alpha blend into each other.
+/// Some exporters write components from 0 to 255, which is detected by a component above 1.
+pub fn color(prop: &Value, frame: f32) -> Option<[f32; 4]> {
+    let k = prop.get("k")?;
+    if !is_animated(prop) {
+        return rgba(&numbers(k)?);
+    }
+
+    let keyframes = k.as_array()?;
+    let frame = expression::loop_frame(prop, keyframes, frame).map_or(frame, |looped| looped.frame);
+
+    match sample(keyframes, frame)? {
+        Sample::Value(value) => rgba(&numbers(value)?),
+        Sample::Between {
+            keyframe,
+            from,
+            to,
+            t,
+        } => {
+            let from = rgba(&numbers(from)?)?;
+            let to = rgba(&numbers(to)?)?;
+            // Colors ease as a whole rather than per component.
+            let t = ease(keyframe, 0, t);
+            Some(std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t))
+        }
+    }
+}
+
+pub(super) fn rgba(components: &[f32]) -> Option<[f32; 4]> {
+    let [r, g, b, rest @ ..] = components else {
+        return None;
+    };
+
+    let range = if [r, g, b].into_iter().any(|c| *c > 1.) {
+        255.
+    } else {
+        1.
+    };
+    let a = rest.first().map_or(range, |a| *a);
+    Some([r / range, g / range, b / range, a / range])
+}
+
+/// Evaluates a bezier shape property at `frame`.
+pub fn shape(prop: &Value, frame: f32) -> Option<BezierShape> {
+    let k = prop.get("k")?;
+    if !is_animated(prop) {
+        return parse_shape(k);
+    }
+
+    let keyframes = k.as_array()?;
+    let frame = expression::loop_frame(prop, keyframes, frame).map_or(frame, |looped| looped.frame);
+
+    match sample(keyframes, frame)? {
+        Sample::Value(value) => parse_shape(value),
+        Sample::Between {
+            keyframe,
+            from,
+            to,
+            t,
+        } => {
+            let from = parse_shape(from)?;
+            let to = parse_shape(to)?;
+            if from.vertices.len() != to.vertices.len() {
+                return Some(from);
+            }
+
+            let t = ease(keyframe, 0, t);
+            let lerp = |a: &[(f32, f32)], b: &[(f32, f32)]| {
+                a.iter()
+                    .zip(b)
+                    .map(|(a, b)| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t))
+                    .collect()
+            };
+
+            Some(BezierShape {
+                vertices: lerp(&from.vertices, &to.vertices),
+                in_tangents: lerp(&from.in_tangents, &to.in_tangents),
+                out_tangents: lerp(&from.out_tangents, &to.out_tangents),
+                closed: from.closed,
+            })
+        }
+    }
+}
+
+fn keyframe_time(keyframe: &Value) -> f32 {
+    keyframe.get("t").and_then(|t| t.as_f64()).unwrap_or(0.) as f32
+}
+
+fn sample(keyframes: &[Value], frame: f32) -> Option<Sample<'_>> {
+    let first = keyframes.first()?;
+    if frame <= keyframe_time(first) {
+        return first.get("s").map(Sample::Value);
+    }
+
+    let idx = keyframes
+        .iter()
+        .rposition(|keyframe| keyframe_time(keyframe) <= frame)?;
+    let keyframe = &keyframes[idx];
+
+    let Some(next) = keyframes.get(idx + 1) else {
+        // Past the last keyframe. Older exporters end the list with a keyframe that only has a
+        // time, in which case the previous keyframe's end value is the final value.
+        return keyframe
+            .get("s")
+            .or_else(|| {
+                let prev = &keyframes[idx.checked_sub(1)?];
+                prev.get("e").or_else(|| prev.get("s"))
+            })
+            .map(Sample::Value);
+    };
+
+    let from = keyframe.get("s")?;
+    if keyframe.get("h").and_then(|h| h.as_i64()) == Some(1) {
+        return Some(Sample::Value(from));
+    }
+
+    let to = keyframe.get("e").or_else(|| next.get("s")).unwrap_or(from);
+
+    let duration = keyframe_time(next) - keyframe_time(keyframe);
+    let t = if duration > 0. {
+        ((frame - keyframe_time(keyframe)) / duration).clamp(0., 1.)
+    } else {
+        1.
+    };
+
+    Some(Sample::Between {
+        keyframe,
+        from,
+        to,
+        t,
+    })
+}
+
+/// Applies the keyframe's bezier easing for dimension `dim` to the linear progress `t`.
+fn ease(keyframe: &Value, dim: usize, t: f32) -> f32 {
+    let coord = |handle: &str, axis: &str| -> Option<f64> {
+        let value = keyframe.get(handle)?.get(axis)?;
+        match value.as_array() {
+            Some(array) => array.get(dim).or_else(|| array.last())?.as_f64(),
+            None => value.as_f64(),
+        }
+    };
+
+    let (Some(x1), Some(y1), Some(x2), Some(y2)) = (
+        coord("o", "x"),
+        coord("o", "y"),
+        coord("i", "x"),
+        coord("i", "y"),
+    ) else {
+        return t;
+    };
+
+    let curve = CubicBezier::new(x1.clamp(0., 1.), y1, x2.clamp(0., 1.), y2);
+    curve.y(f64::from(t)) as f32
+}
+
+pub(super) fn numbers(value: &Value) -> Option<Vec<f32>> {
+    if let Some(number) = value.as_f64() {
+        return Some(vec![number as f32]);
+    }
+
+    value
+        .as_array()?
+        .iter()
+        .map(|v| v.as_f64().map(|v| v as f32))
+        .collect()
+}
+
+fn point(value: &Value) -> Option<(f32, f32)> {
+    let array = value.as_array()?;
+    let x = array.first()?.as_f64()? as f32;
+    let y = array.get(1)?.as_f64()? as f32;
+    Some((x, y))
+}
+
+fn parse_shape(value: &Value) -> Option<BezierShape> {
+    // Keyframed shapes wrap the value in an array.
+    let value = match value.as_array() {
+        Some(array) => array.first()?,
+        None => value,
+    };
+
+    let points = |key: &str| -> Vec<(f32, f32)> {
+        value
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|array| array.iter().filter_map(point).collect())
+            .unwrap_or_default()
+    };
+
+    let vertices = points("v");
+    let mut in_tangents = points("i");
+    let mut out_tangents = points("o");
+    in_tangents.resize(vertices.len(), (0., 0.));
+    out_tangents.resize(vertices.len(), (0., 0.));
+
+    Some(BezierShape {
+        vertices,
+        in_tangents,
+        out_tangents,
+        closed: value.get("c").and_then(|v| v.as_bool()).unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn static_value() {
+        let prop = json!({"a": 0, "k": [1, 2, 3]});
+        assert_eq!(value(&prop, 10.), Some(vec![1., 2., 3.]));
+    }
+
+    #[test]
+    fn linear_keyframes() {
+        let prop = json!({"a": 1, "k": [
+            {"t": 0, "s": [0, 100], "o": {"x": 0, "y": 0}, "i": {"x": 1, "y": 1}},
+            {"t": 10, "s": [10, 0]},
+        ]});
+        assert_eq!(value(&prop, -5.), Some(vec![0., 100.]));
+        assert_eq!(value(&prop, 5.), Some(vec![5., 50.]));
+        assert_eq!(value(&prop, 20.), Some(vec![10., 0.]));
+    }
+
+    #[test]
+    fn bezier_tangents_shape_progress() {
+        // Ease-in: slow start, so the midpoint in time is well below the midpoint in value.
+        let prop = json!({"a": 1, "k": [
+            {"t": 0, "s": [0], "o": {"x": [0.42], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 10, "s": [100]},
+        ]});
+        let mid = scalar(&prop, 5.).unwrap();
+        assert!(mid < 40., "{mid}");
+        assert!(mid > 0., "{mid}");
+    }
+
+    #[test]
+    fn hold_keyframes() {
+        let prop = json!({"a": 1, "k": [
+            {"t": 0, "s": [1], "h": 1},
+            {"t": 10, "s": [2]},
+        ]});
+        assert_eq!(scalar(&prop, 9.), Some(1.));
+        assert_eq!(scalar(&prop, 10.), Some(2.));
+    }
+
+    #[test]
+    fn loop_out_expressions() {
+        let keyframes = json!([
+            {"t": 0, "s": [0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 10, "s": [10]},
+        ]);
+
+        let close = |actual: Option<f32>, expected: f32| {
+            let actual = actual.unwrap();
+            assert!((actual - expected).abs() < 1e-4, "{actual} vs {expected}");
+        };
+
+        let cycle = json!({"a": 1, "k": keyframes, "x": "loopOut('cycle')"});
+        close(scalar(&cycle, 13.), 3.);
+
+        let offset = json!({"a": 1, "k": keyframes, "x": "loopOut('offset')"});
+        close(scalar(&offset, 13.), 13.);
+        close(scalar(&offset, 25.), 25.);
+    }
+
+    #[test]
+    fn legacy_end_values() {
+        let prop = json!({"a": 1, "k": [
+            {"t": 0, "s": [0], "e": [10], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 10},
+        ]});
+        assert_eq!(scalar(&prop, 5.), Some(5.));
+        assert_eq!(scalar(&prop, 15.), Some(10.));
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(
+            color(&json!({"a": 0, "k": [1, 0, 0]}), 0.),
+            Some([1., 0., 0., 1.])
+        );
+        assert_eq!(
+            color(&json!({"a": 0, "k": [255, 0, 51, 255]}), 0.),
+            Some([1., 0., 0.2, 1.])
+        );
+        assert_eq!(color(&json!({"a": 0, "k": [1, 0]}), 0.), None);
+
+        // Pulses from opaque red to half-transparent orange.
+        let prop = json!({"a": 1, "k": [
+            {"t": 0, "s": [1, 0, 0], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 10, "s": [1, 0.5, 0, 0.5], "o": {"x": [0], "y": [0]}, "i": {"x": [1], "y": [1]}},
+            {"t": 20, "s": [1, 0, 0]},
+        ]});
+        assert_eq!(color(&prop, 5.), Some([1., 0.25, 0., 0.75]));
+        assert_eq!(color(&prop, 10.), Some([1., 0.5, 0., 0.5]));
+        assert_eq!(color(&prop, 20.), Some([1., 0., 0., 1.]));
+    }
+}
@@ -0,0 +1,219 @@
+//! Text layers, laid out and shaped by usvg with the fonts of the theme.
+//!
+//! Cursors use text for short badges like a digit or a zoom percentage, so only the basics of the
+//! text document are supported: font, size, fill color, justification, tracking and line breaks.
+//! Text animators and text on a path are ignored.
+
+use std::fmt::Write as _;
+
+use fontdb::Database;
+use serde_json::Value;
+use usvg::Tree;
+
+use super::property;
+
+/// Picks the text document shown at `frame`, with the index of its keyframe.
+///
+/// Documents don't interpolate, each keyframe holds until the next one.
+pub fn document_at(layer: &Value, frame: f32) -> Option<(usize, &Value)> {
+    let keyframes = layer.get("t")?.get("d")?.get("k")?.as_array()?;
+    let time = |keyframe: &Value| keyframe.get("t").and_then(|t| t.as_f64()).unwrap_or(0.);
+
+    let index = keyframes
+        .iter()
+        .rposition(|keyframe| time(keyframe) <= f64::from(frame))
+        .unwrap_or(0);
+    let document = keyframes.get(index)?.get("s")?;
+    Some((index, document))
+}
+
+/// Lays out a text document at the origin of its layer, with `opacity` from 0 to 1.
+///
+/// `composition` provides the `fonts` list that font names in the document refer to, which are
+/// looked up in `fonts`.
+pub fn layout(
+    document: &Value,
+    composition: &Value,
+    opacity: f32,
+    fonts: &Database,
+) -> Option<Tree> {
+    let text = document.get("t")?.as_str()?;
+    let number = |key: &str| document.get(key).and_then(|v| v.as_f64());
+
+    let size = number("s").unwrap_or(12.);
+    // Line height defaults to what After Effects uses for auto leading.
+    let line_height = number("lh").unwrap_or(size * 1.2);
+    let letter_spacing = number("tr").unwrap_or(0.) / 1000. * size;
+    let anchor = match document.get("j").and_then(|v| v.as_i64()) {
+        Some(1) => "end",
+        Some(2) => "middle",
+        _ => "start",
+    };
+
+    let [r, g, b, a] = document
+        .get("fc")
+        .and_then(property::numbers)
+        .and_then(|fc| property::rgba(&fc))
+        .unwrap_or([0., 0., 0., 1.])
+        .map(|c| c.clamp(0., 1.));
+    let channel = |c: f32| (c * 255.).round() as u8;
+
+    let font = document
+        .get("f")
+        .and_then(|v| v.as_str())
+        .map(|name| font(composition, name))
+        .unwrap_or_default();
+
+    let mut svg = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">"#);
+    let _ = write!(
+        svg,
+        concat!(
+            r#"<text font-family="{}" font-style="{}" font-weight="{}" font-size="{}" "#,
+            r#"letter-spacing="{}" text-anchor="{}" fill="rgb({},{},{})" fill-opacity="{}" "#,
+            r#"xml:space="preserve">"#,
+        ),
+        escape(&font.family),
+        if font.italic { "italic" } else { "normal" },
+        font.weight,
+        size,
+        letter_spacing,
+        anchor,
+        channel(r),
+        channel(g),
+        channel(b),
+        a * opacity.clamp(0., 1.),
+    );
+    // Lottie separates lines with carriage returns.
+    for (i, line) in text.split(['\r', '\n']).enumerate() {
+        let _ = write!(
+            svg,
+            r#"<tspan x="0" y="{}">{}</tspan>"#,
+            i as f64 * line_height,
+            escape(line)
+        );
+    }
+    svg.push_str("</text></svg>");
+
+    match Tree::from_str(&svg, &usvg::Options::default(), fonts) {
+        Ok(tree) => Some(tree),
+        Err(err) => {
+            debug!("error laying out Lottie text: {err}");
+            None
+        }
+    }
+}
+
+/// Font of a text document, looked up in the composition's `fonts` list.
+struct Font {
+    family: String,
+    italic: bool,
+    weight: u16,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self {
+            family: String::from("sans-serif"),
+            italic: false,
+            weight: 400,
+        }
+    }
+}
+
+fn font(composition: &Value, name: &str) -> Font {
+    let entry = composition
+        .get("fonts")
+        .and_then(|fonts| fonts.get("list"))
+        .and_then(|list| list.as_array())
+        .and_then(|list| {
+            list.iter()
+                .find(|font| font.get("fName").and_then(|v| v.as_str()) == Some(name))
+        });
+    let Some(entry) = entry else {
+        // Without a fonts list, the name is usually a family name.
+        return Font {
+            family: name.to_owned(),
+            ..Font::default()
+        };
+    };
+
+    let style = entry
+        .get("fStyle")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let weight = if style.contains("black") || style.contains("heavy") {
+        900
+    } else if style.contains("bold") {
+        700
+    } else if style.contains("medium") {
+        500
+    } else if style.contains("light") {
+        300
+    } else {
+        400
+    };
+
+    Font {
+        family: entry
+            .get("fFamily")
+            .and_then(|v| v.as_str())
+            .unwrap_or(name)
+            .to_owned(),
+        italic: style.contains("italic") || style.contains("oblique"),
+        weight,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn documents_hold_until_the_next_keyframe() {
+        let layer = json!({"ty": 5, "t": {"d": {"k": [
+            {"s": {"t": "100%"}, "t": 0},
+            {"s": {"t": "200%"}, "t": 10},
+        ]}}});
+        let text = |frame| document_at(&layer, frame).map(|(i, doc)| (i, doc["t"].clone()));
+
+        assert_eq!(text(0.), Some((0, json!("100%"))));
+        assert_eq!(text(9.5), Some((0, json!("100%"))));
+        assert_eq!(text(10.), Some((1, json!("200%"))));
+    }
+
+    #[test]
+    fn fonts_from_the_fonts_list() {
+        let composition = json!({"fonts": {"list": [
+            {"fName": "Inter-BoldItalic", "fFamily": "Inter", "fStyle": "Bold Italic"},
+        ]}});
+
+        let bold = font(&composition, "Inter-BoldItalic");
+        assert_eq!(bold.family, "Inter");
+        assert_eq!(bold.weight, 700);
+        assert!(bold.italic);
+
+        assert_eq!(font(&composition, "Cantarell").family, "Cantarell");
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(escape(r#"<a & "b">"#), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
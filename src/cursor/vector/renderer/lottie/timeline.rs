@@ -0,0 +1,218 @@
+//! Detection of frame ranges where nothing animates.
+//!
+//! Cursors like "wait" often hold a pose for many frames. Finding the frames that look exactly
+//! like the one before them lets the renderer reuse that frame's buffer instead of rasterizing it
+//! again. The analysis is conservative: anything it can't reason about, like expressions or
+//! precompositions, counts as animating.
+
+use serde_json::Value;
+
+use super::property;
+
+/// For every frame of the composition, the first frame of the static run it belongs to.
+///
+/// Frames that may differ from their predecessor map to themselves.
+pub fn static_runs(composition: &Value, total_frames: u32) -> Vec<u32> {
+    let mut changes = Changes::new(total_frames);
+
+    if let Some(layers) = composition.get("layers").and_then(|v| v.as_array()) {
+        for layer in layers {
+            layer_changes(layer, &mut changes);
+        }
+    }
+
+    let mut runs = Vec::with_capacity(total_frames as usize);
+    for frame in 0..total_frames {
+        let start = match runs.last() {
+            Some(&previous) if !changes.0[frame as usize] => previous,
+            _ => frame,
+        };
+        runs.push(start);
+    }
+    runs
+}
+
+/// Whether each frame may differ from the frame before it.
+struct Changes(Vec<bool>);
+
+impl Changes {
+    fn new(total_frames: u32) -> Self {
+        Self(vec![false; total_frames as usize])
+    }
+
+    /// Marks the frame at which an instant change at `time` becomes visible.
+    fn point(&mut self, time: f32) {
+        let frame = time.ceil();
+        if frame >= 0. && (frame as usize) < self.0.len() {
+            self.0[frame as usize] = true;
+        }
+    }
+
+    /// Marks every frame affected by a continuous change between `from` and `to`.
+    fn range(&mut self, from: f32, to: f32) {
+        let first = (from.floor() + 1.).max(0.) as usize;
+        let last = (to.ceil().max(0.) as usize).min(self.0.len().saturating_sub(1));
+        for frame in first..=last {
+            self.0[frame] = true;
+        }
+    }
+}
+
+fn layer_changes(layer: &Value, changes: &mut Changes) {
+    let number = |key: &str| layer.get(key).and_then(|v| v.as_f64()).map(|v| v as f32);
+    let in_point = number("ip").unwrap_or(0.);
+    let out_point = number("op").unwrap_or(changes.0.len() as f32);
+    let start = number("st").unwrap_or(0.);
+
+    // The layer appears and disappears.
+    changes.point(in_point);
+    changes.point(out_point);
+
+    // Precompositions play content this analysis doesn't look into.
+    let animated_remap = layer.get("tm").is_some_and(property::is_animated);
+    if layer.get("ty").and_then(|v| v.as_i64()) == Some(0) || animated_remap {
+        changes.range(in_point - 1., out_point);
+        return;
+    }
+
+    // A static time remap freezes the layer content, but its transform keeps animating.
+    if layer.get("tm").is_some() {
+        let transform = layer.get("ks");
+        if !transform.map_or(true, |ks| property_changes(ks, start, changes)) {
+            changes.range(in_point - 1., out_point);
+        }
+        return;
+    }
+
+    if !property_changes(layer, start, changes) {
+        changes.range(in_point - 1., out_point);
+    }
+}
+
+/// Marks the changes of every property in `value`, shifted by the layer `start`.
+///
+/// Returns false if some property can't be analyzed and the whole layer must count as animated.
+fn property_changes(value: &Value, start: f32, changes: &mut Changes) -> bool {
+    match value {
+        Value::Object(object) => {
+            // Expressions may change the value at any time.
+            if object.get("x").is_some_and(|x| x.is_string()) {
+                return false;
+            }
+
+            if property::is_animated(value) {
+                if let Some(keyframes) = object.get("k").and_then(|k| k.as_array()) {
+                    keyframe_changes(keyframes, start, changes);
+                }
+                return true;
+            }
+
+            object
+                .values()
+                .all(|child| property_changes(child, start, changes))
+        }
+        Value::Array(array) => array
+            .iter()
+            .all(|child| property_changes(child, start, changes)),
+        _ => true,
+    }
+}
+
+fn keyframe_changes(keyframes: &[Value], start: f32, changes: &mut Changes) {
+    let time =
+        |keyframe: &Value| keyframe.get("t").and_then(|t| t.as_f64()).unwrap_or(0.) as f32 + start;
+
+    for pair in keyframes.windows(2) {
+        let (keyframe, next) = (&pair[0], &pair[1]);
+        let (from, to) = (time(keyframe), time(next));
+
+        let value = keyframe.get("s");
+        // Older files store the end value in `e` instead of the next keyframe's `s`.
+        let end = keyframe.get("e").or_else(|| next.get("s"));
+        if value == end {
+            continue;
+        }
+
+        if keyframe.get("h").and_then(|v| v.as_i64()) == Some(1) {
+            changes.point(to);
+        } else {
+            changes.range(from, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn keyframe(t: f32, s: f32) -> Value {
+        json!({"t": t, "s": [s, 0]})
+    }
+
+    #[test]
+    fn hold_between_keyframes() {
+        let composition = json!({"layers": [{"ty": 4, "ks": {"p": {"a": 1, "k": [
+            keyframe(0., 0.),
+            keyframe(10., 5.),
+            keyframe(40., 5.),
+            keyframe(50., 0.),
+        ]}}}]});
+        let runs = static_runs(&composition, 60);
+
+        assert_eq!(runs[..11], (0..=10).collect::<Vec<_>>());
+        assert!(runs[11..=40].iter().all(|&frame| frame == 10));
+        assert_eq!(runs[41..=50], (41..=50).collect::<Vec<_>>());
+        assert!(runs[51..].iter().all(|&frame| frame == 50));
+    }
+
+    #[test]
+    fn hold_keyframes_and_start_offset() {
+        let composition = json!({"layers": [{"ty": 4, "st": 5, "ks": {"o": {"a": 1, "k": [
+            {"t": 0, "s": [100], "h": 1},
+            {"t": 10, "s": [0]},
+        ]}}}]});
+        let runs = static_runs(&composition, 30);
+
+        assert!(runs[..15].iter().all(|&frame| frame == 0));
+        assert!(runs[15..].iter().all(|&frame| frame == 15));
+    }
+
+    #[test]
+    fn static_time_remap_keeps_transform_keyframes() {
+        let position = json!({"a": 1, "k": [keyframe(10., 0.), keyframe(20., 5.)]});
+        let shape = json!({"a": 1, "k": [
+            {"t": 0, "s": [{"v": [[0, 0]]}]},
+            {"t": 30, "s": [{"v": [[1, 1]]}]},
+        ]});
+        let composition = json!({"layers": [{
+            "ty": 4,
+            "tm": {"a": 0, "k": 0},
+            "ks": {"p": position},
+            "shapes": [{"ty": "sh", "ks": shape}],
+        }]});
+        let runs = static_runs(&composition, 30);
+
+        // The frozen shape doesn't count, the moving layer does.
+        assert!(runs[..=10].iter().all(|&frame| frame == 0));
+        assert_eq!(runs[11..=20], (11..=20).collect::<Vec<_>>());
+        assert!(runs[21..].iter().all(|&frame| frame == 20));
+    }
+
+    #[test]
+    fn layer_bounds_and_expressions() {
+        let composition = json!({"layers": [
+            {"ty": 4, "ip": 5, "op": 20},
+            {"ty": 4, "ip": 25, "op": 28, "ks": {"r": {"a": 0, "k": 0, "x": "time * 360"}}},
+        ]});
+        let runs = static_runs(&composition, 30);
+
+        assert_eq!(runs[4], 0);
+        assert_eq!(runs[5], 5);
+        assert_eq!(runs[19], 5);
+        assert_eq!(runs[24], 20);
+        assert_eq!(runs[25..=28], [25, 26, 27, 28]);
+        assert_eq!(runs[29], 28);
+    }
+}
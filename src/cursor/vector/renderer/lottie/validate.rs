@@ -0,0 +1,501 @@
+//! Checks a composition for problems that would render a blank or broken cursor.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Compositions longer than this are assumed to be broken.
+pub const MAX_FRAMES: f64 = 36_000.;
+
+/// Shape items the built-in rasterizer draws.
+const SUPPORTED_SHAPES: &[&str] = &["gr", "sh", "fl", "st", "tr"];
+
+/// Bounds on the work a composition may cause, so that a bloated file can't stall rendering.
+///
+/// Configured per theme in the `[lottie_limits]` table of `theme.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Largest width and height of a rendered frame, in pixels at the output scale.
+    pub max_size: f64,
+    /// Most layers, including the layers of precomposition assets.
+    pub max_layers: usize,
+    /// Largest estimated raster cost, see [`raster_cost`].
+    pub max_raster_cost: f64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_size: 2048.,
+            max_layers: 1000,
+            // A 256×256 composition with 1000 fills and strokes.
+            max_raster_cost: 256. * 256. * 1000.,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the size of a frame about to be rasterized.
+    pub fn check_raster(&self, width: f64, height: f64) -> Result<(), Issue> {
+        if width > self.max_size || height > self.max_size {
+            return Err(Issue::TooLarge {
+                width,
+                height,
+                max: self.max_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// The composition has no `layers` array.
+    MissingLayers,
+    /// `w` or `h` is not a positive number.
+    InvalidSize { width: f64, height: f64 },
+    /// `fr` is not a positive number.
+    InvalidFrameRate(f64),
+    /// `op` is not after `ip`.
+    EmptyFrameRange { in_point: f64, out_point: f64 },
+    /// The composition has more than [`MAX_FRAMES`] frames.
+    TooManyFrames(f64),
+    /// A frame scaled for an output is wider or taller than [`Limits::max_size`].
+    TooLarge { width: f64, height: f64, max: f64 },
+    /// There are more layers than [`Limits::max_layers`].
+    TooManyLayers { count: usize, max: usize },
+    /// The estimated raster cost is above [`Limits::max_raster_cost`].
+    TooExpensive { cost: f64, max: f64 },
+    /// A layer or shape uses something the rasterizer ignores.
+    Unsupported { path: String, feature: String },
+    /// Layers of a type the rasterizer skips, reported once per type.
+    UnsupportedLayers { ty: LayerType, paths: Vec<String> },
+}
+
+/// The kind of a layer, from its `ty`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerType {
+    Precomposition,
+    Solid,
+    Image,
+    /// Layer without content, used for parenting and the hotspot.
+    Null,
+    Shape,
+    Text,
+    /// A type this renderer doesn't know, or a missing `ty`.
+    Unknown(Option<i64>),
+}
+
+impl LayerType {
+    pub fn of(layer: &Value) -> Self {
+        match layer.get("ty").and_then(|v| v.as_i64()) {
+            Some(0) => LayerType::Precomposition,
+            Some(1) => LayerType::Solid,
+            Some(2) => LayerType::Image,
+            Some(3) => LayerType::Null,
+            Some(4) => LayerType::Shape,
+            Some(5) => LayerType::Text,
+            ty => LayerType::Unknown(ty),
+        }
+    }
+
+    /// Whether the built-in rasterizer handles layers of this type.
+    ///
+    /// Text is laid out by usvg, so it needs the `svg` feature.
+    pub fn is_supported(self) -> bool {
+        match self {
+            LayerType::Null | LayerType::Shape => true,
+            LayerType::Text => cfg!(feature = "svg"),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for LayerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerType::Precomposition => write!(f, "precomposition layer"),
+            LayerType::Solid => write!(f, "solid layer"),
+            LayerType::Image => write!(f, "image layer"),
+            LayerType::Null => write!(f, "null layer"),
+            LayerType::Shape => write!(f, "shape layer"),
+            LayerType::Text => write!(f, "text layer"),
+            LayerType::Unknown(Some(ty)) => write!(f, "layer type {ty}"),
+            LayerType::Unknown(None) => write!(f, "layer without a type"),
+        }
+    }
+}
+
+/// Whether a layer or shape item is hidden (`hd`), i.e. toggled off in the design tool.
+///
+/// Hidden layers and shapes are never drawn. The `hotspot` layer is usually hidden and still
+/// provides the hotspot.
+pub fn is_hidden(item: &Value) -> bool {
+    item.get("hd").and_then(|v| v.as_bool()) == Some(true)
+}
+
+impl Issue {
+    /// Whether the cursor can't be rendered at all because of this issue.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            Issue::Unsupported { .. } | Issue::UnsupportedLayers { .. }
+        )
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::MissingLayers => write!(f, "composition has no layers"),
+            Issue::InvalidSize { width, height } => {
+                write!(f, "invalid composition size {width}x{height}")
+            }
+            Issue::InvalidFrameRate(fr) => write!(f, "invalid frame rate {fr}"),
+            Issue::EmptyFrameRange {
+                in_point,
+                out_point,
+            } => write!(f, "out point {out_point} is not after in point {in_point}"),
+            Issue::TooManyFrames(frames) => {
+                write!(
+                    f,
+                    "composition has {frames} frames, at most {MAX_FRAMES} are allowed"
+                )
+            }
+            Issue::TooLarge { width, height, max } => {
+                write!(f, "frame size {width}x{height} is above the limit of {max}")
+            }
+            Issue::TooManyLayers { count, max } => {
+                write!(
+                    f,
+                    "composition has {count} layers, at most {max} are allowed"
+                )
+            }
+            Issue::TooExpensive { cost, max } => {
+                write!(
+                    f,
+                    "estimated raster cost {cost} is above the limit of {max}"
+                )
+            }
+            Issue::Unsupported { path, feature } => write!(f, "{path}: {feature} is not supported"),
+            Issue::UnsupportedLayers { ty, paths } => {
+                write!(f, "{}: {ty} is not supported", paths.join(", "))
+            }
+        }
+    }
+}
+
+/// The fatal issues that prevented loading a composition.
+#[derive(Debug, Clone)]
+pub struct ValidationError(pub Vec<Issue>);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid Lottie composition")?;
+        for issue in &self.0 {
+            write!(f, "; {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Collects the issues of a composition, including exceeded `limits`.
+pub fn validate(composition: &Value, limits: &Limits) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let number = |key: &str| composition.get(key).and_then(|v| v.as_f64());
+
+    let width = number("w").unwrap_or(24.);
+    let height = number("h").unwrap_or(24.);
+    if width <= 0. || height <= 0. {
+        issues.push(Issue::InvalidSize { width, height });
+    }
+
+    let frame_rate = number("fr").unwrap_or(60.);
+    if frame_rate <= 0. {
+        issues.push(Issue::InvalidFrameRate(frame_rate));
+    }
+
+    let in_point = number("ip").unwrap_or(0.);
+    let out_point = number("op").unwrap_or(0.);
+    if out_point <= in_point {
+        issues.push(Issue::EmptyFrameRange {
+            in_point,
+            out_point,
+        });
+    } else if out_point > MAX_FRAMES {
+        issues.push(Issue::TooManyFrames(out_point));
+    }
+
+    match composition.get("layers").and_then(|v| v.as_array()) {
+        Some(layers) => {
+            for (i, layer) in layers.iter().enumerate() {
+                if !is_hidden(layer) {
+                    check_layer(layer, &format!("layers[{i}]"), &mut issues);
+                }
+            }
+        }
+        None => issues.push(Issue::MissingLayers),
+    }
+
+    let layers = all_layers(composition);
+    if layers.len() > limits.max_layers {
+        issues.push(Issue::TooManyLayers {
+            count: layers.len(),
+            max: limits.max_layers,
+        });
+    }
+
+    let cost = raster_cost(composition);
+    if cost > limits.max_raster_cost {
+        issues.push(Issue::TooExpensive {
+            cost,
+            max: limits.max_raster_cost,
+        });
+    }
+
+    issues
+}
+
+/// Estimates the work of rasterizing one frame at scale 1.
+///
+/// Every fill and stroke may cover the whole composition, so the estimate is the composition area
+/// times the number of fills and strokes.
+pub fn raster_cost(composition: &Value) -> f64 {
+    let number = |key: &str| composition.get(key).and_then(|v| v.as_f64());
+    let area = number("w").unwrap_or(24.).max(0.) * number("h").unwrap_or(24.).max(0.);
+
+    let paints: usize = all_layers(composition)
+        .into_iter()
+        .filter(|layer| !is_hidden(layer))
+        .filter_map(|layer| layer.get("shapes").and_then(|v| v.as_array()))
+        .map(Vec::as_slice)
+        .map(count_paints)
+        .sum();
+
+    area * paints.max(1) as f64
+}
+
+/// Layers of the composition and of its precomposition assets.
+fn all_layers(composition: &Value) -> Vec<&Value> {
+    let assets = composition
+        .get("assets")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten();
+
+    std::iter::once(composition)
+        .chain(assets)
+        .filter_map(|owner| owner.get("layers").and_then(|v| v.as_array()))
+        .flatten()
+        .collect()
+}
+
+fn count_paints(items: &[Value]) -> usize {
+    items
+        .iter()
+        .filter(|item| !is_hidden(item))
+        .map(|item| match item.get("ty").and_then(|v| v.as_str()) {
+            Some("fl" | "st") => 1,
+            _ => item
+                .get("it")
+                .and_then(|v| v.as_array())
+                .map_or(0, |items| count_paints(items)),
+        })
+        .sum()
+}
+
+fn check_layer(layer: &Value, path: &str, issues: &mut Vec<Issue>) {
+    let ty = LayerType::of(layer);
+    if !ty.is_supported() {
+        let known = issues.iter_mut().find_map(|issue| match issue {
+            Issue::UnsupportedLayers { ty: known, paths } if *known == ty => Some(paths),
+            _ => None,
+        });
+        match known {
+            Some(paths) => paths.push(path.to_owned()),
+            None => issues.push(Issue::UnsupportedLayers {
+                ty,
+                paths: vec![path.to_owned()],
+            }),
+        }
+    }
+
+    let mut unsupported = |feature: &str| {
+        issues.push(Issue::Unsupported {
+            path: path.to_owned(),
+            feature: feature.to_owned(),
+        })
+    };
+
+    if layer.get("hasMask").and_then(|v| v.as_bool()) == Some(true) {
+        unsupported("mask");
+    }
+    if layer.get("tt").is_some() {
+        unsupported("track matte");
+    }
+    if layer
+        .get("ef")
+        .and_then(|v| v.as_array())
+        .is_some_and(|ef| !ef.is_empty())
+    {
+        unsupported("layer effect");
+    }
+
+    if let Some(shapes) = layer.get("shapes").and_then(|v| v.as_array()) {
+        check_shapes(shapes, &format!("{path}.shapes"), issues);
+    }
+}
+
+fn check_shapes(items: &[Value], path: &str, issues: &mut Vec<Issue>) {
+    for (i, item) in items.iter().enumerate() {
+        let item_path = format!("{path}[{i}]");
+        let Some(ty) = item.get("ty").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if is_hidden(item) {
+            continue;
+        }
+
+        if !SUPPORTED_SHAPES.contains(&ty) {
+            issues.push(Issue::Unsupported {
+                path: item_path.clone(),
+                feature: format!("shape '{ty}'"),
+            });
+        }
+
+        if let Some(group_items) = item.get("it").and_then(|v| v.as_array()) {
+            check_shapes(group_items, &format!("{item_path}.it"), issues);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn valid_composition() {
+        let composition = json!({
+            "w": 24, "h": 24, "fr": 30, "ip": 0, "op": 30,
+            "layers": [{"ty": 4, "shapes": [{"ty": "gr", "it": [
+                {"ty": "sh"}, {"ty": "fl"}, {"ty": "tr"}
+            ]}]}],
+        });
+        assert_eq!(validate(&composition, &Limits::default()), vec![]);
+    }
+
+    #[test]
+    fn fatal_issues() {
+        let issues = validate(
+            &json!({"w": 0, "h": 24, "fr": 0, "op": 0}),
+            &Limits::default(),
+        );
+        assert_eq!(
+            issues,
+            vec![
+                Issue::InvalidSize {
+                    width: 0.,
+                    height: 24.
+                },
+                Issue::InvalidFrameRate(0.),
+                Issue::EmptyFrameRange {
+                    in_point: 0.,
+                    out_point: 0.
+                },
+                Issue::MissingLayers,
+            ]
+        );
+        assert!(issues.iter().all(Issue::is_fatal));
+
+        let issues = validate(&json!({"op": 1e9, "layers": []}), &Limits::default());
+        assert_eq!(issues, vec![Issue::TooManyFrames(1e9)]);
+    }
+
+    #[test]
+    fn unsupported_features() {
+        let composition = json!({"op": 10, "layers": [
+            {"ty": 2},
+            {"ty": 4, "tt": 1, "shapes": [{"ty": "gr", "it": [{"ty": "gf"}]}]},
+        ]});
+        let issues = validate(&composition, &Limits::default());
+        assert!(!issues.iter().any(Issue::is_fatal));
+        assert_eq!(
+            issues.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "layers[0]: image layer is not supported",
+                "layers[1]: track matte is not supported",
+                "layers[1].shapes[0].it[0]: shape 'gf' is not supported",
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_and_unsupported_layers() {
+        let composition = json!({"op": 10, "layers": [
+            {"ty": 2},
+            {"ty": 4, "hd": true, "tt": 1},
+            {"ty": 4, "shapes": [{"ty": "gf", "hd": true}]},
+            {"ty": 2},
+            {"ty": 3, "hd": true, "nm": "hotspot"},
+            {"ty": 9},
+        ]});
+        let issues = validate(&composition, &Limits::default());
+        assert_eq!(
+            issues.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "layers[0], layers[3]: image layer is not supported",
+                "layers[5]: layer type 9 is not supported",
+            ]
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let limits = Limits {
+            max_size: 100.,
+            max_layers: 2,
+            max_raster_cost: 50. * 50. * 2.,
+        };
+        let paint = json!({"ty": "gr", "it": [{"ty": "sh"}, {"ty": "fl"}, {"ty": "st"}]});
+
+        let composition = json!({"w": 50, "h": 50, "op": 10, "layers": [
+            {"ty": 4, "shapes": [paint.clone()]},
+        ]});
+        assert_eq!(raster_cost(&composition), 50. * 50. * 2.);
+        assert_eq!(validate(&composition, &limits), vec![]);
+
+        let composition = json!({"w": 8000, "h": 50, "op": 10, "layers": [
+            {"ty": 4, "shapes": [paint, {"ty": "fl"}]},
+            {"ty": 3},
+        ], "assets": [{"id": "comp", "layers": [{"ty": 3}]}]});
+        let issues = validate(&composition, &limits);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::TooManyLayers { count: 3, max: 2 },
+                Issue::TooExpensive {
+                    cost: 8000. * 50. * 3.,
+                    max: 50. * 50. * 2.
+                },
+            ]
+        );
+        assert!(issues.iter().all(Issue::is_fatal));
+
+        // The size is checked once scaled for an output.
+        assert_eq!(limits.check_raster(100., 50.), Ok(()));
+        assert_eq!(
+            limits.check_raster(150., 75.),
+            Err(Issue::TooLarge {
+                width: 150.,
+                height: 75.,
+                max: 100.
+            })
+        );
+    }
+}
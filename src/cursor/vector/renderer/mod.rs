@@ -1,21 +1,74 @@
+#[cfg(feature = "svg")]
+pub mod css;
+#[cfg(feature = "svg")]
+pub mod fonts;
+#[cfg(feature = "lottie")]
 pub mod lottie;
+#[cfg(feature = "svg")]
+pub mod rewrite;
+#[cfg(feature = "rlottie")]
+pub mod rlottie;
+#[cfg(feature = "svg")]
+pub mod smil;
+#[cfg(feature = "svg")]
+pub mod sprite;
+#[cfg(feature = "svg")]
+pub mod stylesheet;
+#[cfg(feature = "svg")]
 pub mod svg;
+#[cfg(feature = "svg")]
+pub mod svg_diagnostics;
+#[cfg(feature = "svg")]
+pub mod svg_limits;
+#[cfg(feature = "svg")]
+pub mod svg_outline;
+#[cfg(feature = "svg")]
+pub mod symbolic;
+#[cfg(feature = "svg")]
+pub mod vars;
 
+#[cfg(feature = "lottie")]
 pub use lottie::LottieRenderer;
+#[cfg(feature = "svg")]
 pub use svg::SvgRenderer;
 
 use anyhow::Result;
-use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
-use smithay::utils::Point;
+use smithay::utils::{Physical, Point};
+
+use crate::cursor::vector::morph::Outline;
+use crate::cursor::vector::types::{FrameRange, RenderedFrame};
 
 pub trait VectorRenderer: Send + Sync {
-    fn render_frame(&self, frame: u32, scale: i32) -> Result<RenderedFrameData>;
-    fn hotspot(&self) -> Point<i32, smithay::utils::Physical>;
+    /// Rasterizes `frame` at `scale`, which may be fractional.
+    fn render_frame(&self, frame: u32, scale: f64) -> Result<RenderedFrame>;
+    fn hotspot(&self) -> Point<i32, Physical>;
     fn total_frames(&self) -> u32;
     fn frame_duration_ms(&self) -> u32;
-}
 
-pub struct RenderedFrameData {
-    pub buffer: MemoryRenderBuffer,
-    pub hotspot: Point<i32, smithay::utils::Physical>,
+    /// Hotspot of `frame` rendered at `scale`, without rasterizing it.
+    fn frame_hotspot(&self, _frame: u32, scale: f64) -> Point<i32, Physical> {
+        self.hotspot().to_f64().upscale(scale).to_i32_round()
+    }
+
+    /// Rasterizes the artwork at `frame`, which may fall between two frames, for formats that
+    /// can interpolate their keyframes. Others render the frame it falls into.
+    fn render_frame_at(&self, frame: f32, scale: f64) -> Result<RenderedFrame> {
+        self.render_frame(frame as u32, scale)
+    }
+
+    /// Frames of the named segment, for formats that support markers.
+    fn segment(&self, _name: &str) -> Option<FrameRange> {
+        None
+    }
+
+    /// Filled shapes of `frame` at `scale`, for formats that know the geometry of their artwork
+    /// and can be morphed.
+    fn outline(&self, _frame: u32, _scale: f64) -> Option<Outline> {
+        None
+    }
+
+    /// Frees cached frames and pooled memory after a long idle period.
+    ///
+    /// Renderers may keep the most recently shown frame.
+    fn trim_caches(&self) {}
 }
@@ -0,0 +1,190 @@
+//! Rewriting of SVG documents into the frames of their animations.
+//!
+//! usvg renders a static document, so animated documents are sampled: for each frame, the
+//! animation samplers set attributes on the start tags of their targets and remove elements that
+//! usvg shouldn't see, and the rewritten document is parsed on its own.
+
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Frame rate animated SVGs are sampled at.
+pub const FRAME_RATE: f64 = 30.;
+
+/// Most frames sampled per document, longer animations are sampled more sparsely.
+pub const MAX_FRAMES: usize = 240;
+
+/// Number of frames and duration of each frame to sample a `period` in seconds at.
+pub fn frames(period: f64) -> (usize, Duration) {
+    if period <= 0. || !period.is_finite() {
+        return (1, Duration::ZERO);
+    }
+
+    let frames = ((period * FRAME_RATE).ceil() as usize).clamp(1, MAX_FRAMES);
+    (frames, Duration::from_secs_f64(period / frames as f64))
+}
+
+/// Edits to one frame of a document, by byte ranges of the original text.
+pub struct Rewrite<'a> {
+    text: &'a str,
+    removed: Vec<Range<usize>>,
+    /// Start tags with their attributes in order, with raw (still escaped) values.
+    tags: Vec<(Range<usize>, Vec<(String, String)>)>,
+}
+
+impl<'a> Rewrite<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            removed: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Removes an element from the frame.
+    pub fn remove(&mut self, range: Range<usize>) {
+        self.removed.push(range);
+    }
+
+    /// Attributes of the start tag at `tag`, which edits change in place.
+    pub fn attributes(&mut self, tag: &Range<usize>) -> &mut Vec<(String, String)> {
+        let index = match self.tags.iter().position(|(range, _)| range == tag) {
+            Some(index) => index,
+            None => {
+                let attributes = parse_attributes(&self.text[tag.clone()]);
+                self.tags.push((tag.clone(), attributes));
+                self.tags.len() - 1
+            }
+        };
+        &mut self.tags[index].1
+    }
+
+    /// Sets an attribute of the start tag at `tag`.
+    pub fn set(&mut self, tag: &Range<usize>, name: &str, value: String) {
+        let attributes = self.attributes(tag);
+        match attributes.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = value,
+            None => attributes.push((name.to_owned(), value)),
+        }
+    }
+
+    /// The rewritten document.
+    pub fn finish(self) -> String {
+        let mut edits: Vec<(Range<usize>, String)> = self
+            .removed
+            .into_iter()
+            .map(|range| (range, String::new()))
+            .collect();
+        for (range, attributes) in self.tags {
+            let tag = write_tag(&self.text[range.clone()], &attributes);
+            edits.push((range, tag));
+        }
+
+        // Applied back to front so earlier ranges stay valid.
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut text = self.text.to_owned();
+        let mut last_start = usize::MAX;
+        for (range, replacement) in edits {
+            // Edits inside removed elements were removed with them.
+            if range.end > last_start {
+                continue;
+            }
+            last_start = range.start;
+            text.replace_range(range, &replacement);
+        }
+        text
+    }
+}
+
+/// Byte range of the start tag beginning at `start`.
+pub fn start_tag(text: &str, start: usize) -> Range<usize> {
+    let mut quote = None;
+    for (i, c) in text[start..].char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('>', None) => return start..start + i + 1,
+            _ => {}
+        }
+    }
+    start..text.len()
+}
+
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let inner = inner.strip_suffix('/').unwrap_or(inner);
+    // Skip the element name.
+    let mut rest = inner
+        .find(char::is_whitespace)
+        .map_or("", |name_end| &inner[name_end..]);
+
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        let Some(eq) = rest.find('=') else {
+            break;
+        };
+        let name = rest[..eq].trim().to_owned();
+        let value_part = rest[eq + 1..].trim_start();
+        let Some(quote) = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        else {
+            break;
+        };
+        let Some(end) = value_part[1..].find(quote) else {
+            break;
+        };
+        attributes.push((name, value_part[1..end + 1].to_owned()));
+        rest = &value_part[end + 2..];
+    }
+    attributes
+}
+
+/// Writes a start tag with the name of `tag` and `attributes`.
+fn write_tag(tag: &str, attributes: &[(String, String)]) -> String {
+    let inner = tag.trim_start_matches('<');
+    let name_end = inner
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .unwrap_or(inner.len());
+
+    let mut out = format!("<{}", &inner[..name_end]);
+    for (name, value) in attributes {
+        let quote = if value.contains('"') { '\'' } else { '"' };
+        let _ = write!(out, " {name}={quote}{value}{quote}");
+    }
+    out.push_str(if tag.ends_with("/>") { "/>" } else { ">" });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_tags_and_removes_elements() {
+        let text = r#"<svg><g id="a" opacity='0.5'><set/></g><rect x="1"/></svg>"#;
+        let g = start_tag(text, text.find("<g").unwrap());
+        let rect = start_tag(text, text.find("<rect").unwrap());
+        assert_eq!(&text[g.clone()], r#"<g id="a" opacity='0.5'>"#);
+
+        let mut rewrite = Rewrite::new(text);
+        rewrite.set(&g, "opacity", String::from("1"));
+        rewrite.set(&rect, "y", String::from("2"));
+        let set = text.find("<set/>").unwrap();
+        rewrite.remove(set..set + "<set/>".len());
+
+        assert_eq!(
+            rewrite.finish(),
+            r#"<svg><g id="a" opacity="1"></g><rect x="1" y="2"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn frame_counts() {
+        assert_eq!(frames(0.), (1, Duration::ZERO));
+        assert_eq!(frames(1.).0, 30);
+        assert_eq!(frames(60.).0, MAX_FRAMES);
+    }
+}
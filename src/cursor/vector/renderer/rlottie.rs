@@ -0,0 +1,38 @@
+//! Lottie rendering through rlottie, enabled with the `rlottie` feature.
+
+use parking_lot::Mutex;
+use rlottie::{Animation, Size, Surface};
+
+pub struct RlottieEngine {
+    animation: Mutex<Animation>,
+    total_frames: usize,
+}
+
+impl RlottieEngine {
+    /// Parses the composition with rlottie, returning `None` if rlottie rejects it.
+    pub fn new(cursor_id: &str, lottie_data: &str) -> Option<Self> {
+        // An empty resource path: image assets are expected to be embedded.
+        let animation = Animation::from_data(lottie_data, cursor_id, "")?;
+        let total_frames = animation.totalframe();
+
+        Some(Self {
+            animation: Mutex::new(animation),
+            total_frames,
+        })
+    }
+
+    /// Renders `frame` into a premultiplied ARGB8888 buffer of the given size.
+    pub fn render(&self, frame: u32, width: i32, height: i32) -> Vec<u8> {
+        let mut surface = Surface::new(Size::new(width as usize, height as usize));
+
+        let frame = if self.total_frames > 0 {
+            frame as usize % self.total_frames
+        } else {
+            0
+        };
+        self.animation.lock().render(frame, &mut surface);
+
+        // rlottie's BGRA8 pixels are ARGB8888 in little-endian memory order.
+        surface.data_as_bytes().to_vec()
+    }
+}
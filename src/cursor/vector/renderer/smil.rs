@@ -0,0 +1,425 @@
+//! Sampling of SMIL-animated SVG documents.
+//!
+//! Every `<animate>`, `<animateTransform>` and `<set>` is applied to its target at the frame's
+//! time, and removed from the frame.
+//!
+//! Timing supports offset `begin` values, `dur`, `repeatCount`, `repeatDur` and `fill`. Values
+//! come from `values` (with optional `keyTimes`) or `from`/`to`/`by`, and interpolate linearly
+//! when they're numbers, number lists or `#rrggbb` colors, and discretely otherwise.
+//! `<animateMotion>`, event-based begins and `keySplines` aren't supported.
+
+use std::fmt::Write as _;
+use std::ops::Range;
+
+use roxmltree::{Document, Node};
+
+use super::rewrite::{self, Rewrite};
+
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+/// The animations of an SVG document.
+pub struct Animation {
+    animations: Vec<Animate>,
+    /// Length of one pass through all animations, which the cursor loops.
+    period: f64,
+}
+
+/// One animation element.
+struct Animate {
+    /// Byte range of the animation element, removed from every sampled document.
+    range: Range<usize>,
+    /// Byte range of the target's start tag.
+    target: Range<usize>,
+    attribute: String,
+    /// For `<animateTransform>`, the transform function.
+    transform: Option<String>,
+    /// Whether the value is added to the target's transform instead of replacing it.
+    additive: bool,
+    values: Vec<String>,
+    key_times: Option<Vec<f64>>,
+    discrete: bool,
+    begin: f64,
+    dur: f64,
+    /// Active duration, infinite for indefinite repeats.
+    active: f64,
+    freeze: bool,
+}
+
+impl Animation {
+    /// Finds the animation elements of a document, `None` if it has none.
+    pub fn parse(document: &Document) -> Option<Self> {
+        let mut animations = Vec::new();
+        for node in document.descendants().filter(Node::is_element) {
+            let name = node.tag_name().name();
+            match name {
+                "animate" | "animateTransform" | "set" => {
+                    if let Some(animate) = Animate::parse(document, node) {
+                        animations.push(animate);
+                    }
+                }
+                "animateMotion" | "animateColor" => {
+                    warn!("SVG <{name}> is not supported, ignoring it");
+                }
+                _ => {}
+            }
+        }
+
+        if animations.is_empty() {
+            return None;
+        }
+
+        let period = animations
+            .iter()
+            .map(|animate| {
+                let active = if animate.active.is_finite() {
+                    animate.active
+                } else {
+                    animate.dur
+                };
+                animate.begin + active
+            })
+            .fold(0., f64::max);
+
+        Some(Self { animations, period })
+    }
+
+    /// Length of one pass through all animations in seconds, which the cursor loops.
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    /// Applies the animations at `time` seconds to a frame.
+    pub fn apply(&self, time: f64, rewrite: &mut Rewrite) {
+        for animate in &self.animations {
+            rewrite.remove(animate.range.clone());
+            if let Some(value) = animate.value_at(time) {
+                animate.apply(rewrite.attributes(&animate.target), value);
+            }
+        }
+    }
+}
+
+impl Animate {
+    fn parse(document: &Document, node: Node) -> Option<Self> {
+        let attribute = |name: &str| node.attribute(name);
+        let element = node.tag_name().name();
+
+        let target = match node
+            .attribute((XLINK_NS, "href"))
+            .or_else(|| attribute("href"))
+        {
+            Some(href) => {
+                let id = href.strip_prefix('#')?;
+                document
+                    .descendants()
+                    .find(|node| node.attribute("id") == Some(id))?
+            }
+            None => node.parent_element()?,
+        };
+
+        let attribute_name = match element {
+            "animateTransform" => String::from("transform"),
+            _ => attribute("attributeName")?.to_owned(),
+        };
+
+        let values = if let Some(values) = attribute("values") {
+            values
+                .split(';')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_owned)
+                .collect()
+        } else {
+            let to = attribute("to");
+            let from = attribute("from").or_else(|| target.attribute(attribute_name.as_str()));
+            match (from, to, attribute("by")) {
+                (_, Some(to), _) if element == "set" => vec![to.to_owned()],
+                (Some(from), Some(to), _) => vec![from.to_owned(), to.to_owned()],
+                (None, Some(to), _) => vec![to.to_owned()],
+                (Some(from), None, Some(by)) => {
+                    let to = add_numbers(from, by)?;
+                    vec![from.to_owned(), to]
+                }
+                _ => return None,
+            }
+        };
+        if values.is_empty() {
+            return None;
+        }
+
+        let key_times = attribute("keyTimes").and_then(|key_times| {
+            let key_times: Vec<f64> = key_times
+                .split(';')
+                .map(|t| t.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            (key_times.len() == values.len()).then_some(key_times)
+        });
+
+        let begin = attribute("begin")
+            .and_then(|begin| begin.split(';').next())
+            .and_then(clock_value)
+            .unwrap_or(0.);
+        // A `set` without a duration lasts forever.
+        let dur = attribute("dur").and_then(clock_value).unwrap_or(0.);
+        if dur <= 0. && element != "set" {
+            return None;
+        }
+
+        let repeat_count = match attribute("repeatCount") {
+            Some("indefinite") => Some(f64::INFINITY),
+            Some(count) => count.parse().ok(),
+            None => None,
+        };
+        let repeat_dur = match attribute("repeatDur") {
+            Some("indefinite") => Some(f64::INFINITY),
+            Some(dur) => clock_value(dur),
+            None => None,
+        };
+        let active = match (repeat_count, repeat_dur) {
+            (None, None) if element == "set" && dur <= 0. => f64::INFINITY,
+            (None, None) => dur,
+            (Some(count), None) => dur * count,
+            (None, Some(repeat_dur)) => repeat_dur,
+            (Some(count), Some(repeat_dur)) => (dur * count).min(repeat_dur),
+        };
+
+        let start = node.range().start;
+        Some(Self {
+            range: start..node.range().end,
+            target: rewrite::start_tag(document.input_text(), target.range().start),
+            attribute: attribute_name,
+            transform: (element == "animateTransform")
+                .then(|| attribute("type").unwrap_or("translate").to_owned()),
+            additive: attribute("additive") == Some("sum"),
+            values,
+            key_times,
+            discrete: element == "set" || attribute("calcMode") == Some("discrete"),
+            begin,
+            dur,
+            active,
+            freeze: element == "set" || attribute("fill") == Some("freeze"),
+        })
+    }
+
+    /// Value of the animation at `time`, `None` where it doesn't apply.
+    fn value_at(&self, time: f64) -> Option<String> {
+        let local = time - self.begin;
+        if local < 0. {
+            return None;
+        }
+
+        let progress = if local >= self.active {
+            if !self.freeze {
+                return None;
+            }
+            // Frozen at the end of the last iteration.
+            let rest = self.active % self.dur;
+            if rest == 0. || !rest.is_finite() {
+                1.
+            } else {
+                rest / self.dur
+            }
+        } else if self.dur > 0. {
+            (local % self.dur) / self.dur
+        } else {
+            1.
+        };
+
+        Some(self.interpolate(progress))
+    }
+
+    fn interpolate(&self, progress: f64) -> String {
+        let count = self.values.len();
+        if count == 1 {
+            return self.values[0].clone();
+        }
+
+        let key_times = self.key_times.clone().unwrap_or_else(|| {
+            let intervals = if self.discrete { count } else { count - 1 };
+            (0..count).map(|i| i as f64 / intervals as f64).collect()
+        });
+
+        // The last key time at or before `progress`.
+        let index = key_times
+            .iter()
+            .rposition(|t| *t <= progress)
+            .unwrap_or(0)
+            .min(count - 1);
+        if self.discrete || index == count - 1 {
+            return self.values[index].clone();
+        }
+
+        let span = key_times[index + 1] - key_times[index];
+        let t = if span > 0. {
+            (progress - key_times[index]) / span
+        } else {
+            1.
+        };
+        lerp(&self.values[index], &self.values[index + 1], t)
+            .unwrap_or_else(|| self.values[if t < 0.5 { index } else { index + 1 }].clone())
+    }
+
+    fn apply(&self, attributes: &mut Vec<(String, String)>, value: String) {
+        let value = match &self.transform {
+            Some(function) => format!("{function}({value})"),
+            None => value,
+        };
+
+        match attributes
+            .iter_mut()
+            .find(|(name, _)| *name == self.attribute)
+        {
+            Some((_, existing)) if self.additive => {
+                *existing = format!("{existing} {value}");
+            }
+            Some((_, existing)) => *existing = value,
+            None => attributes.push((self.attribute.clone(), value)),
+        }
+    }
+}
+
+/// Parses a clock value like `2s`, `500ms`, `1.5`, `0:02` or `1min` into seconds.
+fn clock_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+
+    if value.contains(':') {
+        let mut seconds = 0.;
+        for part in value.split(':') {
+            seconds = seconds * 60. + part.parse::<f64>().ok()?;
+        }
+        return Some(seconds);
+    }
+
+    let (number, factor) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 0.001)
+    } else if let Some(min) = value.strip_suffix("min") {
+        (min, 60.)
+    } else if let Some(h) = value.strip_suffix('h') {
+        (h, 3600.)
+    } else if let Some(s) = value.strip_suffix('s') {
+        (s, 1.)
+    } else {
+        (value, 1.)
+    };
+    number.trim().parse::<f64>().ok().map(|n| n * factor)
+}
+
+fn numbers(value: &str) -> Option<Vec<f64>> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect()
+}
+
+fn color(value: &str) -> Option<[f64; 3]> {
+    let hex = value.strip_prefix('#')?;
+    let channel = |i: usize, len: usize| {
+        let digits = hex.get(i * len..(i + 1) * len)?;
+        let value = u8::from_str_radix(digits, 16).ok()?;
+        Some(f64::from(if len == 1 { value * 17 } else { value }))
+    };
+    let len = match hex.len() {
+        3 => 1,
+        6 => 2,
+        _ => return None,
+    };
+    Some([channel(0, len)?, channel(1, len)?, channel(2, len)?])
+}
+
+/// Interpolates two numbers, number lists or colors, `None` if they aren't compatible.
+fn lerp(from: &str, to: &str, t: f64) -> Option<String> {
+    if let (Some(from), Some(to)) = (color(from), color(to)) {
+        let mut value = String::from("#");
+        for (from, to) in from.into_iter().zip(to) {
+            let _ = write!(value, "{:02x}", (from + (to - from) * t).round() as u8);
+        }
+        return Some(value);
+    }
+
+    let from = numbers(from)?;
+    let to = numbers(to)?;
+    if from.len() != to.len() || from.is_empty() {
+        return None;
+    }
+    let values: Vec<String> = from
+        .iter()
+        .zip(&to)
+        .map(|(from, to)| (from + (to - from) * t).to_string())
+        .collect();
+    Some(values.join(" "))
+}
+
+fn add_numbers(from: &str, by: &str) -> Option<String> {
+    let from = numbers(from)?;
+    let by = numbers(by)?;
+    (from.len() == by.len()).then(|| {
+        let values: Vec<String> = from
+            .iter()
+            .zip(&by)
+            .map(|(a, b)| (a + b).to_string())
+            .collect();
+        values.join(" ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(svg: &str, time: f64) -> String {
+        let document = Document::parse(svg).unwrap();
+        let mut rewrite = Rewrite::new(svg);
+        Animation::parse(&document)
+            .unwrap()
+            .apply(time, &mut rewrite);
+        rewrite.finish()
+    }
+
+    const SPINNER: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+<rect width="4" height="4" fill="#000000">
+<animate attributeName="fill" values="#000000;#ffffff" dur="1s" repeatCount="indefinite"/>
+<animateTransform attributeName="transform" type="rotate" from="0 12 12" to="360 12 12" dur="1s" repeatCount="indefinite"/>
+</rect>
+</svg>"##;
+
+    #[test]
+    fn clock_values() {
+        assert_eq!(clock_value("2s"), Some(2.));
+        assert_eq!(clock_value("500ms"), Some(0.5));
+        assert_eq!(clock_value("1.5"), Some(1.5));
+        assert_eq!(clock_value("1:30"), Some(90.));
+        assert_eq!(clock_value("indefinite"), None);
+    }
+
+    #[test]
+    fn samples_period() {
+        let document = Document::parse(SPINNER).unwrap();
+        assert_eq!(Animation::parse(&document).unwrap().period(), 1.);
+
+        let half = sample(SPINNER, 0.5);
+        assert!(!half.contains("<animate"));
+        assert!(half.contains(r##"fill="#808080""##), "{half}");
+        assert!(half.contains(r#"transform="rotate(180 12 12)""#), "{half}");
+        roxmltree::Document::parse(&half).unwrap();
+    }
+
+    #[test]
+    fn static_documents() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="1" height="1"/></svg>"#;
+        assert!(Animation::parse(&Document::parse(svg).unwrap()).is_none());
+    }
+
+    #[test]
+    fn fill_and_key_times() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg">
+<circle r="1"><animate attributeName="r" values="1;5;10" keyTimes="0;0.8;1" begin="1s" dur="2s" fill="freeze"/></circle>
+</svg>"#;
+        let document = Document::parse(svg).unwrap();
+        assert_eq!(Animation::parse(&document).unwrap().period(), 3.);
+
+        assert!(sample(svg, 0.5).contains(r#"r="1""#));
+        assert!(sample(svg, 1.8).contains(r#"r="3""#));
+        assert!(sample(svg, 5.).contains(r#"r="10""#));
+    }
+}
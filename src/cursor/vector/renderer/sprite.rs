@@ -0,0 +1,119 @@
+//! SVG sprite sheets: one top-level group per frame.
+//!
+//! Groups that are children of the root `<svg>` and have the ids `frame-0`, `frame-1`, … are the
+//! frames of the animation, in order of their number. Each frame shows only its own group, and
+//! everything outside of the frame groups is shared by all frames. The root can set the duration
+//! of a frame in milliseconds with `data-frame-duration`.
+
+use std::ops::Range;
+use std::time::Duration;
+
+use roxmltree::Document;
+
+use super::rewrite::{Rewrite, MAX_FRAMES};
+
+/// Duration of a frame without `data-frame-duration`.
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// The frame groups of an SVG document.
+pub struct SpriteSheet {
+    /// Byte ranges of the frame groups, in frame order.
+    frames: Vec<Range<usize>>,
+    frame_duration: Duration,
+}
+
+impl SpriteSheet {
+    /// Finds the frame groups of a document, `None` if it has fewer than two.
+    pub fn parse(document: &Document) -> Option<Self> {
+        let root = document.root_element();
+        let mut frames: Vec<(u32, Range<usize>)> = root
+            .children()
+            .filter(|node| node.has_tag_name("g"))
+            .filter_map(|node| {
+                let index = node.attribute("id")?.strip_prefix("frame-")?.parse().ok()?;
+                Some((index, node.range()))
+            })
+            .collect();
+        if frames.len() < 2 {
+            return None;
+        }
+
+        frames.sort_by_key(|(index, _)| *index);
+        if frames.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            warn!("SVG sprite sheet has duplicate frame groups, using the first of each");
+            frames.dedup_by_key(|(index, _)| *index);
+        }
+        if frames.len() > MAX_FRAMES {
+            warn!(
+                "SVG sprite sheet has {} frames, only using the first {MAX_FRAMES}",
+                frames.len()
+            );
+            frames.truncate(MAX_FRAMES);
+        }
+
+        let frame_duration = root
+            .attribute("data-frame-duration")
+            .and_then(|ms| ms.trim().parse::<f64>().ok())
+            .filter(|ms| ms.is_finite() && *ms > 0.)
+            .map_or(DEFAULT_FRAME_DURATION, |ms| {
+                Duration::from_secs_f64(ms / 1000.)
+            });
+
+        Some(Self {
+            frames: frames.into_iter().map(|(_, range)| range).collect(),
+            frame_duration,
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    /// Removes the groups of every frame but `frame`.
+    pub fn apply(&self, frame: usize, rewrite: &mut Rewrite) {
+        for (i, range) in self.frames.iter().enumerate() {
+            if i != frame {
+                rewrite.remove(range.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SVG: &str = r#"<svg data-frame-duration="50"><rect/><g id="frame-1"><b/></g><g id="frame-0"><a/></g><g><g id="frame-2"/></g></svg>"#;
+
+    #[test]
+    fn frames_in_order() {
+        let document = Document::parse(SVG).unwrap();
+        let sheet = SpriteSheet::parse(&document).unwrap();
+        assert_eq!(sheet.frame_count(), 2);
+        assert_eq!(sheet.frame_duration(), Duration::from_millis(50));
+
+        let frame = |i| {
+            let mut rewrite = Rewrite::new(SVG);
+            sheet.apply(i, &mut rewrite);
+            rewrite.finish()
+        };
+        assert_eq!(
+            frame(0),
+            r#"<svg data-frame-duration="50"><rect/><g id="frame-0"><a/></g><g><g id="frame-2"/></g></svg>"#
+        );
+        assert_eq!(
+            frame(1),
+            r#"<svg data-frame-duration="50"><rect/><g id="frame-1"><b/></g><g><g id="frame-2"/></g></svg>"#
+        );
+    }
+
+    #[test]
+    fn single_group_is_static() {
+        let document = Document::parse(r#"<svg><g id="frame-0"/></svg>"#).unwrap();
+        assert!(SpriteSheet::parse(&document).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+//! Style sheets of the theme, added to SVG cursors.
+//!
+//! The theme-wide `stylesheet` and the one of a cursor are added to the end of the document, so
+//! they take precedence over the document's own style sheets at the same specificity, and over
+//! every presentation attribute. Themes can restyle artwork without editing it, e.g. hide an
+//! optional shadow layer with `#shadow { display: none }`.
+
+use roxmltree::Document;
+
+use super::rewrite;
+
+/// Adds `css` as the last child of the document element.
+///
+/// The CSS goes into a CDATA section as it is, so it must not contain `]]>`.
+/// Documents that roxmltree can't parse and empty ones are returned unchanged.
+pub fn inject(svg: &str, css: &str) -> String {
+    let document = match Document::parse(svg) {
+        Ok(document) => document,
+        Err(err) => {
+            debug!("not adding style sheet to SVG: {err}");
+            return svg.to_owned();
+        }
+    };
+
+    let root = document.root_element().range();
+    // An empty `<svg/>` has nothing to style.
+    if rewrite::start_tag(svg, root.start).end == root.end {
+        return svg.to_owned();
+    }
+    let Some(close) = svg[..root.end].rfind("</") else {
+        return svg.to_owned();
+    };
+
+    let style = format!("<style><![CDATA[{css}]]></style>");
+    let mut out = String::with_capacity(svg.len() + style.len());
+    out.push_str(&svg[..close]);
+    out.push_str(&style);
+    out.push_str(&svg[close..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_to_root() {
+        assert_eq!(
+            inject(
+                "<svg><style>path{fill:red}</style><path/></svg>\n",
+                "path { fill: blue }"
+            ),
+            "<svg><style>path{fill:red}</style><path/><style><![CDATA[path { fill: blue }]]></style></svg>\n"
+        );
+        assert_eq!(inject("<svg/>", "path { fill: blue }"), "<svg/>");
+    }
+}
@@ -1,20 +1,48 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use fontdb::Database;
+use roxmltree::Document;
 use smithay::backend::allocator::Fourcc;
 use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
-use smithay::utils::{Physical, Point, Transform};
-use tiny_skia::Pixmap;
+use smithay::utils::{Physical, Point, Rectangle, Size, Transform};
+use tiny_skia::PixmapMut;
 use usvg::Tree;
 
-use super::{RenderedFrameData, VectorRenderer};
+use super::rewrite::{self, Rewrite};
+use super::svg_diagnostics::{self, Diagnostic};
+use super::svg_limits::Limits;
+use super::{css, fonts, smil, sprite, svg_outline, VectorRenderer};
+use crate::cursor::hotspot::HotspotSpec;
+use crate::cursor::vector::effects::Effects;
+use crate::cursor::vector::frame_cache::{self, FrameCache};
+use crate::cursor::vector::morph::{Outline, Shape};
+use crate::cursor::vector::scale;
+use crate::cursor::vector::types::RenderedFrame;
+use crate::cursor::vector::watermark::{self, Producer};
+
+/// Id of the element whose center is the hotspot.
+const HOTSPOT_ID: &str = "hotspot";
 
 pub struct SvgRenderer {
     _cursor_id: String,
-    tree: Tree,
-    hotspot: Option<(i32, i32)>,
-    _base_size: u8,
+    /// One tree per frame, a single one for static documents.
+    frames: Vec<Tree>,
+    frame_duration: Duration,
+    hotspot: HotspotSpec,
+    /// Pixels per user unit at scale 1, so that the longest side of the artwork is as long as
+    /// the cursor size.
+    design_scale: f64,
     width: f32,
     height: f32,
+    /// Places the artwork on the canvas of `width` × `height`, rotated if the theme asks for it.
+    rotation: usvg::Transform,
+    effects: Effects,
+    /// Rendered frames by scale, since the same document always renders the same pixels.
+    rendered: parking_lot::Mutex<FrameCache>,
+    limits: Limits,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl SvgRenderer {
@@ -24,85 +52,522 @@ impl SvgRenderer {
         hotspot: Option<(i32, i32)>,
         base_size: u8,
     ) -> Result<Self> {
-        let fontdb = Database::default();
-        let tree = Tree::from_str(&svg_data, &usvg::Options::default(), &fontdb)
-            .context("Failed to parse SVG")?;
+        Self::load(
+            cursor_id,
+            svg_data,
+            hotspot,
+            base_size,
+            &Limits::default(),
+            fonts::database,
+        )
+    }
+
+    /// Like [`Self::new`], within `limits` and drawing text with the fonts from `fonts`.
+    ///
+    /// `fonts` is only called for documents with text, so that cursors without any don't wait
+    /// for the system fonts to be scanned.
+    pub fn load(
+        cursor_id: String,
+        svg_data: String,
+        hotspot: Option<(i32, i32)>,
+        base_size: u8,
+        limits: &Limits,
+        fonts: impl FnOnce() -> Arc<Database>,
+    ) -> Result<Self> {
+        // Documents roxmltree can't read are only checked once usvg parsed them, and may have
+        // text too.
+        let (has_text, diagnostics) = match Document::parse(&svg_data) {
+            Ok(document) => {
+                limits
+                    .check_source(&document)
+                    .with_context(|| format!("SVG cursor {cursor_id} is too complex"))?;
+                let has_text = document.descendants().any(|node| node.has_tag_name("text"));
+                (has_text, svg_diagnostics::check(&document))
+            }
+            Err(_) => (true, Vec::new()),
+        };
+        let fontdb = if has_text {
+            fonts()
+        } else {
+            Arc::new(Database::new())
+        };
+        let options = usvg::Options::default();
+        let parse = |data: &str| Tree::from_str(data, &options, &fontdb);
+
+        // A marker element in the artwork overrides the configured hotspot.
+        let (marker, svg_data) = match take_hotspot_marker(&cursor_id, &svg_data, &parse) {
+            Some((position, text)) => {
+                if hotspot.is_some() {
+                    debug!("hotspot marker of {cursor_id} overrides the configured hotspot");
+                }
+                (Some(position), text)
+            }
+            None => (None, svg_data),
+        };
 
-        let size = tree.size();
+        // SMIL and CSS animations are sampled into one document per frame; documents that
+        // roxmltree can't read (e.g. compressed ones) are left to usvg as static images.
+        let sampled = match Document::parse(&svg_data) {
+            Ok(document) => sample(&document, &svg_data),
+            Err(err) => {
+                debug!("not sampling SVG animations of {cursor_id}: {err}");
+                None
+            }
+        };
+        let (frames, frame_duration) = match sampled {
+            Some((texts, duration)) => {
+                let frames = texts
+                    .iter()
+                    .map(|text| parse(text))
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to parse animated SVG frame")?;
+                (frames, duration)
+            }
+            None => (
+                vec![parse(&svg_data).context("Failed to parse SVG")?],
+                Duration::ZERO,
+            ),
+        };
+
+        for tree in &frames {
+            limits
+                .check_tree(tree)
+                .with_context(|| format!("SVG cursor {cursor_id} is too complex"))?;
+        }
+
+        let size = frames[0].size();
         let width = size.width() as f32;
         let height = size.height() as f32;
+        let canvas = (f64::from(width), f64::from(height));
+        let hotspot = match marker {
+            Some(position) => HotspotSpec::new(position, canvas),
+            None => HotspotSpec::from_config(hotspot, canvas),
+        };
+        let longest = width.max(height);
+        let design_scale = if longest > 0. && base_size > 0 {
+            f64::from(base_size) / f64::from(longest)
+        } else {
+            1.
+        };
 
         Ok(Self {
             _cursor_id: cursor_id,
-            tree,
+            frames,
+            frame_duration,
             hotspot,
-            _base_size: base_size,
+            design_scale,
             width,
             height,
+            rotation: usvg::Transform::default(),
+            effects: Effects::default(),
+            rendered: parking_lot::Mutex::new(FrameCache::new(frame_cache::DEFAULT_BUDGET)),
+            limits: *limits,
+            diagnostics,
         })
     }
 
-    fn render_to_buffer(&self, scale: i32) -> Result<RenderedFrameData> {
-        let scaled_width = (self.width * scale as f32).ceil() as i32;
-        let scaled_height = (self.height * scale as f32).ceil() as i32;
+    /// Features of the document that aren't rendered, such as unsupported filters.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
 
-        let size = scaled_width as usize * scaled_height as usize;
-        let mut pixels = vec![0u8; size * 4];
+    /// Rotates the artwork clockwise by `degrees` around its center, along with the hotspot.
+    ///
+    /// The canvas grows to the bounding box of the rotated artwork, which keeps its size.
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        // Quarter turns must swap the sides exactly, not leave a sliver that rounds up.
+        let clean = |v: f64| if v.abs() < 1e-9 { 0. } else { v };
+        let (sin, cos) = (clean(sin), clean(cos));
 
-        let mut pixmap = Pixmap::new(scaled_width as u32, scaled_height as u32)
-            .context("Failed to create pixmap")?;
+        let (width, height) = (f64::from(self.width), f64::from(self.height));
+        let rotated_width = width * cos.abs() + height * sin.abs();
+        let rotated_height = width * sin.abs() + height * cos.abs();
 
-        let transform = usvg::Transform::from_scale(scale as f32, scale as f32);
-        resvg::render(&self.tree, transform, &mut pixmap.as_mut());
+        let (x, y) = self.hotspot.position;
+        let (dx, dy) = (x - width / 2., y - height / 2.);
+        let position = (
+            rotated_width / 2. + dx * cos - dy * sin,
+            rotated_height / 2. + dx * sin + dy * cos,
+        );
+        self.hotspot = HotspotSpec::new(position, (rotated_width, rotated_height));
 
-        let pixmap_data = pixmap.data();
-        for (i, chunk) in pixmap_data.chunks(4).enumerate() {
-            if i * 4 + 4 <= pixels.len() {
-                pixels[i * 4] = chunk[2];
-                pixels[i * 4 + 1] = chunk[1];
-                pixels[i * 4 + 2] = chunk[0];
-                pixels[i * 4 + 3] = chunk[3];
-            }
+        self.rotation = usvg::Transform::from_translate(
+            (rotated_width / 2.) as f32,
+            (rotated_height / 2.) as f32,
+        )
+        .pre_rotate(degrees as f32)
+        .pre_translate(-self.width / 2., -self.height / 2.);
+        self.width = rotated_width as f32;
+        self.height = rotated_height as f32;
+        self.rendered.get_mut().clear();
+        self
+    }
+
+    /// Sets the post-processing applied to every rendered frame.
+    pub fn with_effects(mut self, effects: Effects) -> Self {
+        self.effects = effects;
+        self.rendered.get_mut().clear();
+        self
+    }
+
+    /// Size in pixels of frames rendered at `scale`.
+    fn scaled_size(&self, scale: f64) -> (i32, i32) {
+        // The compositor draws the buffer at a whole logical size, which covers
+        // `round(logical × scale)` physical pixels. A buffer of exactly that many pixels maps 1:1
+        // onto the output at fractional scales too, instead of being resampled.
+        let logical_width = (f64::from(self.width) * self.design_scale).ceil();
+        let logical_height = (f64::from(self.height) * self.design_scale).ceil();
+        (
+            (logical_width * scale).round() as i32,
+            (logical_height * scale).round() as i32,
+        )
+    }
+
+    /// Transform drawing the artwork at `scale`.
+    fn render_transform(&self, scale: f64) -> usvg::Transform {
+        // The buffer keeps the requested scale, only the artwork is drawn at another size.
+        let content_scale = (scale * self.design_scale) as f32;
+        usvg::Transform::from_scale(content_scale, content_scale).pre_concat(self.rotation)
+    }
+
+    fn render_to_buffer(&self, frame: u32, scale: f64) -> Result<RenderedFrame> {
+        let tree = &self.frames[frame as usize % self.frames.len()];
+        let (scaled_width, scaled_height) = self.scaled_size(scale);
+
+        if scaled_width <= 0 || scaled_height <= 0 {
+            anyhow::bail!("SVG has an empty canvas");
         }
+        self.limits.check_raster(scaled_width, scaled_height)?;
+        let size = Size::from((scaled_width, scaled_height));
 
-        let buffer = MemoryRenderBuffer::from_slice(
-            &pixels,
+        // resvg draws straight into the buffer's memory, which starts out transparent.
+        let mut buffer = MemoryRenderBuffer::new(
             Fourcc::Argb8888,
-            (scaled_width, scaled_height),
-            scale,
+            size,
+            scale::memory_buffer_scale(scale),
             Transform::Normal,
             None,
         );
+        let transform = self.render_transform(scale);
+        buffer.render().draw(|pixels| {
+            {
+                let mut pixmap =
+                    PixmapMut::from_bytes(pixels, scaled_width as u32, scaled_height as u32)
+                        .context("Failed to create pixmap")?;
+                resvg::render(tree, transform, &mut pixmap);
+            }
 
-        let hotspot = if let Some((hx, hy)) = self.hotspot {
-            Point::new(hx * scale, hy * scale)
-        } else {
-            Point::new(0, 0)
-        };
+            // tiny-skia produces RGBA, the buffer wants ARGB8888 (BGRA in memory).
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            self.effects.apply(pixels);
+            self.effects.apply_mirror(pixels, scaled_width);
+            if self.effects.watermark {
+                watermark::stamp(pixels, scaled_width, Producer::Svg);
+            }
 
-        Ok(RenderedFrameData {
+            anyhow::Ok(vec![Rectangle::from_size(size)])
+        })?;
+
+        Ok(RenderedFrame::new(
             buffer,
-            hotspot: hotspot.to_physical(scale),
-        })
+            size,
+            scale,
+            self.frame_hotspot(frame, scale),
+            (self.frames.len() > 1).then_some(self.frame_duration),
+        ))
+    }
+}
+
+/// Finds the element with `id="hotspot"`, returning its center in user units and the document
+/// without it.
+///
+/// The marker is located through usvg so that transforms and the `viewBox` apply, which needs it
+/// to be drawn; an invisible marker is ignored.
+fn take_hotspot_marker(
+    cursor_id: &str,
+    text: &str,
+    parse: impl Fn(&str) -> Result<Tree, usvg::Error>,
+) -> Option<((f64, f64), String)> {
+    let document = Document::parse(text).ok()?;
+    let marker = document
+        .descendants()
+        .find(|node| node.attribute("id") == Some(HOTSPOT_ID))?;
+
+    let tree = parse(text).ok()?;
+    let Some(node) = tree.node_by_id(HOTSPOT_ID) else {
+        warn!("hotspot marker of {cursor_id} isn't drawn, ignoring it");
+        return None;
+    };
+    let bounds = node.abs_bounding_box();
+    let center = (
+        f64::from(bounds.left() + bounds.width() / 2.),
+        f64::from(bounds.top() + bounds.height() / 2.),
+    );
+
+    let mut rewrite = Rewrite::new(text);
+    rewrite.remove(marker.range());
+    Some((center, rewrite.finish()))
+}
+
+/// Samples the frames of a sprite sheet, or else the SMIL and CSS animations of a document, into
+/// the text of each frame, `None` for static documents.
+fn sample(document: &Document, text: &str) -> Option<(Vec<String>, Duration)> {
+    if let Some(sheet) = sprite::SpriteSheet::parse(document) {
+        let frames = (0..sheet.frame_count())
+            .map(|frame| {
+                let mut rewrite = Rewrite::new(text);
+                sheet.apply(frame, &mut rewrite);
+                rewrite.finish()
+            })
+            .collect();
+        return Some((frames, sheet.frame_duration()));
     }
+
+    let smil = smil::Animation::parse(document);
+    let css = css::Animation::parse(document);
+    if smil.is_none() && css.is_none() {
+        return None;
+    }
+
+    let period = smil
+        .iter()
+        .map(smil::Animation::period)
+        .chain(css.iter().map(css::Animation::period))
+        .fold(0., f64::max);
+    let (count, duration) = rewrite::frames(period);
+    let frames = (0..count)
+        .map(|i| {
+            let time = duration.as_secs_f64() * i as f64;
+            let mut rewrite = Rewrite::new(text);
+            if let Some(smil) = &smil {
+                smil.apply(time, &mut rewrite);
+            }
+            if let Some(css) = &css {
+                css.apply(time, &mut rewrite);
+            }
+            rewrite.finish()
+        })
+        .collect();
+    Some((frames, duration))
 }
 
 impl VectorRenderer for SvgRenderer {
-    fn render_frame(&self, frame: u32, scale: i32) -> Result<RenderedFrameData> {
-        let _ = frame;
-        self.render_to_buffer(scale)
+    fn render_frame(&self, frame: u32, scale: f64) -> Result<RenderedFrame> {
+        let frame = frame % self.frames.len() as u32;
+        if let Some(rendered) = self.rendered.lock().get(frame, scale) {
+            return Ok(rendered);
+        }
+
+        let rendered = self.render_to_buffer(frame, scale)?;
+        self.rendered.lock().insert(frame, scale, rendered.clone());
+        Ok(rendered)
     }
 
     fn hotspot(&self) -> Point<i32, Physical> {
-        let (hx, hy) = self.hotspot.unwrap_or((0, 0));
-        Point::from((hx, hy))
+        let width = (f64::from(self.width) * self.design_scale).ceil() as i32;
+        self.effects
+            .mirror_hotspot(self.hotspot.resolve(self.design_scale), width)
     }
 
     fn total_frames(&self) -> u32 {
-        1
+        self.frames.len() as u32
+    }
+
+    fn frame_hotspot(&self, _frame: u32, scale: f64) -> Point<i32, Physical> {
+        let (scaled_width, _) = self.scaled_size(scale);
+        self.effects.mirror_hotspot(
+            self.hotspot.resolve(scale * self.design_scale),
+            scaled_width,
+        )
     }
 
     fn frame_duration_ms(&self) -> u32 {
-        0
+        self.frame_duration.as_millis() as u32
+    }
+
+    fn outline(&self, frame: u32, scale: f64) -> Option<Outline> {
+        let tree = &self.frames[frame as usize % self.frames.len()];
+        let (width, _) = self.scaled_size(scale);
+        let hotspot = self
+            .effects
+            .mirror_hotspot(self.hotspot.resolve(scale * self.design_scale), width);
+
+        let polygons = svg_outline::polygons(tree, self.render_transform(scale));
+        let shapes: Vec<_> = polygons
+            .into_iter()
+            .filter_map(|(polygon, color)| {
+                let polygon: Vec<_> = polygon
+                    .into_iter()
+                    .map(|(x, y)| {
+                        let x = if self.effects.mirror {
+                            width as f32 - x
+                        } else {
+                            x
+                        };
+                        (x - hotspot.x as f32, y - hotspot.y as f32)
+                    })
+                    .collect();
+                Shape::new(&polygon, color)
+            })
+            .collect();
+        (!shapes.is_empty()).then_some(Outline { shapes })
+    }
+
+    fn trim_caches(&self) {
+        self.rendered.lock().trim();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotspot_marker() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24" viewBox="0 0 48 48"><circle id="hotspot" cx="10" cy="20" r="2"/></svg>"#;
+        let renderer =
+            SvgRenderer::new(String::from("default"), svg.to_owned(), Some((1, 1)), 24).unwrap();
+
+        assert_eq!(renderer.hotspot.position, (5., 10.));
+        let frame = renderer.render_frame(0, 1.).unwrap();
+        let pixels = frame.read_pixels();
+        // The marker isn't drawn: the pixel under it stays transparent.
+        assert_eq!(pixels[(10 * 24 + 5) * 4 + 3], 0);
+    }
+
+    #[test]
+    fn fits_base_size() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="256" height="128"><rect width="256" height="128"/></svg>"#;
+        let renderer =
+            SvgRenderer::new(String::from("default"), svg.to_owned(), Some((64, 32)), 24).unwrap();
+
+        let frame = renderer.render_frame(0, 2.).unwrap();
+        assert_eq!(frame.size, Size::from((48, 24)));
+        assert_eq!(frame.hotspot, Point::from((12, 6)));
+        assert_eq!(renderer.hotspot(), Point::from((6, 3)));
+    }
+
+    #[test]
+    fn rotation_turns_canvas_and_hotspot() {
+        // A vertical bar with the hotspot at its top.
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="24"><rect width="8" height="24"/></svg>"#;
+        let load = || {
+            SvgRenderer::new(String::from("ns-resize"), svg.to_owned(), Some((4, 0)), 24).unwrap()
+        };
+
+        let renderer = load().with_rotation(90.);
+        let frame = renderer.render_frame(0, 1.).unwrap();
+        assert_eq!(frame.size, Size::from((24, 8)));
+        assert_eq!(frame.hotspot, Point::from((23, 4)));
+        // The bar now fills the canvas.
+        assert_eq!(frame.read_pixels()[3], 255);
+
+        let renderer = load().with_rotation(45.);
+        let frame = renderer.render_frame(0, 1.).unwrap();
+        // 8 × cos 45° + 24 × sin 45° ≈ 22.6 on both sides.
+        assert_eq!(frame.size, Size::from((23, 23)));
+        // Top-right, where the top of the bar went.
+        assert_eq!(frame.hotspot, Point::from((20, 3)));
+    }
+
+    #[test]
+    fn fractional_scales_are_pixel_exact() {
+        // 24×10.8 at scale 1, so the cursor is 24×11 logical pixels.
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="9"><rect width="20" height="9"/></svg>"#;
+        let renderer = SvgRenderer::new(String::from("default"), svg.to_owned(), None, 24).unwrap();
+
+        for (scale, size) in [
+            (1.25, (30, 14)),
+            (1.5, (36, 17)),
+            (1.75, (42, 19)),
+            (2., (48, 22)),
+        ] {
+            let frame = renderer.render_frame(0, scale).unwrap();
+            assert_eq!(frame.size, Size::from(size), "at scale {scale}");
+            assert_eq!(
+                frame.logical_size().to_i32_round(),
+                Size::from((24, 11)),
+                "at scale {scale}"
+            );
+        }
+    }
+
+    #[test]
+    fn loads_fonts_for_text_only() {
+        let load = |svg: &str| {
+            let mut loaded = false;
+            let limits = Limits::default();
+            SvgRenderer::load(
+                String::from("text"),
+                svg.to_owned(),
+                None,
+                24,
+                &limits,
+                || {
+                    loaded = true;
+                    Arc::new(Database::new())
+                },
+            )
+            .unwrap();
+            loaded
+        };
+
+        assert!(!load(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><rect width="8" height="8"/></svg>"#
+        ));
+        assert!(load(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><text>?</text></svg>"#
+        ));
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let limits = Limits {
+            max_elements: 10,
+            max_raster_size: 32,
+            ..Limits::default()
+        };
+        let load = |svg: &str| {
+            SvgRenderer::load(
+                String::from("default"),
+                svg.to_owned(),
+                None,
+                24,
+                &limits,
+                || Arc::new(Database::new()),
+            )
+        };
+
+        // 10 elements in the source, but every `<use>` copies two rectangles.
+        let uses = r##"<use href="#a"/>"##.repeat(5);
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><defs><g id="a"><rect width="4" height="4"/><rect x="4" width="4" height="4"/></g></defs>{uses}</svg>"#
+        );
+        assert!(load(&svg).is_err());
+
+        let renderer = load(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><rect width="8" height="8"/></svg>"#,
+        )
+        .unwrap();
+        assert!(renderer.render_frame(0, 1.).is_ok());
+        assert!(renderer.render_frame(0, 2.).is_err());
+    }
+
+    #[test]
+    fn caches_frames_per_scale() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="8"><rect width="8" height="8"/></svg>"#;
+        let renderer = SvgRenderer::new(String::from("default"), svg.to_owned(), None, 24).unwrap();
+
+        renderer.render_frame(0, 1.).unwrap();
+        renderer.render_frame(3, 1.).unwrap();
+        assert_eq!(renderer.rendered.lock().len(), 1);
+        renderer.render_frame(0, 1.5).unwrap();
+        assert_eq!(renderer.rendered.lock().len(), 2);
+
+        let renderer = renderer.with_effects(Effects::default());
+        assert!(renderer.rendered.lock().is_empty());
     }
 }
@@ -0,0 +1,205 @@
+//! Detection of SVG features that resvg drops, so theme authors learn why a cursor looks off.
+//!
+//! usvg skips what it doesn't support without an error: unknown filter primitives, external
+//! files, and references to filters, masks or clip paths that don't exist. The source document is
+//! checked for these before parsing, and every finding is kept with the renderer.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use roxmltree::{Document, Node};
+
+/// Filter primitives usvg implements.
+const SUPPORTED_PRIMITIVES: &[&str] = &[
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDropShadow",
+    "feFlood",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMorphology",
+    "feOffset",
+    "feSpecularLighting",
+    "feTile",
+    "feTurbulence",
+];
+
+/// Elements usvg ignores along with their content.
+const UNSUPPORTED_ELEMENTS: &[&str] = &["foreignObject", "font", "font-face", "glyph"];
+
+/// Properties referring to another element, and the element they must refer to.
+const REFERENCES: &[(&str, &str)] = &[
+    ("filter", "filter"),
+    ("mask", "mask"),
+    ("clip-path", "clipPath"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// An element, or a filter primitive, that isn't drawn.
+    UnsupportedElement { element: String },
+    /// A reference to another file, which cursors can't load.
+    ExternalReference { element: String, href: String },
+    /// A `filter`, `mask` or `clip-path` that refers to no element of the right kind.
+    BrokenReference {
+        element: String,
+        property: &'static str,
+        target: String,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::UnsupportedElement { element } => {
+                write!(f, "{element} is not supported and not drawn")
+            }
+            Diagnostic::ExternalReference { element, href } => {
+                write!(
+                    f,
+                    "{element} refers to external {href}, which is not loaded"
+                )
+            }
+            Diagnostic::BrokenReference {
+                element,
+                property,
+                target,
+            } => write!(f, "{element} has {property} {target}, which does not exist"),
+        }
+    }
+}
+
+/// Finds the features of `document` that won't be rendered.
+pub fn check(document: &Document) -> Vec<Diagnostic> {
+    let ids: HashMap<&str, &str> = document
+        .descendants()
+        .filter_map(|node| Some((node.attribute("id")?, node.tag_name().name())))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for node in document.descendants().filter(|node| node.is_element()) {
+        let name = node.tag_name().name();
+        let is_primitive = name.starts_with("fe")
+            && node
+                .parent_element()
+                .is_some_and(|parent| parent.has_tag_name("filter"));
+        if UNSUPPORTED_ELEMENTS.contains(&name)
+            || (is_primitive && !SUPPORTED_PRIMITIVES.contains(&name))
+        {
+            diagnostics.push(Diagnostic::UnsupportedElement {
+                element: label(document, node),
+            });
+            continue;
+        }
+
+        // `<a>` links to another document rather than loading it.
+        let href = node.attributes().find(|attr| attr.name() == "href");
+        if let Some(href) = href.filter(|_| name != "a") {
+            let href = href.value().trim();
+            if !href.starts_with('#') && !href.starts_with("data:") {
+                diagnostics.push(Diagnostic::ExternalReference {
+                    element: label(document, node),
+                    href: href.to_owned(),
+                });
+            }
+        }
+
+        for &(property, kind) in REFERENCES {
+            let Some(value) = property_value(node, property) else {
+                continue;
+            };
+            let Some(url) = value
+                .strip_prefix("url(")
+                .and_then(|rest| rest.split_once(')'))
+                .map(|(url, _)| url.trim().trim_matches(['"', '\'']))
+            else {
+                continue;
+            };
+
+            match url.strip_prefix('#') {
+                Some(id) if ids.get(id) == Some(&kind) => (),
+                Some(_) => diagnostics.push(Diagnostic::BrokenReference {
+                    element: label(document, node),
+                    property,
+                    target: url.to_owned(),
+                }),
+                None => diagnostics.push(Diagnostic::ExternalReference {
+                    element: label(document, node),
+                    href: url.to_owned(),
+                }),
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Value of a presentation attribute, or of the declaration in the `style` attribute.
+///
+/// Style sheets aren't considered.
+fn property_value<'a>(node: Node<'a, '_>, property: &str) -> Option<&'a str> {
+    let declared = node.attribute("style").and_then(|style| {
+        style.split(';').find_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            (name.trim() == property).then(|| value.trim())
+        })
+    });
+    declared.or_else(|| node.attribute(property).map(str::trim))
+}
+
+/// Names an element by its `id`, or its line when it has none.
+fn label(document: &Document, node: Node) -> String {
+    let name = node.tag_name().name();
+    match node.attribute("id") {
+        Some(id) => format!("<{name} id=\"{id}\">"),
+        None => {
+            let line = document.text_pos_at(node.range().start).row;
+            format!("<{name}> on line {line}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_dropped_features() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+<filter id="glow"><feGaussianBlur/><feFancy/></filter>
+<mask id="fade"/>
+<path filter="url(#glow)" mask="url(#fade)"/>
+<path id="tip" style="clip-path: url(#fade)"/>
+<image xlink:href="shadow.png"/>
+<image href="data:image/png;base64,"/>
+<foreignObject/>
+</svg>"##;
+        let document = Document::parse(svg).unwrap();
+
+        assert_eq!(
+            check(&document),
+            [
+                Diagnostic::UnsupportedElement {
+                    element: String::from("<feFancy> on line 2"),
+                },
+                Diagnostic::BrokenReference {
+                    element: String::from("<path id=\"tip\">"),
+                    property: "clip-path",
+                    target: String::from("#fade"),
+                },
+                Diagnostic::ExternalReference {
+                    element: String::from("<image> on line 6"),
+                    href: String::from("shadow.png"),
+                },
+                Diagnostic::UnsupportedElement {
+                    element: String::from("<foreignObject> on line 8"),
+                },
+            ]
+        );
+    }
+}
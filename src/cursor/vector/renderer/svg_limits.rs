@@ -0,0 +1,120 @@
+//! Bounds on SVG cursors, so that a pathological file can't exhaust memory in the compositor.
+//!
+//! The source is checked before usvg parses it, the parsed tree again after `<use>` references
+//! were expanded, and every frame before it is rasterized. A cursor over a limit isn't loaded and
+//! falls back like any other cursor that fails to load.
+
+use anyhow::{ensure, Result};
+use roxmltree::Document;
+use serde::{Deserialize, Serialize};
+use usvg::{Group, Node, Tree};
+
+/// Configured per theme in the `[svg_limits]` table of `theme.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Most elements, in the source and after expanding `<use>` references.
+    pub max_elements: usize,
+    /// Largest width and height of a rasterized frame in pixels.
+    pub max_raster_size: i32,
+    /// Most filter primitives, like `<feGaussianBlur>`, over all filters.
+    pub max_filter_primitives: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_elements: 10_000,
+            max_raster_size: 1024,
+            max_filter_primitives: 32,
+        }
+    }
+}
+
+impl Limits {
+    /// Checks the source of a document, before parsing it with usvg.
+    pub fn check_source(&self, document: &Document) -> Result<()> {
+        let elements = document.descendants().filter(|node| node.is_element());
+        let (count, primitives) = elements.fold((0, 0), |(count, primitives), node| {
+            let is_primitive = node.tag_name().name().starts_with("fe");
+            (count + 1, primitives + usize::from(is_primitive))
+        });
+        ensure!(
+            count <= self.max_elements,
+            "SVG has {count} elements, more than the limit of {}",
+            self.max_elements
+        );
+        ensure!(
+            primitives <= self.max_filter_primitives,
+            "SVG has {primitives} filter primitives, more than the limit of {}",
+            self.max_filter_primitives
+        );
+        Ok(())
+    }
+
+    /// Checks a parsed document, where every `<use>` reference is expanded into a copy.
+    pub fn check_tree(&self, tree: &Tree) -> Result<()> {
+        let (count, primitives) = count_group(tree.root());
+        ensure!(
+            count <= self.max_elements,
+            "SVG expands to {count} elements, more than the limit of {}",
+            self.max_elements
+        );
+        ensure!(
+            primitives <= self.max_filter_primitives,
+            "SVG expands to {primitives} filter primitives, more than the limit of {}",
+            self.max_filter_primitives
+        );
+        Ok(())
+    }
+
+    /// Checks the size of a frame about to be rasterized.
+    pub fn check_raster(&self, width: i32, height: i32) -> Result<()> {
+        ensure!(
+            width <= self.max_raster_size && height <= self.max_raster_size,
+            "SVG frame of {width}×{height} pixels is larger than the limit of {}",
+            self.max_raster_size
+        );
+        Ok(())
+    }
+}
+
+/// Number of nodes and filter primitives in a group and its descendants.
+fn count_group(group: &Group) -> (usize, usize) {
+    let primitives = group
+        .filters()
+        .iter()
+        .map(|filter| filter.primitives().len())
+        .sum();
+    group
+        .children()
+        .iter()
+        .fold((1, primitives), |(count, primitives), node| match node {
+            Node::Group(group) => {
+                let (c, p) = count_group(group);
+                (count + c, primitives + p)
+            }
+            _ => (count + 1, primitives),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_source_elements() {
+        let limits = Limits {
+            max_elements: 3,
+            max_filter_primitives: 1,
+            ..Limits::default()
+        };
+        let check = |svg| limits.check_source(&Document::parse(svg).unwrap());
+
+        assert!(check("<svg><rect/><rect/></svg>").is_ok());
+        assert!(check("<svg><rect/><rect/><rect/></svg>").is_err());
+        assert!(check("<svg><filter><feFlood/><feBlend/></filter></svg>").is_err());
+        assert!(limits.check_raster(1024, 1).is_ok());
+        assert!(limits.check_raster(1025, 1).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Outlines of parsed SVG documents, for morph transitions.
+//!
+//! Every filled subpath becomes a polygon in canvas coordinates, with curves flattened into line
+//! segments and the opacity of its groups applied to its color.
+
+use usvg::tiny_skia_path::{self, PathSegment};
+use usvg::{Group, Node, Paint, Transform, Tree, Visibility};
+
+/// Line segments a curve is flattened into.
+const CURVE_STEPS: usize = 8;
+
+/// Filled polygons of `tree` drawn with `transform`, with their straight RGBA colors.
+pub fn polygons(tree: &Tree, transform: Transform) -> Vec<(Vec<(f32, f32)>, [f32; 4])> {
+    let mut polygons = Vec::new();
+    collect(tree.root(), transform, 1., &mut polygons);
+    polygons
+}
+
+fn collect(
+    group: &Group,
+    transform: Transform,
+    opacity: f32,
+    polygons: &mut Vec<(Vec<(f32, f32)>, [f32; 4])>,
+) {
+    let opacity = opacity * group.opacity().get();
+    for node in group.children() {
+        match node {
+            Node::Group(group) => collect(group, transform, opacity, polygons),
+            Node::Text(text) => collect(text.flattened(), transform, opacity, polygons),
+            Node::Path(path) if path.visibility() == Visibility::Visible => {
+                let Some(fill) = path.fill() else {
+                    continue;
+                };
+                let Some([r, g, b, a]) = fill_color(fill.paint()) else {
+                    continue;
+                };
+                let color = [r, g, b, a * fill.opacity().get() * opacity];
+                let transform = transform.pre_concat(path.abs_transform());
+                for polygon in flatten(path.data(), transform) {
+                    polygons.push((polygon, color));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Color of a paint, the first stop for gradients, `None` for patterns.
+fn fill_color(paint: &Paint) -> Option<[f32; 4]> {
+    let (color, opacity) = match paint {
+        Paint::Color(color) => (*color, 1.),
+        Paint::LinearGradient(gradient) => {
+            let stop = gradient.stops().first()?;
+            (stop.color(), stop.opacity().get())
+        }
+        Paint::RadialGradient(gradient) => {
+            let stop = gradient.stops().first()?;
+            (stop.color(), stop.opacity().get())
+        }
+        Paint::Pattern(_) => return None,
+    };
+    let channel = |c: u8| f32::from(c) / 255.;
+    Some([
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+        opacity,
+    ])
+}
+
+/// Closed polygons of the subpaths of `path`.
+fn flatten(path: &tiny_skia_path::Path, transform: Transform) -> Vec<Vec<(f32, f32)>> {
+    let map = |point: tiny_skia_path::Point| {
+        (
+            transform.sx * point.x + transform.kx * point.y + transform.tx,
+            transform.ky * point.x + transform.sy * point.y + transform.ty,
+        )
+    };
+
+    let mut polygons = Vec::new();
+    let mut polygon: Vec<tiny_skia_path::Point> = Vec::new();
+    for segment in path.segments() {
+        let last = polygon.last().copied();
+        match segment {
+            PathSegment::MoveTo(point) => {
+                polygons.push(std::mem::take(&mut polygon));
+                polygon.push(point);
+            }
+            PathSegment::LineTo(point) => polygon.push(point),
+            PathSegment::QuadTo(control, point) => {
+                let start = last.unwrap_or(control);
+                polygon.extend((1..=CURVE_STEPS).map(|i| {
+                    let t = i as f32 / CURVE_STEPS as f32;
+                    let u = 1. - t;
+                    let x = u * u * start.x + 2. * u * t * control.x + t * t * point.x;
+                    let y = u * u * start.y + 2. * u * t * control.y + t * t * point.y;
+                    tiny_skia_path::Point::from_xy(x, y)
+                }));
+            }
+            PathSegment::CubicTo(first, second, point) => {
+                let start = last.unwrap_or(first);
+                polygon.extend((1..=CURVE_STEPS).map(|i| {
+                    let t = i as f32 / CURVE_STEPS as f32;
+                    let u = 1. - t;
+                    let (a, b, c, d) = (u * u * u, 3. * u * u * t, 3. * u * t * t, t * t * t);
+                    let x = a * start.x + b * first.x + c * second.x + d * point.x;
+                    let y = a * start.y + b * first.y + c * second.y + d * point.y;
+                    tiny_skia_path::Point::from_xy(x, y)
+                }));
+            }
+            PathSegment::Close => polygons.push(std::mem::take(&mut polygon)),
+        }
+    }
+    polygons.push(polygon);
+
+    polygons
+        .into_iter()
+        .filter(|polygon| polygon.len() >= 3)
+        .map(|polygon| polygon.into_iter().map(map).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_filled_paths() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24">
+<g opacity="0.5"><rect x="2" y="2" width="8" height="8" fill="#ff0000"/></g>
+<circle cx="16" cy="16" r="4" fill="#0000ff"/>
+<path d="M0 0 L24 24" stroke="#000000" fill="none"/>
+</svg>"##;
+        let tree =
+            Tree::from_str(svg, &usvg::Options::default(), &fontdb::Database::new()).unwrap();
+
+        let polygons = polygons(&tree, Transform::from_scale(2., 2.));
+        assert_eq!(polygons.len(), 2);
+        let (rect, color) = &polygons[0];
+        assert_eq!(color, &[1., 0., 0., 0.5]);
+        assert!(rect.contains(&(4., 4.)) && rect.contains(&(20., 20.)));
+        let (circle, color) = &polygons[1];
+        assert_eq!(color, &[0., 0., 1., 1.]);
+        assert!(circle.len() > 4);
+        assert!(circle.iter().all(|&(x, y)| (x - 32.).hypot(y - 32.) < 8.5));
+    }
+}
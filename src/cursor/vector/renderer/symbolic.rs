@@ -0,0 +1,43 @@
+//! Symbolic SVG cursors, drawn in `currentColor`.
+//!
+//! A monochrome cursor set can use `fill="currentColor"` and `stroke="currentColor"`, and the
+//! foreground color of the theme or the one set at runtime becomes the `color` of the document
+//! element. Elements that set their own `color` keep it.
+
+use roxmltree::Document;
+
+use super::rewrite::{self, Rewrite};
+
+/// Sets the `color` of the document element to `color`.
+///
+/// Documents that roxmltree can't parse are returned unchanged.
+pub fn apply(svg: &str, color: &str) -> String {
+    let document = match Document::parse(svg) {
+        Ok(document) => document,
+        Err(err) => {
+            debug!("not setting currentColor of SVG: {err}");
+            return svg.to_owned();
+        }
+    };
+
+    let tag = rewrite::start_tag(svg, document.root_element().range().start);
+    let mut rewrite = Rewrite::new(svg);
+    rewrite.set(&tag, "color", color.to_owned());
+    rewrite.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_root_color() {
+        assert_eq!(
+            apply(
+                r#"<svg color="black"><path fill="currentColor"/></svg>"#,
+                "#eeeeee"
+            ),
+            r##"<svg color="#eeeeee"><path fill="currentColor"/></svg>"##
+        );
+    }
+}
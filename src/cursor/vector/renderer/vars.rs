@@ -0,0 +1,178 @@
+//! Theme colors as CSS custom properties in SVG cursors.
+//!
+//! The theme's named colors are the custom properties `--<name>` of every SVG cursor, so artwork
+//! can use `fill="var(--accent)"` or `style="stroke: var(--outline, black)"`. usvg doesn't
+//! support custom properties, so references are resolved in the document text before parsing.
+
+use std::collections::HashMap;
+
+/// Replaces `var(--name)` and `var(--name, fallback)` references with the theme's colors.
+///
+/// References to unknown colors use their fallback, and are left alone without one. So are
+/// references to a color from its own value, directly or through other colors.
+pub fn resolve(svg: &str, colors: &HashMap<String, String>) -> String {
+    resolve_within(svg, colors, &mut Vec::new())
+}
+
+/// The first color whose value refers back to itself, directly or through other colors.
+pub fn find_cycle(colors: &HashMap<String, String>) -> Option<&str> {
+    let mut names: Vec<_> = colors.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .find(|name| refers_to(name, &colors[*name], colors, &mut Vec::new()))
+        .map(String::as_str)
+}
+
+/// Whether `value` refers to the color `target`, following the colors it refers to.
+fn refers_to<'a>(
+    target: &str,
+    value: &'a str,
+    colors: &'a HashMap<String, String>,
+    visited: &mut Vec<&'a str>,
+) -> bool {
+    references(value).any(|name| {
+        if name == target {
+            return true;
+        }
+        if visited.contains(&name) {
+            return false;
+        }
+        visited.push(name);
+        colors
+            .get(name)
+            .is_some_and(|value| refers_to(target, value, colors, visited))
+    })
+}
+
+/// Names of the colors `value` refers to, including in fallbacks.
+fn references(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .match_indices("var(")
+        .filter_map(|(start, _)| {
+            value[start + "var(".len()..]
+                .trim_start()
+                .strip_prefix("--")
+        })
+        .map(|name| {
+            let end = name
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                .unwrap_or(name.len());
+            &name[..end]
+        })
+}
+
+/// Resolves references in `svg`, leaving alone the ones to the colors in `resolving`.
+fn resolve_within<'a>(
+    svg: &str,
+    colors: &'a HashMap<String, String>,
+    resolving: &mut Vec<&'a str>,
+) -> String {
+    let mut out = String::with_capacity(svg.len());
+    let mut rest = svg;
+
+    while let Some(start) = rest.find("var(") {
+        out.push_str(&rest[..start]);
+        let args = &rest[start + "var(".len()..];
+        let Some(end) = closing_paren(args) else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let reference = &rest[start..start + "var(".len() + end + 1];
+        rest = &args[end + 1..];
+
+        let inner = &args[..end];
+        let (name, fallback) = match inner.split_once(',') {
+            Some((name, fallback)) => (name.trim(), Some(fallback.trim())),
+            None => (inner.trim(), None),
+        };
+        let color = name
+            .strip_prefix("--")
+            .and_then(|name| colors.get_key_value(name))
+            .filter(|(name, _)| !resolving.contains(&name.as_str()));
+
+        match (color, fallback) {
+            // Colors and fallbacks may refer to other colors.
+            (Some((name, value)), _) => {
+                resolving.push(name);
+                out.push_str(&resolve_within(value, colors, resolving));
+                resolving.pop();
+            }
+            (None, Some(fallback)) => out.push_str(&resolve_within(fallback, colors, resolving)),
+            (None, None) => {
+                debug!("SVG refers to unknown theme color {name}");
+                out.push_str(reference);
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Position of the parenthesis closing an argument list, skipping nested ones.
+fn closing_paren(args: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> HashMap<String, String> {
+        HashMap::from([
+            (String::from("accent"), String::from("#3584e4")),
+            (String::from("outline"), String::from("rgb(0, 0, 0)")),
+        ])
+    }
+
+    #[test]
+    fn resolves_references() {
+        assert_eq!(
+            resolve(
+                r##"<path fill="var(--accent)" style="stroke:var( --outline );"/>"##,
+                &colors()
+            ),
+            r##"<path fill="#3584e4" style="stroke:rgb(0, 0, 0);"/>"##
+        );
+    }
+
+    #[test]
+    fn fallbacks() {
+        let colors = colors();
+        assert_eq!(
+            resolve("var(--shadow, rgb(1, 2, 3))", &colors),
+            "rgb(1, 2, 3)"
+        );
+        assert_eq!(resolve("var(--shadow, var(--accent))", &colors), "#3584e4");
+        assert_eq!(resolve("var(--accent, red)", &colors), "#3584e4");
+        assert_eq!(resolve("var(--shadow)", &colors), "var(--shadow)");
+        assert_eq!(resolve("var(--accent", &colors), "var(--accent");
+    }
+
+    #[test]
+    fn cycles() {
+        let mut colors = colors();
+        colors.insert(String::from("border"), String::from("var(--outline)"));
+        assert_eq!(find_cycle(&colors), None);
+        assert_eq!(resolve("var(--border)", &colors), "rgb(0, 0, 0)");
+
+        colors.insert(String::from("a"), String::from("var(--b, red)"));
+        colors.insert(String::from("b"), String::from("var(--c, var(--a))"));
+        colors.insert(String::from("c"), String::from("var(--a)"));
+        assert_eq!(find_cycle(&colors), Some("a"));
+        assert_eq!(resolve("var(--a)", &colors), "var(--a)");
+
+        colors.insert(String::from("self"), String::from("var( --self, blue)"));
+        assert_eq!(resolve("var(--self)", &colors), "blue");
+    }
+}
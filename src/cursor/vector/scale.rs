@@ -0,0 +1,123 @@
+//! Quantization of fractional render scales.
+//!
+//! Outputs can have arbitrary fractional scales like 1.9583. Rendering cursors at exactly those
+//! scales gives every output its own set of cached frames for no visible benefit, so render
+//! requests snap the scale to a coarse grid first. The rendered buffer is then slightly larger or
+//! smaller than the ideal size, which the hotspot math accounts for by always converting through
+//! the snapped scale.
+
+use serde::{Deserialize, Serialize};
+use smithay::utils::{Logical, Physical, Point};
+
+use crate::cursor::hotspot;
+
+/// Render scale quantization settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScaleSnapping {
+    /// Scales are rounded to the nearest multiple of `1 / steps`; 0 disables snapping.
+    pub steps: u32,
+}
+
+/// An output scale together with the scale the cursor is actually rendered at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderScale {
+    pub requested: f64,
+    pub snapped: f64,
+}
+
+impl Default for ScaleSnapping {
+    fn default() -> Self {
+        Self { steps: 8 }
+    }
+}
+
+impl ScaleSnapping {
+    pub fn snap(&self, scale: f64) -> RenderScale {
+        let snapped = if self.steps == 0 {
+            scale
+        } else {
+            let steps = f64::from(self.steps);
+            ((scale * steps).round() / steps).max(1. / steps)
+        };
+
+        RenderScale {
+            requested: scale,
+            snapped,
+        }
+    }
+}
+
+impl RenderScale {
+    /// Integer scale for renderers that can only rasterize at integer scales.
+    pub fn integer(&self) -> i32 {
+        (self.snapped.ceil() as i32).max(1)
+    }
+
+    /// Key identifying the snapped scale in render caches.
+    ///
+    /// Uses 1/120 units like the fractional-scale protocol, so every snapped scale maps to a
+    /// distinct key.
+    pub fn cache_key(&self) -> u32 {
+        (self.snapped * 120.).round() as u32
+    }
+
+    /// Scale of the buffers rendered for this request.
+    pub fn buffer_scale(&self) -> f64 {
+        self.snapped
+    }
+
+    /// Converts a hotspot in pixels of the rendered buffer to logical coordinates.
+    pub fn hotspot_to_logical(&self, hotspot: Point<i32, Physical>) -> Point<f64, Logical> {
+        hotspot::to_logical(hotspot, self.buffer_scale())
+    }
+}
+
+/// Integer scale of a `MemoryRenderBuffer` holding pixels rasterized at `scale`.
+///
+/// Buffers rasterized at fractional scales get scale 1 and have to be drawn at their logical size
+/// explicitly, see [`RenderedFrame::logical_size`](super::types::RenderedFrame::logical_size).
+pub fn memory_buffer_scale(scale: f64) -> i32 {
+    if scale.fract() == 0. {
+        (scale as i32).max(1)
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearly_integer_scales_snap() {
+        let snapping = ScaleSnapping::default();
+        assert_eq!(snapping.snap(1.9583).snapped, 2.);
+        assert_eq!(snapping.snap(2.04).snapped, 2.);
+        assert_eq!(snapping.snap(2.04).integer(), 2);
+        assert_eq!(snapping.snap(1.5).snapped, 1.5);
+        assert_eq!(snapping.snap(1.26).snapped, 1.25);
+        assert_eq!(snapping.snap(0.01).snapped, 0.125);
+    }
+
+    #[test]
+    fn disabled_snapping_keeps_scale() {
+        let snapping = ScaleSnapping { steps: 0 };
+        assert_eq!(snapping.snap(1.9583).snapped, 1.9583);
+        assert_eq!(snapping.snap(1.9583).integer(), 2);
+        assert_eq!(snapping.snap(2.04).integer(), 3);
+    }
+
+    #[test]
+    fn snapped_scales_share_cache_keys() {
+        let snapping = ScaleSnapping::default();
+        assert_eq!(
+            snapping.snap(1.9583).cache_key(),
+            snapping.snap(2.01).cache_key()
+        );
+        assert_ne!(
+            snapping.snap(1.5).cache_key(),
+            snapping.snap(1.75).cache_key()
+        );
+    }
+}
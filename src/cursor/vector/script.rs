@@ -0,0 +1,166 @@
+//! Theme scripts reacting to cursor events.
+//!
+//! A theme can set `script = "behavior.rhai"` to ship a [Rhai](https://rhai.rs) script. The
+//! script defines any of these functions:
+//!
+//! ```rhai
+//! // Called when the compositor requests another cursor icon. Returning a cursor ID shows that
+//! // cursor instead of the one mapped to the icon.
+//! fn on_icon_change(icon) {
+//!     if icon == "wait" { "wait-alt" }
+//! }
+//!
+//! // Called every time the cursor animation advances. Returns a map with any of `cursor`,
+//! // `opacity` and `scale`.
+//! fn on_tick(time_ms, velocity) {
+//!     #{ scale: if velocity > 3000.0 { 1.5 } else { 1.0 } }
+//! }
+//! ```
+//!
+//! Scripts run sandboxed: they can't touch the file system or the compositor, and every call has
+//! a strict operation budget. Running scripts requires the `scripting` cargo feature.
+
+/// An event passed to the theme script.
+#[derive(Debug, Clone, Copy)]
+pub enum ScriptEvent<'a> {
+    /// The compositor requested a cursor icon, named like the XCursor.
+    IconChanged { icon: &'a str },
+    /// The cursor animation advanced.
+    Tick {
+        time_ms: u64,
+        /// Pointer speed in logical pixels per second.
+        velocity: f64,
+    },
+}
+
+/// Changes requested by the theme script.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptOutput {
+    /// Cursor to show.
+    pub cursor: Option<String>,
+    /// Opacity multiplier.
+    pub opacity: Option<f32>,
+    /// Size multiplier.
+    pub scale: Option<f64>,
+}
+
+#[cfg(feature = "scripting")]
+pub use engine::CursorScript;
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use std::path::Path;
+
+    use anyhow::{Context, Result};
+    use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+    use super::{ScriptEvent, ScriptOutput};
+
+    /// Operation budget of a single script call.
+    const MAX_OPERATIONS: u64 = 10_000;
+
+    pub struct CursorScript {
+        engine: Engine,
+        ast: AST,
+        scope: Scope<'static>,
+    }
+
+    impl CursorScript {
+        pub fn load(path: &Path) -> Result<Self> {
+            let source = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read cursor script: {}", path.display()))?;
+
+            let mut engine = Engine::new();
+            engine.set_max_operations(MAX_OPERATIONS);
+            engine.set_max_call_levels(16);
+            engine.set_max_expr_depths(32, 16);
+            engine.set_max_string_size(1024);
+            engine.set_max_array_size(256);
+            engine.set_max_map_size(64);
+            engine.disable_symbol("eval");
+            engine.on_print(|text| debug!("cursor script: {text}"));
+            engine.on_debug(|text, _, _| debug!("cursor script: {text}"));
+
+            let ast = engine
+                .compile(&source)
+                .with_context(|| format!("Failed to compile cursor script: {}", path.display()))?;
+
+            // Top-level statements initialize the script's global variables.
+            let mut scope = Scope::new();
+            engine
+                .run_ast_with_scope(&mut scope, &ast)
+                .map_err(|err| anyhow::anyhow!("{err}"))
+                .context("Failed to run cursor script")?;
+
+            Ok(Self { engine, ast, scope })
+        }
+
+        /// Runs the script's handler for `event`.
+        ///
+        /// Errors, including exceeding the budget, are logged and request no changes.
+        pub fn handle(&mut self, event: ScriptEvent) -> ScriptOutput {
+            let (name, result) = match event {
+                ScriptEvent::IconChanged { icon } => (
+                    "on_icon_change",
+                    self.call("on_icon_change", (icon.to_owned(),)),
+                ),
+                ScriptEvent::Tick { time_ms, velocity } => (
+                    "on_tick",
+                    self.call("on_tick", (time_ms as rhai::INT, velocity as rhai::FLOAT)),
+                ),
+            };
+
+            match result {
+                Some(Ok(value)) => parse_output(value),
+                Some(Err(err)) => {
+                    warn!("error in cursor script {name}: {err}");
+                    ScriptOutput::default()
+                }
+                None => ScriptOutput::default(),
+            }
+        }
+
+        fn call(
+            &mut self,
+            name: &str,
+            args: impl rhai::FuncArgs,
+        ) -> Option<Result<Dynamic, Box<rhai::EvalAltResult>>> {
+            if !self.ast.iter_functions().any(|f| f.name == name) {
+                return None;
+            }
+
+            let options = CallFnOptions::new().eval_ast(false);
+            Some(
+                self.engine
+                    .call_fn_with_options(options, &mut self.scope, &self.ast, name, args),
+            )
+        }
+    }
+
+    fn parse_output(value: Dynamic) -> ScriptOutput {
+        if value.is_string() {
+            return ScriptOutput {
+                cursor: value.into_string().ok(),
+                ..Default::default()
+            };
+        }
+
+        let Some(map) = value.try_cast::<Map>() else {
+            return ScriptOutput::default();
+        };
+
+        let number = |key: &str| {
+            let value = map.get(key)?;
+            value
+                .as_float()
+                .ok()
+                .or_else(|| value.as_int().ok().map(|v| v as rhai::FLOAT))
+        };
+
+        ScriptOutput {
+            cursor: map.get("cursor").and_then(|v| v.clone().into_string().ok()),
+            opacity: number("opacity").map(|v| (v as f32).clamp(0., 1.)),
+            scale: number("scale").map(|v| v.clamp(0.1, 10.)),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Frame pacing statistics for vector cursor rendering.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Number of bits of precision kept within each power-of-two range.
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKET_COUNT: u64 = 1 << SUB_BUCKET_BITS;
+const SUB_BUCKET_HALF: u64 = SUB_BUCKET_COUNT / 2;
+
+/// Log-linear histogram of durations with microsecond resolution.
+///
+/// Works like a tiny HDR histogram: values below 32 µs are stored exactly, larger values keep
+/// 5 significant bits, so every recorded value is within ~3% of its bucket bounds.
+#[derive(Debug, Clone, Default)]
+pub struct DurationHistogram {
+    counts: Vec<u64>,
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+}
+
+/// Snapshot of the interesting percentiles of a [`DurationHistogram`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSummary {
+    pub count: u64,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Raster durations collected by the cursor manager, overall and per cursor.
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    all: DurationHistogram,
+    per_cursor: HashMap<String, DurationHistogram>,
+}
+
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKET_COUNT {
+        return value as usize;
+    }
+
+    let msb = 63 - value.leading_zeros();
+    let shift = msb - (SUB_BUCKET_BITS - 1);
+    let top = value >> shift;
+    (SUB_BUCKET_COUNT + (shift as u64 - 1) * SUB_BUCKET_HALF + (top - SUB_BUCKET_HALF)) as usize
+}
+
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKET_COUNT {
+        return index;
+    }
+
+    let k = index - SUB_BUCKET_COUNT;
+    let shift = k / SUB_BUCKET_HALF + 1;
+    let top = k % SUB_BUCKET_HALF + SUB_BUCKET_HALF;
+    (top << shift) | ((1 << shift) - 1)
+}
+
+impl DurationHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        let value = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+
+        let index = bucket_index(value);
+        if self.counts.len() <= index {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] += 1;
+
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(value);
+        self.max_us = self.max_us.max(value);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max_us)
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_micros(self.sum_us / self.count)
+    }
+
+    /// Returns the smallest bucket bound that at least `quantile` of the samples fall under.
+    ///
+    /// `quantile` is in `0.0..=1.0`, so the p95 is `percentile(0.95)`.
+    pub fn percentile(&self, quantile: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+
+        let quantile = quantile.clamp(0., 1.);
+        let target = ((quantile * self.count as f64).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                let value = bucket_upper_bound(index).min(self.max_us);
+                return Duration::from_micros(value);
+            }
+        }
+
+        self.max()
+    }
+
+    pub fn summary(&self) -> StatsSummary {
+        StatsSummary {
+            count: self.count,
+            mean: self.mean(),
+            p50: self.percentile(0.5),
+            p95: self.percentile(0.95),
+            p99: self.percentile(0.99),
+            max: self.max(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl RenderStats {
+    pub fn record(&mut self, cursor_id: &str, duration: Duration) {
+        self.all.record(duration);
+
+        if let Some(histogram) = self.per_cursor.get_mut(cursor_id) {
+            histogram.record(duration);
+        } else {
+            let mut histogram = DurationHistogram::new();
+            histogram.record(duration);
+            self.per_cursor.insert(cursor_id.to_string(), histogram);
+        }
+    }
+
+    /// Raster durations across all cursors.
+    pub fn all(&self) -> &DurationHistogram {
+        &self.all
+    }
+
+    /// Raster durations of a single cursor.
+    pub fn cursor(&self, cursor_id: &str) -> Option<&DurationHistogram> {
+        self.per_cursor.get(cursor_id)
+    }
+
+    pub fn cursors(&self) -> impl Iterator<Item = (&str, &DurationHistogram)> {
+        self.per_cursor.iter().map(|(id, h)| (id.as_str(), h))
+    }
+
+    pub fn reset(&mut self) {
+        self.all.reset();
+        self.per_cursor.clear();
+    }
+}
+
+impl From<StatsSummary> for niri_ipc::RenderTimes {
+    fn from(summary: StatsSummary) -> Self {
+        let us = |duration: Duration| u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        Self {
+            count: summary.count,
+            mean_us: us(summary.mean),
+            p50_us: us(summary.p50),
+            p95_us: us(summary.p95),
+            p99_us: us(summary.p99),
+            max_us: us(summary.max),
+        }
+    }
+}
+
+impl fmt::Display for StatsSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} mean={:?} p50={:?} p95={:?} p99={:?} max={:?}",
+            self.count, self.mean, self.p50, self.p95, self.p99, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_bounds_contain_value() {
+        for value in (0..10_000).chain([1 << 20, (1 << 40) + 12345, u64::MAX]) {
+            let index = bucket_index(value);
+            assert!(bucket_upper_bound(index) >= value, "value {value}");
+            if index > 0 {
+                assert!(bucket_upper_bound(index - 1) < value, "value {value}");
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut histogram = DurationHistogram::new();
+        for us in 1..=100 {
+            histogram.record(Duration::from_micros(us * 100));
+        }
+
+        let summary = histogram.summary();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.max, Duration::from_micros(10_000));
+
+        let close = |actual: Duration, expected: u64| {
+            let actual = actual.as_micros() as f64;
+            let expected = expected as f64;
+            assert!(
+                (actual - expected).abs() <= expected * 0.04,
+                "{actual} vs {expected}"
+            );
+        };
+        close(summary.p50, 5_000);
+        close(summary.p95, 9_500);
+        close(summary.p99, 9_900);
+    }
+
+    #[test]
+    fn empty_histogram() {
+        let histogram = DurationHistogram::new();
+        assert_eq!(histogram.summary(), StatsSummary::default());
+    }
+}
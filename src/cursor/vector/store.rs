@@ -1,32 +1,267 @@
+use crate::cursor::vector::config::ColorScheme;
 use crate::cursor::vector::config::CursorFormat;
-use crate::cursor::vector::config::CursorThemeConfig;
-use crate::cursor::vector::renderer::{LottieRenderer, SvgRenderer, VectorRenderer};
+#[cfg(feature = "svg")]
+use crate::cursor::vector::config::SizeVariant;
+use crate::cursor::vector::config::{CursorDefinition, CursorThemeConfig, TransitionConfig};
+#[cfg(feature = "lottie")]
+use crate::cursor::vector::dotlottie;
+#[cfg(any(feature = "svg", feature = "lottie"))]
+use crate::cursor::vector::effects::{Effects, Tint};
+#[cfg(any(feature = "svg", feature = "lottie"))]
+use crate::cursor::vector::integrity::{self, IntegrityMode};
+use crate::cursor::vector::remote::{RemoteConnection, RemoteRenderer};
+#[cfg(feature = "lottie")]
+use crate::cursor::vector::renderer::lottie::validate::{Issue, ValidationError};
+#[cfg(feature = "svg")]
+use crate::cursor::vector::renderer::svg_diagnostics::Diagnostic;
+#[cfg(feature = "lottie")]
+use crate::cursor::vector::renderer::LottieRenderer;
+#[cfg(feature = "svg")]
+use crate::cursor::vector::renderer::SvgRenderer;
+use crate::cursor::vector::renderer::VectorRenderer;
+#[cfg(feature = "svg")]
+use crate::cursor::vector::renderer::{fonts, stylesheet, symbolic, vars};
+#[cfg(any(feature = "svg", feature = "lottie"))]
+use crate::cursor::vector::watermark;
 use anyhow::{Context, Result};
 use std::collections::HashMap;
+#[cfg(any(feature = "svg", feature = "lottie"))]
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
+#[cfg(feature = "svg")]
+use std::sync::OnceLock;
+#[cfg(feature = "svg")]
+use std::thread;
 
 pub struct VectorCursorStore {
+    #[cfg_attr(not(any(feature = "svg", feature = "lottie")), allow(dead_code))]
     base_path: PathBuf,
     config: Arc<CursorThemeConfig>,
+    #[cfg(feature = "svg")]
     svg_cache: Arc<parking_lot::RwLock<HashMap<String, Rc<SvgRenderer>>>>,
+    #[cfg(feature = "lottie")]
     lottie_cache: Arc<parking_lot::RwLock<HashMap<String, Rc<LottieRenderer>>>>,
+    #[cfg(feature = "lottie")]
+    issues: parking_lot::RwLock<HashMap<String, Vec<Issue>>>,
+    /// Lottie animations played by transitions, by file.
+    #[cfg(feature = "lottie")]
+    transition_cache: parking_lot::RwLock<HashMap<String, Rc<LottieRenderer>>>,
+    /// Color of `currentColor` in SVG cursors.
+    #[cfg(feature = "svg")]
+    foreground: Option<String>,
+    /// Picks the light or dark artwork of SVG cursors that have both.
+    #[cfg(feature = "svg")]
+    color_scheme: ColorScheme,
+    /// Fonts for text in cursors, loaded on a thread of their own along with the theme.
+    #[cfg(feature = "svg")]
+    fonts: Arc<OnceLock<Arc<fontdb::Database>>>,
+    /// Whether cursors are mirrored horizontally.
+    mirror: bool,
+    /// Cursor server rendering the cursors instead of this process.
+    remote: Option<Arc<RemoteConnection>>,
+    remote_cache: parking_lot::RwLock<HashMap<String, Rc<RemoteRenderer>>>,
     base_size: u8,
 }
 
 impl VectorCursorStore {
     pub fn new(base_path: PathBuf, config: CursorThemeConfig, base_size: u8) -> Result<Self> {
+        #[cfg(feature = "svg")]
+        let foreground = config.foreground.clone();
+        let mirror = config.mirror;
+
+        // Scanning the system fonts takes a while, so it doesn't wait for the first cursor with
+        // text to be rendered on the compositor thread.
+        #[cfg(feature = "svg")]
+        let fonts = Arc::new(OnceLock::new());
+        #[cfg(feature = "svg")]
+        {
+            let fonts = fonts.clone();
+            let bundled = bundled_fonts(&base_path, &config);
+            let spawned = thread::Builder::new()
+                .name(String::from("Cursor Font Loader"))
+                .spawn(move || {
+                    fonts.get_or_init(|| fonts::with_bundled(&bundled));
+                });
+            if let Err(err) = spawned {
+                warn!("error spawning cursor font loader thread: {err:?}");
+            }
+        }
+
         Ok(Self {
             base_path,
             config: Arc::new(config),
+            #[cfg(feature = "svg")]
             svg_cache: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            #[cfg(feature = "lottie")]
             lottie_cache: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            #[cfg(feature = "lottie")]
+            issues: parking_lot::RwLock::new(HashMap::new()),
+            #[cfg(feature = "lottie")]
+            transition_cache: parking_lot::RwLock::new(HashMap::new()),
+            #[cfg(feature = "svg")]
+            foreground,
+            #[cfg(feature = "svg")]
+            color_scheme: ColorScheme::default(),
+            #[cfg(feature = "svg")]
+            fonts,
+            mirror,
+            remote: None,
+            remote_cache: parking_lot::RwLock::new(HashMap::new()),
             base_size,
         })
     }
 
+    /// Renders the cursors with a cursor server instead of parsing the assets in this process.
+    pub fn with_remote(mut self, remote: Arc<RemoteConnection>) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    /// Sets the color of `currentColor` in SVG cursors, `None` for the theme's `foreground`.
+    ///
+    /// Loaded SVG cursors are dropped to be loaded again in the new color. Cursors rendered by a
+    /// cursor server keep the theme's color.
+    pub fn set_foreground(&mut self, color: Option<String>) {
+        #[cfg(feature = "svg")]
+        {
+            self.foreground = color.or_else(|| self.config.foreground.clone());
+            self.svg_cache.write().clear();
+        }
+
+        #[cfg(not(feature = "svg"))]
+        let _ = color;
+    }
+
+    /// Mirrors every cursor horizontally, or not; `None` for the theme's `mirror`.
+    ///
+    /// Loaded cursors are dropped to be loaded again. Cursors rendered by a cursor server keep the
+    /// theme's setting.
+    pub fn set_mirror(&mut self, mirror: Option<bool>) {
+        self.mirror = mirror.unwrap_or(self.config.mirror);
+        #[cfg(feature = "svg")]
+        self.svg_cache.write().clear();
+        #[cfg(feature = "lottie")]
+        {
+            self.lottie_cache.write().clear();
+            self.transition_cache.write().clear();
+        }
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        self.mirror
+    }
+
+    /// Switches SVG cursors with `file_light` or `file_dark` to the artwork for `scheme`.
+    ///
+    /// Only those cursors are dropped to be loaded again; every other cursor stays loaded.
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        #[cfg(feature = "svg")]
+        {
+            if self.color_scheme == scheme {
+                return;
+            }
+            self.color_scheme = scheme;
+
+            let themed: Vec<_> = self
+                .config
+                .cursors
+                .iter()
+                .filter(|(_, cursor_def)| cursor_def.has_scheme_files())
+                .map(|(cursor_id, _)| cursor_id.as_str())
+                .collect();
+            // Size variants are cached as `cursor@size`.
+            self.svg_cache.write().retain(|key, _| {
+                let cursor_id = key.rsplit_once('@').map_or(key.as_str(), |(id, _)| id);
+                !themed.contains(&key.as_str()) && !themed.contains(&cursor_id)
+            });
+        }
+
+        #[cfg(not(feature = "svg"))]
+        let _ = scheme;
+    }
+
+    /// Whether a cursor or a Lottie transition is drawn from `file`, relative to the theme
+    /// directory.
+    pub fn draws_from(&self, file: &Path) -> bool {
+        self.config
+            .cursors
+            .values()
+            .flat_map(|cursor_def| cursor_def.files())
+            .chain(
+                self.config
+                    .transitions
+                    .values()
+                    .filter_map(|transition| transition.file.as_deref()),
+            )
+            .any(|drawn| Path::new(drawn) == file)
+    }
+
+    /// Drops the loaded cursors and transition animations drawn from any of the `changed`
+    /// files, relative to the theme directory, so that they're loaded again from disk.
+    ///
+    /// Every other cursor stays loaded.
+    pub fn invalidate_files(&self, changed: &[PathBuf]) {
+        let is_changed = |file: &str| changed.iter().any(|path| path == Path::new(file));
+        let stale: Vec<_> = self
+            .config
+            .cursors
+            .iter()
+            .filter(|(_, cursor_def)| cursor_def.files().any(is_changed))
+            .map(|(cursor_id, _)| cursor_id.as_str())
+            .collect();
+        debug!("reloading vector cursors {stale:?}");
+        // Size variants are cached as `cursor@size`.
+        let is_stale = |key: &String| {
+            let cursor_id = key.rsplit_once('@').map_or(key.as_str(), |(id, _)| id);
+            stale.contains(&key.as_str()) || stale.contains(&cursor_id)
+        };
+
+        #[cfg(feature = "svg")]
+        self.svg_cache.write().retain(|key, _| !is_stale(key));
+        #[cfg(feature = "lottie")]
+        {
+            self.lottie_cache.write().retain(|key, _| !is_stale(key));
+            self.issues.write().retain(|key, _| !is_stale(key));
+            self.transition_cache
+                .write()
+                .retain(|file, _| !is_changed(file));
+        }
+        #[cfg(not(any(feature = "svg", feature = "lottie")))]
+        let _ = is_stale;
+    }
+
+    /// Gets the renderer for a cursor shown at `scale`, which uses the closest of its size
+    /// variants.
+    pub fn get_renderer_at(&self, cursor_id: &str, scale: f64) -> Result<Rc<dyn VectorRenderer>> {
+        #[cfg(feature = "svg")]
+        if self.remote.is_none() {
+            let cursor_def = self
+                .config
+                .get_cursor(cursor_id)
+                .context(format!("Cursor '{}' not found in config", cursor_id))?;
+            if cursor_def.format == CursorFormat::Svg {
+                if let Some(variant) = cursor_def.size_variant(self.base_size, scale) {
+                    let key = format!("{cursor_id}@{}", variant.size);
+                    let mut cache = self.svg_cache.write();
+                    if let Some(cached) = cache.get(&key) {
+                        return Ok(cached.clone() as Rc<dyn VectorRenderer>);
+                    }
+
+                    let renderer = Rc::new(self.load_svg_variant(cursor_id, cursor_def, variant)?);
+                    cache.insert(key, renderer.clone());
+                    return Ok(renderer);
+                }
+            }
+        }
+
+        #[cfg(not(feature = "svg"))]
+        let _ = scale;
+
+        self.get_renderer(cursor_id)
+    }
+
     pub fn get_renderer(&self, cursor_id: &str) -> Result<Rc<dyn VectorRenderer>> {
         debug!(
             "VectorCursorStore::get_renderer called for cursor: '{}'",
@@ -38,7 +273,24 @@ impl VectorCursorStore {
             .get_cursor(cursor_id)
             .context(format!("Cursor '{}' not found in config", cursor_id))?;
 
-        let renderer: Rc<dyn VectorRenderer> = match cursor_def.format {
+        if let Some(remote) = &self.remote {
+            let mut cache = self.remote_cache.write();
+            if let Some(cached) = cache.get(cursor_id) {
+                return Ok(cached.clone() as Rc<dyn VectorRenderer>);
+            }
+
+            // Frames larger than a local renderer would rasterize are refused.
+            #[cfg(feature = "svg")]
+            let max_size = self.config.svg_limits.max_raster_size;
+            #[cfg(not(feature = "svg"))]
+            let max_size = crate::cursor::vector::remote::DEFAULT_MAX_FRAME_SIZE;
+            let renderer = Rc::new(RemoteRenderer::load(remote.clone(), cursor_id, max_size)?);
+            cache.insert(cursor_id.to_string(), renderer.clone());
+            return Ok(renderer);
+        }
+
+        match cursor_def.format {
+            #[cfg(feature = "svg")]
             CursorFormat::Svg => {
                 let mut cache = self.svg_cache.write();
                 if let Some(cached) = cache.get(cursor_id) {
@@ -47,61 +299,327 @@ impl VectorCursorStore {
 
                 let renderer = Rc::new(self.load_svg_renderer(cursor_id, cursor_def)?);
                 cache.insert(cursor_id.to_string(), renderer.clone());
-                renderer
+                Ok(renderer)
             }
+            #[cfg(feature = "lottie")]
             CursorFormat::Lottie => {
                 let mut cache = self.lottie_cache.write();
                 if let Some(cached) = cache.get(cursor_id) {
                     return Ok(cached.clone() as Rc<dyn VectorRenderer>);
                 }
 
-                let renderer = Rc::new(self.load_lottie_renderer(cursor_id, cursor_def)?);
+                let renderer = match self.load_lottie_renderer(cursor_id, cursor_def) {
+                    Ok(renderer) => Rc::new(renderer),
+                    Err(err) => {
+                        if let Some(ValidationError(issues)) = err.downcast_ref() {
+                            // Failed cursors are retried on every frame, only log once.
+                            let mut known = self.issues.write();
+                            if !known.contains_key(cursor_id) {
+                                warn!("Lottie cursor '{cursor_id}' can't be rendered: {err:#}");
+                                known.insert(cursor_id.to_string(), issues.clone());
+                            }
+                        }
+                        return Err(err);
+                    }
+                };
+                self.issues
+                    .write()
+                    .insert(cursor_id.to_string(), renderer.issues().to_vec());
                 cache.insert(cursor_id.to_string(), renderer.clone());
-                renderer
+                Ok(renderer)
             }
-        };
-
-        Ok(renderer)
+            #[cfg(not(feature = "svg"))]
+            CursorFormat::Svg => anyhow::bail!("niri was built without SVG cursor support"),
+            #[cfg(not(feature = "lottie"))]
+            CursorFormat::Lottie => anyhow::bail!("niri was built without Lottie cursor support"),
+        }
     }
 
+    #[cfg(feature = "svg")]
     fn load_svg_renderer(
         &self,
         cursor_id: &str,
-        cursor_def: &crate::cursor::vector::config::CursorDefinition,
+        cursor_def: &CursorDefinition,
     ) -> Result<SvgRenderer> {
         debug!("Loading SVG renderer for cursor: '{}'", cursor_id);
+        let scheme_def;
+        let cursor_def = match cursor_def.scheme_file(self.color_scheme) {
+            Some(file) => {
+                scheme_def = CursorDefinition {
+                    file: file.to_owned(),
+                    data: None,
+                    sha256: cursor_def
+                        .scheme_sha256(self.color_scheme)
+                        .map(str::to_owned),
+                    ..cursor_def.clone()
+                };
+                &scheme_def
+            }
+            None => cursor_def,
+        };
         let file_path = self.base_path.join(&cursor_def.file);
         debug!("SVG file path: {}", file_path.display());
 
-        let svg_data = fs::read_to_string(&file_path)
-            .with_context(|| format!("Failed to read SVG file: {}", file_path.display()))?;
+        let data = self.read_asset(cursor_def)?;
+        let mut svg_data = String::from_utf8(data)
+            .with_context(|| format!("SVG file is not UTF-8: {}", file_path.display()))?;
+        // The theme's style sheet comes first, so that the cursor's one wins ties.
+        for css in [&self.config.stylesheet, &cursor_def.stylesheet]
+            .into_iter()
+            .flatten()
+        {
+            svg_data = stylesheet::inject(&svg_data, css);
+        }
+        svg_data = vars::resolve(&svg_data, &self.config.colors);
+        if let Some(color) = &self.foreground {
+            svg_data = symbolic::apply(&svg_data, color);
+        }
 
-        SvgRenderer::new(
+        let mut renderer = SvgRenderer::load(
             cursor_id.to_string(),
             svg_data,
             cursor_def.hotspot,
             self.base_size,
-        )
+            &self.config.svg_limits,
+            || self.fonts(),
+        )?;
+        for diagnostic in renderer.diagnostics() {
+            warn!(
+                "SVG cursor '{cursor_id}' ({}): {diagnostic}",
+                file_path.display()
+            );
+        }
+        if let Some(degrees) = cursor_def.rotate_deg {
+            renderer = renderer.with_rotation(degrees);
+        }
+        Ok(renderer.with_effects(self.effects(cursor_def)?))
+    }
+
+    /// The system fonts and the fonts bundled with the theme, waiting for them to finish loading.
+    #[cfg(feature = "svg")]
+    fn fonts(&self) -> Arc<fontdb::Database> {
+        self.fonts
+            .get_or_init(|| fonts::with_bundled(&bundled_fonts(&self.base_path, &self.config)))
+            .clone()
+    }
+
+    /// Loads the artwork of a cursor for another size than the base size.
+    #[cfg(feature = "svg")]
+    fn load_svg_variant(
+        &self,
+        cursor_id: &str,
+        cursor_def: &CursorDefinition,
+        variant: &SizeVariant,
+    ) -> Result<SvgRenderer> {
+        let ratio = f64::from(self.base_size) / f64::from(variant.size.max(1));
+        let hotspot = variant.hotspot.or_else(|| {
+            let (x, y) = cursor_def.hotspot?;
+            let scale = |v: i32| (f64::from(v) / ratio).round() as i32;
+            Some((scale(x), scale(y)))
+        });
+        let variant_def = CursorDefinition {
+            file: variant.file.clone(),
+            data: None,
+            file_light: None,
+            file_dark: None,
+            hotspot,
+            sha256: variant.sha256.clone(),
+            sizes: Vec::new(),
+            ..cursor_def.clone()
+        };
+
+        // Like the base artwork, the variant is drawn to fill the cursor size.
+        self.load_svg_renderer(cursor_id, &variant_def)
+            .with_context(|| format!("error loading {}px artwork", variant.size))
     }
 
+    #[cfg(feature = "lottie")]
     fn load_lottie_renderer(
         &self,
         cursor_id: &str,
-        cursor_def: &crate::cursor::vector::config::CursorDefinition,
+        cursor_def: &CursorDefinition,
     ) -> Result<LottieRenderer> {
         debug!("Loading Lottie renderer for cursor: '{}'", cursor_id);
         let file_path = self.base_path.join(&cursor_def.file);
         debug!("Lottie file path: {}", file_path.display());
 
-        let lottie_data = fs::read_to_string(&file_path)
-            .with_context(|| format!("Failed to read Lottie file: {}", file_path.display()))?;
-
-        LottieRenderer::new(
+        let data = self.read_asset(cursor_def)?;
+        let renderer = LottieRenderer::new(
             cursor_id.to_string(),
-            lottie_data,
+            lottie_text(data, &file_path)?,
             cursor_def.hotspot,
             self.base_size,
-        )
+            &self.config.lottie_limits,
+        )?;
+        Ok(self
+            .with_fonts(renderer)
+            .with_effects(self.effects(cursor_def)?))
+    }
+
+    /// Gives a Lottie renderer that draws text the system fonts and the fonts bundled with the
+    /// theme.
+    #[cfg(feature = "lottie")]
+    fn with_fonts(&self, renderer: LottieRenderer) -> LottieRenderer {
+        #[cfg(feature = "svg")]
+        if renderer.has_text() {
+            return renderer.with_fonts(self.fonts());
+        }
+        renderer
+    }
+
+    /// Loads the Lottie animation `transition` plays, shared by the transitions playing the same
+    /// file.
+    #[cfg(feature = "lottie")]
+    pub fn get_transition_renderer(
+        &self,
+        transition: &TransitionConfig,
+    ) -> Result<Rc<dyn VectorRenderer>> {
+        let file = transition
+            .file
+            .as_deref()
+            .context("Lottie transition has no file")?;
+        let mut cache = self.transition_cache.write();
+        if let Some(cached) = cache.get(file) {
+            return Ok(cached.clone() as Rc<dyn VectorRenderer>);
+        }
+
+        debug!("Loading Lottie transition: {file}");
+        let file_path = self.base_path.join(file);
+        let data = fs::read(&file_path)
+            .with_context(|| format!("Failed to read transition file: {}", file_path.display()))?;
+        self.check_integrity(file, &data, transition.sha256.as_deref())?;
+        let renderer = LottieRenderer::new(
+            format!("transition {file}"),
+            lottie_text(data, &file_path)?,
+            transition.hotspot,
+            self.base_size,
+            &self.config.lottie_limits,
+        )?;
+        let renderer = self.with_fonts(renderer).with_effects(Effects {
+            tint: None,
+            watermark: watermark::enabled(),
+            mirror: self.mirror,
+        });
+
+        let renderer = Rc::new(renderer);
+        cache.insert(file.to_owned(), renderer.clone());
+        Ok(renderer)
+    }
+
+    #[cfg(not(feature = "lottie"))]
+    pub fn get_transition_renderer(
+        &self,
+        _transition: &TransitionConfig,
+    ) -> Result<Rc<dyn VectorRenderer>> {
+        anyhow::bail!("niri was built without Lottie cursor support")
+    }
+
+    /// Reads the file or inline data of a cursor, checking it against its `sha256`.
+    #[cfg(any(feature = "svg", feature = "lottie"))]
+    fn read_asset(&self, cursor_def: &CursorDefinition) -> Result<Vec<u8>> {
+        let (name, data) = match &cursor_def.data {
+            Some(data) => ("inline data", data.clone().into_bytes()),
+            None => {
+                let file_path = self.base_path.join(&cursor_def.file);
+                let data = fs::read(&file_path).with_context(|| {
+                    format!("Failed to read cursor file: {}", file_path.display())
+                })?;
+                (cursor_def.file.as_str(), data)
+            }
+        };
+
+        self.check_integrity(name, &data, cursor_def.sha256.as_deref())?;
+        Ok(data)
+    }
+
+    /// Checks the contents of `file` against its `sha256`, if it has one, failing on a mismatch
+    /// unless the theme's integrity mode only warns.
+    #[cfg(any(feature = "svg", feature = "lottie"))]
+    fn check_integrity(&self, file: &str, data: &[u8], expected: Option<&str>) -> Result<()> {
+        let Some(expected) = expected else {
+            return Ok(());
+        };
+        if let Err(err) = integrity::verify(file, data, expected) {
+            match self.config.integrity {
+                IntegrityMode::Refuse => return Err(err),
+                IntegrityMode::Warn => warn!("loading anyway: {err:#}"),
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(feature = "svg", feature = "lottie"))]
+    fn effects(&self, cursor_def: &CursorDefinition) -> Result<Effects> {
+        let tint = cursor_def.tint.as_deref().map(Tint::parse).transpose()?;
+        if tint.is_some() && !cfg!(feature = "effects") {
+            warn!("ignoring cursor tint: niri was built without cursor effects support");
+        }
+        Ok(Effects {
+            tint,
+            watermark: watermark::enabled(),
+            mirror: self.mirror,
+        })
+    }
+
+    /// Problems found in the composition of a loaded or failed Lottie cursor.
+    ///
+    /// Empty for SVG cursors and cursors that weren't loaded yet.
+    #[cfg(feature = "lottie")]
+    pub fn issues(&self, cursor_id: &str) -> Vec<Issue> {
+        self.issues
+            .read()
+            .get(cursor_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Features of a loaded SVG cursor that aren't rendered, over all of its loaded artwork.
+    ///
+    /// Empty for Lottie cursors and cursors that weren't loaded yet.
+    #[cfg(feature = "svg")]
+    pub fn svg_diagnostics(&self, cursor_id: &str) -> Vec<Diagnostic> {
+        let cache = self.svg_cache.read();
+        // Size variants are cached as `cursor@size`.
+        let mut loaded: Vec<_> = cache
+            .iter()
+            .filter(|(key, _)| {
+                key.as_str() == cursor_id
+                    || key.rsplit_once('@').is_some_and(|(id, _)| id == cursor_id)
+            })
+            .collect();
+        loaded.sort_by_key(|(key, _)| key.as_str());
+
+        let mut diagnostics = Vec::new();
+        for diagnostic in loaded
+            .iter()
+            .flat_map(|(_, renderer)| renderer.diagnostics())
+        {
+            if !diagnostics.contains(diagnostic) {
+                diagnostics.push(diagnostic.clone());
+            }
+        }
+        diagnostics
+    }
+
+    /// Frees the cached frames of every loaded cursor, see [`VectorRenderer::trim_caches`].
+    ///
+    /// The renderers themselves stay loaded, so showing a cursor again only rasterizes it.
+    pub fn trim_caches(&self) {
+        #[cfg(feature = "svg")]
+        for renderer in self.svg_cache.read().values() {
+            renderer.trim_caches();
+        }
+        #[cfg(feature = "lottie")]
+        for renderer in self.lottie_cache.read().values() {
+            renderer.trim_caches();
+        }
+        #[cfg(feature = "lottie")]
+        for renderer in self.transition_cache.read().values() {
+            renderer.trim_caches();
+        }
+        for renderer in self.remote_cache.read().values() {
+            renderer.trim_caches();
+        }
     }
 
     pub fn get_base_size(&self) -> u8 {
@@ -112,3 +630,25 @@ impl VectorCursorStore {
         &self.config
     }
 }
+
+/// Paths of the fonts bundled with a theme.
+#[cfg(feature = "svg")]
+fn bundled_fonts(base_path: &Path, config: &CursorThemeConfig) -> Vec<PathBuf> {
+    config
+        .fonts
+        .iter()
+        .map(|path| base_path.join(path))
+        .collect()
+}
+
+/// The JSON of a Lottie file, which may be a dotLottie container.
+#[cfg(feature = "lottie")]
+fn lottie_text(data: Vec<u8>, file_path: &Path) -> Result<String> {
+    if dotlottie::is_dotlottie(&data) {
+        dotlottie::extract_animation(&data)
+            .with_context(|| format!("Failed to extract dotLottie file: {}", file_path.display()))
+    } else {
+        String::from_utf8(data)
+            .with_context(|| format!("Lottie file is not UTF-8: {}", file_path.display()))
+    }
+}
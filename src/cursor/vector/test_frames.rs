@@ -0,0 +1,15 @@
+//! Frames for unit tests of the vector cursor system.
+
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Point, Size, Transform};
+
+use crate::cursor::vector::types::RenderedFrame;
+
+/// A frame of `size` at scale 1 with every pixel set to the ARGB8888 `pixel`.
+pub fn solid_frame(size: (i32, i32), hotspot: (i32, i32), pixel: [u8; 4]) -> RenderedFrame {
+    let pixels = pixel.repeat((size.0 * size.1) as usize);
+    let buffer =
+        MemoryRenderBuffer::from_slice(&pixels, Fourcc::Argb8888, size, 1, Transform::Normal, None);
+    RenderedFrame::new(buffer, Size::from(size), 1., Point::from(hotspot), None)
+}
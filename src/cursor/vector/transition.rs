@@ -0,0 +1,402 @@
+//! Transition effects, which composite the frames of two cursors while switching between them.
+//!
+//! Effects are plugins: a downstream crate implements [`TransitionEffect`], registers it under a
+//! name with [`CursorManager::register_transition_effect`], and themes pick it with
+//! `effect = "<name>"` in a transition. The built-in `crossfade` and `transform` effects, also
+//! used for the transition types of the same name, are implementations like any other.
+//!
+//! # Stability
+//!
+//! [`TransitionEffect`] and [`AlignedFrames`] are a stable interface for effects: methods are
+//! only ever added with default implementations, and pixels stay premultiplied ARGB8888 in
+//! buffer coordinates.
+//!
+//! [`CursorManager::register_transition_effect`]: crate::cursor::CursorManager::register_transition_effect
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
+use smithay::utils::{Buffer, Physical, Point, Size, Transform};
+
+use crate::cursor::vector::config::{TransitionConfig, TransitionType};
+use crate::cursor::vector::scale;
+use crate::cursor::vector::types::RenderedFrame;
+
+/// Composites the frames of the cursors on both sides of a transition.
+pub trait TransitionEffect: Send + Sync {
+    /// Produces the frame shown at `progress`, from 0 (only `from`) to 1 (only `to`).
+    ///
+    /// `progress` is already eased. Both frames are rendered at the same scale but may differ in
+    /// size and hotspot, [`AlignedFrames`] puts them on a common canvas.
+    fn composite(
+        &self,
+        from: &RenderedFrame,
+        to: &RenderedFrame,
+        progress: f32,
+    ) -> Result<RenderedFrame>;
+
+    /// The effect set up with the parameters of a transition that uses it, like `rotate_deg`,
+    /// or `None` to use it as it is.
+    fn configure(&self, _config: &TransitionConfig) -> Option<Arc<dyn TransitionEffect>> {
+        None
+    }
+}
+
+/// Named transition effects available to themes.
+#[derive(Clone)]
+pub struct TransitionEffects {
+    effects: HashMap<String, Arc<dyn TransitionEffect>>,
+}
+
+impl Default for TransitionEffects {
+    fn default() -> Self {
+        let mut effects = Self {
+            effects: HashMap::new(),
+        };
+        effects.register("crossfade", Arc::new(CrossFade));
+        effects.register("transform", Arc::new(Tween::default()));
+        effects
+    }
+}
+
+impl TransitionEffects {
+    /// Registers an effect, returning the one it replaces.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        effect: Arc<dyn TransitionEffect>,
+    ) -> Option<Arc<dyn TransitionEffect>> {
+        self.effects.insert(name.into(), effect)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn TransitionEffect>> {
+        self.effects.get(name)
+    }
+
+    /// The effect a transition uses, `None` if it only shows the target cursor.
+    pub fn for_transition(&self, config: &TransitionConfig) -> Option<Arc<dyn TransitionEffect>> {
+        let name = match (&config.effect, &config.transition_type) {
+            (Some(name), _) => name.as_str(),
+            (None, TransitionType::CrossFade) => "crossfade",
+            (None, TransitionType::Transform) => "transform",
+            (None, _) => return None,
+        };
+
+        let Some(effect) = self.get(name) else {
+            debug!("unknown transition effect: {name}");
+            return None;
+        };
+        Some(effect.configure(config).unwrap_or_else(|| effect.clone()))
+    }
+}
+
+/// Two frames on a canvas that fits both with their hotspots on the same pixel.
+pub struct AlignedFrames {
+    pub size: Size<i32, Buffer>,
+    pub hotspot: Point<i32, Physical>,
+    pub scale: f64,
+    /// Premultiplied ARGB8888 pixels of the frames, with their opacity applied.
+    pub from: Vec<u8>,
+    pub to: Vec<u8>,
+}
+
+impl AlignedFrames {
+    pub fn new(from: &RenderedFrame, to: &RenderedFrame) -> Self {
+        let left = from.hotspot.x.max(to.hotspot.x);
+        let top = from.hotspot.y.max(to.hotspot.y);
+        let right = (from.size.w - from.hotspot.x).max(to.size.w - to.hotspot.x);
+        let bottom = (from.size.h - from.hotspot.y).max(to.size.h - to.hotspot.y);
+        let size = Size::from(((left + right).max(0), (top + bottom).max(0)));
+        let hotspot = Point::from((left, top));
+
+        Self {
+            size,
+            hotspot,
+            scale: to.scale,
+            from: place(from, size, hotspot),
+            to: place(to, size, hotspot),
+        }
+    }
+
+    /// Wraps pixels of the canvas into a frame.
+    pub fn to_frame(&self, pixels: &[u8]) -> RenderedFrame {
+        let buffer = MemoryRenderBuffer::from_slice(
+            pixels,
+            Fourcc::Argb8888,
+            (self.size.w, self.size.h),
+            scale::memory_buffer_scale(self.scale),
+            Transform::Normal,
+            None,
+        );
+        RenderedFrame::new(buffer, self.size, self.scale, self.hotspot, None)
+    }
+}
+
+/// Copies a frame onto a canvas of `size`, with its hotspot at `hotspot`.
+fn place(frame: &RenderedFrame, size: Size<i32, Buffer>, hotspot: Point<i32, Physical>) -> Vec<u8> {
+    let mut canvas = vec![0; size.w as usize * size.h as usize * 4];
+    let pixels = frame.read_pixels();
+    let (dx, dy) = (hotspot.x - frame.hotspot.x, hotspot.y - frame.hotspot.y);
+    let opacity = frame.opacity.clamp(0., 1.);
+
+    for y in 0..frame.size.h {
+        let src = (y * frame.size.w * 4) as usize;
+        let dst = (((y + dy) * size.w + dx) * 4) as usize;
+        let row = frame.size.w as usize * 4;
+        let (Some(src), Some(dst)) = (pixels.get(src..src + row), canvas.get_mut(dst..dst + row))
+        else {
+            continue;
+        };
+        if opacity == 1. {
+            dst.copy_from_slice(src);
+        } else {
+            for (dst, src) in dst.iter_mut().zip(src) {
+                *dst = (f32::from(*src) * opacity).round() as u8;
+            }
+        }
+    }
+    canvas
+}
+
+/// Blends from one cursor to the other.
+pub struct CrossFade;
+
+impl TransitionEffect for CrossFade {
+    fn composite(
+        &self,
+        from: &RenderedFrame,
+        to: &RenderedFrame,
+        progress: f32,
+    ) -> Result<RenderedFrame> {
+        let frames = AlignedFrames::new(from, to);
+        let t = progress.clamp(0., 1.);
+        let pixels: Vec<u8> = frames
+            .from
+            .iter()
+            .zip(&frames.to)
+            .map(|(a, b)| (f32::from(*a) * (1. - t) + f32::from(*b) * t).round() as u8)
+            .collect();
+        Ok(frames.to_frame(&pixels))
+    }
+}
+
+/// Moves, scales and turns one cursor into the place of the other while fading between them.
+///
+/// Both cursors stay centered on a point that travels from the center of the source cursor to
+/// the center of the target, relative to the hotspot. The source grows or shrinks to the size of
+/// the target on its way out, and the target starts at the size of the source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tween {
+    /// Clockwise turn over the whole transition.
+    pub rotate_deg: f32,
+}
+
+impl TransitionEffect for Tween {
+    fn configure(&self, config: &TransitionConfig) -> Option<Arc<dyn TransitionEffect>> {
+        Some(Arc::new(Tween {
+            rotate_deg: config.rotate_deg as f32,
+        }))
+    }
+
+    fn composite(
+        &self,
+        from: &RenderedFrame,
+        to: &RenderedFrame,
+        progress: f32,
+    ) -> Result<RenderedFrame> {
+        let t = progress.clamp(0., 1.);
+        let area = |frame: &RenderedFrame| (frame.size.w as f32 * frame.size.h as f32).max(1.);
+        let ratio = (area(to) / area(from)).sqrt();
+        let center = |frame: &RenderedFrame| {
+            (
+                frame.size.w as f32 / 2. - frame.hotspot.x as f32,
+                frame.size.h as f32 / 2. - frame.hotspot.y as f32,
+            )
+        };
+        let (from_center, to_center) = (center(from), center(to));
+        let center = (
+            from_center.0 + (to_center.0 - from_center.0) * t,
+            from_center.1 + (to_center.1 - from_center.1) * t,
+        );
+
+        // Both transforms map the pixels of a frame to the canvas, with the hotspot at 0,0.
+        let place = |frame: &RenderedFrame, scale: f32, degrees: f32| {
+            tiny_skia::Transform::from_translate(center.0, center.1)
+                .pre_rotate(degrees)
+                .pre_scale(scale, scale)
+                .pre_translate(-frame.size.w as f32 / 2., -frame.size.h as f32 / 2.)
+        };
+        let layers = [
+            (
+                from,
+                place(from, 1. + (ratio - 1.) * t, self.rotate_deg * t),
+                (1. - t) * from.opacity,
+            ),
+            (
+                to,
+                place(
+                    to,
+                    1. / ratio + (1. - 1. / ratio) * t,
+                    self.rotate_deg * (t - 1.),
+                ),
+                t * to.opacity,
+            ),
+        ];
+
+        let (mut left, mut top, mut right, mut bottom) = (0f32, 0f32, 0f32, 0f32);
+        for (frame, transform, _) in &layers {
+            let (w, h) = (frame.size.w as f32, frame.size.h as f32);
+            for (x, y) in [(0., 0.), (w, 0.), (0., h), (w, h)] {
+                let cx = transform.sx * x + transform.kx * y + transform.tx;
+                let cy = transform.ky * x + transform.sy * y + transform.ty;
+                (left, top) = (left.min(cx), top.min(cy));
+                (right, bottom) = (right.max(cx), bottom.max(cy));
+            }
+        }
+        let (left, top) = (left.floor() as i32, top.floor() as i32);
+        let size = Size::from((
+            (right.ceil() as i32 - left).max(1),
+            (bottom.ceil() as i32 - top).max(1),
+        ));
+
+        let mut canvas = tiny_skia::Pixmap::new(size.w as u32, size.h as u32)
+            .context("transition canvas is too large")?;
+        for (frame, transform, opacity) in layers {
+            // tiny-skia wants RGBA, the frames have ARGB8888 (BGRA in memory).
+            let mut pixels = frame.read_pixels();
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            let Some(pixmap) =
+                tiny_skia::IntSize::from_wh(frame.size.w as u32, frame.size.h as u32)
+                    .and_then(|size| tiny_skia::Pixmap::from_vec(pixels, size))
+            else {
+                continue;
+            };
+            let paint = tiny_skia::PixmapPaint {
+                opacity: opacity.clamp(0., 1.),
+                quality: tiny_skia::FilterQuality::Bilinear,
+                ..Default::default()
+            };
+            let transform = transform.post_translate(-left as f32, -top as f32);
+            canvas.draw_pixmap(0, 0, pixmap.as_ref(), &paint, transform, None);
+        }
+
+        let mut pixels = canvas.take();
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        let buffer = MemoryRenderBuffer::from_slice(
+            &pixels,
+            Fourcc::Argb8888,
+            (size.w, size.h),
+            scale::memory_buffer_scale(to.scale),
+            Transform::Normal,
+            None,
+        );
+        Ok(RenderedFrame::new(
+            buffer,
+            size,
+            to.scale,
+            Point::from((-left, -top)),
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::vector::test_frames::solid_frame as frame;
+
+    #[test]
+    fn aligns_hotspots() {
+        let from = frame((2, 2), (1, 1), [255; 4]);
+        let to = frame((3, 1), (0, 0), [255; 4]);
+        let frames = AlignedFrames::new(&from, &to);
+
+        assert_eq!(frames.size, Size::from((4, 2)));
+        assert_eq!(frames.hotspot, Point::from((1, 1)));
+        let opaque = |pixels: &[u8]| -> Vec<bool> {
+            pixels.chunks_exact(4).map(|pixel| pixel[3] != 0).collect()
+        };
+        assert_eq!(
+            opaque(&frames.from),
+            [true, true, false, false, true, true, false, false]
+        );
+        assert_eq!(
+            opaque(&frames.to),
+            [false, false, false, false, false, true, true, true]
+        );
+    }
+
+    #[test]
+    fn cross_fades() {
+        let from = frame((1, 1), (0, 0), [200, 0, 0, 200]);
+        let to = frame((1, 1), (0, 0), [0, 100, 0, 100]);
+
+        let frame = CrossFade.composite(&from, &to, 0.25).unwrap();
+        assert_eq!(frame.read_pixels(), [150, 25, 0, 175]);
+    }
+
+    #[test]
+    fn effects_for_transitions() {
+        let config: TransitionConfig = toml::from_str(r#"transition_type = "crossfade""#).unwrap();
+        let effects = TransitionEffects::default();
+        assert!(effects.for_transition(&config).is_some());
+
+        let config: TransitionConfig = toml::from_str(r#"effect = "iris""#).unwrap();
+        assert!(effects.for_transition(&config).is_none());
+
+        let config: TransitionConfig = toml::from_str(r#"transition_type = "transform""#).unwrap();
+        assert!(effects.for_transition(&config).is_some());
+    }
+
+    #[test]
+    fn named_transforms_turn() {
+        let from = frame((4, 4), (0, 0), [0, 0, 255, 255]);
+        let to = frame((4, 4), (0, 0), [255, 0, 0, 255]);
+        let effects = TransitionEffects::default();
+        let halfway = |toml: &str| {
+            let config: TransitionConfig = toml::from_str(toml).unwrap();
+            let effect = effects.for_transition(&config).unwrap();
+            effect.composite(&from, &to, 0.5).unwrap().read_pixels()
+        };
+
+        let by_type = halfway("transition_type = \"transform\"\nrotate_deg = 90.0");
+        let by_name = halfway("effect = \"transform\"\nrotate_deg = 90.0");
+        assert_eq!(by_name, by_type);
+        assert_ne!(by_name, halfway("effect = \"transform\""));
+    }
+
+    #[test]
+    fn tweens_between_frames() {
+        let from = frame((4, 4), (0, 0), [0, 0, 255, 255]);
+        let to = frame((8, 8), (4, 4), [255, 0, 0, 255]);
+        let tween = Tween { rotate_deg: 0. };
+
+        // Both ends show the frames in place.
+        let frame = tween.composite(&from, &to, 0.).unwrap();
+        assert_eq!(frame.size, Size::from((4, 4)));
+        assert_eq!(frame.hotspot, Point::from((0, 0)));
+        assert_eq!(frame.read_pixels()[..4], [0, 0, 255, 255]);
+        let frame = tween.composite(&from, &to, 1.).unwrap();
+        assert_eq!(frame.size, Size::from((8, 8)));
+        assert_eq!(frame.hotspot, Point::from((4, 4)));
+        assert_eq!(frame.read_pixels()[..4], [255, 0, 0, 255]);
+
+        // Halfway both are 6 pixels wide, centered between the centers at 2,2 and 0,0.
+        let frame = tween.composite(&from, &to, 0.5).unwrap();
+        assert_eq!(frame.size, Size::from((6, 6)));
+        assert_eq!(frame.hotspot, Point::from((2, 2)));
+
+        // Turned halfway, the squares stand on their corners.
+        let turned = Tween { rotate_deg: 90. }
+            .composite(&from, &to, 0.5)
+            .unwrap();
+        assert!(turned.size.w > 6);
+    }
+}
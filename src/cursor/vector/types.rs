@@ -1,20 +1,127 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use smithay::backend::renderer::element::memory::MemoryRenderBuffer;
-use smithay::utils::{Physical, Point};
+use smithay::utils::{Buffer, Logical, Physical, Point, Rectangle, Size};
+
+use crate::cursor::scheduler::FrameScheduler;
+use crate::cursor::vector::id::CursorId;
 
+/// A rasterized cursor frame, as produced by every [`VectorRenderer`].
+///
+/// [`VectorRenderer`]: crate::cursor::vector::renderer::VectorRenderer
+#[derive(Clone)]
 pub struct RenderedFrame {
     pub buffer: MemoryRenderBuffer,
+    pub size: Size<i32, Buffer>,
+    /// Scale the frame was rasterized at, which may be fractional.
+    pub scale: f64,
+    /// Hotspot in pixels of `buffer`.
+    ///
+    /// Use [`crate::cursor::hotspot::to_logical`] with the render scale to get the logical
+    /// hotspot.
     pub hotspot: Point<i32, Physical>,
+    /// How long the frame stays on screen, `None` for static cursors.
+    pub duration: Option<Duration>,
+    /// Regions of `buffer` that changed compared to the previous frame of the same renderer.
+    pub damage: Vec<Rectangle<i32, Buffer>>,
+    /// Opacity to draw the frame with.
+    pub opacity: f32,
+    /// Shared by the clones of the frame, to tell when `buffer` can be written to again.
+    holders: Arc<()>,
+}
+
+impl RenderedFrame {
+    /// Creates a fully opaque, fully damaged frame.
+    pub fn new(
+        buffer: MemoryRenderBuffer,
+        size: Size<i32, Buffer>,
+        scale: f64,
+        hotspot: Point<i32, Physical>,
+        duration: Option<Duration>,
+    ) -> Self {
+        Self {
+            buffer,
+            size,
+            scale,
+            hotspot,
+            duration,
+            damage: vec![Rectangle::from_size(size)],
+            opacity: 1.,
+            holders: Arc::new(()),
+        }
+    }
+
+    /// Whether this is the only clone of the frame, so nothing else can still show `buffer`.
+    pub fn is_unique(&self) -> bool {
+        Arc::strong_count(&self.holders) == 1
+    }
+
+    /// Whether `other` is a clone of this frame.
+    pub fn is_same(&self, other: &RenderedFrame) -> bool {
+        Arc::ptr_eq(&self.holders, &other.holders)
+    }
+
+    /// Size the frame covers on screen.
+    pub fn logical_size(&self) -> Size<f64, Logical> {
+        Size::from((
+            f64::from(self.size.w) / self.scale,
+            f64::from(self.size.h) / self.scale,
+        ))
+    }
+
+    /// Copies the ARGB8888 pixels out of `buffer`, for inspecting frames outside of rendering.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        let mut pixels = Vec::new();
+        let mut buffer = self.buffer.clone();
+        // Drawing without reporting damage leaves the buffer as it is.
+        let _ = buffer.render().draw(|data| {
+            pixels.extend_from_slice(data);
+            Result::<_, ()>::Ok(Vec::new())
+        });
+        pixels
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LoopMode {
     Once,
     Loop,
     Bounce,
 }
 
+/// Range of frames played by an animated cursor, `start` inclusive and `end` exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl FrameRange {
+    pub fn new(start: u32, end: u32) -> Self {
+        Self {
+            start,
+            end: end.max(start.saturating_add(1)),
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.end - self.start
+    }
+
+    /// Timing of playing the frames of this range, each lasting `frame_duration`.
+    pub fn scheduler(&self, frame_duration: Duration, loop_mode: LoopMode) -> FrameScheduler {
+        FrameScheduler::uniform(self.len(), frame_duration, loop_mode)
+    }
+
+    /// Returns the frame shown `elapsed` into playback of this range with `scheduler`.
+    pub fn frame_at(&self, scheduler: &FrameScheduler, elapsed: Duration) -> u32 {
+        self.start + scheduler.frame_at(elapsed).index as u32
+    }
+}
+
 pub struct VectorCursorData {
-    pub cursor_id: String,
+    pub cursor_id: CursorId,
     pub format: VectorFormat,
 }
 
@@ -27,13 +134,73 @@ pub enum VectorFormat {
 pub enum TransitionState {
     Static,
     Transitioning {
-        from_id: String,
-        to_id: String,
+        from_id: CursorId,
+        to_id: CursorId,
         progress: f32,
     },
     Animated {
-        cursor_id: String,
+        cursor_id: CursorId,
+        start_time_ms: u32,
+        loop_mode: LoopMode,
+        /// Whether the intro segment is playing, from its start at `start_time_ms`, before the
+        /// loop.
+        intro: bool,
+    },
+    /// `cursor_id` plays out before transitioning to `to_id`, see
+    /// `TransitionConfig::finishes_source` and `CursorDefinition::outro`.
+    Finishing {
+        cursor_id: CursorId,
+        to_id: CursorId,
         start_time_ms: u32,
         loop_mode: LoopMode,
+        /// Whether the loop finished and the done segment is playing, from its start at
+        /// `start_time_ms`.
+        done: bool,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn frame_range_playback() {
+        let range = FrameRange::new(10, 14);
+        let frames = |mode| {
+            let scheduler = range.scheduler(ms(10), mode);
+            (0..9)
+                .map(|i| range.frame_at(&scheduler, ms(i * 10)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(frames(LoopMode::Loop), [10, 11, 12, 13, 10, 11, 12, 13, 10]);
+        assert_eq!(frames(LoopMode::Once), [10, 11, 12, 13, 13, 13, 13, 13, 13]);
+        assert_eq!(
+            frames(LoopMode::Bounce),
+            [10, 11, 12, 13, 12, 11, 10, 11, 12]
+        );
+    }
+
+    #[test]
+    fn empty_range_holds_start() {
+        let range = FrameRange::new(5, 5);
+        assert_eq!(range.len(), 1);
+
+        let scheduler = range.scheduler(ms(10), LoopMode::Bounce);
+        assert!(!scheduler.is_animated());
+        assert_eq!(range.frame_at(&scheduler, ms(30)), 5);
+    }
+
+    #[test]
+    fn range_at_last_frame() {
+        let range = FrameRange::new(u32::MAX, 0);
+        assert_eq!(range.end, u32::MAX);
+
+        let scheduler = range.scheduler(ms(10), LoopMode::Loop);
+        assert_eq!(range.frame_at(&scheduler, ms(30)), u32::MAX);
+    }
+}
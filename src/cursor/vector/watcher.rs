@@ -0,0 +1,152 @@
+//! Watcher of the files of a vector cursor theme, so that edits show up without restarting niri.
+//!
+//! Like the config file watcher, it polls modification times on a thread of its own rather than
+//! subscribing to filesystem events.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+use std::{fs, thread};
+
+use smithay::reexports::calloop::channel::Sender;
+
+const POLLING_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Modification times of the files of a theme, by path relative to the theme directory.
+type Snapshot = HashMap<PathBuf, SystemTime>;
+
+/// Sends the files of a theme directory that changed, were added or were removed, relative to
+/// the directory. Dropping it stops the watcher thread.
+pub struct ThemeWatcher {
+    _stop: mpsc::Sender<()>,
+}
+
+impl ThemeWatcher {
+    pub fn new(dir: PathBuf, changed: Sender<Vec<PathBuf>>) -> Self {
+        let (stop, stop_rx) = mpsc::channel::<()>();
+
+        thread::Builder::new()
+            .name(format!("Cursor Theme Watcher for {dir:?}"))
+            .spawn(move || {
+                let mut last = scan(&dir);
+
+                while let Err(mpsc::RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(POLLING_INTERVAL)
+                {
+                    let new = scan(&dir);
+                    let files = changed_files(&last, &new);
+                    last = new;
+                    if files.is_empty() {
+                        continue;
+                    }
+
+                    trace!("cursor theme files changed: {files:?}");
+                    if let Err(err) = changed.send(files) {
+                        warn!("error sending change notification: {err:?}");
+                        break;
+                    }
+                }
+
+                debug!("exiting watcher thread for {dir:?}");
+            })
+            .unwrap();
+
+        Self { _stop: stop }
+    }
+}
+
+/// Modification times of the files under `dir`, following symlinks.
+///
+/// Hidden files and directories, like editor swap files or `.git`, are left out. Every directory
+/// is read once, by its canonical path, so symlinks that loop back don't recurse forever.
+fn scan(dir: &Path) -> Snapshot {
+    let mut snapshot = HashMap::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let path = dir.join(&relative);
+        let Ok(canonical) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if !visited.insert(canonical) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&path) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = relative.join(name);
+            let Ok(metadata) = fs::metadata(entry.path()) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if let Ok(mtime) = metadata.modified() {
+                snapshot.insert(path, mtime);
+            }
+        }
+    }
+    snapshot
+}
+
+/// Files that differ between two snapshots, sorted.
+fn changed_files(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    let modified = new
+        .iter()
+        .filter(|(path, mtime)| old.get(*path) != Some(*mtime))
+        .map(|(path, _)| path);
+    let removed = old.keys().filter(|path| !new.contains_key(*path));
+    let mut files: Vec<_> = modified.chain(removed).cloned().collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_files_between_snapshots() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let old = Snapshot::from([
+            (PathBuf::from("theme.toml"), at(1)),
+            (PathBuf::from("default.svg"), at(1)),
+            (PathBuf::from("wait.json"), at(1)),
+        ]);
+        let new = Snapshot::from([
+            (PathBuf::from("theme.toml"), at(1)),
+            (PathBuf::from("default.svg"), at(2)),
+            (PathBuf::from("sizes/default-48.svg"), at(2)),
+        ]);
+
+        assert_eq!(
+            changed_files(&old, &new),
+            ["default.svg", "sizes/default-48.svg", "wait.json"].map(PathBuf::from)
+        );
+        assert!(changed_files(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn scan_survives_symlink_loops() {
+        let sh = xshell::Shell::new().unwrap();
+        let dir = sh.create_temp_dir().unwrap();
+        let dir = dir.path();
+        fs::create_dir(dir.join("sizes")).unwrap();
+        fs::write(dir.join("theme.toml"), "").unwrap();
+        fs::write(dir.join("sizes/default-48.svg"), "").unwrap();
+        std::os::unix::fs::symlink(dir, dir.join("sizes/back")).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("sizes/up")).unwrap();
+
+        let mut files: Vec<_> = scan(dir).into_keys().collect();
+        files.sort();
+        assert_eq!(
+            files,
+            ["sizes/default-48.svg", "theme.toml"].map(PathBuf::from)
+        );
+    }
+}
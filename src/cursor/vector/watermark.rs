@@ -0,0 +1,80 @@
+//! Debug marker identifying the code path that produced a cursor frame.
+//!
+//! With `NIRI_CURSOR_WATERMARK=1` in the environment, every rendered vector cursor frame gets a
+//! 2×2 pixel code in its top-right corner, so a screenshot in a bug report shows which renderer
+//! drew the cursor. The top-left and bottom-right code pixels are opaque magenta and mark the code;
+//! the other two have the color of the [`Producer`].
+
+use std::env;
+
+/// Opaque magenta, BGRA.
+const MARKER: [u8; 4] = [0xff, 0x00, 0xff, 0xff];
+
+/// The code path that rasterized a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Producer {
+    /// `SvgRenderer` through resvg. Red.
+    Svg,
+    /// `LottieRenderer` with the built-in rasterizer. Green.
+    Lottie,
+    /// `LottieRenderer` with the rlottie engine. Blue.
+    Rlottie,
+}
+
+impl Producer {
+    /// Opaque BGRA code color.
+    fn color(self) -> [u8; 4] {
+        match self {
+            Producer::Svg => [0x00, 0x00, 0xff, 0xff],
+            Producer::Lottie => [0x00, 0xff, 0x00, 0xff],
+            Producer::Rlottie => [0xff, 0x00, 0x00, 0xff],
+        }
+    }
+}
+
+/// Whether watermarks were requested through the environment.
+pub fn enabled() -> bool {
+    env::var_os("NIRI_CURSOR_WATERMARK").is_some_and(|x| x == "1")
+}
+
+/// Draws the code of `producer` into ARGB8888 pixels of a `width` pixels wide frame.
+pub fn stamp(pixels: &mut [u8], width: i32, producer: Producer) {
+    let width = width.max(0) as usize;
+    if width < 2 || pixels.len() < width * 2 * 4 {
+        return;
+    }
+
+    let code = [[MARKER, producer.color()], [producer.color(), MARKER]];
+    for (y, row) in code.iter().enumerate() {
+        for (x, color) in row.iter().enumerate() {
+            let offset = (y * width + width - 2 + x) * 4;
+            pixels[offset..offset + 4].copy_from_slice(color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_top_right_corner() {
+        let mut pixels = vec![0; 3 * 3 * 4];
+        stamp(&mut pixels, 3, Producer::Lottie);
+
+        let pixel = |x: usize, y: usize| &pixels[(y * 3 + x) * 4..][..4];
+        assert_eq!(pixel(1, 0), MARKER);
+        assert_eq!(pixel(2, 0), Producer::Lottie.color());
+        assert_eq!(pixel(1, 1), Producer::Lottie.color());
+        assert_eq!(pixel(2, 1), MARKER);
+        assert_eq!(pixel(0, 0), [0; 4]);
+        assert_eq!(pixel(2, 2), [0; 4]);
+    }
+
+    #[test]
+    fn skips_tiny_frames() {
+        let mut pixels = vec![0; 4];
+        stamp(&mut pixels, 1, Producer::Svg);
+        assert_eq!(pixels, [0; 4]);
+    }
+}
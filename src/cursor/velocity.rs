@@ -0,0 +1,111 @@
+//! Pointer velocity estimation.
+
+use std::time::Duration;
+
+use smithay::utils::{Logical, Point};
+
+/// Time constant of the exponential smoothing.
+const SMOOTHING: Duration = Duration::from_millis(50);
+
+/// The pointer counts as resting after this long without motion.
+const IDLE: Duration = Duration::from_millis(100);
+
+/// Smoothed pointer speed in logical pixels per second.
+#[derive(Debug, Default)]
+pub struct VelocityTracker {
+    last_motion: Option<Duration>,
+    velocity: f64,
+    /// Smoothed velocity vector, for the direction of motion.
+    vector: (f64, f64),
+}
+
+impl VelocityTracker {
+    /// Records a pointer motion of `distance` logical pixels.
+    pub fn motion(&mut self, distance: f64, now: Duration) {
+        self.record(distance, None, now);
+    }
+
+    /// Records a pointer motion by `delta` logical pixels, also tracking its direction.
+    pub fn motion_delta(&mut self, delta: Point<f64, Logical>, now: Duration) {
+        self.record(delta.x.hypot(delta.y), Some(delta), now);
+    }
+
+    fn record(&mut self, distance: f64, delta: Option<Point<f64, Logical>>, now: Duration) {
+        let dt = match self.last_motion {
+            Some(last) if now.saturating_sub(last) < IDLE => now.saturating_sub(last),
+            _ => {
+                self.velocity = 0.;
+                self.vector = (0., 0.);
+                IDLE
+            }
+        };
+
+        let dt = dt.as_secs_f64().max(0.001);
+        let alpha = (dt / SMOOTHING.as_secs_f64()).min(1.);
+        self.velocity += (distance / dt - self.velocity) * alpha;
+        if let Some(delta) = delta {
+            self.vector.0 += (delta.x / dt - self.vector.0) * alpha;
+            self.vector.1 += (delta.y / dt - self.vector.1) * alpha;
+        }
+        self.last_motion = Some(now);
+    }
+
+    /// Unit vector of the direction the pointer moves in, `None` if it's resting or only
+    /// distances were recorded.
+    pub fn direction(&self, now: Duration) -> Option<(f64, f64)> {
+        if self.velocity(now) == 0. {
+            return None;
+        }
+        let (x, y) = self.vector;
+        let length = x.hypot(y);
+        (length > 0.).then(|| (x / length, y / length))
+    }
+
+    /// Current pointer speed, 0 if the pointer is resting.
+    pub fn velocity(&self, now: Duration) -> f64 {
+        match self.last_motion {
+            Some(last) if now.saturating_sub(last) < IDLE => self.velocity,
+            _ => 0.,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_motion_converges() {
+        let mut tracker = VelocityTracker::default();
+
+        // 5 px every 5 ms is 1000 px/s.
+        for i in 0..100 {
+            tracker.motion(5., Duration::from_millis(i * 5));
+        }
+
+        let velocity = tracker.velocity(Duration::from_millis(500));
+        assert!((velocity - 1000.).abs() < 1., "{velocity}");
+    }
+
+    #[test]
+    fn resting_pointer_has_no_velocity() {
+        let mut tracker = VelocityTracker::default();
+        assert_eq!(tracker.velocity(Duration::ZERO), 0.);
+
+        tracker.motion(5., Duration::from_millis(1000));
+        assert!(tracker.velocity(Duration::from_millis(1000)) > 0.);
+        assert_eq!(tracker.velocity(Duration::from_millis(1200)), 0.);
+    }
+
+    #[test]
+    fn direction_of_motion() {
+        let mut tracker = VelocityTracker::default();
+        for i in 0..20 {
+            tracker.motion_delta(Point::from((3., -4.)), Duration::from_millis(i * 5));
+        }
+
+        let (x, y) = tracker.direction(Duration::from_millis(100)).unwrap();
+        assert!((x - 0.6).abs() < 1e-6 && (y + 0.8).abs() < 1e-6, "{x}, {y}");
+        assert_eq!(tracker.direction(Duration::from_millis(300)), None);
+    }
+}
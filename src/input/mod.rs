@@ -9,7 +9,7 @@ use input::event::gesture::GestureEventCoordinates as _;
 use niri_config::{
     Action, Bind, Binds, Config, Key, ModKey, Modifiers, MruDirection, SwitchBinds, Trigger,
 };
-use niri_ipc::LayoutSwitchTarget;
+use niri_ipc::{CursorColorScheme, LayoutSwitchTarget};
 use smithay::backend::input::{
     AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability, Event,
     GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent as _, GestureSwipeUpdateEvent as _,
@@ -43,6 +43,7 @@ use touch_overview_grab::TouchOverviewGrab;
 use self::move_grab::MoveGrab;
 use self::resize_grab::ResizeGrab;
 use self::spatial_movement_grab::SpatialMovementGrab;
+use crate::cursor::vector::config::ColorScheme;
 #[cfg(feature = "dbus")]
 use crate::dbus::freedesktop_a11y::KbMonBlock;
 use crate::layout::scrolling::ScrollDirection;
@@ -2297,6 +2298,42 @@ impl State {
                     watcher.load_config();
                 }
             }
+            Action::SetCursorColor(color) => {
+                match self.niri.cursor_manager.set_foreground_color(color) {
+                    Ok(()) => self.niri.queue_redraw_all(),
+                    Err(err) => warn!("error setting cursor color: {err:?}"),
+                }
+            }
+            Action::SetCursorColorScheme(scheme) => {
+                let scheme = match scheme {
+                    CursorColorScheme::Light => ColorScheme::Light,
+                    CursorColorScheme::Dark => ColorScheme::Dark,
+                };
+                self.niri.cursor_manager.set_color_scheme(scheme);
+                self.niri.queue_redraw_all();
+            }
+            Action::ToggleCursorMirror => {
+                self.niri.cursor_manager.toggle_mirror();
+                self.niri.queue_redraw_all();
+            }
+            Action::PauseCursorAnimations => {
+                self.niri.cursor_manager.set_animations_paused(true);
+            }
+            Action::ResumeCursorAnimations => {
+                self.niri.cursor_manager.set_animations_paused(false);
+                self.niri.cursor_manager.freeze_animation_at_frame(None);
+                self.niri.queue_redraw_all();
+            }
+            Action::FreezeCursorFrame(frame) => {
+                self.niri
+                    .cursor_manager
+                    .freeze_animation_at_frame(Some(frame));
+                self.niri.queue_redraw_all();
+            }
+            Action::StepCursorFrame(count) => {
+                self.niri.cursor_manager.step_animation_frame(count);
+                self.niri.queue_redraw_all();
+            }
             Action::MruConfirm => {
                 self.confirm_mru();
             }
@@ -2403,6 +2440,10 @@ impl State {
         // We have an output, so we can compute the new location and focus.
         let mut new_pos = pos + event.delta();
 
+        let delta = event.delta();
+        self.niri.cursor_manager.pointer_motion(delta);
+        self.niri.queue_cursor_prerender();
+
         // We received an event for the regular pointer, so show it now.
         self.niri.pointer_visibility = PointerVisibility::Visible;
         self.niri.tablet_cursor_location = None;
@@ -2654,6 +2695,10 @@ impl State {
 
         let pointer = self.niri.seat.get_pointer().unwrap();
 
+        let delta = pos - pointer.current_location();
+        self.niri.cursor_manager.pointer_motion(delta);
+        self.niri.queue_cursor_prerender();
+
         if let Some(output) = self.niri.screenshot_ui.selection_output() {
             let geom = self.niri.global_space.output_geometry(output).unwrap();
             let mut point = (pos - geom.loc.to_f64())
@@ -7,8 +7,9 @@ use anyhow::{anyhow, bail, Context};
 use niri_config::OutputName;
 use niri_ipc::socket::Socket;
 use niri_ipc::{
-    Action, Cast, CastKind, CastTarget, Event, KeyboardLayouts, LogicalOutput, Mode, Output,
-    OutputConfigChanged, Overview, Request, Response, Transform, Window, WindowLayout,
+    Action, Cast, CastKind, CastTarget, CursorStats, Event, KeyboardLayouts, LogicalOutput, Mode,
+    Output, OutputConfigChanged, Overview, RenderTimes, Request, Response, Transform, Window,
+    WindowLayout,
 };
 use serde_json::json;
 
@@ -49,6 +50,7 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
         Msg::RequestError => Request::ReturnError,
         Msg::OverviewState => Request::OverviewState,
         Msg::Casts => Request::Casts,
+        Msg::CursorStats => Request::CursorStats,
     };
 
     let mut socket = Socket::connect().context("error connecting to the niri socket")?;
@@ -550,11 +552,70 @@ pub fn handle_msg(mut msg: Msg, json: bool) -> anyhow::Result<()> {
                 println!();
             }
         }
+        Msg::CursorStats => {
+            let Response::CursorStats(stats) = response else {
+                bail!("unexpected response: expected CursorStats, got {response:?}");
+            };
+
+            if json {
+                let stats = serde_json::to_string(&stats).context("error formatting response")?;
+                println!("{stats}");
+                return Ok(());
+            }
+
+            print_cursor_stats(stats);
+        }
     }
 
     Ok(())
 }
 
+fn print_cursor_stats(stats: CursorStats) {
+    let CursorStats {
+        render_times,
+        cursor_render_times,
+        xcursor_upload_bandwidth,
+        vector_upload_bandwidth,
+    } = stats;
+
+    println!("Cursor buffer uploads:");
+    println!("  XCursor: {}", fmt_bandwidth(xcursor_upload_bandwidth));
+    println!("  Vector: {}", fmt_bandwidth(vector_upload_bandwidth));
+
+    let Some(render_times) = render_times else {
+        println!("No vector cursor theme is in use.");
+        return;
+    };
+
+    println!("Vector cursor raster times:");
+    println!("  All cursors: {}", fmt_render_times(render_times));
+
+    let mut cursors: Vec<_> = cursor_render_times.into_iter().collect();
+    cursors.sort_by(|a, b| a.0.cmp(&b.0));
+    for (cursor_id, times) in cursors {
+        println!("  {cursor_id}: {}", fmt_render_times(times));
+    }
+}
+
+fn fmt_bandwidth(bytes_per_second: f64) -> String {
+    format!("{:.1} KiB/s", bytes_per_second / 1024.)
+}
+
+fn fmt_render_times(times: RenderTimes) -> String {
+    let RenderTimes {
+        count,
+        mean_us,
+        p50_us,
+        p95_us,
+        p99_us,
+        max_us,
+    } = times;
+    format!(
+        "{count} frames, mean {mean_us} µs, p50 {p50_us} µs, p95 {p95_us} µs, \
+         p99 {p99_us} µs, max {max_us} µs"
+    )
+}
+
 fn print_output(output: Output) -> anyhow::Result<()> {
     let Output {
         name,
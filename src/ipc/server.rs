@@ -455,6 +455,15 @@ async fn process(ctx: &ClientCtx, request: Request) -> Reply {
             let casts = state.casts.casts.values().cloned().collect();
             Response::Casts(casts)
         }
+        Request::CursorStats => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.niri.cursor_manager.ipc_stats());
+            });
+            let result = rx.recv().await;
+            let stats = result.map_err(|_| String::from("error getting cursor stats"))?;
+            Response::CursorStats(stats)
+        }
     };
 
     Ok(response)
@@ -110,6 +110,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Ok(());
             }
             Sub::Panic => cause_panic(),
+            Sub::CursorServer {
+                theme,
+                socket,
+                size,
+            } => {
+                niri::cursor::vector::remote::serve(&theme, size, &socket)?;
+                return Ok(());
+            }
             Sub::Completions { shell } => {
                 match shell {
                     CompletionShell::Nushell => {
@@ -114,6 +114,7 @@ use crate::a11y::A11y;
 use crate::animation::Clock;
 use crate::backend::tty::SurfaceDmabufFeedback;
 use crate::backend::{Backend, Headless, RenderResult, Tty, Winit};
+use crate::cursor::vector::watcher::ThemeWatcher;
 use crate::cursor::{CursorManager, CursorTextureCache, RenderCursor, XCursor};
 #[cfg(feature = "dbus")]
 use crate::dbus::freedesktop_locale1::Locale1ToNiri;
@@ -356,6 +357,12 @@ pub struct Niri {
     /// Used for limiting the reset to once per iteration, so that it's not spammed with high
     /// resolution mice.
     pub pointer_inactivity_timer_got_reset: bool,
+    /// Timer redrawing when the next frame of an animated cursor is due, with its deadline.
+    pub cursor_frame_timer: Option<(RegistrationToken, Duration)>,
+    /// Watcher reloading the vector cursor theme when its files change.
+    pub cursor_theme_watcher: Option<ThemeWatcher>,
+    /// Whether an idle callback pre-rendering vector cursor frames is queued.
+    pub cursor_prerender_queued: bool,
     /// Whether the (idle notifier) activity was notified this event loop iteration.
     ///
     /// Used for limiting the notify to once per iteration, so that it's not spammed with high
@@ -1465,6 +1472,15 @@ impl State {
             self.niri
                 .cursor_manager
                 .reload(&config.cursor.xcursor_theme, config.cursor.xcursor_size);
+            self.niri
+                .cursor_manager
+                .set_fallback(config.cursor.fallback.clone());
+            self.niri
+                .cursor_manager
+                .set_reduce_motion(config.cursor.reduce_motion);
+            self.niri
+                .cursor_manager
+                .set_max_animation_fps(config.cursor.max_animation_fps);
             self.niri.cursor_texture_cache.clear();
         }
 
@@ -2337,11 +2353,15 @@ impl Niri {
         let cursor_shape_manager_state = CursorShapeManagerState::new::<State>(&display_handle);
         let vector_theme_path =
             std::path::PathBuf::from("/home/duck/Desktop/coding/niri/resources/cursors");
-        let cursor_manager = CursorManager::new_with_vector_theme(
+        let mut cursor_manager = CursorManager::new_with_vector_theme(
+            animation_clock.clone(),
             &config_.cursor.xcursor_theme,
             config_.cursor.xcursor_size,
             Some(vector_theme_path),
         );
+        cursor_manager.set_fallback(config_.cursor.fallback.clone());
+        cursor_manager.set_reduce_motion(config_.cursor.reduce_motion);
+        cursor_manager.set_max_animation_fps(config_.cursor.max_animation_fps);
 
         let mod_key = backend.mod_key(&config.borrow());
         let mods_with_mouse_binds = mods_with_mouse_binds(mod_key, &config_.binds);
@@ -2373,6 +2393,32 @@ impl Niri {
             )
             .unwrap();
 
+        event_loop
+            .insert_source(
+                Timer::from_duration(Duration::from_secs(60)),
+                |_, _, state| {
+                    state.niri.cursor_manager.trim_caches_if_idle();
+                    TimeoutAction::ToDuration(Duration::from_secs(60))
+                },
+            )
+            .unwrap();
+
+        let cursor_theme_watcher = cursor_manager.vector_theme_path().map(|path| {
+            let (tx, rx) = calloop::channel::channel();
+            event_loop
+                .insert_source(rx, |event, _, state| {
+                    if let calloop::channel::Event::Msg(changed) = event {
+                        let res = state.niri.cursor_manager.reload_vector_files(&changed);
+                        match res {
+                            Ok(()) => state.niri.queue_redraw_all(),
+                            Err(err) => warn!("error reloading vector cursor theme: {err:?}"),
+                        }
+                    }
+                })
+                .unwrap();
+            ThemeWatcher::new(path.to_owned(), tx)
+        });
+
         let socket_name = create_wayland_socket.then(|| {
             let socket_source = ListeningSocketSource::new_auto().unwrap();
             let socket_name = socket_source.socket_name().to_os_string();
@@ -2518,6 +2564,9 @@ impl Niri {
             pointer_visibility: PointerVisibility::Visible,
             pointer_inactivity_timer: None,
             pointer_inactivity_timer_got_reset: false,
+            cursor_frame_timer: None,
+            cursor_theme_watcher,
+            cursor_prerender_queued: false,
             notified_activity_this_iteration: false,
             pointer_inside_hot_corner: false,
             tablet_cursor_location: None,
@@ -2742,6 +2791,30 @@ impl Niri {
                 self.queue_redraw(&output);
             }
         }
+
+        let scales: Vec<f64> = self
+            .global_space
+            .outputs()
+            .map(|output| output.current_scale().fractional_scale())
+            .collect();
+        self.cursor_manager.update_scales(&scales);
+        self.queue_cursor_prerender();
+
+        let config = self.config.borrow();
+        let colors: Vec<_> = self
+            .global_space
+            .outputs()
+            .map(|output| {
+                let name = output.user_data().get::<OutputName>().unwrap();
+                let color = config
+                    .outputs
+                    .find(name)
+                    .and_then(|c| c.cursor_color.clone());
+                (output.name(), color)
+            })
+            .collect();
+        drop(config);
+        self.cursor_manager.update_output_colors(&colors);
     }
 
     pub fn add_output(&mut self, output: Output, refresh_interval: Option<Duration>, vrr: bool) {
@@ -2954,6 +3027,7 @@ impl Niri {
 
         self.monitors_active = false;
         backend.set_monitors_active(false);
+        self.cursor_manager.set_monitors_active(false);
     }
 
     pub fn activate_monitors(&mut self, backend: &mut Backend) {
@@ -2963,6 +3037,7 @@ impl Niri {
 
         self.monitors_active = true;
         backend.set_monitors_active(true);
+        self.cursor_manager.set_monitors_active(true);
 
         self.queue_redraw_all();
     }
@@ -3584,6 +3659,20 @@ impl Niri {
         }
     }
 
+    /// Pre-renders the queued vector cursor frames in idle callbacks, a few frames each.
+    pub fn queue_cursor_prerender(&mut self) {
+        if self.cursor_prerender_queued || !self.cursor_manager.has_prerender_work() {
+            return;
+        }
+
+        self.cursor_prerender_queued = true;
+        self.event_loop.insert_idle(|state| {
+            state.niri.cursor_prerender_queued = false;
+            state.niri.cursor_manager.prerender_some();
+            state.niri.queue_cursor_prerender();
+        });
+    }
+
     /// Schedules an immediate redraw if one is not already scheduled.
     pub fn queue_redraw(&mut self, output: &Output) {
         let state = self.output_state.get_mut(output).unwrap();
@@ -3622,8 +3711,9 @@ impl Niri {
         let pointer_pos = pointer_pos - output_pos.to_f64();
 
         // Get the render cursor to draw.
-        let cursor_scale = output_scale.integer_scale();
-        let render_cursor = self.cursor_manager.get_render_cursor(cursor_scale);
+        let render_cursor = self
+            .cursor_manager
+            .get_render_cursor(output_scale, Some(&output.name()));
 
         let output_scale = Scale::from(output.current_scale().fractional_scale());
 
@@ -3649,9 +3739,8 @@ impl Niri {
                 cursor,
             } => {
                 let (idx, frame) = cursor.frame(self.start_time.elapsed().as_millis() as u32);
-                let hotspot = XCursor::hotspot(frame).to_logical(scale);
-                let pointer_pos =
-                    (pointer_pos - hotspot.to_f64()).to_physical_precise_round(output_scale);
+                let hotspot = XCursor::hotspot_logical(frame, scale);
+                let pointer_pos = (pointer_pos - hotspot).to_physical_precise_round(output_scale);
 
                 let texture = self.cursor_texture_cache.get(icon, scale, &cursor, idx);
                 match MemoryRenderBufferRenderElement::from_buffer(
@@ -3669,18 +3758,21 @@ impl Niri {
                     }
                 }
             }
-            RenderCursor::Vector { hotspot, buffer } => {
-                let hotspot_logical = hotspot.to_logical(1);
-                let pointer_pos = (pointer_pos - hotspot_logical.to_f64())
-                    .to_physical_precise_round(output_scale);
+            RenderCursor::Vector {
+                hotspot,
+                buffer,
+                size,
+                opacity,
+            } => {
+                let pointer_pos = (pointer_pos - hotspot).to_physical_precise_round(output_scale);
 
                 match MemoryRenderBufferRenderElement::from_buffer(
                     renderer,
                     pointer_pos,
                     &buffer,
+                    Some(opacity),
                     None,
-                    None,
-                    None,
+                    size,
                     Kind::Cursor,
                 ) {
                     Ok(element) => push(element.into()),
@@ -4350,6 +4442,18 @@ impl Niri {
 
         let mut res = RenderResult::Skipped;
         if self.monitors_active {
+            // Cursor frames due after this one are redrawn when they are due rather than on every
+            // vblank.
+            let cursor_animated = match self.cursor_manager.next_frame_deadline() {
+                Some(deadline) if deadline > target_presentation_time => {
+                    self.schedule_cursor_frame(deadline);
+                    false
+                }
+                _ => self
+                    .cursor_manager
+                    .is_current_cursor_animated(output.current_scale().integer_scale()),
+            };
+
             let state = self.output_state.get_mut(output).unwrap();
             state.unfinished_animations_remain = self.layout.are_animations_ongoing(Some(output));
             state.unfinished_animations_remain |=
@@ -4360,9 +4464,7 @@ impl Niri {
             state.unfinished_animations_remain |= state.screen_transition.is_some();
 
             // Also keep redrawing if the current cursor is animated.
-            state.unfinished_animations_remain |= self
-                .cursor_manager
-                .is_current_cursor_animated(output.current_scale().integer_scale());
+            state.unfinished_animations_remain |= cursor_animated;
 
             // Also check layer surfaces.
             if !state.unfinished_animations_remain {
@@ -6063,6 +6165,27 @@ impl Niri {
         self.pointer_inactivity_timer_got_reset = true;
     }
 
+    /// Redraws at `deadline`, a monotonic time, when the next frame of the cursor is due.
+    fn schedule_cursor_frame(&mut self, deadline: Duration) {
+        if let Some((token, scheduled)) = self.cursor_frame_timer {
+            if scheduled == deadline {
+                return;
+            }
+            self.event_loop.remove(token);
+        }
+
+        let timer = Timer::from_duration(deadline.saturating_sub(get_monotonic_time()));
+        let token = self
+            .event_loop
+            .insert_source(timer, |_, _, state| {
+                state.niri.cursor_frame_timer = None;
+                state.niri.queue_redraw_all();
+                TimeoutAction::Drop
+            })
+            .unwrap();
+        self.cursor_frame_timer = Some((token, deadline));
+    }
+
     pub fn notify_activity(&mut self) {
         if self.notified_activity_this_iteration {
             return;
@@ -0,0 +1,196 @@
+//! Headless driver of the [`CursorManager`] for tests of the vector cursor system.
+//!
+//! A [`CursorFixture`] writes a vector cursor theme into a temporary directory and runs a cursor
+//! manager on it without a compositor. Tests go through the same calls as the compositor (icon
+//! changes, output scale changes, theme reloads) and inspect what would be drawn as a
+//! [`Capture`]. Client cursor surfaces need a Wayland connection and aren't supported; tests
+//! stick to named cursors.
+
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use smithay::input::pointer::{CursorIcon, CursorImageStatus};
+use smithay::output::Scale;
+use smithay::utils::{Buffer, Logical, Point, Size};
+use xshell::{Shell, TempDir};
+
+use crate::animation::Clock;
+use crate::cursor::vector::golden::Image;
+use crate::cursor::vector::renderer::VectorRenderer;
+use crate::cursor::{CursorManager, RenderCursor};
+
+/// XCursor theme that doesn't exist, so that named cursors come from the embedded fallback.
+const XCURSOR_THEME: &str = "niri-test-missing-theme";
+
+pub struct CursorFixture {
+    pub manager: CursorManager,
+    /// Time of the manager, which only passes through [`Self::advance`].
+    clock: Clock,
+    sh: Shell,
+    dir: TempDir,
+}
+
+/// What the cursor manager would draw.
+#[derive(Debug)]
+pub enum Capture {
+    Hidden,
+    Surface,
+    Named(CursorIcon),
+    Vector {
+        /// Pixels of the buffer, straight RGBA.
+        image: Image,
+        hotspot: Point<f64, Logical>,
+        /// Logical size the buffer is drawn at, if it differs from its natural size.
+        size: Option<Size<i32, Logical>>,
+        opacity: f32,
+        /// Cursor the frame was rendered for.
+        cursor_id: String,
+    },
+}
+
+impl CursorFixture {
+    /// Writes `theme_toml` and `files`, as paths relative to the theme directory and their
+    /// contents, and loads the theme at cursor size 24.
+    pub fn new(theme_toml: &str, files: &[(&str, &str)]) -> Self {
+        let sh = Shell::new().unwrap();
+        let dir = sh.create_temp_dir().unwrap();
+        let clock = Clock::with_time(Duration::ZERO);
+        let mut fixture = Self {
+            manager: CursorManager::new(clock.clone(), XCURSOR_THEME, 24),
+            clock,
+            sh,
+            dir,
+        };
+        fixture.write("theme.toml", theme_toml);
+        for (path, contents) in files {
+            fixture.write(path, contents);
+        }
+        fixture.reload().unwrap();
+        fixture
+    }
+
+    pub fn theme_dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Writes a file of the theme, which the manager only sees after [`Self::reload`].
+    pub fn write(&self, path: &str, contents: &str) {
+        self.sh
+            .write_file(self.theme_dir().join(path), contents)
+            .unwrap();
+    }
+
+    /// Loads the theme from disk again, like a theme switch.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let path = self.theme_dir().to_path_buf();
+        self.manager.set_vector_theme(path)
+    }
+
+    /// Lets `ms` milliseconds pass for animations.
+    pub fn advance(&mut self, ms: u64) {
+        let now = self.clock.now_unadjusted();
+        self.clock.set_unadjusted(now + Duration::from_millis(ms));
+    }
+
+    pub fn set_icon(&mut self, icon: CursorIcon) {
+        self.manager
+            .set_cursor_image(CursorImageStatus::Named(icon));
+    }
+
+    /// Tells the manager the fractional scales of the connected outputs.
+    pub fn set_output_scales(&mut self, scales: &[f64]) {
+        self.manager.update_scales(scales);
+    }
+
+    /// The renderer the theme uses for a cursor at `scale`, to check which ones are reused.
+    pub fn renderer(&self, cursor_id: &str, scale: f64) -> Rc<dyn VectorRenderer> {
+        self.manager
+            .vector_store()
+            .expect("no vector theme loaded")
+            .get_renderer_at(cursor_id, scale)
+            .unwrap()
+    }
+
+    /// Renders the cursor for an output with `scale`.
+    pub fn capture(&mut self, scale: f64) -> Capture {
+        let scale = if scale.fract() == 0. {
+            Scale::Integer(scale as i32)
+        } else {
+            Scale::Fractional(scale)
+        };
+
+        // The buffer doesn't tell its size, but the history keeps a copy of the frame.
+        self.manager.set_history_capacity(1);
+        let (hotspot, size, opacity) = match self.manager.get_render_cursor(scale, None) {
+            RenderCursor::Hidden => return Capture::Hidden,
+            RenderCursor::Surface { .. } => return Capture::Surface,
+            RenderCursor::Named { icon, .. } => return Capture::Named(icon),
+            RenderCursor::Vector {
+                hotspot,
+                size,
+                opacity,
+                ..
+            } => (hotspot, size, opacity),
+        };
+
+        let history = self.manager.cursor_history();
+        let snapshot = history
+            .snapshots()
+            .last()
+            .expect("vector cursor wasn't recorded");
+        Capture::Vector {
+            image: snapshot.image.clone(),
+            hotspot,
+            size,
+            opacity,
+            cursor_id: snapshot.cursor_id.clone(),
+        }
+    }
+}
+
+impl Capture {
+    /// The cursor a vector capture shows, panicking for every other source.
+    pub fn cursor_id(&self) -> &str {
+        match self {
+            Capture::Vector { cursor_id, .. } => cursor_id,
+            other => panic!("expected a vector cursor, got {other:?}"),
+        }
+    }
+
+    pub fn image(&self) -> &Image {
+        match self {
+            Capture::Vector { image, .. } => image,
+            other => panic!("expected a vector cursor, got {other:?}"),
+        }
+    }
+
+    pub fn hotspot(&self) -> Point<f64, Logical> {
+        match self {
+            Capture::Vector { hotspot, .. } => *hotspot,
+            other => panic!("expected a vector cursor, got {other:?}"),
+        }
+    }
+
+    pub fn buffer_size(&self) -> Size<i32, Buffer> {
+        self.image().size
+    }
+
+    /// Straight RGBA of the pixel at `x`, `y` of the buffer.
+    pub fn pixel(&self, x: i32, y: i32) -> [u8; 4] {
+        let image = self.image();
+        assert!(
+            x < image.size.w && y < image.size.h,
+            "({x}, {y}) is out of bounds"
+        );
+        let at = ((y * image.size.w + x) * 4) as usize;
+        image.pixels[at..at + 4].try_into().unwrap()
+    }
+}
+
+/// A square SVG of `size` user units filled with `color`.
+pub fn square_svg(size: u32, color: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}"><rect width="{size}" height="{size}" fill="{color}"/></svg>"#
+    )
+}
@@ -1,6 +1,8 @@
 use fixture::Fixture;
 
 mod client;
+#[cfg(feature = "svg")]
+mod cursor_fixture;
 mod fixture;
 mod server;
 
@@ -9,4 +11,6 @@ mod floating;
 mod fullscreen;
 mod layer_shell;
 mod transactions;
+#[cfg(feature = "svg")]
+mod vector_cursors;
 mod window_opening;
@@ -0,0 +1,612 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+use smithay::input::pointer::{CursorIcon, CursorImageStatus};
+use smithay::utils::{Point, Size};
+
+use super::cursor_fixture::{square_svg, Capture, CursorFixture};
+use crate::cursor::vector::config::ColorScheme;
+use crate::cursor::vector::integrity;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+const THEME: &str = r#"
+[cursors.default]
+format = "svg"
+file = "default.svg"
+hotspot = [0, 0]
+
+[cursors.pointer]
+format = "svg"
+file = "pointer.svg"
+hotspot = [4, 4]
+"#;
+
+fn fixture() -> CursorFixture {
+    CursorFixture::new(
+        THEME,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    )
+}
+
+#[test]
+fn icon_changes() {
+    let mut f = fixture();
+
+    let capture = f.capture(1.);
+    assert_eq!(capture.cursor_id(), "default");
+    assert_eq!(capture.buffer_size(), Size::from((24, 24)));
+    assert_eq!(capture.pixel(0, 0), RED);
+    assert_eq!(capture.hotspot(), Point::from((0., 0.)));
+
+    f.set_icon(CursorIcon::Pointer);
+    let capture = f.capture(1.);
+    assert_eq!(capture.cursor_id(), "pointer");
+    assert_eq!(capture.pixel(7, 7), BLUE);
+    assert_eq!(capture.hotspot(), Point::from((4., 4.)));
+
+    // Icons the theme doesn't have keep the cursor that was shown.
+    f.set_icon(CursorIcon::ZoomIn);
+    assert_eq!(f.capture(1.).cursor_id(), "pointer");
+
+    f.set_icon(CursorIcon::Default);
+    assert_eq!(f.capture(1.).pixel(4, 4), RED);
+}
+
+#[test]
+fn scale_changes() {
+    let mut f = fixture();
+    f.set_icon(CursorIcon::Pointer);
+    f.set_output_scales(&[1., 2.]);
+
+    let capture = f.capture(2.);
+    assert_eq!(capture.buffer_size(), Size::from((48, 48)));
+    assert_eq!(capture.pixel(47, 47), BLUE);
+    // The hotspot stays on the same spot of the artwork.
+    assert_eq!(capture.hotspot(), Point::from((4., 4.)));
+    let Capture::Vector { size, .. } = capture else {
+        unreachable!()
+    };
+    assert_eq!(size, None);
+
+    let capture = f.capture(1.);
+    assert_eq!(capture.buffer_size(), Size::from((24, 24)));
+    assert_eq!(capture.hotspot(), Point::from((4., 4.)));
+}
+
+#[test]
+fn renderers_are_cached() {
+    let mut f = fixture();
+    let renderer = f.renderer("default", 1.);
+
+    f.capture(1.);
+    f.set_icon(CursorIcon::Pointer);
+    f.capture(1.);
+    f.set_icon(CursorIcon::Default);
+    f.capture(2.);
+
+    assert!(Rc::ptr_eq(&renderer, &f.renderer("default", 1.)));
+    assert!(Rc::ptr_eq(&renderer, &f.renderer("default", 2.)));
+}
+
+#[test]
+fn reload_picks_up_changes() {
+    let mut f = fixture();
+    f.set_icon(CursorIcon::Pointer);
+    let renderer = f.renderer("pointer", 1.);
+    assert_eq!(f.capture(1.).pixel(0, 0), BLUE);
+
+    f.write("pointer.svg", &square_svg(24, "#00ff00"));
+    // Nothing changes until the theme is reloaded.
+    assert_eq!(f.capture(1.).pixel(0, 0), BLUE);
+
+    f.reload().unwrap();
+    let capture = f.capture(1.);
+    assert_eq!(capture.cursor_id(), "pointer");
+    assert_eq!(capture.pixel(0, 0), GREEN);
+    assert!(!Rc::ptr_eq(&renderer, &f.renderer("pointer", 1.)));
+}
+
+#[test]
+fn broken_reload_keeps_theme() {
+    let mut f = fixture();
+    f.write("theme.toml", "cursors = 1");
+
+    assert!(f.reload().is_err());
+    assert_eq!(f.capture(1.).pixel(0, 0), RED);
+}
+
+#[test]
+fn changed_files_reload_their_cursors() {
+    let mut f = fixture();
+    f.set_icon(CursorIcon::Pointer);
+    let default = f.renderer("default", 1.);
+    let pointer = f.renderer("pointer", 1.);
+
+    f.write("pointer.svg", &square_svg(24, "#00ff00"));
+    f.manager
+        .reload_vector_files(&[PathBuf::from("pointer.svg")])
+        .unwrap();
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+    assert!(!Rc::ptr_eq(&pointer, &f.renderer("pointer", 1.)));
+    // Cursors drawn from other files stay loaded.
+    assert!(Rc::ptr_eq(&default, &f.renderer("default", 1.)));
+
+    // A changed theme.toml reloads the whole theme.
+    f.write("theme.toml", &THEME.replace("pointer.svg", "default.svg"));
+    f.manager
+        .reload_vector_files(&[PathBuf::from("theme.toml")])
+        .unwrap();
+    let capture = f.capture(1.);
+    assert_eq!(capture.cursor_id(), "pointer");
+    assert_eq!(capture.pixel(0, 0), RED);
+    assert!(!Rc::ptr_eq(&default, &f.renderer("default", 1.)));
+}
+
+#[test]
+fn color_scheme_switches_artwork() {
+    let theme =
+        format!("{THEME}file_light = \"pointer-light.svg\"\nfile_dark = \"pointer-dark.svg\"\n");
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+            ("pointer-light.svg", &square_svg(24, "#00ff00")),
+            ("pointer-dark.svg", &square_svg(24, "#000000")),
+        ],
+    );
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+    let default = f.renderer("default", 1.);
+
+    f.manager.set_color_scheme(ColorScheme::Dark);
+    assert_eq!(f.capture(1.).pixel(0, 0), [0, 0, 0, 255]);
+    // Cursors without light and dark artwork stay loaded.
+    assert!(Rc::ptr_eq(&default, &f.renderer("default", 1.)));
+
+    f.manager.set_color_scheme(ColorScheme::Light);
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+}
+
+#[test]
+fn color_scheme_artwork_is_verified() {
+    let light = square_svg(24, "#00ff00");
+    let theme = format!(
+        "{THEME}file_light = \"pointer-light.svg\"\nfile_dark = \"pointer-dark.svg\"\n\
+         sha256_light = \"{}\"\nsha256_dark = \"{}\"\n",
+        integrity::sha256_hex(light.as_bytes()),
+        integrity::sha256_hex(b"something else"),
+    );
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+            ("pointer-light.svg", &light),
+            ("pointer-dark.svg", &square_svg(24, "#000000")),
+        ],
+    );
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+
+    f.manager.set_color_scheme(ColorScheme::Dark);
+    let store = f.manager.vector_store().unwrap();
+    let err = store.get_renderer_at("pointer", 1.).err().unwrap();
+    assert!(format!("{err:#}").contains("pointer-dark.svg"), "{err:#}");
+}
+
+#[test]
+fn stylesheets_override_artwork() {
+    let theme = format!(
+        "stylesheet = \"rect {{ fill: #00ff00 }}\"\n{THEME}stylesheet = \"rect {{ fill: #000 }}\"\n"
+    );
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).pixel(0, 0), [0, 0, 0, 255]);
+}
+
+#[test]
+fn inline_data() {
+    let theme = format!(
+        "[cursors.default]\nformat = \"svg\"\ndata = '''{}'''\n",
+        square_svg(24, "#00ff00")
+    );
+    let mut f = CursorFixture::new(&theme, &[]);
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+}
+
+#[test]
+fn diagnostics_name_dropped_features() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24"><rect width="24" height="24" filter="url(#glow)"/></svg>"#;
+    let mut f = CursorFixture::new(THEME, &[("default.svg", svg), ("pointer.svg", svg)]);
+    let store = f.manager.vector_store().unwrap();
+    // Nothing is known before the cursor is loaded.
+    assert!(store.svg_diagnostics("default").is_empty());
+
+    f.capture(1.);
+    let diagnostics = f.manager.vector_store().unwrap().svg_diagnostics("default");
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["<rect> on line 1 has filter #glow, which does not exist"]
+    );
+}
+
+#[test]
+fn rotated_cursors_share_artwork() {
+    let theme = r#"
+[cursors.ns-resize]
+format = "svg"
+file = "resize.svg"
+hotspot = [4, 0]
+
+[cursors.ew-resize]
+format = "svg"
+file = "resize.svg"
+hotspot = [4, 0]
+rotate_deg = 90
+"#;
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="24"><rect width="8" height="24" fill="#00ff00"/></svg>"##;
+    let mut f = CursorFixture::new(theme, &[("resize.svg", svg)]);
+
+    f.set_icon(CursorIcon::NsResize);
+    let capture = f.capture(1.);
+    assert_eq!(capture.buffer_size(), Size::from((8, 24)));
+    assert_eq!(capture.hotspot(), Point::from((4., 0.)));
+
+    f.set_icon(CursorIcon::EwResize);
+    let capture = f.capture(1.);
+    assert_eq!(capture.buffer_size(), Size::from((24, 8)));
+    assert_eq!(capture.hotspot(), Point::from((23., 4.)));
+    assert_eq!(capture.pixel(20, 0), GREEN);
+}
+
+#[test]
+fn mirror_flips_cursors() {
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="24"><rect width="12" height="24" fill="#ff0000"/><rect x="12" width="12" height="24" fill="#0000ff"/></svg>"##;
+    let mut f = CursorFixture::new(
+        &format!("mirror = true\n{THEME}"),
+        &[("default.svg", svg), ("pointer.svg", svg)],
+    );
+    f.set_icon(CursorIcon::Pointer);
+    let capture = f.capture(1.);
+    assert_eq!(capture.pixel(0, 0), BLUE);
+    assert_eq!(capture.pixel(23, 0), RED);
+    assert_eq!(capture.hotspot(), Point::from((20., 4.)));
+
+    f.manager.toggle_mirror();
+    let capture = f.capture(1.);
+    assert_eq!(capture.pixel(0, 0), RED);
+    assert_eq!(capture.hotspot(), Point::from((4., 4.)));
+
+    // The runtime setting outlasts theme reloads.
+    f.reload().unwrap();
+    assert_eq!(f.capture(2.).hotspot(), Point::from((4., 4.)));
+    assert_eq!(f.capture(2.).pixel(0, 0), RED);
+}
+
+#[test]
+fn transitions_follow_the_clock() {
+    let theme = format!(
+        "{THEME}[transitions.\"default->pointer\"]\nduration_ms = 100\neasing = \"linear\"\n"
+    );
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+
+    assert_eq!(f.capture(1.).pixel(8, 8), RED);
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).pixel(8, 8), RED);
+
+    // Halfway through, both cursors are blended.
+    f.advance(50);
+    let [r, _, b, a] = f.capture(1.).pixel(8, 8);
+    assert!(r > 0 && b > 0 && a == 255, "{:?}", [r, b, a]);
+
+    // Time only passes through the clock.
+    assert_eq!(f.capture(1.).pixel(8, 8), [r, 0, b, a]);
+
+    f.advance(50);
+    assert_eq!(f.capture(1.).pixel(8, 8), BLUE);
+}
+
+#[test]
+fn presented_frames_advance_transitions() {
+    let theme = format!("{THEME}[transitions.\"default->pointer\"]\nduration_ms = 100\n");
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+
+    assert_eq!(f.capture(1.).pixel(8, 8), RED);
+    f.set_icon(CursorIcon::Pointer);
+
+    // A frame presented later than the clock says catches the animation up.
+    f.manager.presented(Duration::from_millis(100));
+    assert_eq!(f.capture(1.).pixel(8, 8), BLUE);
+}
+
+#[test]
+fn invisible_cursors_stop_animating() {
+    let theme = format!("{THEME}[transitions.\"default->pointer\"]\nduration_ms = 100\n");
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+    f.capture(1.);
+    f.set_icon(CursorIcon::Pointer);
+    assert!(f.manager.is_current_cursor_animated(1));
+
+    // With the monitors off, the transition waits without asking for redraws.
+    f.manager.set_monitors_active(false);
+    assert!(!f.manager.is_current_cursor_animated(1));
+    assert_eq!(f.manager.next_frame_deadline(), None);
+    f.advance(1000);
+    f.manager.set_monitors_active(true);
+    assert!(f.manager.is_current_cursor_animated(1));
+    assert_ne!(f.capture(1.).pixel(8, 8), BLUE);
+
+    // Clients hiding the cursor hide vector cursors too.
+    f.manager.set_cursor_image(CursorImageStatus::Hidden);
+    assert!(matches!(f.capture(1.), Capture::Hidden));
+    assert!(!f.manager.is_current_cursor_animated(1));
+    assert_eq!(f.manager.next_frame_deadline(), None);
+}
+
+#[test]
+fn idle_cursors_fall_asleep() {
+    let theme = format!(
+        "{THEME}[cursors.sleepy]\nformat = \"svg\"\nfile = \"sleepy.svg\"\n\
+         [idle]\nafter_ms = 1000\ncursor = \"sleepy\"\n"
+    );
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+            ("sleepy.svg", &square_svg(24, "#00ff00")),
+        ],
+    );
+
+    assert_eq!(f.capture(1.).cursor_id(), "default");
+    assert_eq!(
+        f.manager.next_frame_deadline(),
+        Some(Duration::from_secs(1))
+    );
+    f.advance(999);
+    assert_eq!(f.capture(1.).cursor_id(), "default");
+    f.advance(1);
+    assert_eq!(f.capture(1.).pixel(0, 0), GREEN);
+    assert_eq!(f.manager.next_frame_deadline(), None);
+
+    // Icon changes to the cursor that fell asleep don't wake it up, motion does.
+    f.set_icon(CursorIcon::Default);
+    assert_eq!(f.capture(1.).cursor_id(), "sleepy");
+    f.manager.pointer_motion(Point::from((1., 0.)));
+    assert_eq!(f.capture(1.).cursor_id(), "default");
+
+    // Cursors fall asleep after the last motion.
+    f.set_icon(CursorIcon::Pointer);
+    f.advance(999);
+    assert_eq!(f.capture(1.).cursor_id(), "pointer");
+    f.advance(1);
+    assert_eq!(f.capture(1.).cursor_id(), "sleepy");
+}
+
+#[test]
+fn idle_cursors_wake_up_with_a_transition() {
+    let theme = format!(
+        "{THEME}[cursors.sleepy]\nformat = \"svg\"\nfile = \"sleepy.svg\"\n\
+         [idle]\nafter_ms = 1000\ncursor = \"sleepy\"\n\
+         [transitions.\"sleepy->default\"]\nduration_ms = 100\neasing = \"linear\"\n"
+    );
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+            ("sleepy.svg", &square_svg(24, "#00ff00")),
+        ],
+    );
+
+    f.advance(1000);
+    assert_eq!(f.capture(1.).pixel(8, 8), GREEN);
+    assert!(!f.manager.is_current_cursor_animated(1));
+
+    // Motion plays the theme's transition back instead of snapping to the active cursor.
+    f.manager.pointer_motion(Point::from((1., 0.)));
+    assert!(f.manager.is_current_cursor_animated(1));
+    f.advance(50);
+    let [r, g, _, a] = f.capture(1.).pixel(8, 8);
+    assert!(r > 0 && g > 0 && a == 255, "{:?}", [r, g, a]);
+
+    f.advance(50);
+    assert_eq!(f.capture(1.).pixel(8, 8), RED);
+}
+
+/// Fixture with a Lottie transition from `default` to `pointer` playing a green square, set up
+/// further by the keys in `extra_toml`.
+#[cfg(feature = "lottie")]
+fn lottie_transition_fixture(extra_toml: &str) -> CursorFixture {
+    let theme = format!(
+        "{THEME}[transitions.\"default->pointer\"]\ntransition_type = \"lottie\"\n\
+         file = \"melt.json\"\n{extra_toml}"
+    );
+    let square = r#"{"v": [[0, 0], [24, 0], [24, 24], [0, 24]], "i": [[0, 0], [0, 0], [0, 0], [0, 0]], "o": [[0, 0], [0, 0], [0, 0], [0, 0]], "c": true}"#;
+    let melt = format!(
+        r#"{{"w": 24, "h": 24, "fr": 30, "ip": 0, "op": 1, "layers": [{{"ty": 4, "shapes": [
+            {{"ty": "sh", "ks": {{"a": 0, "k": {square}}}}},
+            {{"ty": "fl", "c": {{"a": 0, "k": [0, 1, 0, 1]}}}}
+        ]}}]}}"#
+    );
+    CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+            ("melt.json", &melt),
+        ],
+    )
+}
+
+#[cfg(feature = "lottie")]
+#[test]
+fn lottie_transitions_play_their_animation() {
+    let mut f = lottie_transition_fixture("hotspot = [2, 2]\nduration_ms = 60000\n");
+
+    assert_eq!(f.capture(1.).pixel(0, 0), RED);
+    f.set_icon(CursorIcon::Pointer);
+    let capture = f.capture(1.);
+    assert_eq!(capture.cursor_id(), "pointer");
+    assert_eq!(capture.pixel(0, 0), GREEN);
+    assert_eq!(capture.hotspot(), Point::from((2., 2.)));
+}
+
+#[cfg(feature = "lottie")]
+#[test]
+fn lottie_transitions_carry_the_hotspot_over() {
+    let mut f = lottie_transition_fixture("duration_ms = 1000\n");
+
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).hotspot(), Point::from((0., 0.)));
+    f.advance(500);
+    assert_eq!(f.capture(1.).hotspot(), Point::from((2., 2.)));
+    f.advance(250);
+    assert_eq!(f.capture(1.).hotspot(), Point::from((3., 3.)));
+    f.advance(250);
+    assert_eq!(f.capture(1.).hotspot(), Point::from((4., 4.)));
+}
+
+#[cfg(feature = "lottie")]
+#[test]
+fn lottie_transitions_are_verified() {
+    let mut f = lottie_transition_fixture(&format!(
+        "duration_ms = 60000\nsha256 = \"{}\"\n",
+        integrity::sha256_hex(b"something else")
+    ));
+
+    // The transition doesn't play, the target cursor shows right away.
+    f.set_icon(CursorIcon::Pointer);
+    assert_eq!(f.capture(1.).pixel(0, 0), BLUE);
+
+    let store = f.manager.vector_store().unwrap();
+    let transition = &store.get_config().transitions["default->pointer"];
+    let err = store.get_transition_renderer(transition).err().unwrap();
+    assert!(format!("{err:#}").contains("melt.json"), "{err:#}");
+}
+
+#[cfg(feature = "lottie")]
+#[test]
+fn new_scales_prerender_what_the_cache_holds() {
+    let square = r#"{"v": [[0, 0], [24, 0], [24, 24], [0, 24]], "i": [[0, 0], [0, 0], [0, 0], [0, 0]], "o": [[0, 0], [0, 0], [0, 0], [0, 0]], "c": true}"#;
+    let spinner = format!(
+        r#"{{"w": 24, "h": 24, "fr": 30, "ip": 0, "op": 1000, "layers": [{{"ty": 4, "shapes": [
+            {{"ty": "sh", "ks": {{"a": 0, "k": {square}}}}},
+            {{"ty": "fl", "c": {{"a": 0, "k": [0, 1, 0, 1]}}}}
+        ]}}]}}"#
+    );
+    let mut f = CursorFixture::new(
+        "[cursors.default]\nformat = \"lottie\"\nfile = \"spinner.json\"\n",
+        &[("spinner.json", &spinner)],
+    );
+    f.capture(2.);
+
+    // A new output scale only queues the frames for idle callbacks.
+    f.set_output_scales(&[2.]);
+    assert!(f.manager.has_prerender_work());
+
+    // 1000 frames of 48×48 pixels are more than a frame cache holds, so pre-rendering stops
+    // well before the last frame.
+    let mut calls = 0;
+    while f.manager.has_prerender_work() {
+        f.manager.prerender_some();
+        calls += 1;
+    }
+    assert!(calls < 1000 / 4, "{calls} calls");
+}
+
+#[test]
+fn reduced_motion_doesnt_smear() {
+    let theme = format!("[motion_blur]\nthreshold = 0\n{THEME}");
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+    let swipe = |f: &mut CursorFixture| {
+        for _ in 0..5 {
+            f.manager.pointer_motion(Point::from((10., 0.)));
+            f.advance(10);
+        }
+    };
+
+    swipe(&mut f);
+    assert!(f.capture(1.).buffer_size().w > 24);
+    assert!(f.manager.is_current_cursor_animated(1));
+
+    f.manager.set_reduce_motion(true);
+    swipe(&mut f);
+    assert_eq!(f.capture(1.).buffer_size(), Size::from((24, 24)));
+    assert!(!f.manager.is_current_cursor_animated(1));
+}
+
+#[test]
+fn motion_blur_redraws_above_threshold() {
+    let theme = format!("[motion_blur]\nthreshold = 1000\n{THEME}");
+    let mut f = CursorFixture::new(
+        &theme,
+        &[
+            ("default.svg", &square_svg(24, "#ff0000")),
+            ("pointer.svg", &square_svg(24, "#0000ff")),
+        ],
+    );
+    let swipe = |f: &mut CursorFixture, distance| {
+        for _ in 0..5 {
+            f.manager.pointer_motion(Point::from((distance, 0.)));
+            f.advance(10);
+        }
+    };
+
+    // 100 px/s doesn't smear, so it doesn't need redraws.
+    swipe(&mut f, 1.);
+    assert!(!f.manager.is_current_cursor_animated(1));
+    assert_eq!(f.capture(1.).buffer_size(), Size::from((24, 24)));
+
+    // 10000 px/s does, and the smear is drawn away once the pointer rests.
+    swipe(&mut f, 100.);
+    assert!(f.manager.is_current_cursor_animated(1));
+    assert!(f.capture(1.).buffer_size().w > 24);
+    f.advance(200);
+    assert!(f.manager.is_current_cursor_animated(1));
+    assert_eq!(f.capture(1.).buffer_size(), Size::from((24, 24)));
+    assert!(!f.manager.is_current_cursor_animated(1));
+}